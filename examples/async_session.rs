@@ -0,0 +1,25 @@
+use std::{error::Error, sync::Arc};
+use rand::rngs::OsRng;
+use wake_implementation::params::WakeParams;
+use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_async::run_async_compiler_key_exchange;
+use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_async::run_async_optimized_key_exchange;
+
+///
+/// Runs a session of the compiler-version and of the optimized version of WAKE using Bulletproofs, where every party is a
+/// tokio task exchanging its messages over in-memory channels
+/// only whether the parties agree on the key is printed, as the key itself is secret
+/// run with: cargo run --release --features async --example async_session
+///
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let params = Arc::new(WakeParams::bulletproofs(10, u8::MAX as u64)?);
+    let balances = vec![20, 30, 40, 50, 60];
+
+    let keys = run_async_compiler_key_exchange(balances.len(), balances.clone(), &params, &mut OsRng).await?;
+    println!("compiler WAKE: {} parties, keys agree: {}", keys.len(), keys.iter().all(|key| *key == keys[0]));
+
+    let keys = run_async_optimized_key_exchange(balances.len(), balances, &params, &mut OsRng).await?;
+    println!("optimized WAKE: {} parties, keys agree: {}", keys.len(), keys.iter().all(|key| *key == keys[0]));
+
+    Ok(())
+}
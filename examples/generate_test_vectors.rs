@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+use wake_implementation::test_vectors::write_test_vectors;
+
+///
+/// Generates the known-answer test vectors of all WAKE variants, replayed by the tests in tests/test_vectors
+/// run with: cargo run --release --example generate_test_vectors [output directory]
+///
+fn main() -> Result<(), Box<dyn Error>> {
+    let dir = std::env::args().nth(1).unwrap_or_else(|| "tests/test_vectors/data".to_string());
+    write_test_vectors(Path::new(&dir))?;
+    println!("test vectors written to {}", dir);
+
+    Ok(())
+}
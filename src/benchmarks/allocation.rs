@@ -0,0 +1,61 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+///
+/// File for measuring the memory allocated by the benchmarks. The CountingAllocator wraps the system allocator, and counts the bytes
+/// allocated in total and the bytes currently live. It is installed as the global allocator of the benchmark binary (see main.rs),
+/// without which the counters stay zero
+///
+//bytes allocated since the start of the program, including reallocations
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+//bytes allocated and not yet deallocated
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            count_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            count_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            count_alloc(new_size);
+            LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        new_ptr
+    }
+}
+
+fn count_alloc(size: usize) {
+    ALLOCATED.fetch_add(size, Ordering::Relaxed);
+    LIVE.fetch_add(size, Ordering::Relaxed);
+}
+
+//bytes allocated since the start of the program
+pub fn allocated_bytes() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+//bytes currently live
+pub fn live_bytes() -> usize {
+    LIVE.load(Ordering::Relaxed)
+}
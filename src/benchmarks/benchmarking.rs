@@ -1,26 +1,35 @@
 use crate::{
+    benchmarks::allocation::{allocated_bytes, live_bytes},
+    params::{WakeParams, Gm17Keys, MarlinKeys},
     utility::{
-        setup_gm17, 
-        setup_gm17_single_party,
+        setup_marlin,
+        upperbound_log,
         mimc
     },
     proof_systems::{
-        range_proof::RangeProofStruct, 
         discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct, 
+        proof_system_gm17_aggregation::AggregationSRS,
     },
     protocols::{
         compiler_bp_wake::{compiler_bp_wake_protocol::{run_compiler_key_exchange, Nonces}, compiler_bp_wake_signature::{CompSignature, CompMessage}},
         optimized_bp_wake::{optimized_bp_wake_protocol::{run_optimized_key_exchange, VK}, optimized_bp_wake_signature_and_session_authentication::{OptimizedSignatureAndSessionAuthentication, OptimizedMessage}},
-        compiler_gm17_wake::{compiler_gm17_wake_protocol::run_compiler_key_exchange as run_gm17_compiler_key_exchange, compiler_gm17_wake_signature::CompSignature as GM17CompSignature},
+        compiler_gm17_wake::{compiler_gm17_wake_protocol::{run_compiler_key_exchange as run_gm17_compiler_key_exchange, run_aggregated_compiler_key_exchange as run_gm17_aggregated_compiler_key_exchange}, compiler_gm17_wake_signature::CompSignature as GM17CompSignature},
         optimized_gm17_wake::{optimized_gm17_wake_protocol::run_optimized_key_exchange as run_gm17_optimized_key_exchange, optimized_gm17_wake_signature_and_session_authentication::OptimizedSignatureAndSessionAuthentication as GM17OptimizedSignatureAndSessionAuthentication},
+        compiler_marlin_wake::{compiler_marlin_wake_protocol::run_compiler_key_exchange as run_marlin_compiler_key_exchange, compiler_marlin_wake_signature::CompSignature as MarlinCompSignature},
+        optimized_marlin_wake::{optimized_marlin_wake_protocol::run_optimized_key_exchange as run_marlin_optimized_key_exchange, optimized_marlin_wake_signature_and_session_authentication::OptimizedSignatureAndSessionAuthentication as MarlinOptimizedSignatureAndSessionAuthentication},
         burmester_desmedt::key_exchange::key_exchange,
+        party_id::PartyId,
     }
 };
 use csv::WriterBuilder;
-use std::{error::Error, fs::File, time::Instant};
+use std::{error::Error, fs::File, time::Instant, sync::Arc};
 use rand::{rngs::OsRng, Rng};
-use ark_bls12_377::{Fr, Parameters}; 
+use ark_bls12_377::{Bls12_377, Fr, Parameters}; 
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
 use ark_ec::{PairingEngine, bls12::Bls12};
+use ark_ff::UniformRand;
+use ark_serialize::CanonicalSerialize;
 use bulletproofs::PedersenGens;
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::CompressedRistretto};
 
@@ -29,6 +38,7 @@ const MIN_BAL: u64 = 10;
 
 #[cfg(not(tarpaulin_include))]
 pub fn benchmark_sizes() -> Result<(), Box<dyn Error>> {
+    let rng = &mut OsRng;
     let mut data = Vec::new();
     
     let g1_affine_size = std::mem::size_of::<<Bls12<Parameters> as PairingEngine>::G1Affine>();
@@ -38,18 +48,26 @@ pub fn benchmark_sizes() -> Result<(), Box<dyn Error>> {
     
     let logs_of_upperbounds = [3, 4, 5, 6];
     
-    for l in logs_of_upperbounds {
+    for (l, upperbound) in logs_of_upperbounds.iter().zip(UPPERBOUNDS.iter()) {
         let discreet_log_size = 2*scalar_size + comp_ris_size;
         let comp_size = (4+(2*l))*comp_ris_size + (3+2)*scalar_size;
         let opti_size = comp_size + discreet_log_size;
         let gm17_comp_size = 2*g1_affine_size + g2_affine_size;
         let gm17_opti_size = gm17_comp_size + discreet_log_size;
+
+        //marlin proofs consist of a varying amount of commitments and evaluations, so the size of a serialized proof is measured
+        let (marlin_darkpool, _, marlin_pk) = setup_marlin::<Bls12_377, _>(*upperbound, rng)?;
+        let marlin_proof = marlin_darkpool.prove(Fr::from(*upperbound), Fr::from(1u64), "".to_string(), &marlin_pk, rng)?;
+        let marlin_comp_size = marlin_proof.serialized_size();
+        let marlin_opti_size = marlin_comp_size + discreet_log_size;
         
         data.push(
             (comp_size.to_string(),
             opti_size.to_string(),
             gm17_comp_size.to_string(),
-            gm17_opti_size.to_string()
+            gm17_opti_size.to_string(),
+            marlin_comp_size.to_string(),
+            marlin_opti_size.to_string()
         )
     );
 }
@@ -58,9 +76,9 @@ let mut writer = WriterBuilder::new()
 .delimiter(b',')
 .from_writer(File::create("src/benchmarks/data/signature_sizes.csv")?);
 
-for (comp_size, opti_size, gm17_comp_size, gm17_opti_size) in &data {
+for (comp_size, opti_size, gm17_comp_size, gm17_opti_size, marlin_comp_size, marlin_opti_size) in &data {
     writer.write_record(
-        &[comp_size, opti_size, gm17_comp_size, gm17_opti_size]
+        [comp_size, opti_size, gm17_comp_size, gm17_opti_size, marlin_comp_size, marlin_opti_size]
     )?;
 }
 
@@ -82,13 +100,28 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
             println!("upperbound: {}", upperbound);
             
 
+            //bulletproofs setup values
+            let bp_params = Arc::new(WakeParams::bulletproofs(MIN_BAL, *upperbound)?);
+
             //gm17 setup values
-            let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(*party_amount, *upperbound)?;
+            let gm17_params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(*party_amount, MIN_BAL, *upperbound, rng)?);
+
+            //gm17 aggregation setup values, aggregation requires a key pair shared by all parties
+            let gm17_darkpool = gm17_params.keys().gm17_darkpool().clone();
+            let (shared_pk, shared_pvk) = gm17_darkpool.setup(rng);
+            let gm17_shared_params = Arc::new(WakeParams::new(MIN_BAL, *upperbound, Gm17Keys::shared(gm17_darkpool, shared_pvk, shared_pk, *party_amount))?);
+            let aggregation_srs = AggregationSRS::<Bls12_377>::setup(*party_amount, rng)?;
+
+            //marlin setup values
+            let marlin_params: Arc<_> = WakeParams::<MarlinKeys<Bls12_377>>::marlin(MIN_BAL, *upperbound, rng)?.into();
             
             let mut total_time_elapsed_compiler: u128 = 0;
             let mut total_time_elapsed_optimized: u128 = 0;
             let mut total_time_elapsed_gm17_compiler: u128 = 0;
             let mut total_time_elapsed_gm17_optimized: u128 = 0;
+            let mut total_time_elapsed_gm17_aggregated: u128 = 0;
+            let mut total_time_elapsed_marlin_compiler: u128 = 0;
+            let mut total_time_elapsed_marlin_optimized: u128 = 0;
             let mut total_time_elapsed_bd: u128 = 0;
             
             //run over sample-size and take average of results
@@ -103,33 +136,51 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
                 
                 //run and benchmark compiler WAKE key exchange protocol
                 let start_time_compiler = Instant::now();
-                let _ = run_compiler_key_exchange(*party_amount, MIN_BAL, balances.to_vec(), *upperbound).unwrap();
+                let _ = run_compiler_key_exchange(*party_amount, balances.to_vec(), &bp_params, rng).unwrap();
                 let time_elapsed_compiler = start_time_compiler.elapsed().as_nanos();
                 
                 //run and benchmark optimized WAKE key exchange protocol
                 let start_time_optimized = Instant::now();
-                let _ = run_optimized_key_exchange(*party_amount, MIN_BAL, balances.to_vec(), *upperbound).unwrap();
+                let _ = run_optimized_key_exchange(*party_amount, balances.to_vec(), &bp_params, rng).unwrap();
                 let time_elapsed_optimized = start_time_optimized.elapsed().as_nanos();
                 
                 //run and benchmark GM17 compiler WAKE key exchange protocol
                 let start_time_gm17_compiler = Instant::now();
-                let _ = run_gm17_compiler_key_exchange(*party_amount, MIN_BAL, balances.to_vec(), *upperbound, &gm17_darkpool, &pvk_list, &pk_list);
+                let _ = run_gm17_compiler_key_exchange(*party_amount, balances.to_vec(), &gm17_params, rng);
                 let time_elapsed_gm17_compiler = start_time_gm17_compiler.elapsed().as_nanos();
                 
                 //run and benchmark GM17 optimized WAKE key exchange protocol
                 let start_time_gm17_optimized = Instant::now();
-                let _ = run_gm17_optimized_key_exchange(*party_amount, MIN_BAL, balances, *upperbound, &gm17_darkpool, &pvk_list, &pk_list);
+                let _ = run_gm17_optimized_key_exchange(*party_amount, balances.to_vec(), &gm17_params, rng);
                 let time_elapzed_gm17_optimized = start_time_gm17_optimized.elapsed().as_nanos();
+
+                //run and benchmark GM17 compiler WAKE key exchange protocol, with signatures aggregated into one proof per round
+                let start_time_gm17_aggregated = Instant::now();
+                let _ = run_gm17_aggregated_compiler_key_exchange(*party_amount, balances.to_vec(), &gm17_shared_params, &aggregation_srs, rng);
+                let time_elapsed_gm17_aggregated = start_time_gm17_aggregated.elapsed().as_nanos();
+
+                //run and benchmark Marlin compiler WAKE key exchange protocol
+                let start_time_marlin_compiler = Instant::now();
+                let _ = run_marlin_compiler_key_exchange(*party_amount, balances.to_vec(), &marlin_params, rng);
+                let time_elapsed_marlin_compiler = start_time_marlin_compiler.elapsed().as_nanos();
+
+                //run and benchmark Marlin optimized WAKE key exchange protocol
+                let start_time_marlin_optimized = Instant::now();
+                let _ = run_marlin_optimized_key_exchange(*party_amount, balances, &marlin_params, rng);
+                let time_elapsed_marlin_optimized = start_time_marlin_optimized.elapsed().as_nanos();
                 
                 //run and benchmark Burmester-Desmedt key exchange protocol
                 let start_time_bd = Instant::now();
-                let _ = key_exchange(*party_amount);
+                let _ = key_exchange(*party_amount, rng);
                 let time_elapsed_bd = start_time_bd.elapsed().as_nanos();
                 
                 total_time_elapsed_compiler += time_elapsed_compiler;
                 total_time_elapsed_optimized += time_elapsed_optimized;
                 total_time_elapsed_gm17_compiler += time_elapsed_gm17_compiler;
                 total_time_elapsed_gm17_optimized += time_elapzed_gm17_optimized;
+                total_time_elapsed_gm17_aggregated += time_elapsed_gm17_aggregated;
+                total_time_elapsed_marlin_compiler += time_elapsed_marlin_compiler;
+                total_time_elapsed_marlin_optimized += time_elapsed_marlin_optimized;
                 total_time_elapsed_bd += time_elapsed_bd;
             }
             
@@ -152,7 +203,13 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
                 (total_time_elapsed_gm17_compiler/sample_size).to_string(),
                 ((total_time_elapsed_gm17_compiler/sample_size)/(*party_amount) as u128).to_string(),
                 (total_time_elapsed_gm17_optimized/sample_size).to_string(),
-                ((total_time_elapsed_gm17_optimized/sample_size)/(*party_amount) as u128).to_string()
+                ((total_time_elapsed_gm17_optimized/sample_size)/(*party_amount) as u128).to_string(),
+                (total_time_elapsed_marlin_compiler/sample_size).to_string(),
+                ((total_time_elapsed_marlin_compiler/sample_size)/(*party_amount) as u128).to_string(),
+                (total_time_elapsed_marlin_optimized/sample_size).to_string(),
+                ((total_time_elapsed_marlin_optimized/sample_size)/(*party_amount) as u128).to_string(),
+                (total_time_elapsed_gm17_aggregated/sample_size).to_string(),
+                ((total_time_elapsed_gm17_aggregated/sample_size)/(*party_amount) as u128).to_string()
             ));
         }
     }
@@ -172,7 +229,13 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
         time_gm17_comp, 
         time_gm17_comp_pr, 
         time_gm17_opti, 
-        time_gm17_opti_pr) in &data {
+        time_gm17_opti_pr,
+        time_marlin_comp,
+        time_marlin_comp_pr,
+        time_marlin_opti,
+        time_marlin_opti_pr,
+        time_gm17_aggr,
+        time_gm17_aggr_pr) in &data {
             writer.write_record(
                 &[upperbound_exp, 
                 party_amount, 
@@ -185,7 +248,13 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
                 time_gm17_comp, 
                 time_gm17_comp_pr, 
                 time_gm17_opti, 
-                time_gm17_opti_pr]
+                time_gm17_opti_pr,
+                time_marlin_comp,
+                time_marlin_comp_pr,
+                time_marlin_opti,
+                time_marlin_opti_pr,
+                time_gm17_aggr,
+                time_gm17_aggr_pr]
             )?;
         }
         
@@ -222,12 +291,21 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
             let z_i = g * x_i;
             
             //messages
-            let vk_struct = VK::new(vec![1], vec![vk_ristretto]);
-            let compiler_message = CompMessage::new(1, 2, x, Nonces::new(Vec::new(), Vec::new()));
-            let optimized_message = OptimizedMessage::new(1, x, vk_struct);
+            let id = PartyId::from_commitment(&og_commitment);
+            let vk_struct = VK::new(vec![id], vec![vk_ristretto]);
+            let compiler_message = CompMessage::new(id, 2, x, Nonces::new(Vec::new(), Vec::new()));
+            let optimized_message = OptimizedMessage::new(id, x, vk_struct);
             
+            //bulletproofs setup values
+            let bp_params = WakeParams::bulletproofs(MIN_BAL, *upperbound)?;
+
             //gm17 setup values
-            let (gm17_darkpool, pvk, pk) = setup_gm17_single_party(*upperbound)?;
+            let gm17_params = WakeParams::<Gm17Keys<Bls12_377>>::gm17(1, MIN_BAL, *upperbound, rng)?;
+            let gm17_darkpool = gm17_params.keys().gm17_darkpool();
+
+            //marlin setup values
+            let marlin_params = WakeParams::<MarlinKeys<Bls12_377>>::marlin(MIN_BAL, *upperbound, rng)?;
+            let marlin_darkpool = marlin_params.keys().marlin_darkpool();
             
             //total bullet time
             let mut total_time_elapsed_compiler_sig: u128 = 0;
@@ -240,6 +318,12 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
             let mut total_time_elapsed_gm17_compiler_verify: u128 = 0;
             let mut total_time_elapsed_gm17_optimized_sig: u128 = 0;
             let mut total_time_elapsed_gm17_optimized_verify: u128 = 0;
+
+            //total marlin time
+            let mut total_time_elapsed_marlin_compiler_sig: u128 = 0;
+            let mut total_time_elapsed_marlin_compiler_verify: u128 = 0;
+            let mut total_time_elapsed_marlin_optimized_sig: u128 = 0;
+            let mut total_time_elapsed_marlin_optimized_verify: u128 = 0;
             
             
             for i in 0..sample_size {
@@ -247,51 +331,71 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
                 
                 //time compiler proof
                 let start_time_compiler_sig = Instant::now();
-                let sig = CompSignature::sign(compiler_message.to_string(), MIN_BAL, balance, og_blinding, *upperbound);
+                let sig = CompSignature::sign(&bp_params, compiler_message.to_string(), balance, og_blinding, rng);
                 let time_elapsed_compiler_sig = start_time_compiler_sig.elapsed().as_nanos();
                 
                 //time compiler verifiaction
                 let start_time_compiler_verify = Instant::now();
-                let range_proof = RangeProofStruct::new(MIN_BAL, *upperbound);
-                let _ = sig.verify_signature(compiler_message.to_string(), &range_proof, &og_commitment);
+                let _ = sig.verify_signature(&bp_params, compiler_message.to_string(), &og_commitment);
                 let time_elapsed_compiler_verify = start_time_compiler_verify.elapsed().as_nanos();
                 
                 //time optimized proof
                 let start_time_optimized_sig = Instant::now();
-                let optimized_sig = OptimizedSignatureAndSessionAuthentication::sign(&optimized_message.to_string(), MIN_BAL, balance, og_blinding, z_i.compress(), x_i, *upperbound, &og_commitment);
+                let optimized_sig = OptimizedSignatureAndSessionAuthentication::sign(&bp_params, &optimized_message.to_string(), balance, og_blinding, z_i.compress(), x_i, &og_commitment, rng);
                 let time_elapsed_optimized_sig = start_time_optimized_sig.elapsed().as_nanos();
                 
                 //time optimized verification
                 let start_time_optimized_verify = Instant::now();
                 let dl_proof = DiscreteLogKnowledgeProofStruct::new(z_i.compress());
-                let range_proof = RangeProofStruct::new(MIN_BAL, *upperbound);
-                let _ = optimized_sig.clone().verify_signature(&optimized_message.to_string(), &dl_proof, &range_proof, &og_commitment);
+                let _ = optimized_sig.clone().verify_signature(&bp_params, &optimized_message.to_string(), &dl_proof, &og_commitment);
                 let time_elapsed_optimized_verify = start_time_optimized_verify.elapsed().as_nanos();
                 
                 //time gm17 compiler proof
                 let start_time_gm17_comp_sig = Instant::now();
-                let gm17_comp_sig = GM17CompSignature::sign(&gm17_darkpool, compiler_message.to_string(), MIN_BAL, balance, og_blinding_fr, *upperbound, &pk);
+                let gm17_comp_sig = GM17CompSignature::sign(&gm17_params, 0, compiler_message.to_string(), balance, og_blinding_fr, rng);
                 let time_elapsed_gm17_comp_sig = start_time_gm17_comp_sig.elapsed().as_nanos();
                 
                 //time gm17 compiler verification
                 let v = Fr::from(upperbound - (balance - MIN_BAL));
                 let image = mimc(v, og_blinding_fr, &gm17_darkpool.mimc_constants);
                 let start_time_gm17_comp_ver = Instant::now();
-                let _ = gm17_comp_sig.verify_signature(&gm17_darkpool, &pvk, image, compiler_message.to_string());
+                let _ = gm17_comp_sig.verify_signature(&gm17_params, 0, image, compiler_message.to_string());
                 let time_elapsed_gm17_comp_ver = start_time_gm17_comp_ver.elapsed().as_nanos();
                 
                 //time gm17 optimized proof
                 let image = mimc(v, og_blinding_fr, &gm17_darkpool.mimc_constants);
                 let start_time_gm17_opti_sig = Instant::now();
-                let gm17_opti_sig = GM17OptimizedSignatureAndSessionAuthentication::sign(&gm17_darkpool, image, &optimized_message.to_string(), MIN_BAL, balance, og_blinding_fr, z_i.compress(), x_i, *upperbound, &pk);
+                let gm17_opti_sig = GM17OptimizedSignatureAndSessionAuthentication::sign(&gm17_params, 0, image, &optimized_message.to_string(), balance, og_blinding_fr, z_i.compress(), x_i, rng);
                 let time_elapsed_gm17_opti_sig = start_time_gm17_opti_sig.elapsed().as_nanos();
                 
                 //time gm17 optimized verification
                 let image = mimc(v, og_blinding_fr, &gm17_darkpool.mimc_constants);
                 let start_time_gm17_opti_ver = Instant::now();
                 let dl_proof = DiscreteLogKnowledgeProofStruct::new(z_i.compress());
-                let _ = gm17_opti_sig.verify_signature(&optimized_message.to_string(), &dl_proof, &pvk, &gm17_darkpool, image);
+                let _ = gm17_opti_sig.verify_signature(&gm17_params, 0, &optimized_message.to_string(), &dl_proof, image);
                 let time_elapsed_gm17_opti_ver = start_time_gm17_opti_ver.elapsed().as_nanos();
+
+                //time marlin compiler proof
+                let marlin_image = mimc(v, og_blinding_fr, &marlin_darkpool.mimc_constants);
+                let start_time_marlin_comp_sig = Instant::now();
+                let marlin_comp_sig = MarlinCompSignature::sign(&marlin_params, compiler_message.to_string(), balance, og_blinding_fr, rng);
+                let time_elapsed_marlin_comp_sig = start_time_marlin_comp_sig.elapsed().as_nanos();
+
+                //time marlin compiler verification
+                let start_time_marlin_comp_ver = Instant::now();
+                let _ = marlin_comp_sig.verify_signature(&marlin_params, marlin_image, compiler_message.to_string(), rng);
+                let time_elapsed_marlin_comp_ver = start_time_marlin_comp_ver.elapsed().as_nanos();
+
+                //time marlin optimized proof
+                let start_time_marlin_opti_sig = Instant::now();
+                let marlin_opti_sig = MarlinOptimizedSignatureAndSessionAuthentication::sign(&marlin_params, &optimized_message.to_string(), balance, og_blinding_fr, z_i.compress(), x_i, rng);
+                let time_elapsed_marlin_opti_sig = start_time_marlin_opti_sig.elapsed().as_nanos();
+
+                //time marlin optimized verification
+                let start_time_marlin_opti_ver = Instant::now();
+                let dl_proof = DiscreteLogKnowledgeProofStruct::new(z_i.compress());
+                let _ = marlin_opti_sig.verify_signature(&marlin_params, &optimized_message.to_string(), &dl_proof, marlin_image, rng);
+                let time_elapsed_marlin_opti_ver = start_time_marlin_opti_ver.elapsed().as_nanos();
                 
                 total_time_elapsed_compiler_sig += time_elapsed_compiler_sig;
                 total_time_elapsed_compiler_verify += time_elapsed_compiler_verify;
@@ -301,6 +405,10 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
                 total_time_elapsed_gm17_compiler_verify += time_elapsed_gm17_comp_ver;
                 total_time_elapsed_gm17_optimized_sig += time_elapsed_gm17_opti_sig;
                 total_time_elapsed_gm17_optimized_verify += time_elapsed_gm17_opti_ver;
+                total_time_elapsed_marlin_compiler_sig += time_elapsed_marlin_comp_sig;
+                total_time_elapsed_marlin_compiler_verify += time_elapsed_marlin_comp_ver;
+                total_time_elapsed_marlin_optimized_sig += time_elapsed_marlin_opti_sig;
+                total_time_elapsed_marlin_optimized_verify += time_elapsed_marlin_opti_ver;
             }
             
             let time_elapsed_comp_sig = total_time_elapsed_compiler_sig / sample_size;
@@ -312,6 +420,11 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
             let time_elapsed_gm17_comp_ver = total_time_elapsed_gm17_compiler_verify / sample_size;
             let time_elapsed_gm17_opti_sig = total_time_elapsed_gm17_optimized_sig / sample_size;
             let time_elapsed_gm17_opti_ver = total_time_elapsed_gm17_optimized_verify / sample_size;
+
+            let time_elapsed_marlin_comp_sig = total_time_elapsed_marlin_compiler_sig / sample_size;
+            let time_elapsed_marlin_comp_ver = total_time_elapsed_marlin_compiler_verify / sample_size;
+            let time_elapsed_marlin_opti_sig = total_time_elapsed_marlin_optimized_sig / sample_size;
+            let time_elapsed_marlin_opti_ver = total_time_elapsed_marlin_optimized_verify / sample_size;
             
             let upperbound_exp = match i {
                 0 => "8",
@@ -331,6 +444,10 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
                 time_elapsed_gm17_comp_ver.to_string(),
                 time_elapsed_gm17_opti_sig.to_string(),
                 time_elapsed_gm17_opti_ver.to_string(),
+                time_elapsed_marlin_comp_sig.to_string(),
+                time_elapsed_marlin_comp_ver.to_string(),
+                time_elapsed_marlin_opti_sig.to_string(),
+                time_elapsed_marlin_opti_ver.to_string(),
             )
         );
     }
@@ -347,7 +464,11 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
         time_elapsed_gm17_comp_sig, 
         time_elapsed_gm17_comp_ver, 
         time_elapsed_gm17_opti_sig, 
-        time_elapsed_gm17_opti_ver
+        time_elapsed_gm17_opti_ver,
+        time_elapsed_marlin_comp_sig,
+        time_elapsed_marlin_comp_ver,
+        time_elapsed_marlin_opti_sig,
+        time_elapsed_marlin_opti_ver
     ) in &data {
         writer.write_record(
             &[upperbound_exp, 
@@ -358,7 +479,11 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
             time_elapsed_gm17_comp_sig, 
             time_elapsed_gm17_comp_ver, 
             time_elapsed_gm17_opti_sig, 
-            time_elapsed_gm17_opti_ver
+            time_elapsed_gm17_opti_ver,
+            time_elapsed_marlin_comp_sig,
+            time_elapsed_marlin_comp_ver,
+            time_elapsed_marlin_opti_sig,
+            time_elapsed_marlin_opti_ver
             ])?;
         }
         
@@ -367,4 +492,325 @@ pub fn benchmark_protocol(sample_size: u128, party_amounts: &Vec<usize>) -> Resu
         println!("###############Benchmark of signature and session authentication finished###############");
         
         Ok(())
-    }
\ No newline at end of file
+    }
+
+#[cfg(not(tarpaulin_include))]
+pub fn benchmark_gm17_curves(sample_size: u128) -> Result<(), Box<dyn Error>> {
+
+    println!("#################Benchmark of GM17 signatures over different curves initiated#################");
+    let mut data = Vec::new();
+
+    benchmark_gm17_curve::<Bls12_377>("bls12_377", sample_size, &mut data)?;
+    benchmark_gm17_curve::<Bls12_381>("bls12_381", sample_size, &mut data)?;
+    benchmark_gm17_curve::<Bn254>("bn254", sample_size, &mut data)?;
+
+    let mut writer = WriterBuilder::new()
+    .delimiter(b',')
+    .from_writer(File::create("src/benchmarks/data/gm17_curves_sign_and_verify_outputs.csv")?);
+
+    for (curve,
+        upperbound_exp,
+        time_elapsed_gm17_comp_sig,
+        time_elapsed_gm17_comp_ver,
+        time_elapsed_gm17_opti_sig,
+        time_elapsed_gm17_opti_ver
+    ) in &data {
+        writer.write_record(
+            [curve,
+            upperbound_exp,
+            time_elapsed_gm17_comp_sig,
+            time_elapsed_gm17_comp_ver,
+            time_elapsed_gm17_opti_sig,
+            time_elapsed_gm17_opti_ver
+            ])?;
+    }
+
+    writer.flush()?;
+
+    println!("#################Benchmark of GM17 signatures over different curves finished#################");
+
+    Ok(())
+}
+
+//benchmark GM17 compiler signatures and optimized signatures with session authentication over a single curve
+#[cfg(not(tarpaulin_include))]
+fn benchmark_gm17_curve<E: PairingEngine>(curve: &str, sample_size: u128, data: &mut Vec<(String, String, String, String, String, String)>) -> Result<(), Box<dyn Error>> {
+    let rng = &mut OsRng;
+    let g = PedersenGens::default().B;
+
+    for upperbound in UPPERBOUNDS.iter() {
+        println!("curve: {}, upperbound: {}", curve, upperbound);
+
+        let balance = rng.gen_range(MIN_BAL..=*upperbound);
+        let blinding = E::Fr::rand(rng);
+
+        //messages
+        let x_i = Scalar::random(rng);
+        let x = g * Scalar::random(rng) * x_i;
+        let z_i = g * x_i;
+        let id = PartyId::from_commitment(&z_i);
+        let compiler_message = CompMessage::new(id, 2, x, Nonces::new(Vec::new(), Vec::new()));
+        let optimized_message = OptimizedMessage::new(id, x, VK::new(vec![id], vec![g * Scalar::random(rng)]));
+
+        //gm17 setup values
+        let gm17_params = WakeParams::<Gm17Keys<E>>::gm17(1, MIN_BAL, *upperbound, rng)?;
+        let v = E::Fr::from(upperbound - (balance - MIN_BAL));
+        let image = mimc(v, blinding, &gm17_params.keys().gm17_darkpool().mimc_constants);
+
+        let mut total_time_elapsed_gm17_compiler_sig: u128 = 0;
+        let mut total_time_elapsed_gm17_compiler_verify: u128 = 0;
+        let mut total_time_elapsed_gm17_optimized_sig: u128 = 0;
+        let mut total_time_elapsed_gm17_optimized_verify: u128 = 0;
+
+        for i in 0..sample_size {
+            println!("iteration {}", i+1);
+
+            //time gm17 compiler proof
+            let start_time_gm17_comp_sig = Instant::now();
+            let gm17_comp_sig = GM17CompSignature::sign(&gm17_params, 0, compiler_message.to_string(), balance, blinding, rng);
+            total_time_elapsed_gm17_compiler_sig += start_time_gm17_comp_sig.elapsed().as_nanos();
+
+            //time gm17 compiler verification
+            let start_time_gm17_comp_ver = Instant::now();
+            let _ = gm17_comp_sig.verify_signature(&gm17_params, 0, image, compiler_message.to_string());
+            total_time_elapsed_gm17_compiler_verify += start_time_gm17_comp_ver.elapsed().as_nanos();
+
+            //time gm17 optimized proof
+            let start_time_gm17_opti_sig = Instant::now();
+            let gm17_opti_sig = GM17OptimizedSignatureAndSessionAuthentication::sign(&gm17_params, 0, image, &optimized_message.to_string(), balance, blinding, z_i.compress(), x_i, rng);
+            total_time_elapsed_gm17_optimized_sig += start_time_gm17_opti_sig.elapsed().as_nanos();
+
+            //time gm17 optimized verification
+            let start_time_gm17_opti_ver = Instant::now();
+            let dl_proof = DiscreteLogKnowledgeProofStruct::new(z_i.compress());
+            let _ = gm17_opti_sig.verify_signature(&gm17_params, 0, &optimized_message.to_string(), &dl_proof, image);
+            total_time_elapsed_gm17_optimized_verify += start_time_gm17_opti_ver.elapsed().as_nanos();
+        }
+
+        let upperbound_exp = upperbound_log(*upperbound)?;
+
+        data.push(
+            (curve.to_string(),
+            upperbound_exp.to_string(),
+            (total_time_elapsed_gm17_compiler_sig / sample_size).to_string(),
+            (total_time_elapsed_gm17_compiler_verify / sample_size).to_string(),
+            (total_time_elapsed_gm17_optimized_sig / sample_size).to_string(),
+            (total_time_elapsed_gm17_optimized_verify / sample_size).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(tarpaulin_include))]
+pub fn benchmark_gm17_key_sharing(sample_size: u128, party_amount: usize) -> Result<(), Box<dyn Error>> {
+
+    println!("#################Benchmark of GM17 key sharing initiated#################");
+    let mut data = Vec::new();
+    let rng = &mut OsRng;
+
+    for upperbound in UPPERBOUNDS.iter() {
+        println!("upperbound: {}", upperbound);
+
+        let params = WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, MIN_BAL, *upperbound, rng)?;
+        let gm17_darkpool = params.keys().gm17_darkpool();
+        let balance = rng.gen_range(MIN_BAL..=*upperbound);
+        let blinding = Fr::rand(rng);
+        let v = Fr::from(upperbound - (balance - MIN_BAL));
+        let image = mimc(v, blinding, &gm17_darkpool.mimc_constants);
+
+        //memory held by the keys of all parties, measured as the bytes live while a single copy of all key pairs is shared by all parties,
+        //against the bytes live while every party holds a copy of all key pairs
+        let live_before = live_bytes();
+        let shared: Vec<_> = (0..party_amount).map(|idx| (params.keys().pk(idx).clone(), params.keys().pvk(idx).clone())).collect();
+        let held_shared = live_bytes() - live_before;
+        let copied: Vec<_> = (0..party_amount).map(|_| shared.clone()).collect();
+        let held_copied = live_bytes() - live_before - held_shared;
+        drop(copied);
+        drop(shared);
+
+        let mut total_time_elapsed_sign_cloned: u128 = 0;
+        let mut total_time_elapsed_sign_borrowed: u128 = 0;
+        let mut total_time_elapsed_verify_cloned: u128 = 0;
+        let mut total_time_elapsed_verify_borrowed: u128 = 0;
+        let mut total_allocated_cloned: usize = 0;
+        let mut total_allocated_borrowed: usize = 0;
+
+        //time a round: the signatures of all parties, and the verifications of a single party
+        for i in 0..sample_size {
+            println!("iteration {}", i+1);
+            let message = i.to_string();
+
+            //bytes allocated by the signatures and verifications of the round, which include the copies of the circuit and the keys
+            let allocated_before_cloned = allocated_bytes();
+            let start_time_sign_cloned = Instant::now();
+            let proofs: Vec<_> = (0..party_amount).map(|idx| gm17_darkpool.clone().prove(v, blinding, message.clone(), &params.keys().pk(idx).clone(), rng)).collect();
+            total_time_elapsed_sign_cloned += start_time_sign_cloned.elapsed().as_nanos();
+
+            let start_time_verify_cloned = Instant::now();
+            for (idx, proof) in proofs.iter().enumerate().skip(1) {
+                let _ = gm17_darkpool.clone().verify(image, message.clone(), &params.keys().pvk(idx).clone(), proof);
+            }
+            total_time_elapsed_verify_cloned += start_time_verify_cloned.elapsed().as_nanos();
+            total_allocated_cloned += allocated_bytes() - allocated_before_cloned;
+
+            let allocated_before_borrowed = allocated_bytes();
+            let start_time_sign_borrowed = Instant::now();
+            let _: Vec<_> = (0..party_amount).map(|idx| gm17_darkpool.prove(v, blinding, message.clone(), params.keys().pk(idx), rng)).collect();
+            total_time_elapsed_sign_borrowed += start_time_sign_borrowed.elapsed().as_nanos();
+
+            let start_time_verify_borrowed = Instant::now();
+            for (idx, proof) in proofs.iter().enumerate().skip(1) {
+                let _ = gm17_darkpool.verify(image, message.clone(), params.keys().pvk(idx), proof);
+            }
+            total_time_elapsed_verify_borrowed += start_time_verify_borrowed.elapsed().as_nanos();
+            total_allocated_borrowed += allocated_bytes() - allocated_before_borrowed;
+        }
+
+        let upperbound_exp = upperbound_log(*upperbound)?;
+
+        data.push(
+            (upperbound_exp.to_string(),
+            party_amount.to_string(),
+            held_copied.to_string(),
+            held_shared.to_string(),
+            (total_allocated_cloned / sample_size as usize).to_string(),
+            (total_allocated_borrowed / sample_size as usize).to_string(),
+            (total_time_elapsed_sign_cloned / sample_size).to_string(),
+            (total_time_elapsed_sign_borrowed / sample_size).to_string(),
+            (total_time_elapsed_verify_cloned / sample_size).to_string(),
+            (total_time_elapsed_verify_borrowed / sample_size).to_string(),
+        ));
+    }
+
+    let mut writer = WriterBuilder::new()
+    .delimiter(b',')
+    .from_writer(File::create("src/benchmarks/data/gm17_key_sharing_outputs.csv")?);
+
+    for (upperbound_exp,
+        party_amount,
+        held_copied,
+        held_shared,
+        allocated_cloned,
+        allocated_borrowed,
+        time_elapsed_sign_cloned,
+        time_elapsed_sign_borrowed,
+        time_elapsed_verify_cloned,
+        time_elapsed_verify_borrowed
+    ) in &data {
+        writer.write_record(
+            [upperbound_exp,
+            party_amount,
+            held_copied,
+            held_shared,
+            allocated_cloned,
+            allocated_borrowed,
+            time_elapsed_sign_cloned,
+            time_elapsed_sign_borrowed,
+            time_elapsed_verify_cloned,
+            time_elapsed_verify_borrowed
+            ])?;
+    }
+
+    writer.flush()?;
+
+    println!("#################Benchmark of GM17 key sharing finished#################");
+
+    Ok(())
+}
+
+//benchmark the key-exchanges with the rounds of the parties run on a single thread, and on all threads of the machine
+#[cfg(all(feature = "parallel", not(tarpaulin_include)))]
+pub fn benchmark_parallel(sample_size: u128, party_amounts: &Vec<usize>) -> Result<(), Box<dyn Error>> {
+
+    println!("#################Benchmark of parallel execution initiated#################");
+    let mut data = Vec::new();
+    let rng = &mut OsRng;
+    let thread_amounts = [1, rayon::current_num_threads()];
+
+    for party_amount in party_amounts {
+        println!("new party amount: {:?}", party_amount);
+
+        for upperbound in UPPERBOUNDS.iter() {
+            println!("upperbound: {}", upperbound);
+
+            let bp_params = Arc::new(WakeParams::bulletproofs(MIN_BAL, *upperbound)?);
+            let gm17_params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(*party_amount, MIN_BAL, *upperbound, rng)?);
+
+            for thread_amount in thread_amounts.iter() {
+                println!("threads: {}", thread_amount);
+                let pool = rayon::ThreadPoolBuilder::new().num_threads(*thread_amount).build()?;
+
+                let mut total_time_elapsed_compiler: u128 = 0;
+                let mut total_time_elapsed_optimized: u128 = 0;
+                let mut total_time_elapsed_gm17_compiler: u128 = 0;
+                let mut total_time_elapsed_gm17_optimized: u128 = 0;
+
+                for i in 0..sample_size {
+                    println!("iteration {}", i+1);
+                    let balances: Vec<u64> = (0..*party_amount).map(|_| rng.gen_range(MIN_BAL..=*upperbound)).collect();
+
+                    //all rayon work of a key-exchange, including arkworks proving and verification, runs on the threads of the pool
+                    pool.install(|| {
+                        let start_time_compiler = Instant::now();
+                        let _ = run_compiler_key_exchange(*party_amount, balances.to_vec(), &bp_params, rng).unwrap();
+                        total_time_elapsed_compiler += start_time_compiler.elapsed().as_nanos();
+
+                        let start_time_optimized = Instant::now();
+                        let _ = run_optimized_key_exchange(*party_amount, balances.to_vec(), &bp_params, rng).unwrap();
+                        total_time_elapsed_optimized += start_time_optimized.elapsed().as_nanos();
+
+                        let start_time_gm17_compiler = Instant::now();
+                        let _ = run_gm17_compiler_key_exchange(*party_amount, balances.to_vec(), &gm17_params, rng).unwrap();
+                        total_time_elapsed_gm17_compiler += start_time_gm17_compiler.elapsed().as_nanos();
+
+                        let start_time_gm17_optimized = Instant::now();
+                        let _ = run_gm17_optimized_key_exchange(*party_amount, balances.to_vec(), &gm17_params, rng).unwrap();
+                        total_time_elapsed_gm17_optimized += start_time_gm17_optimized.elapsed().as_nanos();
+                    });
+                }
+
+                let upperbound_exp = upperbound_log(*upperbound)?;
+
+                data.push(
+                    (upperbound_exp.to_string(),
+                    party_amount.to_string(),
+                    thread_amount.to_string(),
+                    (total_time_elapsed_compiler / sample_size).to_string(),
+                    (total_time_elapsed_optimized / sample_size).to_string(),
+                    (total_time_elapsed_gm17_compiler / sample_size).to_string(),
+                    (total_time_elapsed_gm17_optimized / sample_size).to_string(),
+                ));
+            }
+        }
+    }
+
+    let mut writer = WriterBuilder::new()
+    .delimiter(b',')
+    .from_writer(File::create("src/benchmarks/data/parallel_outputs.csv")?);
+
+    for (upperbound_exp,
+        party_amount,
+        thread_amount,
+        time_elapsed_compiler,
+        time_elapsed_optimized,
+        time_elapsed_gm17_compiler,
+        time_elapsed_gm17_optimized
+    ) in &data {
+        writer.write_record(
+            [upperbound_exp,
+            party_amount,
+            thread_amount,
+            time_elapsed_compiler,
+            time_elapsed_optimized,
+            time_elapsed_gm17_compiler,
+            time_elapsed_gm17_optimized
+            ])?;
+    }
+
+    writer.flush()?;
+
+    println!("#################Benchmark of parallel execution finished#################");
+
+    Ok(())
+}
@@ -1 +1,2 @@
-pub mod benchmarking;
\ No newline at end of file
+pub mod benchmarking;
+pub mod allocation;
\ No newline at end of file
@@ -10,12 +10,18 @@ time_compiler = [[] for _ in range(4)]
 time_optimized = [[] for _ in range(4)]
 time_gm17_compiler = [[] for _ in range(4)]
 time_gm17_optimized = [[] for _ in range(4)]
+time_marlin_compiler = [[] for _ in range(4)]
+time_marlin_optimized = [[] for _ in range(4)]
+time_gm17_aggregated = [[] for _ in range(4)]
 time_bd = [[] for _ in range(4)]
 
 time_pr_party_compiler = [[] for _ in range(4)]
 time_pr_party_optimized = [[] for _ in range(4)]
 time_per_party_gm17_compiler = [[] for _ in range(4)]
 time_per_party_gm17_optimized = [[] for _ in range(4)]
+time_per_party_marlin_compiler = [[] for _ in range(4)]
+time_per_party_marlin_optimized = [[] for _ in range(4)]
+time_per_party_gm17_aggregated = [[] for _ in range(4)]
 time_pr_party_bd = [[] for _ in range(4)]
 
 with open('data/optimized_and_compiler_output.csv','r') as csvfile:
@@ -34,6 +40,16 @@ with open('data/optimized_and_compiler_output.csv','r') as csvfile:
         time_per_party_gm17_compiler[i].append(float(row[9])/1e9)
         time_gm17_optimized[i].append(float(row[10])/1e9)
         time_per_party_gm17_optimized[i].append(float(row[11])/1e9)
+        #marlin columns are only present in outputs produced after the marlin backend was added
+        if len(row) > 12:
+            time_marlin_compiler[i].append(float(row[12])/1e9)
+            time_per_party_marlin_compiler[i].append(float(row[13])/1e9)
+            time_marlin_optimized[i].append(float(row[14])/1e9)
+            time_per_party_marlin_optimized[i].append(float(row[15])/1e9)
+        #aggregated gm17 columns are only present in outputs produced after aggregation was added
+        if len(row) > 16:
+            time_gm17_aggregated[i].append(float(row[16])/1e9)
+            time_per_party_gm17_aggregated[i].append(float(row[17])/1e9)
 
 title_strings = ["$2^{8}-1$","$2^{16}-1$","$2^{32}-1$","$2^{64}-1$"]
 
@@ -45,6 +61,11 @@ for i in range(4):
     plt.plot(parties, time_optimized[i], color = 'b', linestyle = 'solid', marker = 'x',label = "OPT BPRP")
     plt.plot(parties, time_gm17_compiler[i], color = 'g', linestyle = 'solid', marker = 'D',label = "COMP GM17")
     plt.plot(parties, time_gm17_optimized[i], color = 'y', linestyle = 'solid', marker = '^',label = "OPT GM17")
+    if time_marlin_compiler[i]:
+        plt.plot(parties, time_marlin_compiler[i], color = 'c', linestyle = 'solid', marker = 'v',label = "COMP MARLIN")
+        plt.plot(parties, time_marlin_optimized[i], color = 'm', linestyle = 'solid', marker = 'P',label = "OPT MARLIN")
+    if time_gm17_aggregated[i]:
+        plt.plot(parties, time_gm17_aggregated[i], color = 'k', linestyle = 'solid', marker = '*',label = "COMP GM17 AGGR")
     plt.plot(parties, time_bd[i], color = 'indigo', linestyle = 'solid', marker = 's', label = "BD")
 
     plt.xlabel('party amount')
@@ -62,6 +83,11 @@ for i in range(4):
     plt.plot(parties, time_pr_party_optimized[i], color = 'b', linestyle = 'solid', marker = 'x',label = "OPT BPRP")
     plt.plot(parties, time_per_party_gm17_compiler[i], color = 'g', linestyle = 'solid', marker = 'D',label = "COMP GM17")
     plt.plot(parties, time_per_party_gm17_optimized[i], color = 'y', linestyle = 'solid', marker = '^',label = "OPT GM17")
+    if time_per_party_marlin_compiler[i]:
+        plt.plot(parties, time_per_party_marlin_compiler[i], color = 'c', linestyle = 'solid', marker = 'v',label = "COMP MARLIN")
+        plt.plot(parties, time_per_party_marlin_optimized[i], color = 'm', linestyle = 'solid', marker = 'P',label = "OPT MARLIN")
+    if time_per_party_gm17_aggregated[i]:
+        plt.plot(parties, time_per_party_gm17_aggregated[i], color = 'k', linestyle = 'solid', marker = '*',label = "COMP GM17 AGGR")
     plt.plot(parties, time_pr_party_bd[i], color = 'indigo', linestyle = 'solid', marker = 's', label = "BD")
     plt.xlabel('party amount')
     plt.ylabel('seconds / party amount')
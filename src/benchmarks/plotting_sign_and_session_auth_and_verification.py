@@ -17,6 +17,11 @@ time_gm17_comp_ver = []
 time_gm17_opt_sign_and_session_auth = []
 time_gm17_opt_ver = []
 
+time_marlin_comp_sign = []
+time_marlin_comp_ver = []
+time_marlin_opt_sign_and_session_auth = []
+time_marlin_opt_ver = []
+
 with open('data/sign_and_verify_outputs.csv','r') as csvfile:
     lines = csv.reader(csvfile, delimiter=',')
     for row in lines:
@@ -28,12 +33,20 @@ with open('data/sign_and_verify_outputs.csv','r') as csvfile:
         time_gm17_comp_ver.append(float(row[6])/1e6)
         time_gm17_opt_sign_and_session_auth.append(float(row[7])/1e6)
         time_gm17_opt_ver.append(float(row[8])/1e6)
+        #marlin columns are only present in outputs produced after the marlin backend was added
+        if len(row) > 9:
+            time_marlin_comp_sign.append(float(row[9])/1e6)
+            time_marlin_comp_ver.append(float(row[10])/1e6)
+            time_marlin_opt_sign_and_session_auth.append(float(row[11])/1e6)
+            time_marlin_opt_ver.append(float(row[12])/1e6)
 
 barWidth = 0.125
 br1 = np.arange(len(bounds))
 br2 = [x + barWidth for x in br1]
 br3 = [x + 2*barWidth for x in br1]
 br4 = [x + 3*barWidth for x in br1]
+br5 = [x + 4*barWidth for x in br1]
+br6 = [x + 5*barWidth for x in br1]
 
 #plot sign and session auth (BPRP and GM17)
 plt.bar(br1, time_comp_sign, color ='r', width = barWidth,
@@ -44,6 +57,11 @@ plt.bar(br3, time_gm17_comp_sign, color ='g', width = barWidth,
         edgecolor ='grey', label ='COMP GM17')
 plt.bar(br4, time_gm17_opt_sign_and_session_auth, color ='y', width = barWidth,
         edgecolor ='grey', label ='OPT GM17')
+if time_marlin_comp_sign:
+    plt.bar(br5, time_marlin_comp_sign, color ='c', width = barWidth,
+            edgecolor ='grey', label ='COMP MARLIN')
+    plt.bar(br6, time_marlin_opt_sign_and_session_auth, color ='m', width = barWidth,
+            edgecolor ='grey', label ='OPT MARLIN')
  
 plt.xlabel('Upperbounds')
 plt.ylabel('Time in milliseconds (ms)')
@@ -64,6 +82,11 @@ plt.bar(br3, time_gm17_comp_ver, color ='g', width = barWidth,
         edgecolor ='grey', label ='COMP GM17')
 plt.bar(br4, time_gm17_opt_ver, color ='y', width = barWidth,
         edgecolor ='grey', label ='OPT GM17')
+if time_marlin_comp_ver:
+    plt.bar(br5, time_marlin_comp_ver, color ='c', width = barWidth,
+            edgecolor ='grey', label ='COMP MARLIN')
+    plt.bar(br6, time_marlin_opt_ver, color ='m', width = barWidth,
+            edgecolor ='grey', label ='OPT MARLIN')
 
 plt.xlabel('Upperbounds')
 plt.ylabel('Time in milliseconds (ms)')
@@ -112,6 +135,21 @@ plt.ylabel('Signature verification time in milliseconds (ms)')
 plt.savefig(f'plots/sign_vs_verification_times/sign_vs_verification_plot_gm17.png', dpi=300)
 plt.clf()
 
+#Marlin
+if time_marlin_comp_sign:
+        for i in range(4):
+                plt.scatter(time_marlin_comp_sign[i], time_marlin_comp_ver[i], color='c', label = 'COMP MARLIN', marker=markers[i], s=markersize)
+                plt.scatter(time_marlin_opt_sign_and_session_auth[i], time_marlin_opt_ver[i], color='m', label = 'OPT MARLIN', marker=markers[i], s=markersize)
+        plt.plot(time_marlin_comp_sign, time_marlin_comp_ver, color='c', zorder=0)
+        plt.plot(time_marlin_opt_sign_and_session_auth, time_marlin_opt_ver, color='m', zorder=0)
+        cyan_patch = mpatches.Patch(color='cyan', label='COMP MARLIN')
+        magenta_patch = mpatches.Patch(color='magenta', label='OPT MARLIN')
+        plt.legend(loc='center', handles=[cyan_patch, magenta_patch, dot, cross, diamond, triangle])
+        plt.xlabel('Signature creation time in milliseconds (ms)')
+        plt.ylabel('Signature verification time in milliseconds (ms)')
+        plt.savefig(f'plots/sign_vs_verification_times/sign_vs_verification_plot_marlin.png', dpi=300)
+        plt.clf()
+
 
 from sklearn.linear_model import LinearRegression
 from sklearn.metrics import mean_squared_error
@@ -6,6 +6,8 @@ comp_sizes = []
 opti_sizes = []
 gm17_comp_sizes = []
 gm17_opti_sizes = []
+marlin_comp_sizes = []
+marlin_opti_sizes = []
 
 with open('data/signature_sizes.csv','r') as csvfile:
     lines = csv.reader(csvfile, delimiter=',')
@@ -14,12 +16,18 @@ with open('data/signature_sizes.csv','r') as csvfile:
      opti_sizes.append(float(row[1]))
      gm17_comp_sizes.append(float(row[2]))
      gm17_opti_sizes.append(float(row[3]))
+     if len(row) > 4:
+      marlin_comp_sizes.append(float(row[4]))
+      marlin_opti_sizes.append(float(row[5]))
 
 
 plt.plot(bounds, comp_sizes, color = 'r', linestyle = 'solid', marker = 'o',label = "COMP BPRP")
 plt.plot(bounds, opti_sizes, color = 'b', linestyle = 'solid', marker = 'x',label = "OPT BPRP")
 plt.plot(bounds, gm17_comp_sizes, color = 'g', linestyle = 'solid', marker = 'D',label = "COMP GM17")
 plt.plot(bounds, gm17_opti_sizes, color = 'indigo', linestyle = 'solid', marker = '^',label = "OPT GM17")
+if marlin_comp_sizes:
+    plt.plot(bounds, marlin_comp_sizes, color = 'c', linestyle = 'solid', marker = 'v',label = "COMP MARLIN")
+    plt.plot(bounds, marlin_opti_sizes, color = 'm', linestyle = 'solid', marker = 'P',label = "OPT MARLIN")
 plt.xlabel('Upperbounds')
 plt.ylabel('size in bytes')
 plt.grid()
@@ -1,4 +1,6 @@
 pub mod utility;
+pub mod params;
 pub mod protocols;
 pub mod benchmarks;
-pub mod proof_systems;
\ No newline at end of file
+pub mod proof_systems;
+pub mod test_vectors;
\ No newline at end of file
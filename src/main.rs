@@ -1,5 +1,12 @@
 use std::error::Error;
-use wake_implementation::benchmarks::benchmarking::{benchmark_sizes, benchmark_protocol, benchmark_signature_and_session_authentication};
+use wake_implementation::benchmarks::benchmarking::{benchmark_sizes, benchmark_protocol, benchmark_signature_and_session_authentication, benchmark_gm17_curves, benchmark_gm17_key_sharing};
+#[cfg(feature = "parallel")]
+use wake_implementation::benchmarks::benchmarking::benchmark_parallel;
+use wake_implementation::benchmarks::allocation::CountingAllocator;
+
+//counts the allocations of the benchmarks, such that the GM17 key sharing benchmark measures the memory held and allocated
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 #[cfg(not(tarpaulin_include))]
 fn main() -> Result<(), Box<dyn Error>> {
@@ -10,6 +17,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     let _ = benchmark_sizes();
     let _ = benchmark_protocol(protocol_sample_size, &party_amounts)?;
     let _ = benchmark_signature_and_session_authentication(sign_and_session_auth_sample_size)?;
+    let _ = benchmark_gm17_curves(sign_and_session_auth_sample_size)?;
+    let _ = benchmark_gm17_key_sharing(protocol_sample_size, 25)?;
+    #[cfg(feature = "parallel")]
+    benchmark_parallel(protocol_sample_size, &party_amounts)?;
 
     Ok(())
 }
\ No newline at end of file
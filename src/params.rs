@@ -0,0 +1,431 @@
+use crate::{
+    proof_systems::{
+        proof_system_gm17::Gm17Darkpool,
+        proof_system_marlin::{MarlinDarkpool, MarlinProvingKey, MarlinVerifyingKey},
+        range_proof::RangeProofStruct,
+        r1cs_proof::R1csProofStruct
+    },
+    utility::{setup_gm17, setup_marlin, upperbound_log}
+};
+use std::{error::Error, sync::Arc};
+use ark_ec::PairingEngine;
+use ark_ff::PrimeField;
+use ark_gm17::{prepare_verifying_key, PreparedVerifyingKey, ProvingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use bulletproofs::PedersenGens;
+use rand::{CryptoRng, RngCore};
+use curve25519_dalek_ng::ristretto::RistrettoPoint;
+
+///
+/// File holding the public parameters shared by all parties of a WAKE session
+///
+//version of the serialization format of WakeParams
+const PARAMS_VERSION: u8 = 2;
+
+///
+/// Keys and circuit of the proof system underlying a WAKE protocol
+///
+pub trait WakeKeys: Sized {
+    //identifier of the proof system, and the curve it is instantiated over
+    fn ciphersuite() -> String;
+
+    //check that the keys are consistent with each other and with the bounds
+    fn validate(&self, min_bal: u64, upperbound: u64) -> Result<(), Box<dyn Error>>;
+
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<(), Box<dyn Error>>;
+
+    fn read(bytes: &mut &[u8], min_bal: u64, upperbound: u64) -> Result<Self, Box<dyn Error>>;
+}
+
+///
+/// Public parameters of a WAKE session: the ciphersuite, the bounds on the balances, the generators of the key exchange,
+/// and the keys and circuit of the underlying proof system. Parameters are validated once when created or deserialized,
+/// after which they are shared by reference, or by Arc between parties
+///
+#[derive(Clone)]
+pub struct WakeParams<K: WakeKeys> {
+    ciphersuite: String,
+    min_bal: u64,
+    upperbound: u64,
+    pc_gens: PedersenGens,
+    keys: K,
+}
+
+impl<K: WakeKeys> WakeParams<K> {
+    pub fn new(min_bal: u64, upperbound: u64, keys: K) -> Result<WakeParams<K>, Box<dyn Error>> {
+        validate_bounds(min_bal, upperbound)?;
+        keys.validate(min_bal, upperbound)?;
+
+        Ok(WakeParams { ciphersuite: K::ciphersuite(), min_bal, upperbound, pc_gens: PedersenGens::default(), keys })
+    }
+
+    pub fn ciphersuite(&self) -> &str {
+        &self.ciphersuite
+    }
+
+    pub fn min_bal(&self) -> u64 {
+        self.min_bal
+    }
+
+    pub fn upperbound(&self) -> u64 {
+        self.upperbound
+    }
+
+    pub fn pc_gens(&self) -> &PedersenGens {
+        &self.pc_gens
+    }
+
+    //generator of the Burmester-Desmedt key exchange
+    pub fn g(&self) -> RistrettoPoint {
+        self.pc_gens.B
+    }
+
+    pub fn keys(&self) -> &K {
+        &self.keys
+    }
+
+    ///
+    /// serialize the parameters as: version, ciphersuite, minimum balance, upperbound and keys
+    /// the generators are derived deterministically, and therefore not serialized
+    ///
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut bytes = vec![PARAMS_VERSION];
+        self.ciphersuite.serialize(&mut bytes)?;
+        self.min_bal.serialize(&mut bytes)?;
+        self.upperbound.serialize(&mut bytes)?;
+        self.keys.write(&mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    ///
+    /// deserialize and validate parameters, which must be of the ciphersuite of K
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<WakeParams<K>, Box<dyn Error>> {
+        let (version, mut reader) = bytes.split_first().ok_or("parameters are empty")?;
+        if *version != PARAMS_VERSION {
+            return Err(format!("unsupported parameters version {}", version).into());
+        }
+
+        let ciphersuite = String::deserialize(&mut reader)?;
+        if ciphersuite != K::ciphersuite() {
+            return Err(format!("expected ciphersuite {}, found {}", K::ciphersuite(), ciphersuite).into());
+        }
+        let min_bal = u64::deserialize(&mut reader)?;
+        let upperbound = u64::deserialize(&mut reader)?;
+        validate_bounds(min_bal, upperbound)?;
+        let keys = K::read(&mut reader, min_bal, upperbound)?;
+        if !reader.is_empty() {
+            return Err("trailing bytes after parameters".into());
+        }
+
+        WakeParams::new(min_bal, upperbound, keys)
+    }
+}
+
+impl WakeParams<BulletproofsKeys> {
+    pub fn bulletproofs(min_bal: u64, upperbound: u64) -> Result<WakeParams<BulletproofsKeys>, Box<dyn Error>> {
+        WakeParams::bulletproofs_with_signature_backend(min_bal, upperbound, SignatureBackend::RangeProof)
+    }
+
+    //parameters of which the parties sign their messages with the given Bulletproofs proof
+    pub fn bulletproofs_with_signature_backend(min_bal: u64, upperbound: u64, signature_backend: SignatureBackend) -> Result<WakeParams<BulletproofsKeys>, Box<dyn Error>> {
+        validate_bounds(min_bal, upperbound)?;
+        WakeParams::new(min_bal, upperbound, BulletproofsKeys::with_signature_backend(min_bal, upperbound, signature_backend))
+    }
+}
+
+impl<E: PairingEngine> WakeParams<Gm17Keys<E>> {
+    //run the GM17 setup, generating a key pair for every party
+    pub fn gm17<R: RngCore + CryptoRng>(party_amount: usize, min_bal: u64, upperbound: u64, rng: &mut R) -> Result<WakeParams<Gm17Keys<E>>, Box<dyn Error>> {
+        validate_bounds(min_bal, upperbound)?;
+        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17::<E, R>(party_amount, upperbound, rng)?;
+        WakeParams::new(min_bal, upperbound, Gm17Keys::new(gm17_darkpool, pvk_list, pk_list))
+    }
+}
+
+impl<E: PairingEngine> WakeParams<MarlinKeys<E>> {
+    //run the Marlin universal setup, and derive the key pair shared by all parties
+    pub fn marlin<R: RngCore + CryptoRng>(min_bal: u64, upperbound: u64, rng: &mut R) -> Result<WakeParams<MarlinKeys<E>>, Box<dyn Error>> {
+        validate_bounds(min_bal, upperbound)?;
+        let (marlin_darkpool, vk, pk) = setup_marlin::<E, R>(upperbound, rng)?;
+        WakeParams::new(min_bal, upperbound, MarlinKeys::new(marlin_darkpool, vk, pk))
+    }
+}
+
+//check if upperbound is among the valid options 2^8, 2^16, 2^32, 2^64, and that the minimum balance is within it
+fn validate_bounds(min_bal: u64, upperbound: u64) -> Result<(), Box<dyn Error>> {
+    if upperbound != u8::MAX as u64 && upperbound != u16::MAX as u64 && upperbound != u32::MAX as u64 && upperbound != u64::MAX {
+        return Err(format!("upperbound was not among the valid values: {}, {}, {}, {}\n was: {}", u8::MAX, u16::MAX, u32::MAX, u64::MAX, upperbound).into());
+    }
+    if min_bal > upperbound {
+        return Err("minimum balance exceeds the upperbound".into());
+    }
+    Ok(())
+}
+
+//name of the curve of a pairing engine, identified by the size of its base field
+fn curve_name<E: PairingEngine>() -> String {
+    match <E::Fq as PrimeField>::size_in_bits() {
+        254 => "BN254".to_string(),
+        377 => "BLS12-377".to_string(),
+        381 => "BLS12-381".to_string(),
+        bits => format!("{}BIT", bits),
+    }
+}
+
+///
+/// Proof the parties of the Bulletproofs protocols sign their messages with: the range proof, or the R1CS proof of the same darkpool relation
+/// all parties of a session must select the same backend, as signatures of the other backend are rejected
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SignatureBackend {
+    #[default]
+    RangeProof,
+    R1cs,
+}
+
+impl SignatureBackend {
+    fn to_byte(self) -> u8 {
+        match self {
+            SignatureBackend::RangeProof => 0,
+            SignatureBackend::R1cs => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<SignatureBackend, Box<dyn Error>> {
+        match byte {
+            0 => Ok(SignatureBackend::RangeProof),
+            1 => Ok(SignatureBackend::R1cs),
+            _ => Err(format!("unknown signature backend {}", byte).into()),
+        }
+    }
+}
+
+///
+/// Bulletproofs keys: the statements of the range proof and the R1CS proof, holding their generators, and the signature backend
+/// no trusted setup is involved, so the keys are fully determined by the bounds and the backend
+///
+#[derive(Clone)]
+pub struct BulletproofsKeys {
+    range_proof_struct: RangeProofStruct,
+    r1cs_proof_struct: R1csProofStruct,
+    signature_backend: SignatureBackend,
+}
+
+impl BulletproofsKeys {
+    pub fn new(min_bal: u64, upperbound: u64) -> BulletproofsKeys {
+        BulletproofsKeys::with_signature_backend(min_bal, upperbound, SignatureBackend::RangeProof)
+    }
+
+    pub fn with_signature_backend(min_bal: u64, upperbound: u64, signature_backend: SignatureBackend) -> BulletproofsKeys {
+        BulletproofsKeys {
+            range_proof_struct: RangeProofStruct::new(min_bal, upperbound),
+            r1cs_proof_struct: R1csProofStruct::darkpool(upperbound),
+            signature_backend,
+        }
+    }
+
+    pub fn signature_backend(&self) -> SignatureBackend {
+        self.signature_backend
+    }
+
+    pub fn range_proof_struct(&self) -> &RangeProofStruct {
+        &self.range_proof_struct
+    }
+
+    pub fn r1cs_proof_struct(&self) -> &R1csProofStruct {
+        &self.r1cs_proof_struct
+    }
+}
+
+impl WakeKeys for BulletproofsKeys {
+    fn ciphersuite() -> String {
+        "WAKE-BULLETPROOFS-RISTRETTO255".to_string()
+    }
+
+    fn validate(&self, min_bal: u64, upperbound: u64) -> Result<(), Box<dyn Error>> {
+        if self.range_proof_struct.bounds() != (min_bal, upperbound) {
+            return Err("range proof statement does not match the bounds".into());
+        }
+        Ok(())
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<(), Box<dyn Error>> {
+        self.signature_backend.to_byte().serialize(&mut *bytes)?;
+        Ok(())
+    }
+
+    fn read(bytes: &mut &[u8], min_bal: u64, upperbound: u64) -> Result<BulletproofsKeys, Box<dyn Error>> {
+        let signature_backend = SignatureBackend::from_byte(u8::deserialize(&mut *bytes)?)?;
+        Ok(BulletproofsKeys::with_signature_backend(min_bal, upperbound, signature_backend))
+    }
+}
+
+///
+/// GM17 keys: the darkpool circuit, and a key pair per party, indexed by party index
+/// the key pairs are reference counted, such that parties sharing a key pair hold a single copy of it
+///
+#[derive(Clone)]
+pub struct Gm17Keys<E: PairingEngine> {
+    gm17_darkpool: Gm17Darkpool<E>,
+    pvk_list: Vec<Arc<PreparedVerifyingKey<E>>>,
+    pk_list: Vec<Arc<ProvingKey<E>>>,
+}
+
+impl<E: PairingEngine> Gm17Keys<E> {
+    pub fn new(gm17_darkpool: Gm17Darkpool<E>, pvk_list: Vec<PreparedVerifyingKey<E>>, pk_list: Vec<ProvingKey<E>>) -> Gm17Keys<E> {
+        Gm17Keys {
+            gm17_darkpool,
+            pvk_list: pvk_list.into_iter().map(Arc::new).collect(),
+            pk_list: pk_list.into_iter().map(Arc::new).collect()
+        }
+    }
+
+    //a single key pair, shared by all parties, as required for aggregating their proofs
+    pub fn shared(gm17_darkpool: Gm17Darkpool<E>, pvk: PreparedVerifyingKey<E>, pk: ProvingKey<E>, party_amount: usize) -> Gm17Keys<E> {
+        let (pvk, pk) = (Arc::new(pvk), Arc::new(pk));
+        Gm17Keys {
+            gm17_darkpool,
+            pvk_list: (0..party_amount).map(|_| pvk.clone()).collect(),
+            pk_list: (0..party_amount).map(|_| pk.clone()).collect()
+        }
+    }
+
+    pub fn gm17_darkpool(&self) -> &Gm17Darkpool<E> {
+        &self.gm17_darkpool
+    }
+
+    pub fn pvk_list(&self) -> &[Arc<PreparedVerifyingKey<E>>] {
+        &self.pvk_list
+    }
+
+    pub fn pvk(&self, idx: usize) -> &PreparedVerifyingKey<E> {
+        &self.pvk_list[idx]
+    }
+
+    pub fn pk(&self, idx: usize) -> &ProvingKey<E> {
+        &self.pk_list[idx]
+    }
+
+    pub fn party_amount(&self) -> usize {
+        self.pk_list.len()
+    }
+}
+
+impl<E: PairingEngine> WakeKeys for Gm17Keys<E> {
+    fn ciphersuite() -> String {
+        format!("WAKE-GM17-{}", curve_name::<E>())
+    }
+
+    fn validate(&self, _min_bal: u64, upperbound: u64) -> Result<(), Box<dyn Error>> {
+        if self.gm17_darkpool.upperbound_exp() != upperbound_log(upperbound)? {
+            return Err("GM17 circuit does not match the upperbound".into());
+        }
+        if self.pk_list.is_empty() || self.pvk_list.len() != self.pk_list.len() {
+            return Err("expected a GM17 key pair for every party".into());
+        }
+        //public inputs of the darkpool circuit: aux and image
+        if self.pvk_list.iter().zip(self.pk_list.iter()).any(|(pvk, pk)| pvk.vk != pk.vk || pvk.vk.query.len() != 3) {
+            return Err("GM17 verifying key does not match proving key".into());
+        }
+        Ok(())
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<(), Box<dyn Error>> {
+        self.gm17_darkpool.mimc_constants.serialize(&mut *bytes)?;
+        //the verifying keys are contained in the proving keys
+        (self.pk_list.len() as u64).serialize(&mut *bytes)?;
+        for pk in &self.pk_list {
+            pk.serialize(&mut *bytes)?;
+        }
+        Ok(())
+    }
+
+    fn read(bytes: &mut &[u8], _min_bal: u64, upperbound: u64) -> Result<Gm17Keys<E>, Box<dyn Error>> {
+        let mimc_constants = Vec::<E::Fr>::deserialize(&mut *bytes)?;
+        let gm17_darkpool = Gm17Darkpool::from_mimc_constants(upperbound as usize, mimc_constants)?;
+        //a key pair equal to the one of the previous party is shared with it, rather than held twice
+        let party_amount = u64::deserialize(&mut *bytes)?;
+        let mut pk_list: Vec<Arc<ProvingKey<E>>> = Vec::new();
+        let mut pvk_list: Vec<Arc<PreparedVerifyingKey<E>>> = Vec::new();
+        for _ in 0..party_amount {
+            let pk = ProvingKey::<E>::deserialize(&mut *bytes)?;
+            match (pk_list.last(), pvk_list.last()) {
+                (Some(prev_pk), Some(prev_pvk)) if **prev_pk == pk => {
+                    pk_list.push(prev_pk.clone());
+                    pvk_list.push(prev_pvk.clone());
+                }
+                _ => {
+                    pvk_list.push(Arc::new(prepare_verifying_key::<E>(&pk.vk)));
+                    pk_list.push(Arc::new(pk));
+                }
+            }
+        }
+
+        Ok(Gm17Keys { gm17_darkpool, pvk_list, pk_list })
+    }
+}
+
+///
+/// Marlin keys: the darkpool circuit, and the key pair derived from the universal SRS, shared by all parties
+///
+#[derive(Clone)]
+pub struct MarlinKeys<E: PairingEngine> {
+    marlin_darkpool: MarlinDarkpool<E>,
+    vk: MarlinVerifyingKey<E>,
+    pk: MarlinProvingKey<E>,
+}
+
+impl<E: PairingEngine> MarlinKeys<E> {
+    pub fn new(marlin_darkpool: MarlinDarkpool<E>, vk: MarlinVerifyingKey<E>, pk: MarlinProvingKey<E>) -> MarlinKeys<E> {
+        MarlinKeys { marlin_darkpool, vk, pk }
+    }
+
+    pub fn marlin_darkpool(&self) -> &MarlinDarkpool<E> {
+        &self.marlin_darkpool
+    }
+
+    pub fn vk(&self) -> &MarlinVerifyingKey<E> {
+        &self.vk
+    }
+
+    pub fn pk(&self) -> &MarlinProvingKey<E> {
+        &self.pk
+    }
+}
+
+impl<E: PairingEngine> WakeKeys for MarlinKeys<E> {
+    fn ciphersuite() -> String {
+        format!("WAKE-MARLIN-{}", curve_name::<E>())
+    }
+
+    fn validate(&self, _min_bal: u64, upperbound: u64) -> Result<(), Box<dyn Error>> {
+        if self.marlin_darkpool.upperbound_exp() != upperbound_log(upperbound)? {
+            return Err("Marlin circuit does not match the upperbound".into());
+        }
+        //the verifying key is contained in the proving key
+        let (mut vk_bytes, mut pk_vk_bytes) = (Vec::new(), Vec::new());
+        self.vk.serialize(&mut vk_bytes)?;
+        self.pk.index_vk.serialize(&mut pk_vk_bytes)?;
+        if vk_bytes != pk_vk_bytes {
+            return Err("Marlin verifying key does not match proving key".into());
+        }
+        Ok(())
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<(), Box<dyn Error>> {
+        self.marlin_darkpool.mimc_constants.serialize(&mut *bytes)?;
+        self.pk.serialize(&mut *bytes)?;
+        Ok(())
+    }
+
+    fn read(bytes: &mut &[u8], _min_bal: u64, upperbound: u64) -> Result<MarlinKeys<E>, Box<dyn Error>> {
+        let mimc_constants = Vec::<E::Fr>::deserialize(&mut *bytes)?;
+        let marlin_darkpool = MarlinDarkpool::from_mimc_constants(upperbound as usize, mimc_constants)?;
+        let pk = MarlinProvingKey::<E>::deserialize(&mut *bytes)?;
+
+        Ok(MarlinKeys { marlin_darkpool, vk: pk.index_vk.clone(), pk })
+    }
+}
+
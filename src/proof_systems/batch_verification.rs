@@ -0,0 +1,148 @@
+use crate::utility::map_items;
+use crate::proof_systems::{
+    discrete_log_knowledege_proof::{DiscreteLogKnowledgeProof, DiscreteLogKnowledgeProofStruct},
+    range_proof::RangeProofStruct
+};
+use bulletproofs::{PedersenGens, RangeProof};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::{CompressedRistretto, RistrettoPoint}, traits::{IsIdentity, VartimeMultiscalarMul}};
+use lazy_static::lazy_static;
+use rand::{CryptoRng, RngCore};
+use sha3::{Shake256, digest::{ExtendableOutput, Update, XofReader}};
+
+//largest bitsize of a range proof, which the G and H generators are derived for
+const MAX_BITSIZE: usize = 64;
+
+lazy_static!{
+    //the G and H generators of BulletproofGens::new(64, 1), which bulletproofs does not expose, but derives deterministically
+    static ref BP_G: Vec<RistrettoPoint> = generators_chain(b'G', MAX_BITSIZE);
+    static ref BP_H: Vec<RistrettoPoint> = generators_chain(b'H', MAX_BITSIZE);
+}
+
+//reproduces the GeneratorsChain of bulletproofs for party 0, labelled with either 'G' or 'H'
+fn generators_chain(label: u8, amount: usize) -> Vec<RistrettoPoint> {
+    let mut shake = Shake256::default();
+    shake.update(b"GeneratorsChain");
+    shake.update([label, 0, 0, 0, 0]);
+    let mut reader = shake.finalize_xof();
+
+    (0..amount).map(|_| {
+        let mut uniform_bytes = [0u8; 64];
+        reader.read(&mut uniform_bytes);
+        RistrettoPoint::from_uniform_bytes(&uniform_bytes)
+    }).collect()
+}
+
+///
+/// the verification equation of a single proof, as scalars of a multiscalar multiplication which equals the identity for a valid proof
+/// the proof's own points are kept seperately, while the scalars of the fixed generators can be summed over all proofs of a batch
+///
+pub(crate) struct VerificationEquation {
+    pub(crate) points: Vec<(Scalar, CompressedRistretto)>,
+    pub(crate) b: Scalar,
+    pub(crate) b_blinding: Scalar,
+    pub(crate) g: Vec<Scalar>,
+    pub(crate) h: Vec<Scalar>,
+}
+
+///
+/// Verifies the range proofs and discrete logarithm knowledge proofs received in a round with one multiscalar multiplication
+/// every proof is added together with the index of the party that sent it, such that a failing batch can be traced back to its sender(s)
+///
+pub struct BatchVerifier<'a> {
+    range_proof_struct: &'a RangeProofStruct,
+    range_proofs: Vec<(usize, RangeProof, CompressedRistretto, String)>,
+    discrete_log_knowledge_proofs: Vec<(usize, DiscreteLogKnowledgeProofStruct, DiscreteLogKnowledgeProof, String)>,
+}
+
+impl<'a> BatchVerifier<'a> {
+    ///
+    /// initialize an empty batch, for range proofs of the given statement
+    ///
+    pub fn new(range_proof_struct: &'a RangeProofStruct) -> BatchVerifier<'a> {
+        BatchVerifier { range_proof_struct, range_proofs: Vec::new(), discrete_log_knowledge_proofs: Vec::new() }
+    }
+
+    pub fn add_range_proof(&mut self, idx: usize, proof: RangeProof, committed_value: CompressedRistretto, auxilliary_challenge_string: String) {
+        self.range_proofs.push((idx, proof, committed_value, auxilliary_challenge_string));
+    }
+
+    pub fn add_discrete_log_knowledge_proof(&mut self, idx: usize, proof_struct: DiscreteLogKnowledgeProofStruct, proof: DiscreteLogKnowledgeProof, auxilliary_challenge_string: String) {
+        self.discrete_log_knowledge_proofs.push((idx, proof_struct, proof, auxilliary_challenge_string));
+    }
+
+    ///
+    /// Verification function
+    /// checks all proofs at once, and if the batch fails, falls back to checking every proof on its own
+    /// range proofs are then checked with verify_single of bulletproofs, and discrete log knowledge proofs with their own verify, such that blame never depends on the replayed transcript
+    /// the random weights of the batch are drawn from the given rng, and do not affect the outcome for valid proofs
+    /// outputs the sorted indices of all parties with at least one invalid proof
+    ///
+    pub fn verify<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), Vec<usize>> {
+        let equations = self.equations(rng);
+
+        if equations.iter().all(|(_, equation)| equation.is_some()) && check(equations.iter().filter_map(|(_, equation)| equation.as_ref()), rng) {
+            return Ok(())
+        }
+
+        //identify the invalid proofs
+        let range_proofs_valid = map_items(&self.range_proofs, |(idx, proof, committed_value, aux)| (*idx, self.range_proof_struct.verify(proof.clone(), *committed_value, aux.clone())));
+        let discrete_log_proofs_valid = map_items(&self.discrete_log_knowledge_proofs, |(idx, proof_struct, proof, aux)| (*idx, proof_struct.verify(*proof, aux.clone())));
+        let mut invalid_idxs: Vec<usize> = range_proofs_valid.into_iter()
+            .chain(discrete_log_proofs_valid)
+            .filter(|(_, valid)| !valid)
+            .map(|(idx, _)| idx)
+            .collect();
+        invalid_idxs.sort_unstable();
+        invalid_idxs.dedup();
+
+        Err(invalid_idxs)
+    }
+
+    //verification equations of all range proofs followed by all discrete log knowledge proofs of the batch, or None for proofs that are malformed, or whose challenge does not match
+    fn equations<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Vec<(usize, Option<VerificationEquation>)> {
+        let range_proof_equations = self.range_proofs.iter()
+            .map(|(idx, proof, committed_value, aux)| (*idx, self.range_proof_struct.verification_equation(proof, *committed_value, aux, rng)));
+        let discrete_log_equations = self.discrete_log_knowledge_proofs.iter()
+            .map(|(idx, proof_struct, proof, aux)| (*idx, proof_struct.verification_equation(*proof, aux)));
+
+        range_proof_equations.chain(discrete_log_equations).collect()
+    }
+}
+
+//weigh every equation by a random scalar, and check that their sum holds with a single multiscalar multiplication
+fn check<'b, R: RngCore + CryptoRng>(equations: impl Iterator<Item = &'b VerificationEquation>, rng: &mut R) -> bool {
+    let pc_gens = PedersenGens::default();
+
+    let mut scalars: Vec<Scalar> = Vec::new();
+    let mut points: Vec<Option<RistrettoPoint>> = Vec::new();
+    let mut b = Scalar::zero();
+    let mut b_blinding = Scalar::zero();
+    let mut g = vec![Scalar::zero(); MAX_BITSIZE];
+    let mut h = vec![Scalar::zero(); MAX_BITSIZE];
+
+    for equation in equations {
+        let weight = Scalar::random(rng);
+        for (scalar, point) in &equation.points {
+            scalars.push(weight * scalar);
+            points.push(point.decompress());
+        }
+        b += weight * equation.b;
+        b_blinding += weight * equation.b_blinding;
+        for (acc, scalar) in g.iter_mut().zip(equation.g.iter()) {
+            *acc += weight * scalar;
+        }
+        for (acc, scalar) in h.iter_mut().zip(equation.h.iter()) {
+            *acc += weight * scalar;
+        }
+    }
+
+    let mega_check = RistrettoPoint::optional_multiscalar_mul(
+        scalars.into_iter().chain([b, b_blinding]).chain(g).chain(h),
+        points.into_iter()
+            .chain([Some(pc_gens.B), Some(pc_gens.B_blinding)])
+            .chain(BP_G.iter().map(|p| Some(*p)))
+            .chain(BP_H.iter().map(|p| Some(*p)))
+    );
+
+    mega_check.is_some_and(|p| p.is_identity())
+}
@@ -0,0 +1,147 @@
+use ark_ff::Field;
+use ark_relations::{lc, ns, r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable}};
+
+pub(crate) const MIMC_ROUNDS: usize = 322;
+
+///
+/// struct for proving knowledge of witness for the Darkpool transaction relation
+/// 
+pub(crate) struct DarkpoolCircuit<'a, F: Field> {
+    pub(crate) aux: Option<F>,
+    pub(crate) v_bits: &'a Vec<Option<F>>,
+    pub(crate) r: Option<F>,
+    pub(crate) constants: &'a [F],
+    pub(crate) upperbound: usize,
+}
+
+///
+/// darkpool circuit for inspecting a witness: v and the image are allocated with the given values, rather than the ones
+/// computed from v_bits and the MiMC rounds, such that a witness the prover would never produce can be checked against the constraints
+/// 
+pub(crate) struct InspectedDarkpoolCircuit<'a, F: Field> {
+    pub(crate) circuit: DarkpoolCircuit<'a, F>,
+    pub(crate) v: F,
+    pub(crate) image: F,
+}
+
+impl<'a, F: Field> ConstraintSynthesizer<F> for DarkpoolCircuit<'a, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.synthesize(cs, None, None)
+    }
+}
+
+impl<'a, F: Field> ConstraintSynthesizer<F> for InspectedDarkpoolCircuit<'a, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.circuit.synthesize(cs, Some(self.v), Some(self.image))
+    }
+}
+
+impl<'a, F: Field> DarkpoolCircuit<'a, F> {
+    //if v or image are set, they are allocated with these values rather than the computed ones
+    fn synthesize(self, cs: ConstraintSystemRef<F>, v_override: Option<F>, image_override: Option<F>) -> Result<(), SynthesisError> {
+        assert_eq!(self.constants.len(), MIMC_ROUNDS);
+
+        //Define aux as input variable for circuit
+        cs.new_input_variable(|| self.aux.ok_or(SynthesisError::AssignmentMissing))?;
+
+        // Allocate the second component of the preimage.
+        let mut r_value = self.r;
+        let mut r = cs.new_witness_variable(|| r_value.ok_or(SynthesisError::AssignmentMissing))?;
+        
+        let mut v_bits_val: Vec<Option<F>> = self.v_bits.clone();
+        v_bits_val.reverse();
+
+        let mut curr_exp = F::from(1 as u64);
+        let mut v_value = Some(F::zero());
+        let mut v_bits_sum = lc!();
+        for i in 0..self.upperbound {
+            let ns = ns!(cs, "bit decomposition");
+            let cs = ns.cs();
+
+            //***********************Assert v_bits are bits***********************
+            //extract bit of v: b_i
+            let b_i = v_bits_val[i];
+            let b_i_var =
+                cs.new_witness_variable(|| b_i.ok_or(SynthesisError::AssignmentMissing))?;
+
+            //enforce b_i*(1-b_i)=0
+            cs.enforce_constraint(lc!() + b_i_var, lc!() + Variable::One - b_i_var, lc!())?;
+
+            //****************************accumulate 2^i*b_i****************************
+            v_bits_sum += (curr_exp, b_i_var);
+            v_value = v_value.zip(b_i).map(|(mut e, b)| {
+                e.add_assign(curr_exp * b);
+                e
+            });
+
+            //update current exponent value
+            curr_exp.double_in_place();
+        }
+
+        //v has now been reconstructed from its bits. enforce the preimage of the image to be exactly this value
+        if v_override.is_some() {
+            v_value = v_override;
+        }
+        let mut v = cs.new_witness_variable(|| v_value.ok_or(SynthesisError::AssignmentMissing))?;
+        {
+            let ns = ns!(cs, "reconstruction");
+            let cs = ns.cs();
+            cs.enforce_constraint(v_bits_sum, lc!() + Variable::One, lc!() + v)?;
+        }
+
+        for i in 0..MIMC_ROUNDS {
+            // v, r := r + (v + Ci)^3, v
+            let ns = ns!(cs, "round");
+            let cs = ns.cs();
+
+            // tmp = (v + Ci)^2
+            let tmp_value = (v_value).map(|mut e| {
+                e.add_assign(&self.constants[i]);
+                e.square_in_place();
+                e
+            });
+            let tmp =
+                cs.new_witness_variable(|| tmp_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+            cs.enforce_constraint(
+                lc!() + v + (self.constants[i], Variable::One),
+                lc!() + v + (self.constants[i], Variable::One),
+                lc!() + tmp,
+            )?;
+
+            // new_v = r + (v + Ci)^3
+            // new_v = r + tmp * (v + Ci)
+            // new_v - r = tmp * (v + Ci)
+            let new_v_value = v_value.map(|mut e| {
+                e.add_assign(&self.constants[i]);
+                e.mul_assign(&tmp_value.unwrap());
+                e.add_assign(&r_value.unwrap());
+                e
+            });
+
+            let new_v = if i == (MIMC_ROUNDS - 1) {
+                // This is the last round, v is our image and so
+                // we allocate a public input.
+                cs.new_input_variable(|| image_override.or(new_v_value).ok_or(SynthesisError::AssignmentMissing))?
+            } else {
+                cs.new_witness_variable(|| new_v_value.ok_or(SynthesisError::AssignmentMissing))?
+            };
+
+            cs.enforce_constraint(
+                lc!() + tmp,
+                lc!() + v + (self.constants[i], Variable::One),
+                lc!() + new_v - r,
+            )?;
+
+            // r = v
+            r = v;
+            r_value = v_value;
+
+            // v = new_v
+            v = new_v;
+            v_value = new_v_value;
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
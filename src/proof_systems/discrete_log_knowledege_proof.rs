@@ -1,7 +1,9 @@
 use bulletproofs::PedersenGens;
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::CompressedRistretto};
-use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
 use crate::utility::{ristretto_to_string, scalar_to_string, compressed_ristretto_to_string, hash_string_to_scalar};
+use super::batch_verification::VerificationEquation;
 
 ///
 /// holds values considered as values in the proof
@@ -20,6 +22,14 @@ impl DiscreteLogKnowledgeProof {
     pub fn to_string(self) -> String{
         return compressed_ristretto_to_string(self.statement) + &scalar_to_string(self.challenge) + &scalar_to_string(self.response); 
     }
+
+    //serializes the proof as the statement, challenge and response, 32 bytes each
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.statement.to_bytes().to_vec();
+        bytes.extend_from_slice(self.challenge.as_bytes());
+        bytes.extend_from_slice(self.response.as_bytes());
+        bytes
+    }
 }
 
 ///
@@ -38,19 +48,29 @@ impl DiscreteLogKnowledgeProofStruct{
         return DiscreteLogKnowledgeProofStruct { statement }
     }
     
+    ///
+    /// Fiat-Shamir challenge H(g || statement || commitment || aux)
+    /// the commitment of the prover is hashed, as otherwise anyone could pick the response first and solve for a commitment passing verification
+    /// 
+    fn challenge(self, commitment: CompressedRistretto, auxilliary_challenge_string: &str) -> Scalar {
+        let g = PedersenGens::default().B;
+        hash_string_to_scalar(ristretto_to_string(g) + &compressed_ristretto_to_string(self.statement) + &compressed_ristretto_to_string(commitment) + auxilliary_challenge_string)
+    }
+
     ///
     /// Proving function
     /// outputs a proof
     /// 
-    pub fn prove(self, witness: Scalar, auxilliary_challenge_string: String) -> DiscreteLogKnowledgeProof {
+    pub fn prove<R: RngCore + CryptoRng>(self, witness: Scalar, auxilliary_challenge_string: String, rng: &mut R) -> DiscreteLogKnowledgeProof {
         let g = PedersenGens::default().B;
-        let rng = &mut OsRng;
-        let r = Scalar::random(rng);
+        let mut r = Scalar::random(rng);
         
         let statement = (g * r).compress();
-        let challenge = hash_string_to_scalar(ristretto_to_string(g) + &compressed_ristretto_to_string(self.statement) + &auxilliary_challenge_string);
+        let challenge = self.challenge(statement, &auxilliary_challenge_string);
         
         let response = r + challenge * witness;
+        //the nonce reveals the witness together with the response, so it is wiped
+        r.zeroize();
         
         return DiscreteLogKnowledgeProof { statement, challenge, response };
     }
@@ -62,7 +82,7 @@ impl DiscreteLogKnowledgeProofStruct{
     pub fn verify(self, proof: DiscreteLogKnowledgeProof,  auxilliary_challenge_string: String) -> bool {
         let g = PedersenGens::default().B;
 
-        let self_computed_challenge = hash_string_to_scalar(ristretto_to_string(g) + &compressed_ristretto_to_string(self.statement) + &auxilliary_challenge_string);
+        let self_computed_challenge = self.challenge(proof.statement, &auxilliary_challenge_string);
 
         let challenge_verification = self_computed_challenge == proof.challenge;
         
@@ -70,4 +90,23 @@ impl DiscreteLogKnowledgeProofStruct{
         
         return challenge_verification & verification;
     }
-}
\ No newline at end of file
+
+    ///
+    /// Verification equation g^response * statement^(-1) * self.statement^(-challenge) = 1, for the BatchVerifier
+    /// outputs None if the challenge was not computed correctly
+    /// 
+    pub(crate) fn verification_equation(self, proof: DiscreteLogKnowledgeProof, auxilliary_challenge_string: &str) -> Option<VerificationEquation> {
+        let self_computed_challenge = self.challenge(proof.statement, auxilliary_challenge_string);
+        if self_computed_challenge != proof.challenge {
+            return None;
+        }
+
+        Some(VerificationEquation {
+            points: vec![(-Scalar::one(), proof.statement), (-proof.challenge, self.statement)],
+            b: proof.response,
+            b_blinding: Scalar::zero(),
+            g: Vec::new(),
+            h: Vec::new(),
+        })
+    }
+}
@@ -1,3 +1,9 @@
+pub mod batch_verification;
+mod darkpool_circuit;
 pub mod discrete_log_knowledege_proof;
 pub mod proof_system_gm17;
+pub mod proof_system_gm17_aggregation;
+pub mod proof_system_gm17_pedersen;
+pub mod proof_system_marlin;
+pub mod r1cs_proof;
 pub mod range_proof;
\ No newline at end of file
@@ -1,23 +1,25 @@
-use crate::utility::{upperbound_log, hash_string_to_fr, fr_to_bits};
+use crate::utility::{upperbound_log, hash_to_field, fr_to_bits, map_items, GM17_AUX_DOMAIN};
 use std::error::Error;
-use rand::{rngs::OsRng, Rng};
-use ark_bls12_377::{Bls12_377, Fr, FrParameters, Parameters};
-use ark_ec::bls12::Bls12;
-use ark_ff::{Field, Fp256};
+use rand::{CryptoRng, RngCore};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
 use ark_gm17::{create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, ProvingKey, PreparedVerifyingKey, Proof};
-use ark_relations::{lc, ns, r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable}};
-
-const MIMC_ROUNDS: usize = 322;
+use ark_relations::r1cs::{ConstraintLayer, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, TracingMode};
+use super::darkpool_circuit::{DarkpoolCircuit, InspectedDarkpoolCircuit, MIMC_ROUNDS};
+use tracing_subscriber::layer::SubscriberExt;
 
+///
+/// GM17 proof for the darkpool transaction relation, over any pairing-friendly curve
+/// the circuit is defined over the scalar field of the curve
+/// 
 #[derive(Clone)]
-pub struct Gm17Darkpool {
-    pub mimc_constants: Vec<Fp256<FrParameters>>,
+pub struct Gm17Darkpool<E: PairingEngine> {
+    pub mimc_constants: Vec<E::Fr>,
     upperbound: usize,
-    rng: OsRng,
 }
 
-impl Gm17Darkpool {
-    pub fn new(upperbound: usize) -> Result<Gm17Darkpool, Box<dyn Error>> {
+impl<E: PairingEngine> Gm17Darkpool<E> {
+    pub fn new<R: RngCore + CryptoRng>(upperbound: usize, rng: &mut R) -> Result<Gm17Darkpool<E>, Box<dyn Error>> {
         let upperbound_exp = upperbound_log(upperbound as u64);
 
         match upperbound_exp {
@@ -25,25 +27,37 @@ impl Gm17Darkpool {
             Err(error) => {return Err(error)}
         }
 
-        let rng = &mut OsRng;
-        let mimc_constants = (0..MIMC_ROUNDS).map(|_| rng.gen()).collect::<Vec<_>>();
+        let mimc_constants = (0..MIMC_ROUNDS).map(|_| E::Fr::rand(rng)).collect::<Vec<_>>();
         return Ok(Gm17Darkpool { 
             mimc_constants,
-            rng: *rng,
             upperbound: upperbound_exp.unwrap()
         })
     }
 
-    pub fn setup(mut self) -> (ProvingKey<Bls12<ark_bls12_377::Parameters>>, PreparedVerifyingKey<Bls12<Parameters>>) {
+    //rebuild the darkpool circuit from previously generated MiMC constants, such that it matches existing keys
+    pub fn from_mimc_constants(upperbound: usize, mimc_constants: Vec<E::Fr>) -> Result<Gm17Darkpool<E>, Box<dyn Error>> {
+        if mimc_constants.len() != MIMC_ROUNDS {
+            return Err(format!("expected {} MiMC constants, found {}", MIMC_ROUNDS, mimc_constants.len()).into());
+        }
+
+        Ok(Gm17Darkpool { mimc_constants, upperbound: upperbound_log(upperbound as u64)? })
+    }
+
+    //logarithm of the upperbound the circuit is built for
+    pub fn upperbound_exp(&self) -> usize {
+        self.upperbound
+    }
+
+    pub fn setup<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (ProvingKey<E>, PreparedVerifyingKey<E>) {
         // Create parameters for our circuit
-        let mut dummy_v_bits_vec: Vec<Option<Fr>> = Vec::new();
+        let mut dummy_v_bits_vec: Vec<Option<E::Fr>> = Vec::new();
         for _ in 0..self.upperbound {
-            dummy_v_bits_vec.push(Some(Fr::from(1 as u64)));
+            dummy_v_bits_vec.push(Some(E::Fr::from(1 as u64)));
         }
-        let dummy_r: Fr = Fr::from(1 as u64);
+        let dummy_r = E::Fr::from(1 as u64);
 
         let pk = {
-            let c = DarkpoolCircuit::<Fr> {
+            let c = DarkpoolCircuit::<E::Fr> {
                 aux: None,
                 v_bits: &dummy_v_bits_vec,
                 r: Some(dummy_r),
@@ -51,7 +65,7 @@ impl Gm17Darkpool {
                 upperbound: self.upperbound,
             };
 
-            generate_random_parameters::<Bls12_377, _, _>(c, &mut self.rng).unwrap()
+            generate_random_parameters::<E, _, _>(c, rng).unwrap()
         };
 
         // Prepare the verification key (for proof verification)
@@ -60,9 +74,10 @@ impl Gm17Darkpool {
         return (pk, pvk)
     }
 
-    pub fn prove(mut self, v: Fr, r: Fr, aux: String, pk: ProvingKey<Bls12<ark_bls12_377::Parameters>>) -> Proof<Bls12<Parameters>> {
-        let aux_fr = hash_string_to_fr(aux);
-        let v_bits = fr_to_bits(v);
+    //proves against a borrowed proving key, such that neither the key nor the MiMC constants are copied per proof
+    pub fn prove<R: RngCore + CryptoRng>(&self, v: E::Fr, r: E::Fr, aux: String, pk: &ProvingKey<E>, rng: &mut R) -> Proof<E> {
+        let aux_fr = hash_to_field(GM17_AUX_DOMAIN, aux.as_bytes());
+        let v_bits = fr_to_bits(v, self.upperbound);
         // Create an instance of our circuit (with the witness)
         let c = DarkpoolCircuit {
             aux: Some(aux_fr),
@@ -73,158 +88,171 @@ impl Gm17Darkpool {
         };
 
         // Create a gm17 proof with our parameters.
-        let proof = create_random_proof(c, &pk, &mut self.rng).unwrap();
+        let proof = create_random_proof(c, pk, rng).unwrap();
 
         return proof
     }
 
-    pub fn verify(self, image: Fr, aux: String, pvk: PreparedVerifyingKey<Bls12<Parameters>>, proof: Proof<Bls12<Parameters>>) -> bool {
+    pub fn verify(&self, image: E::Fr, aux: String, pvk: &PreparedVerifyingKey<E>, proof: &Proof<E>) -> bool {
         return verify_proof(
-            &pvk,
-            &proof,
-            &[hash_string_to_fr(aux), image]
+            pvk,
+            proof,
+            &[hash_to_field(GM17_AUX_DOMAIN, aux.as_bytes()), image]
         )
         .unwrap();
     }
-}
 
-///
-/// struct for proving knowledge of witness for the Darkpool transaction relation
-/// 
-struct DarkpoolCircuit<'a, F: Field> {
-    aux: Option<F>,
-    v_bits: &'a Vec<Option<F>>,
-    r: Option<F>,
-    constants: &'a [F],
-    upperbound: usize,
-}
+    ///
+    /// Batch verification function
+    /// checks all proofs with a single multi-pairing: both GM17 verification equations of every proof are raised to random exponents and multiplied,
+    /// and the pairings sharing a verifying key are merged. If the batch fails, every proof is verified on its own
+    /// the random exponents are drawn from the given rng
+    /// outputs the sorted indices of all parties with an invalid proof
+    ///
+    pub fn batch_verify<R: RngCore + CryptoRng>(&self, batch: &[Gm17BatchItem<E>], rng: &mut R) -> Result<(), Vec<usize>> {
+        if self.batch_check(batch, rng) {
+            return Ok(())
+        }
 
-impl<'a, F: Field> ConstraintSynthesizer<F> for DarkpoolCircuit<'a, F> {
-    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
-        assert_eq!(self.constants.len(), MIMC_ROUNDS);
+        //identify the invalid proofs
+        let mut invalid_idxs: Vec<usize> = map_items(batch, |item| (item.idx, verify_proof(item.pvk, &item.proof, &[hash_to_field(GM17_AUX_DOMAIN, item.aux.as_bytes()), item.image]).unwrap_or(false)))
+            .into_iter()
+            .filter(|(_, valid)| !valid)
+            .map(|(idx, _)| idx)
+            .collect();
+        invalid_idxs.sort_unstable();
+        invalid_idxs.dedup();
 
-        //Define aux as input variable for circuit
-        cs.new_input_variable(|| self.aux.ok_or(SynthesisError::AssignmentMissing))?;
+        Err(invalid_idxs)
+    }
 
-        // Allocate the second component of the preimage.
-        let mut r_value = self.r;
-        let mut r = cs.new_witness_variable(|| r_value.ok_or(SynthesisError::AssignmentMissing))?;
-        
-        let mut v_bits_val: Vec<F> = Vec::new();
-        for v_bit in self.v_bits {
-            v_bits_val.push(v_bit.unwrap());
+    //for every proof with random r, s: e(A + alpha, B + beta)^-r * e(alpha, beta)^r * e(psi, gamma)^r * e(C, H)^r = 1 and e(A, gamma)^s * e(gamma, B)^-s = 1
+    //all G1 elements paired with the same G2 element of a verifying key are summed, such that only e(A + alpha, B + beta) is paired per proof
+    //the random exponents are local to the verifier, and do not affect the outcome for valid proofs
+    fn batch_check<R: RngCore + CryptoRng>(&self, batch: &[Gm17BatchItem<E>], rng: &mut R) -> bool {
+        let mut groups: Vec<BatchGroup<E>> = Vec::new();
+        let mut pairs: Vec<(E::G1Prepared, E::G2Prepared)> = Vec::new();
+
+        for item in batch {
+            if item.pvk.query.len() != 3 {
+                return false;
+            }
+            let (r, s) = (E::Fr::rand(rng), E::Fr::rand(rng));
+
+            let inputs = [hash_to_field(GM17_AUX_DOMAIN, item.aux.as_bytes()), item.image];
+            let mut psi = item.pvk.query[0].into_projective();
+            for (input, query) in inputs.iter().zip(item.pvk.query.iter().skip(1)) {
+                psi += &query.mul(*input);
+            }
+
+            let mut a_alpha = item.proof.a.into_projective();
+            a_alpha.add_assign_mixed(&item.pvk.g_alpha);
+            let mut b_beta = item.proof.b.into_projective();
+            b_beta.add_assign_mixed(&item.pvk.h_beta);
+            pairs.push(((-a_alpha.into_affine().mul(r)).into_affine().into(), b_beta.into_affine().into()));
+
+            let group_idx = match groups.iter().position(|group| group.pvk.vk == item.pvk.vk) {
+                Some(group_idx) => group_idx,
+                None => {
+                    groups.push(BatchGroup {
+                        pvk: item.pvk,
+                        r_sum: E::Fr::zero(),
+                        gamma_acc: E::G1Projective::zero(),
+                        c_acc: E::G1Projective::zero(),
+                        b_acc: E::G2Projective::zero(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[group_idx];
+            group.r_sum += r;
+            group.gamma_acc += psi.mul(r.into_repr()) + item.proof.a.mul(s);
+            group.c_acc += item.proof.c.mul(r);
+            group.b_acc += item.proof.b.mul(s);
         }
-        v_bits_val.reverse();
-
-        let mut curr_exp = F::from(1 as u64);
-        let mut curr_sum = F::from(1 as u64);
-        let mut curr_sum_var = cs.new_witness_variable(|| Some(curr_exp).ok_or(SynthesisError::AssignmentMissing))?;
-        for i in 0..self.upperbound {
-            //***********************Assert v_bits are bits***********************
-            //extract bit of v: b_i
-            let b_i = v_bits_val[i];
-            let b_i_var =
-                cs.new_witness_variable(|| Some(b_i).ok_or(SynthesisError::AssignmentMissing))?;
-
-            //define new variable: a_i = 1 - b_i
-            let a_i = Some(F::from(1 as u64)).map(|mut e| {
-                e.sub_assign(&b_i);
-                e
-            });
-            let a_i_var =
-                cs.new_witness_variable(|| a_i.ok_or(SynthesisError::AssignmentMissing))?;
-
-            //enforce b_i*a_i=0
-            cs.enforce_constraint(lc!() + b_i_var, lc!() + a_i_var, lc!())?;
-
-            //****************************check v_bits v in bits****************************
-            //compute 2^i*b_i
-            let curr_val = Some(curr_exp).map(|mut e| {
-                e.mul_assign(b_i);
-                e
-            });
-            let curr_val_var =
-                cs.new_witness_variable(|| curr_val.ok_or(SynthesisError::AssignmentMissing))?;
-
-            //compute new sum
-            let new_curr_sum = Some(curr_sum).map(|mut e| {
-                e.add_assign(curr_val.unwrap());
-                e
-            });
-            let new_curr_sum_var =
-                cs.new_witness_variable(|| new_curr_sum.ok_or(SynthesisError::AssignmentMissing))?;
-            cs.enforce_constraint(
-                lc!() + curr_sum_var,
-                lc!() + Variable::One,
-                lc!() + new_curr_sum_var - curr_val_var,
-            )?;
-
-            //update sum and current exponent value
-            curr_sum = new_curr_sum.unwrap();
-            curr_sum_var = new_curr_sum_var;
-            curr_exp.add_assign(curr_exp);
+
+        for group in groups {
+            pairs.push((group.pvk.g_alpha.mul(group.r_sum).into_affine().into(), group.pvk.h_beta.into()));
+            pairs.push((group.gamma_acc.into_affine().into(), group.pvk.h_gamma_pc.clone()));
+            pairs.push((group.c_acc.into_affine().into(), group.pvk.h_pc.clone()));
+            pairs.push((group.pvk.g_gamma_pc.clone(), (-group.b_acc).into_affine().into()));
         }
-    
-        let mut v_value = Some(curr_sum).map(|mut e| {
-            e.sub_assign(F::from(1 as u64));
-            e
-        });
-        //v has now been reconstructed, assuming it was indeed bits of the right size. now merely compute the image
-        let mut v = cs.new_witness_variable(|| v_value.ok_or(SynthesisError::AssignmentMissing))?;
-
-        for i in 0..MIMC_ROUNDS {
-            // v, r := r + (v + Ci)^3, v
-            let ns = ns!(cs, "round");
-            let cs = ns.cs();
-
-            // tmp = (v + Ci)^2
-            let tmp_value = (v_value).map(|mut e| {
-                e.add_assign(&self.constants[i]);
-                e.square_in_place();
-                e
-            });
-            let tmp =
-                cs.new_witness_variable(|| tmp_value.ok_or(SynthesisError::AssignmentMissing))?;
-
-            cs.enforce_constraint(
-                lc!() + v + (self.constants[i], Variable::One),
-                lc!() + v + (self.constants[i], Variable::One),
-                lc!() + tmp,
-            )?;
-
-            // new_v = r + (v + Ci)^3
-            // new_v = r + tmp * (v + Ci)
-            // new_v - r = tmp * (v + Ci)
-            let new_v_value = v_value.map(|mut e| {
-                e.add_assign(&self.constants[i]);
-                e.mul_assign(&tmp_value.unwrap());
-                e.add_assign(&r_value.unwrap());
-                e
-            });
-
-            let new_v = if i == (MIMC_ROUNDS - 1) {
-                // This is the last round, v is our image and so
-                // we allocate a public input.
-                cs.new_input_variable(|| new_v_value.ok_or(SynthesisError::AssignmentMissing))?
-            } else {
-                cs.new_witness_variable(|| new_v_value.ok_or(SynthesisError::AssignmentMissing))?
-            };
 
-            cs.enforce_constraint(
-                lc!() + tmp,
-                lc!() + v + (self.constants[i], Variable::One),
-                lc!() + new_v - r,
-            )?;
+        E::final_exponentiation(&E::miller_loop(pairs.iter())).is_some_and(|result| result.is_one())
+    }
 
-            // r = v
-            r = v;
-            r_value = v_value;
+    //synthesize the darkpool circuit for the witness (v, r) against the public inputs (aux, image)
+    //constraints are traced while synthesizing, such that they can be identified by their namespace
+    pub fn synthesize(&self, v: E::Fr, r: E::Fr, aux: String, image: E::Fr) -> Result<ConstraintSystemRef<E::Fr>, SynthesisError> {
+        let v_bits = fr_to_bits(v, self.upperbound);
+        let c = InspectedDarkpoolCircuit {
+            circuit: DarkpoolCircuit {
+                aux: Some(hash_to_field(GM17_AUX_DOMAIN, aux.as_bytes())),
+                v_bits: &v_bits,
+                r: Some(r),
+                constants: &self.mimc_constants,
+                upperbound: self.upperbound,
+            },
+            v,
+            image,
+        };
 
-            // v = new_v
-            v = new_v;
-            v_value = new_v_value;
-        }
+        let mut layer = ConstraintLayer::default();
+        layer.mode = TracingMode::OnlyConstraints;
+        let subscriber = tracing_subscriber::Registry::default().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let cs = ConstraintSystem::<E::Fr>::new_ref();
+            c.generate_constraints(cs.clone())?;
+            Ok(cs)
+        })
+    }
 
-        Ok(())
+    //synthesize the darkpool circuit for a witness, and report on its size and satisfiability
+    //useful for finding out why a witness is rejected, as verification only outputs a bool
+    pub fn inspect(&self, v: E::Fr, r: E::Fr, aux: String, image: E::Fr) -> Result<CircuitInspection, SynthesisError> {
+        let cs = self.synthesize(v, r, aux, image)?;
+
+        Ok(CircuitInspection {
+            num_constraints: cs.num_constraints(),
+            num_instance_variables: cs.num_instance_variables(),
+            num_witness_variables: cs.num_witness_variables(),
+            is_satisfied: cs.is_satisfied()?,
+            first_unsatisfied: cs.which_is_unsatisfied()?,
+        })
     }
-}
\ No newline at end of file
+}
+
+///
+/// a GM17 darkpool proof to be verified in a batch, together with its public inputs, the verifying key it was produced for,
+/// and the index of the party that sent it
+/// 
+pub struct Gm17BatchItem<'a, E: PairingEngine> {
+    pub idx: usize,
+    pub image: E::Fr,
+    pub aux: String,
+    pub pvk: &'a PreparedVerifyingKey<E>,
+    pub proof: Proof<E>,
+}
+
+//sums over the proofs of a batch sharing a verifying key: of r, of r * psi + s * A, of r * C, and of s * B
+struct BatchGroup<'a, E: PairingEngine> {
+    pvk: &'a PreparedVerifyingKey<E>,
+    r_sum: E::Fr,
+    gamma_acc: E::G1Projective,
+    c_acc: E::G1Projective,
+    b_acc: E::G2Projective,
+}
+
+///
+/// report of synthesizing the darkpool circuit for a given witness
+/// the instance variables include the constant one-variable, and first_unsatisfied holds the namespace path of the first unsatisfied constraint
+/// 
+#[derive(Clone, Debug)]
+pub struct CircuitInspection {
+    pub num_constraints: usize,
+    pub num_instance_variables: usize,
+    pub num_witness_variables: usize,
+    pub is_satisfied: bool,
+    pub first_unsatisfied: Option<String>,
+}
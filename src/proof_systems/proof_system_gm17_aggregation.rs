@@ -0,0 +1,530 @@
+use crate::utility::{hash_to_field, GM17_AUX_DOMAIN};
+use std::error::Error;
+use rand::{CryptoRng, RngCore};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_gm17::{PreparedVerifyingKey, Proof};
+use ark_serialize::CanonicalSerialize;
+
+//domain separation tag for the Fiat-Shamir challenges of the aggregation
+const AGGREGATION_DOMAIN: &[u8] = b"WAKE-GM17-AGGREGATION-V1";
+
+///
+/// Structured reference string for aggregating GM17 darkpool proofs, SnarkPack-style (https://eprint.iacr.org/2021/529.pdf)
+/// holds powers of two secrets a and b in both groups, which serve as the commitment keys of the inner pairing product arguments,
+/// and as KZG keys for opening the folded commitment keys. As for the GM17 setup, a and b are toxic waste
+///
+#[derive(Clone)]
+pub struct AggregationSRS<E: PairingEngine> {
+    //g^(a^i) and g^(b^i) for i < 2 * max_proofs
+    g_a: Vec<E::G1Affine>,
+    g_b: Vec<E::G1Affine>,
+    //h^(a^i) and h^(b^i) for i <= max_proofs
+    h_a: Vec<E::G2Affine>,
+    h_b: Vec<E::G2Affine>,
+    max_proofs: usize,
+}
+
+///
+/// cross terms of a single round of the inner pairing product argument, for the commitments T_ab, U_ab, T_c, U_c and the pairing product Z,
+/// as well as for the weighted sums of A, B and C. Left terms are folded in with x^-1, right terms with x
+///
+#[derive(Clone)]
+pub struct AggregationRound<E: PairingEngine> {
+    gt_left: [E::Fqk; 5],
+    gt_right: [E::Fqk; 5],
+    a_left: E::G1Affine,
+    a_right: E::G1Affine,
+    b_left: E::G2Affine,
+    b_right: E::G2Affine,
+    c_left: E::G1Affine,
+    c_right: E::G1Affine,
+}
+
+///
+/// a single proof for a list of GM17 darkpool proofs under the same verifying key, of size logarithmic in the amount of proofs
+/// holds commitments to the vectors of proof elements A, B and C, their random linear combinations under challenge r,
+/// the rounds of the inner pairing product argument, its final elements, and KZG openings of the final commitment keys
+///
+#[derive(Clone)]
+pub struct AggregateProof<E: PairingEngine> {
+    com_ab: (E::Fqk, E::Fqk),
+    com_c: (E::Fqk, E::Fqk),
+    z_ab: E::Fqk,
+    a_r: E::G1Affine,
+    b_r: E::G2Affine,
+    c_r: E::G1Affine,
+    rounds: Vec<AggregationRound<E>>,
+    a_final: E::G1Affine,
+    b_final: E::G2Affine,
+    c_final: E::G1Affine,
+    v1_final: E::G2Affine,
+    v2_final: E::G2Affine,
+    w1_final: E::G1Affine,
+    w2_final: E::G1Affine,
+    v1_opening: E::G2Affine,
+    v2_opening: E::G2Affine,
+    w1_opening: E::G1Affine,
+    w2_opening: E::G1Affine,
+}
+
+impl<E: PairingEngine> AggregateProof<E> {
+    pub fn serialized_size(&self) -> usize {
+        let gt_size = self.z_ab.serialized_size();
+        let g1_size = self.a_r.serialized_size();
+        let g2_size = self.b_r.serialized_size();
+
+        //5 GT, 8 G1 and 6 G2 elements, and 10 GT, 4 G1 and 2 G2 elements per round
+        5 * gt_size + 8 * g1_size + 6 * g2_size + self.rounds.len() * (10 * gt_size + 4 * g1_size + 2 * g2_size)
+    }
+}
+
+//Fiat-Shamir transcript, every challenge hashes all prior messages
+struct AggregationTranscript {
+    bytes: Vec<u8>,
+}
+
+impl AggregationTranscript {
+    fn new() -> AggregationTranscript {
+        AggregationTranscript { bytes: Vec::new() }
+    }
+
+    fn append<T: CanonicalSerialize>(&mut self, item: &T) {
+        item.serialize(&mut self.bytes).expect("serializing into a vector does not fail");
+    }
+
+    fn challenge<F: PrimeField>(&mut self) -> F {
+        let challenge: F = hash_to_field(AGGREGATION_DOMAIN, &self.bytes);
+        self.append(&challenge);
+        challenge
+    }
+
+    fn append_round<E: PairingEngine>(&mut self, round: &AggregationRound<E>) {
+        for (left, right) in round.gt_left.iter().zip(round.gt_right.iter()) {
+            self.append(left);
+            self.append(right);
+        }
+        self.append(&round.a_left);
+        self.append(&round.a_right);
+        self.append(&round.b_left);
+        self.append(&round.b_right);
+        self.append(&round.c_left);
+        self.append(&round.c_right);
+    }
+}
+
+impl<E: PairingEngine> AggregationSRS<E> {
+    ///
+    /// generate an SRS supporting the aggregation of up to max_proofs proofs
+    /// a and b are sampled in-process, so whoever runs the setup knows them. Knowing a breaks the binding of the commitment keys,
+    /// such that the party running the setup can forge an aggregate which verifies for proofs that do not. The setup must thus be run
+    /// by a party that every verifier trusts to erase a and b, or replaced by a multi-party ceremony
+    ///
+    pub fn setup<R: RngCore + CryptoRng>(max_proofs: usize, rng: &mut R) -> Result<AggregationSRS<E>, Box<dyn Error>> {
+        if max_proofs == 0 {
+            return Err("an aggregation SRS must support at least one proof".into());
+        }
+        let max_proofs = max_proofs.next_power_of_two();
+
+        let a = E::Fr::rand(rng);
+        let b = E::Fr::rand(rng);
+
+        Ok(AggregationSRS {
+            g_a: powers::<E::G1Affine>(a, 2 * max_proofs),
+            g_b: powers::<E::G1Affine>(b, 2 * max_proofs),
+            h_a: powers::<E::G2Affine>(a, max_proofs + 1),
+            h_b: powers::<E::G2Affine>(b, max_proofs + 1),
+            max_proofs,
+        })
+    }
+
+    pub fn max_proofs(&self) -> usize {
+        self.max_proofs
+    }
+
+    ///
+    /// Aggregation function, run by a relaying party
+    /// aggregates GM17 darkpool proofs under the verifying key pvk, for the public inputs (aux, image) of every proof
+    ///
+    pub fn aggregate(&self, pvk: &PreparedVerifyingKey<E>, proofs: &[Proof<E>], images: &[E::Fr], auxs: &[String]) -> Result<AggregateProof<E>, Box<dyn Error>> {
+        let n = self.padded_amount(proofs.len(), images.len(), auxs.len()).ok_or("invalid amount of proofs or public inputs")?;
+
+        //proof elements, padded with the identity, which trivially satisfies the aggregated equations
+        let mut a: Vec<E::G1Affine> = proofs.iter().map(|proof| proof.a).collect();
+        let mut b: Vec<E::G2Affine> = proofs.iter().map(|proof| proof.b).collect();
+        let mut c: Vec<E::G1Affine> = proofs.iter().map(|proof| proof.c).collect();
+        a.resize(n, E::G1Affine::zero());
+        b.resize(n, E::G2Affine::zero());
+        c.resize(n, E::G1Affine::zero());
+
+        //commitment keys v = h^(a^i), w = g^(a^(n + i)), and likewise for b
+        let mut v1 = self.h_a[..n].to_vec();
+        let mut v2 = self.h_b[..n].to_vec();
+        let mut w1 = self.g_a[n..2 * n].to_vec();
+        let mut w2 = self.g_b[n..2 * n].to_vec();
+
+        let com_ab = (
+            inner_pairing_product::<E>(&a, &v1) * inner_pairing_product::<E>(&w1, &b),
+            inner_pairing_product::<E>(&a, &v2) * inner_pairing_product::<E>(&w2, &b),
+        );
+        let com_c = (inner_pairing_product::<E>(&c, &v1), inner_pairing_product::<E>(&c, &v2));
+
+        let mut transcript = AggregationTranscript::new();
+        let r = initial_challenge(&mut transcript, pvk, &com_ab, &com_c, images, auxs);
+        let r_inv = r.inverse().ok_or("challenge r is zero")?;
+
+        //rescale B by r^i, and w by r^-i, such that the commitment to B is unchanged
+        let mut s = powers_of(r, n);
+        let r_inv_powers = powers_of(r_inv, n);
+        b = scale_points(&b, &s);
+        w1 = scale_points(&w1, &r_inv_powers);
+        w2 = scale_points(&w2, &r_inv_powers);
+        let mut t = vec![E::Fr::one(); n];
+
+        let z_ab = inner_pairing_product::<E>(&a, &b);
+        let a_r = msm(&a, &s).into_affine();
+        let b_r = msm(&b, &t).into_affine();
+        let c_r = msm(&c, &s).into_affine();
+        transcript.append(&z_ab);
+        transcript.append(&a_r);
+        transcript.append(&b_r);
+        transcript.append(&c_r);
+
+        //inner pairing product argument, halving all vectors every round
+        let mut rounds = Vec::new();
+        let mut challenges = Vec::new();
+        while a.len() > 1 {
+            let m = a.len() / 2;
+            let (a_l, a_r) = a.split_at(m);
+            let (b_l, b_r) = b.split_at(m);
+            let (c_l, c_r) = c.split_at(m);
+            let (v1_l, v1_r) = v1.split_at(m);
+            let (v2_l, v2_r) = v2.split_at(m);
+            let (w1_l, w1_r) = w1.split_at(m);
+            let (w2_l, w2_r) = w2.split_at(m);
+            let (s_l, s_r) = s.split_at(m);
+            let (t_l, t_r) = t.split_at(m);
+
+            let round = AggregationRound {
+                gt_left: [
+                    inner_pairing_product::<E>(a_l, v1_r) * inner_pairing_product::<E>(w1_l, b_r),
+                    inner_pairing_product::<E>(a_l, v2_r) * inner_pairing_product::<E>(w2_l, b_r),
+                    inner_pairing_product::<E>(c_l, v1_r),
+                    inner_pairing_product::<E>(c_l, v2_r),
+                    inner_pairing_product::<E>(a_l, b_r),
+                ],
+                gt_right: [
+                    inner_pairing_product::<E>(a_r, v1_l) * inner_pairing_product::<E>(w1_r, b_l),
+                    inner_pairing_product::<E>(a_r, v2_l) * inner_pairing_product::<E>(w2_r, b_l),
+                    inner_pairing_product::<E>(c_r, v1_l),
+                    inner_pairing_product::<E>(c_r, v2_l),
+                    inner_pairing_product::<E>(a_r, b_l),
+                ],
+                a_left: msm(a_l, s_r).into_affine(),
+                a_right: msm(a_r, s_l).into_affine(),
+                b_left: msm(b_r, t_l).into_affine(),
+                b_right: msm(b_l, t_r).into_affine(),
+                c_left: msm(c_l, s_r).into_affine(),
+                c_right: msm(c_r, s_l).into_affine(),
+            };
+            transcript.append_round(&round);
+            let x: E::Fr = transcript.challenge();
+            let x_inv = x.inverse().ok_or("challenge x is zero")?;
+
+            //A, C, w and t are folded with x, B, v and s with x^-1
+            let (folded_a, folded_b, folded_c) = (fold_points(a_l, a_r, x), fold_points(b_l, b_r, x_inv), fold_points(c_l, c_r, x));
+            let (folded_v1, folded_v2) = (fold_points(v1_l, v1_r, x_inv), fold_points(v2_l, v2_r, x_inv));
+            let (folded_w1, folded_w2) = (fold_points(w1_l, w1_r, x), fold_points(w2_l, w2_r, x));
+            let (folded_s, folded_t) = (fold_scalars(s_l, s_r, x_inv), fold_scalars(t_l, t_r, x));
+            a = folded_a;
+            b = folded_b;
+            c = folded_c;
+            v1 = folded_v1;
+            v2 = folded_v2;
+            w1 = folded_w1;
+            w2 = folded_w2;
+            s = folded_s;
+            t = folded_t;
+
+            rounds.push(round);
+            challenges.push(x);
+        }
+
+        let (a_final, b_final, c_final) = (a[0], b[0], c[0]);
+        let (v1_final, v2_final, w1_final, w2_final) = (v1[0], v2[0], w1[0], w2[0]);
+        let z = final_challenge(&mut transcript, &[a_final, c_final, w1_final, w2_final], &[b_final, v1_final, v2_final]);
+
+        //the final commitment keys are KZG commitments to polynomials determined by the challenges, open them at z
+        let challenges_inv: Vec<E::Fr> = challenges.iter().map(|x| x.inverse().unwrap()).collect();
+        let v_poly = key_polynomial(&challenges_inv, E::Fr::one(), 0);
+        let w_poly = key_polynomial(&challenges, r_inv, n);
+
+        Ok(AggregateProof {
+            com_ab,
+            com_c,
+            z_ab,
+            a_r,
+            b_r,
+            c_r,
+            rounds,
+            a_final,
+            b_final,
+            c_final,
+            v1_final,
+            v2_final,
+            w1_final,
+            w2_final,
+            v1_opening: msm(&self.h_a, &kzg_quotient(&v_poly, z)).into_affine(),
+            v2_opening: msm(&self.h_b, &kzg_quotient(&v_poly, z)).into_affine(),
+            w1_opening: msm(&self.g_a, &kzg_quotient(&w_poly, z)).into_affine(),
+            w2_opening: msm(&self.g_b, &kzg_quotient(&w_poly, z)).into_affine(),
+        })
+    }
+
+    ///
+    /// Verification function
+    /// outputs whether all aggregated GM17 darkpool proofs verify under pvk, for the public inputs (aux, image) of every proof
+    ///
+    pub fn verify(&self, pvk: &PreparedVerifyingKey<E>, images: &[E::Fr], auxs: &[String], proof: &AggregateProof<E>) -> bool {
+        let n = match self.padded_amount(images.len(), images.len(), auxs.len()) {
+            Some(n) => n,
+            None => return false,
+        };
+        if proof.rounds.len() != n.trailing_zeros() as usize || pvk.query.len() != 3 {
+            return false;
+        }
+
+        let mut transcript = AggregationTranscript::new();
+        let r = initial_challenge(&mut transcript, pvk, &proof.com_ab, &proof.com_c, images, auxs);
+        let r_inv = match r.inverse() {
+            Some(r_inv) => r_inv,
+            None => return false,
+        };
+        transcript.append(&proof.z_ab);
+        transcript.append(&proof.a_r);
+        transcript.append(&proof.b_r);
+        transcript.append(&proof.c_r);
+
+        //fold the claimed values with the cross terms of every round
+        let mut gt_values = [proof.com_ab.0, proof.com_ab.1, proof.com_c.0, proof.com_c.1, proof.z_ab];
+        let mut a_r = proof.a_r.into_projective();
+        let mut b_r = proof.b_r.into_projective();
+        let mut c_r = proof.c_r.into_projective();
+        let mut challenges = Vec::new();
+        let mut challenges_inv = Vec::new();
+        for round in &proof.rounds {
+            transcript.append_round(round);
+            let x: E::Fr = transcript.challenge();
+            let x_inv = match x.inverse() {
+                Some(x_inv) => x_inv,
+                None => return false,
+            };
+
+            for (value, (left, right)) in gt_values.iter_mut().zip(round.gt_left.iter().zip(round.gt_right.iter())) {
+                *value *= left.pow(x_inv.into_repr()) * right.pow(x.into_repr());
+            }
+            a_r += round.a_left.mul(x_inv) + round.a_right.mul(x);
+            b_r += round.b_left.mul(x_inv) + round.b_right.mul(x);
+            c_r += round.c_left.mul(x_inv) + round.c_right.mul(x);
+
+            challenges.push(x);
+            challenges_inv.push(x_inv);
+        }
+
+        //the folded values must match the final elements under the final commitment keys
+        let [t_ab, u_ab, t_c, u_c, z_ab] = gt_values;
+        let final_check = t_ab == E::pairing(proof.a_final, proof.v1_final) * E::pairing(proof.w1_final, proof.b_final)
+            && u_ab == E::pairing(proof.a_final, proof.v2_final) * E::pairing(proof.w2_final, proof.b_final)
+            && t_c == E::pairing(proof.c_final, proof.v1_final)
+            && u_c == E::pairing(proof.c_final, proof.v2_final)
+            && z_ab == E::pairing(proof.a_final, proof.b_final)
+            && a_r == proof.a_final.mul(fold_powers(&challenges_inv, r, n))
+            && b_r == proof.b_final.mul(fold_powers(&challenges, E::Fr::one(), n))
+            && c_r == proof.c_final.mul(fold_powers(&challenges_inv, r, n));
+        if !final_check {
+            return false;
+        }
+
+        //the final commitment keys must be derived from the SRS, as committed polynomials evaluated at z
+        let z = final_challenge(&mut transcript, &[proof.a_final, proof.c_final, proof.w1_final, proof.w2_final], &[proof.b_final, proof.v1_final, proof.v2_final]);
+        let v_eval = evaluate_key_polynomial(&challenges_inv, E::Fr::one(), 0, n, z);
+        let w_eval = evaluate_key_polynomial(&challenges, r_inv, n, n, z);
+        let kzg_check = self.verify_g2_opening(&self.g_a, &self.h_a, proof.v1_final, proof.v1_opening, z, v_eval)
+            && self.verify_g2_opening(&self.g_b, &self.h_b, proof.v2_final, proof.v2_opening, z, v_eval)
+            && self.verify_g1_opening(&self.g_a, &self.h_a, proof.w1_final, proof.w1_opening, z, w_eval)
+            && self.verify_g1_opening(&self.g_b, &self.h_b, proof.w2_final, proof.w2_opening, z, w_eval);
+        if !kzg_check {
+            return false;
+        }
+
+        //GM17 equations, raised to r^i and multiplied over all proofs, where e(alpha, beta)^(sum r^i) cancels out:
+        //Z * e(A_r, beta) * e(alpha, B_r) = e(psi_r, gamma) * e(C_r, H) and e(A_r, gamma) = e(gamma, B_r)
+        let r_powers = powers_of(r, images.len());
+        let aux_frs: Vec<E::Fr> = auxs.iter().map(|aux| hash_to_field(GM17_AUX_DOMAIN, aux.as_bytes())).collect();
+        let psi_r = pvk.query[0].mul(r_powers.iter().copied().sum::<E::Fr>())
+            + pvk.query[1].mul(inner_product(&r_powers, &aux_frs))
+            + pvk.query[2].mul(inner_product(&r_powers, images));
+
+        let (a_r, b_r, c_r) = (proof.a_r, proof.b_r, proof.c_r);
+        let gm17_check_1 = proof.z_ab * E::product_of_pairings(&[
+            (a_r.into(), pvk.h_beta.into()),
+            (pvk.g_alpha.into(), b_r.into()),
+            ((-psi_r).into_affine().into(), pvk.h_gamma_pc.clone()),
+            ((-c_r).into(), pvk.h_pc.clone()),
+        ]);
+        let gm17_check_2 = E::product_of_pairings(&[
+            (a_r.into(), pvk.h_gamma_pc.clone()),
+            (pvk.g_gamma_pc.clone(), (-b_r).into()),
+        ]);
+
+        gm17_check_1.is_one() && gm17_check_2.is_one()
+    }
+
+    //amount of proofs padded to a power of two, or None if the amounts do not match, or exceed the SRS
+    fn padded_amount(&self, proof_amount: usize, image_amount: usize, aux_amount: usize) -> Option<usize> {
+        if proof_amount == 0 || proof_amount != image_amount || proof_amount != aux_amount || proof_amount > self.max_proofs {
+            return None;
+        }
+        Some(proof_amount.next_power_of_two())
+    }
+
+    //check that key = h^f(a) for a polynomial f with f(z) = eval: e(g^a - g^z, opening) = e(g, key - h^eval)
+    fn verify_g2_opening(&self, g_powers: &[E::G1Affine], h_powers: &[E::G2Affine], key: E::G2Affine, opening: E::G2Affine, z: E::Fr, eval: E::Fr) -> bool {
+        let g_a_minus_z = g_powers[1].into_projective() - g_powers[0].mul(z);
+        let key_minus_eval = key.into_projective() - h_powers[0].mul(eval);
+        E::product_of_pairings(&[
+            (g_a_minus_z.into_affine().into(), opening.into()),
+            ((-g_powers[0]).into(), key_minus_eval.into_affine().into()),
+        ]).is_one()
+    }
+
+    //check that key = g^f(a) for a polynomial f with f(z) = eval: e(opening, h^a - h^z) = e(key - g^eval, h)
+    fn verify_g1_opening(&self, g_powers: &[E::G1Affine], h_powers: &[E::G2Affine], key: E::G1Affine, opening: E::G1Affine, z: E::Fr, eval: E::Fr) -> bool {
+        let h_a_minus_z = h_powers[1].into_projective() - h_powers[0].mul(z);
+        let key_minus_eval = key.into_projective() - g_powers[0].mul(eval);
+        E::product_of_pairings(&[
+            (opening.into(), h_a_minus_z.into_affine().into()),
+            ((-key_minus_eval).into_affine().into(), h_powers[0].into()),
+        ]).is_one()
+    }
+}
+
+//challenge r, binding the commitments to the proofs to the verifying key and all public inputs
+fn initial_challenge<E: PairingEngine>(transcript: &mut AggregationTranscript, pvk: &PreparedVerifyingKey<E>, com_ab: &(E::Fqk, E::Fqk), com_c: &(E::Fqk, E::Fqk), images: &[E::Fr], auxs: &[String]) -> E::Fr {
+    transcript.append(&pvk.vk);
+    transcript.append(&(images.len() as u64));
+    for (image, aux) in images.iter().zip(auxs.iter()) {
+        transcript.append(image);
+        transcript.append(&hash_to_field::<E::Fr>(GM17_AUX_DOMAIN, aux.as_bytes()));
+    }
+    transcript.append(&com_ab.0);
+    transcript.append(&com_ab.1);
+    transcript.append(&com_c.0);
+    transcript.append(&com_c.1);
+    transcript.challenge()
+}
+
+//challenge z, at which the final commitment keys are opened
+fn final_challenge<G1: CanonicalSerialize, G2: CanonicalSerialize, F: PrimeField>(transcript: &mut AggregationTranscript, g1_elements: &[G1], g2_elements: &[G2]) -> F {
+    for element in g1_elements {
+        transcript.append(element);
+    }
+    for element in g2_elements {
+        transcript.append(element);
+    }
+    transcript.challenge()
+}
+
+//generator^(x^i) for i < amount
+fn powers<G: AffineCurve>(x: G::ScalarField, amount: usize) -> Vec<G> {
+    let generator = G::prime_subgroup_generator();
+    let points: Vec<G::Projective> = powers_of(x, amount).into_iter().map(|x_i| generator.mul(x_i)).collect();
+    G::Projective::batch_normalization_into_affine(&points)
+}
+
+//x^i for i < amount
+fn powers_of<F: Field>(x: F, amount: usize) -> Vec<F> {
+    let mut powers = Vec::with_capacity(amount);
+    let mut power = F::one();
+    for _ in 0..amount {
+        powers.push(power);
+        power *= x;
+    }
+    powers
+}
+
+fn inner_product<F: Field>(left: &[F], right: &[F]) -> F {
+    left.iter().zip(right.iter()).map(|(l, r)| *l * r).sum()
+}
+
+fn inner_pairing_product<E: PairingEngine>(g1_elements: &[E::G1Affine], g2_elements: &[E::G2Affine]) -> E::Fqk {
+    let pairs: Vec<(E::G1Prepared, E::G2Prepared)> = g1_elements.iter().zip(g2_elements.iter())
+        .map(|(g1, g2)| ((*g1).into(), (*g2).into()))
+        .collect();
+    E::product_of_pairings(&pairs)
+}
+
+fn msm<G: AffineCurve>(bases: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+    let scalars: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    VariableBaseMSM::multi_scalar_mul(&bases[..scalars.len().min(bases.len())], &scalars)
+}
+
+fn scale_points<G: AffineCurve>(points: &[G], scalars: &[G::ScalarField]) -> Vec<G> {
+    let scaled: Vec<G::Projective> = points.iter().zip(scalars.iter()).map(|(p, s)| p.mul(*s)).collect();
+    G::Projective::batch_normalization_into_affine(&scaled)
+}
+
+//left + x * right
+fn fold_points<G: AffineCurve>(left: &[G], right: &[G], x: G::ScalarField) -> Vec<G> {
+    let folded: Vec<G::Projective> = left.iter().zip(right.iter()).map(|(l, r)| r.mul(x).add_mixed(l)).collect();
+    G::Projective::batch_normalization_into_affine(&folded)
+}
+
+fn fold_scalars<F: Field>(left: &[F], right: &[F], x: F) -> Vec<F> {
+    left.iter().zip(right.iter()).map(|(l, r)| *l + x * r).collect()
+}
+
+//final element of the vector (y^i) for i < n, folded as left + c_j * right in round j: prod_j (1 + c_j * y^(n / 2^(j + 1)))
+fn fold_powers<F: Field>(challenges: &[F], y: F, n: usize) -> F {
+    let mut half = n;
+    challenges.iter().map(|c| {
+        half /= 2;
+        F::one() + *c * y.pow([half as u64])
+    }).product()
+}
+
+//coefficients of X^shift * prod_j (1 + c_j * (y * X)^(n / 2^(j + 1))), whose evaluation at the secret of the SRS is the final commitment key
+fn key_polynomial<F: Field>(challenges: &[F], y: F, shift: usize) -> Vec<F> {
+    let mut coefficients = vec![F::one()];
+    let mut half = 1 << challenges.len();
+    for c in challenges {
+        half /= 2;
+        let factor = *c * y.pow([half as u64]);
+        let mut product = coefficients.clone();
+        product.resize(coefficients.len() + half, F::zero());
+        for (i, coefficient) in coefficients.iter().enumerate() {
+            product[i + half] += factor * coefficient;
+        }
+        coefficients = product;
+    }
+
+    let mut shifted = vec![F::zero(); shift];
+    shifted.extend(coefficients);
+    shifted
+}
+
+//evaluation of the key polynomial at z, in time logarithmic in n
+fn evaluate_key_polynomial<F: Field>(challenges: &[F], y: F, shift: usize, n: usize, z: F) -> F {
+    z.pow([shift as u64]) * fold_powers(challenges, y * z, n)
+}
+
+//coefficients of (f(X) - f(z)) / (X - z), by synthetic division
+fn kzg_quotient<F: Field>(coefficients: &[F], z: F) -> Vec<F> {
+    let mut quotient = vec![F::zero(); coefficients.len().saturating_sub(1)];
+    let mut carry = F::zero();
+    for i in (1..coefficients.len()).rev() {
+        carry = coefficients[i] + z * carry;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
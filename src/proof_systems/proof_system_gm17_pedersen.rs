@@ -0,0 +1,171 @@
+use crate::utility::{upperbound_log, hash_string_to_fr, hash_string};
+use std::error::Error;
+use rand::{CryptoRng, RngCore};
+use ark_bls12_377::{Bls12_377, Fr, Parameters};
+use ark_ec::{bls12::Bls12, AffineCurve, ProjectiveCurve};
+use ark_ed_on_bls12_377::{constraints::EdwardsVar, EdwardsAffine, EdwardsProjective, Fr as EdFr};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_gm17::{create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, ProvingKey, PreparedVerifyingKey, Proof};
+use ark_r1cs_std::{prelude::*, fields::fp::FpVar};
+use ark_relations::{ns, r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError}};
+
+const BLINDING_GENERATOR_LABEL: &str = "WAKE darkpool pedersen blinding generator";
+
+///
+/// GM17 darkpool proof, where the public statement is a Pedersen commitment on the Edwards curve embedded in BLS12-377
+/// holds the Pedersen generators and the upperbound of the darkpool relation
+/// the commitment to v with blinding r is: v*g + r*h
+///
+#[derive(Clone)]
+pub struct Gm17PedersenDarkpool {
+    pub g: EdwardsProjective,
+    pub h: EdwardsProjective,
+    upperbound: usize,
+}
+
+impl Gm17PedersenDarkpool {
+    pub fn new(upperbound: usize) -> Result<Gm17PedersenDarkpool, Box<dyn Error>> {
+        let upperbound_exp = upperbound_log(upperbound as u64)?;
+
+        Ok(Gm17PedersenDarkpool {
+            g: EdwardsProjective::prime_subgroup_generator(),
+            h: hash_to_edwards(BLINDING_GENERATOR_LABEL),
+            upperbound: upperbound_exp
+        })
+    }
+
+    //compute the Pedersen commitment: v*g + r*h
+    pub fn commit(&self, v: u64, r: EdFr) -> EdwardsAffine {
+        (self.g.mul(EdFr::from(v).into_repr()) + self.h.mul(r.into_repr())).into_affine()
+    }
+
+    //homomorphically account for an increase of the balance, i.e. a decrease of v = upperbound - (b - min_bal)
+    pub fn add_to_balance(&self, commitment: EdwardsAffine, amount: u64) -> EdwardsAffine {
+        (commitment.into_projective() - self.g.mul(EdFr::from(amount).into_repr())).into_affine()
+    }
+
+    //homomorphically account for a decrease of the balance, i.e. an increase of v = upperbound - (b - min_bal)
+    pub fn subtract_from_balance(&self, commitment: EdwardsAffine, amount: u64) -> EdwardsAffine {
+        (commitment.into_projective() + self.g.mul(EdFr::from(amount).into_repr())).into_affine()
+    }
+
+    pub fn setup<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (ProvingKey<Bls12<Parameters>>, PreparedVerifyingKey<Bls12<Parameters>>) {
+        // Create parameters for our circuit
+        let pk = {
+            let c = PedersenDarkpoolCircuit {
+                aux: None,
+                commitment: None,
+                v: None,
+                r: None,
+                g: self.g,
+                h: self.h,
+                upperbound: self.upperbound,
+            };
+
+            generate_random_parameters::<Bls12_377, _, _>(c, rng).unwrap()
+        };
+
+        // Prepare the verification key (for proof verification)
+        let pvk = prepare_verifying_key(&pk.vk);
+
+        (pk, pvk)
+    }
+
+    pub fn prove<R: RngCore + CryptoRng>(&self, v: u64, r: EdFr, aux: String, pk: &ProvingKey<Bls12<Parameters>>, rng: &mut R) -> Proof<Bls12<Parameters>> {
+        let aux_fr = hash_string_to_fr(aux);
+        // Create an instance of our circuit (with the witness)
+        let c = PedersenDarkpoolCircuit {
+            aux: Some(aux_fr),
+            commitment: Some(self.commit(v, r)),
+            v: Some(v),
+            r: Some(r),
+            g: self.g,
+            h: self.h,
+            upperbound: self.upperbound,
+        };
+
+        // Create a gm17 proof with our parameters.
+        create_random_proof(c, pk, rng).unwrap()
+    }
+
+    pub fn verify(&self, commitment: EdwardsAffine, aux: String, pvk: &PreparedVerifyingKey<Bls12<Parameters>>, proof: &Proof<Bls12<Parameters>>) -> bool {
+        verify_proof(
+            pvk,
+            proof,
+            &[hash_string_to_fr(aux), commitment.x, commitment.y]
+        )
+        .unwrap()
+    }
+}
+
+//hash a label to a point in the prime-order subgroup of the Edwards curve (try-and-increment)
+fn hash_to_edwards(label: &str) -> EdwardsProjective {
+    let mut counter: u64 = 0;
+    loop {
+        let x = Fr::from_be_bytes_mod_order(&hash_string(&(label.to_string() + &counter.to_string())));
+        if let Some(point) = EdwardsAffine::get_point_from_x(x, false) {
+            let point = point.mul_by_cofactor_to_projective();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+        counter += 1;
+    }
+}
+
+///
+/// struct for proving knowledge of an opening (v, r) of a Pedersen commitment v*g + r*h, where v lies in [0, 2^upperbound)
+///
+struct PedersenDarkpoolCircuit {
+    aux: Option<Fr>,
+    commitment: Option<EdwardsAffine>,
+    v: Option<u64>,
+    r: Option<EdFr>,
+    g: EdwardsProjective,
+    h: EdwardsProjective,
+    upperbound: usize,
+}
+
+impl ConstraintSynthesizer<Fr> for PedersenDarkpoolCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        //Define aux and the commitment coordinates as input variables for circuit
+        let _aux = FpVar::new_input(ns!(cs, "aux"), || self.aux.ok_or(SynthesisError::AssignmentMissing))?;
+        let commitment_x = FpVar::new_input(ns!(cs, "commitment x"), || self.commitment.map(|c| c.x).ok_or(SynthesisError::AssignmentMissing))?;
+        let commitment_y = FpVar::new_input(ns!(cs, "commitment y"), || self.commitment.map(|c| c.y).ok_or(SynthesisError::AssignmentMissing))?;
+
+        //allocate exactly upperbound bits of v, which enforces v in [0, 2^upperbound)
+        let v_bits = (0..self.upperbound).map(|i| {
+            Boolean::new_witness(ns!(cs, "v bit"), || self.v.map(|v| (v >> i) & 1 == 1).ok_or(SynthesisError::AssignmentMissing))
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        //allocate the bits of the blinding factor
+        let r_bits_val = self.r.map(|r| r.into_repr().to_bits_le());
+        let r_bits = (0..EdFr::size_in_bits()).map(|i| {
+            Boolean::new_witness(ns!(cs, "r bit"), || r_bits_val.as_ref().map(|bits| bits[i]).ok_or(SynthesisError::AssignmentMissing))
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        //compute v*g + r*h using the public power-of-two multiples of the generators
+        let g_multiples = powers_of_two(self.g, v_bits.len());
+        let h_multiples = powers_of_two(self.h, r_bits.len());
+        let mut commitment = EdwardsVar::zero();
+        commitment.precomputed_base_scalar_mul_le(v_bits.iter().zip(g_multiples.iter()))?;
+        commitment.precomputed_base_scalar_mul_le(r_bits.iter().zip(h_multiples.iter()))?;
+
+        //the computed commitment must equal the public commitment
+        commitment.x.enforce_equal(&commitment_x)?;
+        commitment.y.enforce_equal(&commitment_y)?;
+
+        Ok(())
+    }
+}
+
+//compute base, 2*base, 4*base, ..., 2^(amount-1)*base
+fn powers_of_two(base: EdwardsProjective, amount: usize) -> Vec<EdwardsProjective> {
+    let mut multiples = Vec::with_capacity(amount);
+    let mut curr = base;
+    for _ in 0..amount {
+        multiples.push(curr);
+        curr.double_in_place();
+    }
+    multiples
+}
@@ -0,0 +1,113 @@
+use crate::utility::{upperbound_log, hash_to_field, fr_to_bits, MARLIN_AUX_DOMAIN};
+use super::darkpool_circuit::{DarkpoolCircuit, MIMC_ROUNDS};
+use std::error::Error;
+use rand::{CryptoRng, RngCore};
+use ark_ec::PairingEngine;
+use ark_ff::UniformRand;
+use ark_marlin::{AHPForR1CS, IndexProverKey, IndexVerifierKey, Marlin, Proof, UniversalSRS};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use blake2::Blake2s;
+
+//the darkpool circuit with the largest supported upperbound, which the universal SRS is sized for
+const MAX_UPPERBOUND_EXP: usize = 64;
+
+pub type MarlinPC<E> = MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>;
+pub type MarlinScheme<E> = Marlin<<E as PairingEngine>::Fr, MarlinPC<E>, Blake2s>;
+pub type MarlinSRS<E> = UniversalSRS<<E as PairingEngine>::Fr, MarlinPC<E>>;
+pub type MarlinProvingKey<E> = IndexProverKey<<E as PairingEngine>::Fr, MarlinPC<E>>;
+pub type MarlinVerifyingKey<E> = IndexVerifierKey<<E as PairingEngine>::Fr, MarlinPC<E>>;
+pub type MarlinProof<E> = Proof<<E as PairingEngine>::Fr, MarlinPC<E>>;
+
+///
+/// Marlin proof for the darkpool transaction relation, over any pairing-friendly curve
+/// Marlin has a universal and updatable setup: a single SRS supports the darkpool circuit for every upperbound,
+/// and the proving and verifying keys are derived from it deterministically, so every party can recompute and check them
+///
+#[derive(Clone)]
+pub struct MarlinDarkpool<E: PairingEngine> {
+    pub mimc_constants: Vec<E::Fr>,
+    upperbound: usize,
+}
+
+impl<E: PairingEngine> MarlinDarkpool<E> {
+    pub fn new<R: RngCore + CryptoRng>(upperbound: usize, rng: &mut R) -> Result<MarlinDarkpool<E>, Box<dyn Error>> {
+        let upperbound_exp = upperbound_log(upperbound as u64)?;
+
+        let mimc_constants = (0..MIMC_ROUNDS).map(|_| E::Fr::rand(rng)).collect::<Vec<_>>();
+        Ok(MarlinDarkpool {
+            mimc_constants,
+            upperbound: upperbound_exp
+        })
+    }
+
+    //rebuild the darkpool circuit from previously generated MiMC constants, such that it matches existing keys
+    pub fn from_mimc_constants(upperbound: usize, mimc_constants: Vec<E::Fr>) -> Result<MarlinDarkpool<E>, Box<dyn Error>> {
+        if mimc_constants.len() != MIMC_ROUNDS {
+            return Err(format!("expected {} MiMC constants, found {}", MIMC_ROUNDS, mimc_constants.len()).into());
+        }
+
+        Ok(MarlinDarkpool { mimc_constants, upperbound: upperbound_log(upperbound as u64)? })
+    }
+
+    //logarithm of the upperbound the circuit is built for
+    pub fn upperbound_exp(&self) -> usize {
+        self.upperbound
+    }
+
+    //generate a universal SRS, large enough for the darkpool circuit with the largest upperbound
+    pub fn universal_setup<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<MarlinSRS<E>, Box<dyn Error>> {
+        let dummy_v_bits_vec: Vec<Option<E::Fr>> = vec![None; MAX_UPPERBOUND_EXP];
+        let c = DarkpoolCircuit::<E::Fr> {
+            aux: None,
+            v_bits: &dummy_v_bits_vec,
+            r: None,
+            constants: &self.mimc_constants,
+            upperbound: MAX_UPPERBOUND_EXP,
+        };
+        let index_info = AHPForR1CS::<E::Fr>::index(c).map_err(|e| format!("indexing failed: {:?}", e))?.index_info;
+
+        MarlinScheme::<E>::universal_setup(index_info.num_constraints, index_info.num_variables, index_info.num_non_zero, rng)
+            .map_err(|e| format!("universal setup failed: {:?}", e).into())
+    }
+
+    //derive the proving and verifying keys of the darkpool circuit from the universal SRS
+    pub fn index(&self, srs: &MarlinSRS<E>) -> Result<(MarlinProvingKey<E>, MarlinVerifyingKey<E>), Box<dyn Error>> {
+        let dummy_v_bits_vec: Vec<Option<E::Fr>> = vec![None; self.upperbound];
+        let c = DarkpoolCircuit::<E::Fr> {
+            aux: None,
+            v_bits: &dummy_v_bits_vec,
+            r: None,
+            constants: &self.mimc_constants,
+            upperbound: self.upperbound,
+        };
+
+        MarlinScheme::<E>::index(srs, c).map_err(|e| format!("indexing failed: {:?}", e).into())
+    }
+
+    pub fn prove<R: RngCore + CryptoRng>(&self, v: E::Fr, r: E::Fr, aux: String, pk: &MarlinProvingKey<E>, rng: &mut R) -> Result<MarlinProof<E>, Box<dyn Error>> {
+        let aux_fr = hash_to_field(MARLIN_AUX_DOMAIN, aux.as_bytes());
+        let v_bits = fr_to_bits(v, self.upperbound);
+        // Create an instance of our circuit (with the witness)
+        let c = DarkpoolCircuit {
+            aux: Some(aux_fr),
+            v_bits: &v_bits,
+            r: Some(r),
+            constants: &self.mimc_constants,
+            upperbound: self.upperbound,
+        };
+
+        MarlinScheme::<E>::prove(pk, c, rng).map_err(|e| format!("proving failed: {:?}", e).into())
+    }
+
+    //the randomness of the polynomial commitment check is local to the verifier, and drawn from the given rng
+    pub fn verify<R: RngCore + CryptoRng>(&self, image: E::Fr, aux: String, vk: &MarlinVerifyingKey<E>, proof: &MarlinProof<E>, rng: &mut R) -> bool {
+        MarlinScheme::<E>::verify(
+            vk,
+            &[hash_to_field(MARLIN_AUX_DOMAIN, aux.as_bytes()), image],
+            proof,
+            rng
+        )
+        .unwrap_or(false)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::utility::hash_string;
+use std::error::Error;
+use merlin::Transcript;
+use bulletproofs::{BulletproofGens, PedersenGens, r1cs::{ConstraintSystem, LinearCombination, Prover, R1CSError, R1CSProof, Variable, Verifier}};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::CompressedRistretto};
+
+//amount of bits used to bound a committed value, such that any u64 bounds are supported, and the proof does not depend on the bounds
+const BOUND_BITS: usize = 64;
+
+///
+/// a statement over committed values, referenced by their index in the list of commitments
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DarkpoolConstraint {
+    //lower <= value <= upper
+    Bounded { idx: usize, lower: u64, upper: u64 },
+    //the values at idxs sum to the value at total
+    Sum { idxs: Vec<usize>, total: usize },
+    //the values at a and b are equal
+    Equal { a: usize, b: usize },
+}
+
+//struct responsible for holding all public values of a bulletproofs R1CS proof over a list of commitments, and to invoke prove and verification functions
+//unlike RangeProofStruct, the statement is not limited to a range check on a single commitment, and no trusted setup is needed
+#[derive(Clone)]
+pub struct R1csProofStruct {
+    pc_gens: PedersenGens,
+    bp_gens: BulletproofGens,
+    commitment_amount: usize,
+    constraints: Vec<DarkpoolConstraint>,
+}
+
+impl R1csProofStruct {
+    pub fn new(commitment_amount: usize, constraints: Vec<DarkpoolConstraint>) -> Result<R1csProofStruct, Box<dyn Error>> {
+        let in_range = |idx: &usize| *idx < commitment_amount;
+        for constraint in &constraints {
+            let valid = match constraint {
+                DarkpoolConstraint::Bounded { idx, lower, upper } => in_range(idx) && lower <= upper,
+                DarkpoolConstraint::Sum { idxs, total } => !idxs.is_empty() && idxs.iter().all(in_range) && in_range(total),
+                DarkpoolConstraint::Equal { a, b } => in_range(a) && in_range(b),
+            };
+            if !valid {
+                return Err(format!("invalid constraint for {} commitments: {:?}", commitment_amount, constraint).into());
+            }
+        }
+
+        //every bound uses two range checks, each allocating one multiplier per bit
+        let bound_amount = constraints.iter().filter(|c| matches!(c, DarkpoolConstraint::Bounded { .. })).count();
+        let gens_capacity = (2 * BOUND_BITS * bound_amount).next_power_of_two();
+
+        Ok(R1csProofStruct {
+            pc_gens: PedersenGens::default(),
+            bp_gens: BulletproofGens::new(gens_capacity, 1),
+            commitment_amount,
+            constraints,
+        })
+    }
+
+    //the darkpool relation of the WAKE signatures, over a commitment to v = upperbound - (b - min_bal): 0 <= v <= upperbound
+    pub fn darkpool(upperbound: u64) -> R1csProofStruct {
+        R1csProofStruct::new(1, vec![DarkpoolConstraint::Bounded { idx: 0, lower: 0, upper: upperbound }]).unwrap()
+    }
+
+    //the bulletproofs R1CS prover draws its blinding factors from a transcript RNG seeded by the thread RNG, so unlike the other proofs it takes no RNG
+    pub fn prove(&self, values: &[u64], blindings: &[Scalar], auxilliary_challenge_string: String) -> Result<(R1CSProof, Vec<CompressedRistretto>), Box<dyn Error>> {
+        if values.len() != self.commitment_amount || blindings.len() != self.commitment_amount {
+            return Err(format!("expected {} values and blindings", self.commitment_amount).into());
+        }
+
+        let mut prover_transcript = self.transcript(&auxilliary_challenge_string);
+        let mut prover = Prover::new(&self.pc_gens, &mut prover_transcript);
+
+        let (commitments, vars): (Vec<CompressedRistretto>, Vec<Variable>) = values.iter().zip(blindings.iter())
+            .map(|(v, r)| prover.commit(Scalar::from(*v), *r))
+            .unzip();
+
+        self.constrain(&mut prover, &vars, Some(values))?;
+        let proof = prover.prove(&self.bp_gens)?;
+
+        Ok((proof, commitments))
+    }
+
+    pub fn verify(&self, proof: &R1CSProof, commitments: &[CompressedRistretto], auxilliary_challenge_string: String) -> bool {
+        if commitments.len() != self.commitment_amount {
+            return false;
+        }
+
+        let mut verifier_transcript = self.transcript(&auxilliary_challenge_string);
+        let mut verifier = Verifier::new(&mut verifier_transcript);
+
+        let vars: Vec<Variable> = commitments.iter().map(|c| verifier.commit(*c)).collect();
+
+        self.constrain(&mut verifier, &vars, None).is_ok() && verifier.verify(proof, &self.pc_gens, &self.bp_gens).is_ok()
+    }
+
+    //transcript bound to both the auxilliary string and the statement
+    fn transcript(&self, auxilliary_challenge_string: &str) -> Transcript {
+        let mut transcript = Transcript::new(b"r1cs darkpool proof");
+        transcript.append_message(b"auxilliary challenge string", &hash_string(auxilliary_challenge_string));
+        transcript.append_u64(b"commitment amount", self.commitment_amount as u64);
+        for constraint in &self.constraints {
+            match constraint {
+                DarkpoolConstraint::Bounded { idx, lower, upper } => {
+                    transcript.append_message(b"constraint", b"bounded");
+                    transcript.append_u64(b"idx", *idx as u64);
+                    transcript.append_u64(b"lower", *lower);
+                    transcript.append_u64(b"upper", *upper);
+                }
+                DarkpoolConstraint::Sum { idxs, total } => {
+                    transcript.append_message(b"constraint", b"sum");
+                    transcript.append_u64(b"length", idxs.len() as u64);
+                    for idx in idxs {
+                        transcript.append_u64(b"idx", *idx as u64);
+                    }
+                    transcript.append_u64(b"total", *total as u64);
+                }
+                DarkpoolConstraint::Equal { a, b } => {
+                    transcript.append_message(b"constraint", b"equal");
+                    transcript.append_u64(b"a", *a as u64);
+                    transcript.append_u64(b"b", *b as u64);
+                }
+            }
+        }
+        transcript
+    }
+
+    //add the constraints of the statement to the constraint system, values are only known to the prover
+    fn constrain<CS: ConstraintSystem>(&self, cs: &mut CS, vars: &[Variable], values: Option<&[u64]>) -> Result<(), R1CSError> {
+        for constraint in &self.constraints {
+            match constraint {
+                DarkpoolConstraint::Bounded { idx, lower, upper } => {
+                    //value - lower and upper - value are both in [0, 2^64), which gives lower <= value <= upper
+                    let value = values.map(|v| v[*idx]);
+                    range_check(cs, vars[*idx] - Scalar::from(*lower), value.map(|v| v.wrapping_sub(*lower)))?;
+                    range_check(cs, LinearCombination::from(Scalar::from(*upper)) - vars[*idx], value.map(|v| upper.wrapping_sub(v)))?;
+                }
+                DarkpoolConstraint::Sum { idxs, total } => {
+                    let sum: LinearCombination = idxs.iter().map(|idx| (vars[*idx], Scalar::one())).collect();
+                    cs.constrain(sum - vars[*total]);
+                }
+                DarkpoolConstraint::Equal { a, b } => {
+                    cs.constrain(vars[*a] - vars[*b]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//enforce v in [0, 2^BOUND_BITS) by decomposing it into bits
+fn range_check<CS: ConstraintSystem>(cs: &mut CS, mut v: LinearCombination, v_assignment: Option<u64>) -> Result<(), R1CSError> {
+    let mut exp_2 = Scalar::one();
+    for i in 0..BOUND_BITS {
+        //a = 1 - bit, b = bit, a*b = 0
+        let (a, b, o) = cs.allocate_multiplier(v_assignment.map(|q| {
+            let bit = (q >> i) & 1;
+            (Scalar::from(1 - bit), Scalar::from(bit))
+        }))?;
+        cs.constrain(o.into());
+        cs.constrain(a + (b - Scalar::one()));
+
+        v = v - b * exp_2;
+        exp_2 = exp_2 + exp_2;
+    }
+    cs.constrain(v);
+    Ok(())
+}
@@ -1,8 +1,17 @@
 use crate::utility::{hash_string, upperbound_log};
-use std::error::Error;
+use super::batch_verification::VerificationEquation;
+use std::{error::Error, iter};
 use merlin::Transcript;
 use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
-use curve25519_dalek_ng::{scalar::Scalar, ristretto::CompressedRistretto};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::CompressedRistretto, traits::IsIdentity};
+use lazy_static::lazy_static;
+use rand::{CryptoRng, RngCore};
+
+lazy_static!{
+    //Generators for Bulletproofs, valid for proofs up to bitsize 64 and aggregation size up to 1.
+    //deriving them is expensive, so they are derived once and shared by all range proofs
+    static ref BP_GENS: BulletproofGens = BulletproofGens::new(64, 1);
+}
 
 //struct responsible for holding all public values, and to invoke prove and verification functions
 #[derive(Clone)]
@@ -15,17 +24,22 @@ pub struct RangeProofStruct {
 
 impl RangeProofStruct {
     //Initialize generators for Pedersen Commitments and bulletproofs independently. 
-    //Generators for Bulletproofs, valid for proofs up to bitsize 64 and aggregation size up to 1.
+    //Generators for Bulletproofs are copied from the shared BP_GENS.
     pub fn new(min_bal: u64, upperbound: u64) -> RangeProofStruct {
         RangeProofStruct {
             pc_gens: PedersenGens::default(),
-            bp_gens: BulletproofGens::new(64, 1),
+            bp_gens: BP_GENS.clone(),
             min_bal,
             upperbound,
         }
     }
     
-    pub fn prove(&self, b: u64, r: Scalar, auxilliary_challenge_string: String) -> Result<RangeProof, Box<dyn Error>> {
+    //minimum balance and upperbound of the statement
+    pub fn bounds(&self) -> (u64, u64) {
+        (self.min_bal, self.upperbound)
+    }
+
+    pub fn prove<R: RngCore + CryptoRng>(&self, b: u64, r: Scalar, auxilliary_challenge_string: String, rng: &mut R) -> Result<RangeProof, Box<dyn Error>> {
         //assume bound up to 2^64, such that proof doesnt depend on size of "b". Which would compromise some information about "b"
         if b < self.min_bal {
             return Err(format!("minimum balance requirement was not met").into());
@@ -37,13 +51,14 @@ impl RangeProofStruct {
 
         let n = upperbound_log(self.upperbound).unwrap();
 
-        let (proof, _) = RangeProof::prove_single(
+        let (proof, _) = RangeProof::prove_single_with_rng(
             &self.bp_gens,
             &self.pc_gens,
             &mut prover_transcript,
             v,
             &r,
             n,
+            rng,
         ).expect("Failed to prove balance b_B in range [0, 2^min_bal_bits]");
 
         return Ok(proof)
@@ -59,4 +74,111 @@ impl RangeProofStruct {
         proof.verify_single(&self.bp_gens, &self.pc_gens, &mut verifier_transcript, &committed_value, n)
         .is_ok()
     }
-}
\ No newline at end of file
+
+    //replay the transcript of verify_single to recompute the challenges of the proof, and output its verification equation,
+    //such that it can be checked together with other proofs by the BatchVerifier. None if the proof is malformed
+    //the transcript and proof layout are those of bulletproofs 4.0.0, which is pinned in Cargo.toml for this reason
+    pub(crate) fn verification_equation<R: RngCore + CryptoRng>(&self, proof: &RangeProof, committed_value: CompressedRistretto, auxilliary_challenge_string: &str, rng: &mut R) -> Option<VerificationEquation> {
+        let n = upperbound_log(self.upperbound).ok()?;
+        let lg_n = n.trailing_zeros() as usize;
+
+        //proof layout: points A, S, T_1, T_2, scalars t_x, t_x_blinding, e_blinding, lg_n pairs of points L, R, and scalars a, b
+        let bytes = proof.to_bytes();
+        if bytes.len() != 32 * (9 + 2 * lg_n) {
+            return None;
+        }
+        let chunks: Vec<[u8; 32]> = bytes.chunks(32).map(|c| c.try_into().unwrap()).collect();
+        let point = |i: usize| CompressedRistretto(chunks[i]);
+        let scalar = |i: usize| Scalar::from_canonical_bytes(chunks[i]);
+
+        let (a_point, s_point, t_1, t_2) = (point(0), point(1), point(2), point(3));
+        let (t_x, t_x_blinding, e_blinding) = (scalar(4)?, scalar(5)?, scalar(6)?);
+        let l_vec: Vec<CompressedRistretto> = (0..lg_n).map(|i| point(7 + 2 * i)).collect();
+        let r_vec: Vec<CompressedRistretto> = (0..lg_n).map(|i| point(8 + 2 * i)).collect();
+        let (ipp_a, ipp_b) = (scalar(7 + 2 * lg_n)?, scalar(8 + 2 * lg_n)?);
+
+        let mut transcript = Transcript::new(b"range proof");
+        transcript.append_message(b"auxilliary challenge string", &hash_string(auxilliary_challenge_string));
+        transcript.append_message(b"dom-sep", b"rangeproof v1");
+        transcript.append_u64(b"n", n as u64);
+        transcript.append_u64(b"m", 1);
+        transcript.append_message(b"V", committed_value.as_bytes());
+
+        validate_and_append_point(&mut transcript, b"A", &a_point)?;
+        validate_and_append_point(&mut transcript, b"S", &s_point)?;
+        let y = challenge_scalar(&mut transcript, b"y");
+        let z = challenge_scalar(&mut transcript, b"z");
+        let zz = z * z;
+
+        validate_and_append_point(&mut transcript, b"T_1", &t_1)?;
+        validate_and_append_point(&mut transcript, b"T_2", &t_2)?;
+        let x = challenge_scalar(&mut transcript, b"x");
+
+        transcript.append_message(b"t_x", t_x.as_bytes());
+        transcript.append_message(b"t_x_blinding", t_x_blinding.as_bytes());
+        transcript.append_message(b"e_blinding", e_blinding.as_bytes());
+        let w = challenge_scalar(&mut transcript, b"w");
+
+        //inner product proof challenges u_i, their squares and inverse squares
+        transcript.append_message(b"dom-sep", b"ipp v1");
+        transcript.append_u64(b"n", n as u64);
+        let mut u_sq = Vec::with_capacity(lg_n);
+        for (l, r) in l_vec.iter().zip(r_vec.iter()) {
+            validate_and_append_point(&mut transcript, b"L", l)?;
+            validate_and_append_point(&mut transcript, b"R", r)?;
+            u_sq.push(challenge_scalar(&mut transcript, b"u"));
+        }
+        let mut u_inv_sq = u_sq.clone();
+        let all_inv = Scalar::batch_invert(&mut u_inv_sq);
+        for i in 0..lg_n {
+            u_sq[i] = u_sq[i] * u_sq[i];
+            u_inv_sq[i] = u_inv_sq[i] * u_inv_sq[i];
+        }
+
+        //s_i = prod_j u_j^(+-1), computed inductively
+        let mut s = Vec::with_capacity(n);
+        s.push(all_inv);
+        for i in 1..n {
+            let lg_i = (usize::BITS - 1 - i.leading_zeros()) as usize;
+            s.push(s[i - (1 << lg_i)] * u_sq[(lg_n - 1) - lg_i]);
+        }
+
+        //random factor combining the range check with the inner product check
+        let c = Scalar::random(rng);
+
+        let powers_of_y: Vec<Scalar> = powers(y, n);
+        let powers_of_y_inv: Vec<Scalar> = powers(y.invert(), n);
+        let powers_of_2: Vec<Scalar> = powers(Scalar::from(2u64), n);
+        let delta = (z - zz) * powers_of_y.iter().sum::<Scalar>() - zz * z * powers_of_2.iter().sum::<Scalar>();
+
+        let mut points = vec![(Scalar::one(), a_point), (x, s_point), (c * x, t_1), (c * x * x, t_2), (c * zz, committed_value)];
+        points.extend(u_sq.into_iter().zip(l_vec));
+        points.extend(u_inv_sq.into_iter().zip(r_vec));
+
+        Some(VerificationEquation {
+            points,
+            b: w * (t_x - ipp_a * ipp_b) + c * (delta - t_x),
+            b_blinding: -e_blinding - c * t_x_blinding,
+            g: s.iter().map(|s_i| -z - ipp_a * s_i).collect(),
+            h: (0..n).map(|i| z + powers_of_y_inv[i] * (zz * powers_of_2[i] - ipp_b * s[n - 1 - i])).collect(),
+        })
+    }
+}
+
+fn powers(x: Scalar, n: usize) -> Vec<Scalar> {
+    iter::successors(Some(Scalar::one()), |p| Some(p * x)).take(n).collect()
+}
+
+fn challenge_scalar(transcript: &mut Transcript, label: &'static [u8]) -> Scalar {
+    let mut buf = [0u8; 64];
+    transcript.challenge_bytes(label, &mut buf);
+    Scalar::from_bytes_mod_order_wide(&buf)
+}
+
+fn validate_and_append_point(transcript: &mut Transcript, label: &'static [u8], point: &CompressedRistretto) -> Option<()> {
+    if point.is_identity() {
+        return None;
+    }
+    transcript.append_message(label, point.as_bytes());
+    Some(())
+}
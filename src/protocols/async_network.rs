@@ -0,0 +1,96 @@
+use super::{party_id::{PartyId, Ring}, timeout::{Clock, RoundCollector, RoundStatus, SystemClock}};
+use tokio::sync::mpsc;
+
+///
+/// File for exchanging the messages of the rounds between parties running as tokio tasks. Every party holds an endpoint,
+/// from which it broadcasts its messages to all members of the ring, and awaits the messages of the other members.
+/// Messages of a later round, arriving while a party still awaits an earlier round, are kept until that round is collected.
+/// A round is collected with a RoundCollector of the party, such that it ends with a timeout once its deadline passes
+///
+//message of a round as sent over the network: the round, the sender and the message
+pub type Envelope<M> = (usize, PartyId, M);
+
+///
+/// Endpoint of a party in a network of in-memory tokio channels
+///
+pub struct Endpoint<M> {
+    id: PartyId,
+    clock: SystemClock,
+    peers: Vec<mpsc::Sender<Envelope<M>>>,
+    inbox: mpsc::Receiver<Envelope<M>>,
+    //messages of rounds that are not collected yet
+    early: Vec<Envelope<M>>,
+}
+
+impl<M: Clone + Send> Endpoint<M> {
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //clock the deadlines of the rounds are measured on
+    pub fn clock(&self) -> &SystemClock {
+        &self.clock
+    }
+
+    //sends the message of round round_idx to all members, including the party itself
+    pub async fn broadcast(&self, round_idx: usize, message: M) -> Result<(), String> {
+        for peer in self.peers.iter() {
+            peer.send((round_idx, self.id, message.clone())).await.map_err(|_| "Network is closed".to_string())?;
+        }
+        Ok(())
+    }
+
+    //awaits the messages of all members for the round of collector, outputting them in ring order
+    //fails with the timeout of the round once its deadline passes, or when a message of an unknown party or a second message of a member is received
+    pub async fn recieve_round(&mut self, mut collector: RoundCollector<M>) -> Result<Vec<(PartyId, M)>, String> {
+        let round_idx = collector.round();
+        let (current, later): (Vec<Envelope<M>>, Vec<Envelope<M>>) = self.early.drain(..).partition(|(round, _, _)| *round == round_idx);
+        self.early = later;
+        for (_, sender, message) in current {
+            collector.recieve(sender, message)?;
+        }
+
+        loop {
+            match collector.poll(&self.clock) {
+                RoundStatus::Complete(messages) => return Ok(messages),
+                RoundStatus::TimedOut(timeout) => return Err(timeout.to_string()),
+                RoundStatus::Waiting => (),
+            }
+
+            let remaining = collector.deadline().saturating_sub(self.clock.now());
+            match tokio::time::timeout(remaining, self.inbox.recv()).await {
+                Ok(Some((round, sender, message))) if round == round_idx => collector.recieve(sender, message)?,
+                Ok(Some(envelope)) if envelope.0 > round_idx => self.early.push(envelope),
+                Ok(Some((round, sender, _))) => return Err(format!("Message of party {} for past round {}", sender, round)),
+                Ok(None) => return Err("Network is closed".to_string()),
+                //the deadline passed, reported by the next poll
+                Err(_) => (),
+            }
+        }
+    }
+}
+
+///
+/// Creates a network of in-memory tokio channels between the members of ring, for a protocol of the given amount of rounds
+/// the inbox of a member holds the messages of all members in all rounds, such that parties broadcasting at the same time
+/// never wait on each other
+/// outputs an endpoint per member, in ring order
+///
+pub fn in_memory_network<M>(ring: &Ring, rounds: usize) -> Vec<Endpoint<M>> {
+    let clock = SystemClock::new();
+    let capacity = (ring.len() * rounds).max(1);
+    let (peers, inboxes): (Vec<_>, Vec<_>) = ring.ids().iter().map(|_| mpsc::channel(capacity)).unzip();
+
+    ring.ids().iter().zip(inboxes).map(|(id, inbox)| Endpoint { id: *id, clock, peers: peers.clone(), inbox, early: Vec::new() }).collect()
+}
+
+///
+/// Runs f over state on the blocking thread pool of tokio, such that CPU-heavy proving and verification do not stall the runtime
+/// outputs the state together with the output of f
+///
+pub async fn run_blocking<S: Send + 'static, T: Send + 'static, F: FnOnce(&mut S) -> T + Send + 'static>(mut state: S, f: F) -> Result<(S, T), String> {
+    tokio::task::spawn_blocking(move || {
+        let output = f(&mut state);
+        (state, output)
+    }).await.map_err(|err| format!("Blocking task failed: {}", err))
+}
@@ -0,0 +1,129 @@
+use super::{party_id::PartyId, robust::Misbehaviour};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+///
+/// File for the reliable broadcast of the WAKE protocols. The protocols assume that all parties receive the same message of a sender,
+/// while a malicious sender could send conflicting signed messages to different parties. After a round, every party echoes the digests
+/// of the messages it received to all members. A party finding an echo that conflicts with its own view requests the message behind
+/// the echo as evidence: a validly signed message of the same session proves that its sender equivocated, while an echo that is not
+/// backed by such a message blames the party that echoed it, such that no party can be framed.
+/// The reliable broadcast is available to the parties of the Bulletproofs protocols only. The GM17 and Marlin protocols, including
+/// their robust runners, still assume that every party receives the same message of a sender
+///
+//domain separation tag for the digests of the messages
+pub const BROADCAST_DOMAIN: &[u8] = b"WAKE-BROADCAST-V1";
+
+//SHA-256 digest of a broadcast message
+pub type MessageDigest = [u8; 32];
+
+//digest of the encoding of a message, as sent by sender in the given round
+pub fn message_digest(round: usize, sender: &PartyId, message: &str) -> MessageDigest {
+    let mut hasher = Sha256::new();
+    hasher.update(BROADCAST_DOMAIN);
+    hasher.update((round as u64).to_le_bytes());
+    hasher.update(sender.to_bytes());
+    hasher.update(message.as_bytes());
+    hasher.finalize().into()
+}
+
+///
+/// Echo of a party: the digests of the messages it received in a round, listed by their sender
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Echo {
+    pub round: usize,
+    pub echoer: PartyId,
+    pub digests: Vec<(PartyId, MessageDigest)>,
+}
+
+///
+/// Conflict between the view of a party and an echo: the echoer echoed another digest for the message of sender in round,
+/// or none at all
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub round: usize,
+    pub sender: PartyId,
+    pub echoer: PartyId,
+    pub digest: Option<MessageDigest>,
+}
+
+///
+/// View of a party on the broadcast: the digest of the message received per round and sender, together with the message itself,
+/// which is handed out as evidence for the echo of the party
+///
+#[derive(Clone)]
+pub struct BroadcastView<M> {
+    messages: BTreeMap<(usize, PartyId), (MessageDigest, M)>,
+}
+
+impl<M> Default for BroadcastView<M> {
+    fn default() -> BroadcastView<M> {
+        BroadcastView { messages: BTreeMap::new() }
+    }
+}
+
+impl<M: Clone> BroadcastView<M> {
+    pub fn new() -> BroadcastView<M> {
+        BroadcastView::default()
+    }
+
+    //records the message of sender in round, by the encoding that is signed
+    pub fn record(&mut self, round: usize, sender: PartyId, encoding: &str, message: M) {
+        self.messages.insert((round, sender), (message_digest(round, &sender, encoding), message));
+    }
+
+    //echo of the messages received in round
+    pub fn echo(&self, round: usize, echoer: PartyId) -> Echo {
+        let digests = self.messages.iter().filter(|((r, _), _)| *r == round).map(|((_, sender), (digest, _))| (*sender, *digest)).collect();
+        Echo { round, echoer, digests }
+    }
+
+    //message received from sender in round
+    pub fn evidence(&self, round: usize, sender: &PartyId) -> Option<M> {
+        self.messages.get(&(round, *sender)).map(|(_, message)| message.clone())
+    }
+
+    //messages of the view, for which the echo holds another digest or none
+    pub fn conflicts(&self, echo: &Echo) -> Vec<Conflict> {
+        let echoed: BTreeMap<PartyId, MessageDigest> = echo.digests.iter().copied().collect();
+        self.messages.iter().filter(|((round, _), _)| *round == echo.round).filter_map(|((round, sender), (digest, _))| {
+            match echoed.get(sender) {
+                Some(echoed) if echoed == digest => None,
+                echoed => Some(Conflict { round: *round, sender: *sender, echoer: echo.echoer, digest: echoed.copied() }),
+            }
+        }).collect()
+    }
+
+    //checks the echoes of the other members against the view
+    //evidence outputs the message behind a conflicting echo, as handed out by its echoer, and verify outputs the signed encoding
+    //of that message if it is validly signed by the sender of the conflict in the session of the party
+    //on failure, blames the parties that sent conflicting messages, and the parties whose echoes are not backed by evidence
+    pub fn check_echoes<E, V>(&self, echoes: &[Echo], evidence: E, verify: V) -> Result<(), Misbehaviour>
+    where
+        E: Fn(&Conflict) -> Option<M>,
+        V: Fn(&Conflict, &M) -> Option<String>,
+    {
+        let mut misbehaviour = Misbehaviour::default();
+
+        for conflict in echoes.iter().flat_map(|echo| self.conflicts(echo)) {
+            let digest = match conflict.digest {
+                Some(digest) => digest,
+                None => {
+                    misbehaviour.blame(conflict.echoer, format!("Echo of party {} misses the message of party {} in round {}", conflict.echoer, conflict.sender, conflict.round));
+                    continue;
+                }
+            };
+
+            //the sender equivocated if the echoer holds another message, validly signed by the sender
+            match evidence(&conflict).and_then(|message| verify(&conflict, &message)) {
+                Some(encoding) if message_digest(conflict.round, &conflict.sender, &encoding) == digest => {
+                    misbehaviour.blame(conflict.sender, format!("Party {} sent conflicting messages in round {}", conflict.sender, conflict.round));
+                }
+                _ => misbehaviour.blame(conflict.echoer, format!("Echo of party {} is not backed by a message of party {} in round {}", conflict.echoer, conflict.sender, conflict.round)),
+            }
+        }
+        misbehaviour.into_result()
+    }
+}
@@ -1,6 +1,7 @@
 use crate::utility::{find_prev_idx, get_adjacent_elements};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint, constants::RISTRETTO_BASEPOINT_POINT};
-use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
 
 /// 
 /// File for running Burmester Desmedt key-exchange
@@ -9,20 +10,20 @@ use rand::rngs::OsRng;
 
 ///
 /// Struct representing a single party
+/// the exponent r is secret, and wiped when the key is computed or the party is dropped
 /// 
-#[derive(Copy, Clone, Debug)]
 pub struct Party{
     idx: usize,
     alpha: RistrettoPoint,
-    r: Option<Scalar>,
+    r: Option<Zeroizing<Scalar>>,
     z: Option<RistrettoPoint>,
 }
 
 impl Party {
     //round 1
-    fn round1(&mut self, rng: &mut OsRng) -> RistrettoPoint {
-        self.r = Some(Scalar::random(rng));
-        self.z = Some(self.alpha * self.r.unwrap());
+    fn round1<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> RistrettoPoint {
+        self.r = Some(Zeroizing::new(Scalar::random(rng)));
+        self.z = Some(self.alpha * **self.r.as_ref().unwrap());
 
         return self.z.unwrap();
     }
@@ -30,7 +31,7 @@ impl Party {
     //round 2
     fn round2(&mut self, z_list: &Vec<RistrettoPoint>) -> RistrettoPoint {
         let (z_1, z_2) = get_adjacent_elements(&z_list, &self.idx);
-        let x = (z_2 - z_1) * self.r.unwrap();
+        let x = (z_2 - z_1) * **self.r.as_ref().unwrap();
 
         return x;
     }
@@ -40,7 +41,7 @@ impl Party {
         let num_parties = x_list.len();
 
         let z = z_list[find_prev_idx(self.idx, num_parties)];
-        let exponent = self.r.unwrap() * Scalar::from(num_parties as u64);
+        let mut exponent = **self.r.as_ref().unwrap() * Scalar::from(num_parties as u64);
         let mut key = z * exponent;
 
         for idx in 0..num_parties - 1 {
@@ -48,13 +49,16 @@ impl Party {
             key += x_list[i] * Scalar::from((num_parties - idx - 1) as u64);
         }
 
+        //erase the ephemeral exponent, for forward secrecy
+        exponent.zeroize();
+        self.r = None;
+
         return key;
     }
 }
 
 //execute Burmester-Desmedt key-exchange with specified amount of parties
-pub fn key_exchange(party_amount: usize) -> Vec<RistrettoPoint> {
-    let rng = &mut OsRng;
+pub fn key_exchange<R: RngCore + CryptoRng>(party_amount: usize, rng: &mut R) -> Vec<RistrettoPoint> {
     let alpha = RISTRETTO_BASEPOINT_POINT;
 
     //Initialize parties
@@ -0,0 +1,313 @@
+use super::{party_id::PartyId, ratchet::{EpochKey, KeyRatchet}, compiler_bp_wake::compiler_bp_wake_signature::CompSignature};
+use crate::{
+    params::{WakeParams, BulletproofsKeys},
+    utility::ristretto_to_string
+};
+use bulletproofs::PedersenGens;
+use merlin::Transcript;
+use chacha20poly1305::{aead::{Aead, KeyInit, Payload}, ChaCha20Poly1305, Key, Nonce};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::{CompressedRistretto, RistrettoPoint}};
+use hkdf::Hkdf;
+use rand::{CryptoRng, RngCore};
+use sha2::Sha256;
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+///
+/// File for messaging the group once a WAKE session has established its key. Every member encrypts its broadcast messages with
+/// ChaCha20-Poly1305, under a sender key derived from the key of the current epoch and its own identifier, and numbers them with its own
+/// sequence numbers. A receiver decrypts a message under the key of its claimed sender, such that it is only accepted from that sender, and
+/// rejects any message whose sequence number is not above the last one accepted from the sender. As every member holds the session key,
+/// sender keys authenticate the sender against parties outside the group only. Within the group, every member additionally signs its
+/// messages with its own Schnorr signing key, whose verifying key the other members hold, such that no member can send in the name of another.
+/// The challenge of a signature hashes the verifying key, the commitment and the message in a merlin transcript.
+/// A member binds its verifying key to its WAKE statement by announcing it with its WAKE signature (see ChannelKeyAnnouncement).
+/// The epoch keys are derived from the session key by a ratchet (see ratchet.rs), such that rotating the channel to the next epoch
+/// wipes the keys of the previous epoch
+///
+//domain separation tag of the key derivation, and of the associated data of a message
+pub const CHANNEL_DOMAIN: &[u8] = b"WAKE-CHANNEL-V1";
+pub const CHANNEL_NONCE_LEN: usize = 12;
+//length of the header of a serialized message: the sender, the epoch, the sequence number and the nonce
+pub const CHANNEL_HEADER_LEN: usize = 32 + 8 + 8 + CHANNEL_NONCE_LEN;
+//length of the signature of a message: the commitment and response of a Schnorr signature
+pub const CHANNEL_SIGNATURE_LEN: usize = 64;
+
+///
+/// Signing key of a member of the group, with which it signs its messages, and its verifying key
+///
+pub struct ChannelSigningKey {
+    secret: Zeroizing<Scalar>,
+    verifying_key: RistrettoPoint,
+}
+
+impl ChannelSigningKey {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> ChannelSigningKey {
+        let secret = Zeroizing::new(Scalar::random(rng));
+        let verifying_key = PedersenGens::default().B * *secret;
+        ChannelSigningKey { secret, verifying_key }
+    }
+
+    pub fn verifying_key(&self) -> RistrettoPoint {
+        self.verifying_key
+    }
+
+    //Schnorr signature (R, s) of bytes, with R = g^k and s = k + c * secret
+    fn sign<R: RngCore + CryptoRng>(&self, bytes: &[u8], rng: &mut R) -> [u8; CHANNEL_SIGNATURE_LEN] {
+        let nonce = Zeroizing::new(Scalar::random(rng));
+        let commitment = (PedersenGens::default().B * *nonce).compress();
+        let response = *nonce + signature_challenge(&self.verifying_key.compress(), &commitment, bytes) * *self.secret;
+
+        let mut signature = [0u8; CHANNEL_SIGNATURE_LEN];
+        signature[..32].copy_from_slice(commitment.as_bytes());
+        signature[32..].copy_from_slice(response.as_bytes());
+        signature
+    }
+}
+
+//challenge of a Schnorr signature, binding the verifying key, the commitment and the signed bytes
+fn signature_challenge(verifying_key: &CompressedRistretto, commitment: &CompressedRistretto, bytes: &[u8]) -> Scalar {
+    let mut transcript = Transcript::new(b"WAKE-CHANNEL-SIGNATURE-V1");
+    transcript.append_message(b"X", verifying_key.as_bytes());
+    transcript.append_message(b"R", commitment.as_bytes());
+    transcript.append_message(b"m", bytes);
+    let mut challenge = [0u8; 64];
+    transcript.challenge_bytes(b"c", &mut challenge);
+    Scalar::from_bytes_mod_order_wide(&challenge)
+}
+
+//verifies the Schnorr signature of bytes under verifying_key, g^s = R * X^c, rejecting malformed signatures
+fn verify_signature(verifying_key: &RistrettoPoint, bytes: &[u8], signature: &[u8; CHANNEL_SIGNATURE_LEN]) -> bool {
+    let commitment = CompressedRistretto::from_slice(&signature[..32]);
+    let mut response = [0u8; 32];
+    response.copy_from_slice(&signature[32..]);
+
+    match (commitment.decompress(), Scalar::from_canonical_bytes(response)) {
+        (Some(commitment_point), Some(response)) => {
+            let challenge = signature_challenge(&verifying_key.compress(), &commitment, bytes);
+            PedersenGens::default().B * response == commitment_point + verifying_key * challenge
+        }
+        _ => false
+    }
+}
+
+///
+/// Announcement of the verifying key of a member, signed with the WAKE signature of the member (a Bulletproofs range proof under its
+/// commitment), such that the key is bound to the statement the identifier of the member is derived from
+///
+#[derive(Clone)]
+pub struct ChannelKeyAnnouncement {
+    pub id: PartyId,
+    pub verifying_key: RistrettoPoint,
+    signature: CompSignature,
+}
+
+impl ChannelKeyAnnouncement {
+    //announces the verifying key of signing_key, for the member with the given balance and blinding factor of its commitment
+    pub fn new<R: RngCore + CryptoRng>(params: &WakeParams<BulletproofsKeys>, id: PartyId, signing_key: &ChannelSigningKey, balance: u64, blinding: Scalar, rng: &mut R) -> ChannelKeyAnnouncement {
+        let verifying_key = signing_key.verifying_key();
+        let signature = CompSignature::sign(params, announcement_string(&id, &verifying_key), balance, blinding, rng);
+        ChannelKeyAnnouncement { id, verifying_key, signature }
+    }
+
+    //verifies the announcement against the commitment of the member
+    //outputs the identifier of the member, with its verifying key
+    pub fn verify(&self, params: &WakeParams<BulletproofsKeys>, commitment: &RistrettoPoint) -> Result<(PartyId, RistrettoPoint), String> {
+        if PartyId::from_commitment(commitment) != self.id {
+            return Err(format!("Commitment does not belong to party {}", self.id))
+        }
+        let updated_bal_comm = commitment + (params.g() * Scalar::from(params.min_bal()));
+        if !self.signature.clone().verify_signature(params, announcement_string(&self.id, &self.verifying_key), &updated_bal_comm) {
+            return Err(format!("Verification of the channel key of party {} failed", self.id))
+        }
+        Ok((self.id, self.verifying_key))
+    }
+}
+
+//message signed by the announcement of a verifying key
+fn announcement_string(id: &PartyId, verifying_key: &RistrettoPoint) -> String {
+    String::from_utf8_lossy(CHANNEL_DOMAIN).to_string() + "##" + &id.to_string() + "##" + &ristretto_to_string(*verifying_key)
+}
+
+///
+/// Encrypted broadcast message of a member of the group, signed by its sender
+/// serialized as the sender, the epoch, the sequence number, the nonce, the signature, followed by the ciphertext
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelMessage {
+    pub sender: PartyId,
+    pub epoch: u64,
+    pub sequence: u64,
+    nonce: [u8; CHANNEL_NONCE_LEN],
+    signature: [u8; CHANNEL_SIGNATURE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl ChannelMessage {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.sender.to_bytes().to_vec();
+        bytes.extend_from_slice(&self.epoch.to_le_bytes());
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.signature);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ChannelMessage, String> {
+        if bytes.len() < CHANNEL_HEADER_LEN + CHANNEL_SIGNATURE_LEN {
+            return Err("Channel message is too short".to_string())
+        }
+        let mut sender = [0u8; 32];
+        sender.copy_from_slice(&bytes[..32]);
+        let mut epoch = [0u8; 8];
+        epoch.copy_from_slice(&bytes[32..40]);
+        let mut sequence = [0u8; 8];
+        sequence.copy_from_slice(&bytes[40..48]);
+        let mut nonce = [0u8; CHANNEL_NONCE_LEN];
+        nonce.copy_from_slice(&bytes[48..CHANNEL_HEADER_LEN]);
+        let mut signature = [0u8; CHANNEL_SIGNATURE_LEN];
+        signature.copy_from_slice(&bytes[CHANNEL_HEADER_LEN..CHANNEL_HEADER_LEN + CHANNEL_SIGNATURE_LEN]);
+
+        Ok(ChannelMessage {
+            sender: PartyId::from_bytes(sender),
+            epoch: u64::from_le_bytes(epoch),
+            sequence: u64::from_le_bytes(sequence),
+            nonce,
+            signature,
+            ciphertext: bytes[CHANNEL_HEADER_LEN + CHANNEL_SIGNATURE_LEN..].to_vec()
+        })
+    }
+
+    //associated data of the message, binding the ciphertext to its sender, epoch and sequence number
+    fn associated_data(&self) -> Vec<u8> {
+        let mut aad = CHANNEL_DOMAIN.to_vec();
+        aad.extend_from_slice(&self.sender.to_bytes());
+        aad.extend_from_slice(&self.epoch.to_le_bytes());
+        aad.extend_from_slice(&self.sequence.to_le_bytes());
+        aad
+    }
+
+    //bytes signed by the sender: the associated data, the nonce and the ciphertext
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.associated_data();
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+}
+
+//sender keys of the members, by their identifier
+type SenderKeys = HashMap<PartyId, Zeroizing<[u8; 32]>>;
+
+///
+/// Channel of a member to the other members of the group, holding the ratchet of the epoch keys, and the sender keys of all members
+/// in the current epoch, which are wiped when the channel rotates or is dropped
+/// holds the signing key of the member, and the verifying keys of all members
+///
+pub struct GroupChannel {
+    id: PartyId,
+    members: Vec<PartyId>,
+    signing_key: ChannelSigningKey,
+    verifying_keys: HashMap<PartyId, RistrettoPoint>,
+    ratchet: KeyRatchet,
+    sender_keys: SenderKeys,
+    //sequence number of the next message sent
+    sequence: u64,
+    //sequence number of the last message accepted from every other member
+    accepted: HashMap<PartyId, u64>,
+}
+
+impl GroupChannel {
+    //opens the channel of party id at epoch 0, with the key established by a WAKE session among the members
+    //members lists every member with its verifying key, which must match the signing key of the party for the party itself
+    pub fn new(session_key: &RistrettoPoint, id: PartyId, signing_key: ChannelSigningKey, members: &[(PartyId, RistrettoPoint)]) -> Result<GroupChannel, String> {
+        let verifying_keys: HashMap<PartyId, RistrettoPoint> = members.iter().copied().collect();
+        match verifying_keys.get(&id) {
+            None => return Err(format!("Party {} is not a member of the group", id)),
+            Some(verifying_key) if *verifying_key != signing_key.verifying_key() => return Err(format!("Signing key does not belong to party {}", id)),
+            Some(_) => (),
+        }
+
+        let members: Vec<PartyId> = members.iter().map(|(member, _)| *member).collect();
+        let ratchet = KeyRatchet::new(session_key);
+        let sender_keys = sender_keys(&ratchet.epoch_key(), &members)?;
+        Ok(GroupChannel { id, members, signing_key, verifying_keys, ratchet, sender_keys, sequence: 0, accepted: HashMap::new() })
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    pub fn members(&self) -> &[PartyId] {
+        &self.members
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.ratchet.epoch()
+    }
+
+    //rotates the channel to the next epoch, after which the sequence numbers start over
+    //messages of earlier epochs can no longer be decrypted
+    pub fn rotate(&mut self) -> Result<(), String> {
+        self.sender_keys = sender_keys(&self.ratchet.advance(), &self.members)?;
+        self.sequence = 0;
+        self.accepted.clear();
+        Ok(())
+    }
+
+    //encrypts a broadcast message to the group, under the sender key of the party, with the next sequence number, and signs it
+    pub fn send<R: RngCore + CryptoRng>(&mut self, plaintext: &[u8], rng: &mut R) -> Result<ChannelMessage, String> {
+        let mut message = ChannelMessage { sender: self.id, epoch: self.epoch(), sequence: self.sequence, nonce: [0u8; CHANNEL_NONCE_LEN], signature: [0u8; CHANNEL_SIGNATURE_LEN], ciphertext: Vec::new() };
+        rng.fill_bytes(&mut message.nonce);
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(self.sender_keys[&self.id].as_ref()));
+        let aad = message.associated_data();
+        message.ciphertext = cipher.encrypt(Nonce::from_slice(&message.nonce), Payload { msg: plaintext, aad: &aad }).map_err(|_| "Encryption of message failed".to_string())?;
+        message.signature = self.signing_key.sign(&message.signed_bytes(), rng);
+
+        self.sequence += 1;
+        Ok(message)
+    }
+
+    //decrypts a broadcast message of another member in the current epoch, rejecting messages of non-members, altered messages,
+    //messages not signed by their sender, and replayed messages, whose sequence number is not above the last one accepted from the sender
+    pub fn recieve(&mut self, message: &ChannelMessage) -> Result<Zeroizing<Vec<u8>>, String> {
+        if message.sender == self.id {
+            return Err("Message was sent by the party itself".to_string())
+        }
+        if message.epoch != self.epoch() {
+            return Err(format!("Message of epoch {} was received in epoch {}", message.epoch, self.epoch()))
+        }
+        let key = self.sender_keys.get(&message.sender).ok_or(format!("Party {} is not a member of the group", message.sender))?;
+        if let Some(last) = self.accepted.get(&message.sender) {
+            if message.sequence <= *last {
+                return Err(format!("Replayed message {} from party {}", message.sequence, message.sender))
+            }
+        }
+
+        //the signature authenticates the sender against the other members, who all hold its sender key
+        if !verify_signature(&self.verifying_keys[&message.sender], &message.signed_bytes(), &message.signature) {
+            return Err(format!("Signature of message from party {} is invalid", message.sender))
+        }
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+        let aad = message.associated_data();
+        let plaintext = cipher.decrypt(Nonce::from_slice(&message.nonce), Payload { msg: &message.ciphertext, aad: &aad }).map_err(|_| format!("Authentication of message from party {} failed", message.sender))?;
+
+        self.accepted.insert(message.sender, message.sequence);
+        Ok(Zeroizing::new(plaintext))
+    }
+}
+
+//sender keys of the members in an epoch, derived from the key of the epoch
+fn sender_keys(epoch_key: &EpochKey, members: &[PartyId]) -> Result<SenderKeys, String> {
+    let hkdf = Hkdf::<Sha256>::new(Some(CHANNEL_DOMAIN), epoch_key.as_bytes());
+    let mut sender_keys = HashMap::new();
+    for member in members.iter() {
+        let mut key = Zeroizing::new([0u8; 32]);
+        hkdf.expand(&member.to_bytes(), key.as_mut()).map_err(|_| "Derivation of sender key failed".to_string())?;
+        sender_keys.insert(*member, key);
+    }
+    Ok(sender_keys)
+}
@@ -0,0 +1,160 @@
+use super::{compiler_bp_wake_protocol::{setup_parties, Party}, compiler_bp_wake_signature::{CompMessage, CompSignature}};
+use crate::{
+    params::{WakeParams, BulletproofsKeys},
+    protocols::async_network::{in_memory_network, run_blocking, Endpoint},
+    protocols::party_id::{PartyId, Ring},
+    utility::fork_rngs
+};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+use rand::{CryptoRng, RngCore};
+use rand_chacha::ChaCha20Rng;
+use std::{error::Error, sync::Arc};
+
+///
+/// File for running a party of the compiler-version of the WAKE protocol as a tokio task. Every round sends the message of
+/// the party over its endpoint, and every receive awaits the messages of all members of the round. Signing and verification
+/// run on the blocking thread pool of tokio, such that they do not stall the tasks of other parties
+///
+//amount of rounds of the compiler-version
+pub const COMPILER_ROUNDS: usize = 3;
+
+///
+/// Message of the compiler-version as sent over the network: the nonce of round 1, or a signed message of round 2/3
+///
+#[derive(Clone)]
+pub enum CompilerNetworkMessage {
+    Nonce(Scalar),
+    Signed(Box<(CompMessage, CompSignature)>),
+}
+
+///
+/// Party of the compiler-version of the WAKE protocol, communicating over an endpoint
+/// holds the party and its rng, which are moved to the blocking thread pool while signing or verifying,
+/// and the Burmester-Desmedt values received in rounds 2 and 3
+///
+pub struct AsyncParty {
+    state: Option<(Party, ChaCha20Rng)>,
+    endpoint: Endpoint<CompilerNetworkMessage>,
+    z_list: Vec<(PartyId, RistrettoPoint)>,
+    x_list: Vec<(PartyId, RistrettoPoint)>,
+}
+
+impl AsyncParty {
+    pub fn new(party: Party, endpoint: Endpoint<CompilerNetworkMessage>, rng: ChaCha20Rng) -> Result<AsyncParty, String> {
+        if party.id() != endpoint.id() {
+            return Err("Endpoint does not belong to the party".to_string())
+        }
+        Ok(AsyncParty { state: Some((party, rng)), endpoint, z_list: Vec::new(), x_list: Vec::new() })
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.endpoint.id()
+    }
+
+    //the party is missing only if a blocking task panicked
+    fn state(&mut self) -> Result<&mut (Party, ChaCha20Rng), String> {
+        self.state.as_mut().ok_or("Party was lost in a failed blocking task".to_string())
+    }
+
+    //runs f over the party and its rng on the blocking thread pool
+    async fn blocking<T: Send + 'static, F: FnOnce(&mut Party, &mut ChaCha20Rng) -> T + Send + 'static>(&mut self, f: F) -> Result<T, String> {
+        let state = self.state.take().ok_or("Party was lost in a failed blocking task".to_string())?;
+        let (state, output) = run_blocking(state, move |(party, rng)| f(party, rng)).await?;
+        self.state = Some(state);
+        Ok(output)
+    }
+
+    //awaits the signed messages of all members in round round_idx
+    async fn recieve_signed(&mut self, round_idx: usize) -> Result<Vec<(CompMessage, CompSignature)>, String> {
+        let clock = *self.endpoint.clock();
+        let collector = self.state()?.0.collect_round(round_idx, &clock);
+        let messages = self.endpoint.recieve_round(collector).await?;
+        messages.into_iter().map(|(sender, message)| match message {
+            CompilerNetworkMessage::Signed(signed) if signed.0.sender == sender => Ok(*signed),
+            _ => Err(format!("Unexpected message of party {} in round {}", sender, round_idx)),
+        }).collect()
+    }
+
+    //round 1: broadcast a fresh nonce
+    pub async fn round_1(&mut self) -> Result<(), String> {
+        let (party, rng) = self.state()?;
+        let (_, nonce) = party.round_1(rng);
+        self.endpoint.broadcast(1, CompilerNetworkMessage::Nonce(nonce)).await
+    }
+
+    //round 1 receive: await the nonces of all members
+    pub async fn round1_recieve(&mut self) -> Result<(), String> {
+        let clock = *self.endpoint.clock();
+        let collector = self.state()?.0.collect_round(1, &clock);
+        let messages = self.endpoint.recieve_round(collector).await?;
+        let round_1_outputs = messages.into_iter().map(|(sender, message)| match message {
+            CompilerNetworkMessage::Nonce(nonce) => Ok((sender, nonce)),
+            _ => Err(format!("Unexpected message of party {} in round 1", sender)),
+        }).collect::<Result<Vec<(PartyId, Scalar)>, String>>()?;
+        self.state()?.0.round1_recieve(&round_1_outputs)
+    }
+
+    //round 2: sign and broadcast the Burmester-Desmedt z value
+    pub async fn round_2(&mut self) -> Result<(), String> {
+        let (message, signature) = self.blocking(|party, rng| party.round_2(rng)).await?;
+        self.endpoint.broadcast(2, CompilerNetworkMessage::Signed(Box::new((message, signature)))).await
+    }
+
+    //round 3: sign and broadcast the Burmester-Desmedt X value
+    pub async fn round_3(&mut self) -> Result<(), String> {
+        let z_list = self.z_list.clone();
+        let (message, signature) = self.blocking(move |party, rng| party.round_3(&z_list, rng)).await??;
+        self.endpoint.broadcast(3, CompilerNetworkMessage::Signed(Box::new((message, signature)))).await
+    }
+
+    //receive round 2/3: await and verify the messages of all members, keeping their Burmester-Desmedt values
+    pub async fn round_recieve(&mut self, round_idx: usize) -> Result<(), String> {
+        let round_outputs = self.recieve_signed(round_idx).await?;
+        let payloads: Vec<(PartyId, RistrettoPoint)> = round_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+        self.blocking(move |party, rng| party.round_recieve(round_outputs, round_idx, rng)).await?.map_err(|misbehaviour| misbehaviour.to_string())?;
+
+        match round_idx {
+            2 => self.z_list = payloads,
+            _ => self.x_list = payloads,
+        }
+        Ok(())
+    }
+
+    //compute Burmester Desmedt key
+    pub fn compute_key(&mut self) -> Result<RistrettoPoint, String> {
+        let (z_list, x_list) = (self.z_list.clone(), self.x_list.clone());
+        self.state()?.0.compute_key(&z_list, &x_list)
+    }
+
+    //runs all rounds of a session
+    pub async fn run(mut self) -> Result<RistrettoPoint, String> {
+        self.round_1().await?;
+        self.round1_recieve().await?;
+        self.round_2().await?;
+        self.round_recieve(2).await?;
+        self.round_3().await?;
+        self.round_recieve(3).await?;
+        self.compute_key()
+    }
+}
+
+// Implements the compiler-version of the WAKE protocol, running every party as a tokio task over a network of in-memory channels
+// outputs the keys of the parties in the order of their balances
+pub async fn run_async_compiler_key_exchange<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let parties = setup_parties(rng, party_amount, balances, params)?;
+    let ring = Ring::new(parties.iter().map(|party| party.id()).collect())?;
+    let mut endpoints: Vec<Option<Endpoint<CompilerNetworkMessage>>> = in_memory_network(&ring, COMPILER_ROUNDS).into_iter().map(Some).collect();
+
+    let mut tasks = Vec::new();
+    for (party, rng) in parties.into_iter().zip(fork_rngs(rng, party_amount)) {
+        let position = ring.position(&party.id()).ok_or("Party is not a member of the ring")?;
+        let endpoint = endpoints[position].take().ok_or("Endpoint is already taken")?;
+        tasks.push(tokio::spawn(AsyncParty::new(party, endpoint, rng)?.run()));
+    }
+
+    let mut keys = Vec::new();
+    for task in tasks {
+        keys.push(task.await??);
+    }
+    Ok(keys)
+}
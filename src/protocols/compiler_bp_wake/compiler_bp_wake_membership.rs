@@ -0,0 +1,497 @@
+use super::compiler_bp_wake_signature::CompSignature;
+use crate::{
+    params::{WakeParams, BulletproofsKeys},
+    proof_systems::discrete_log_knowledege_proof::{DiscreteLogKnowledgeProof, DiscreteLogKnowledgeProofStruct},
+    protocols::party_id::{PartyId, Ring},
+    utility::{find_prev_idx, get_adjacent_elements, hash_string, ristretto_to_string}
+};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+use hex::ToHex;
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+use std::{error::Error, fmt, sync::Arc};
+
+///
+/// File for changing the members of an established compiler WAKE session, without re-running the whole key-exchange.
+/// A joining party proves the darkpool relation once, and is inserted into the Burmester-Desmedt ring, where only its ring
+/// neighbours contribute fresh values. A leaving party is removed from the ring, after which its former neighbours contribute
+/// fresh values. In both cases only the parties next to a fresh value recompute their Burmester-Desmedt X value, and all members
+/// derive a fresh key, which a departed party cannot compute and a joining party cannot use to compute earlier keys.
+/// Without a membership change, all members can re-key the session with a fresh Burmester-Desmedt among themselves.
+/// Re-key messages of members are authenticated by a proof of knowledge of their current Burmester-Desmedt exponent,
+/// as their Burmester-Desmedt value was authenticated by the darkpool relation when they joined. The challenge of the proof hashes
+/// the commitment of the prover and the message, such that a departed party or an outsider cannot inject values for a member
+///
+/// Corresponds to a re-key message of an established session
+/// holds the identifier of the sending party, the epoch of the session, the round-index, the actual contents of the message,
+/// and the fingerprint of the session before the membership change
+///
+#[derive(Clone)]
+pub struct RekeyMessage {
+    pub sender: PartyId,
+    pub epoch: usize,
+    pub round_idx: usize,
+    pub payload: RistrettoPoint,
+    pub context: String,
+}
+
+impl RekeyMessage {
+    pub fn new(sender: PartyId, epoch: usize, round_idx: usize, payload: RistrettoPoint, context: String) -> RekeyMessage {
+        RekeyMessage { sender, epoch, round_idx, payload, context }
+    }
+
+}
+
+impl fmt::Display for RekeyMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}##{}##{}##{}##{}", self.sender, self.epoch, self.round_idx, ristretto_to_string(self.payload), self.context)
+    }
+}
+
+///
+/// Request of a party to join an established session
+/// holds the commitment of the party, its Burmester-Desmedt value, and a signature for the darkpool relation over the message
+///
+#[derive(Clone)]
+pub struct JoinRequest {
+    pub commitment: RistrettoPoint,
+    pub message: RekeyMessage,
+    pub signature: CompSignature,
+}
+
+///
+/// Public values of an established session, handed to a party that wants to join it
+/// the commitments are in ring order, and the Burmester-Desmedt values are listed by the identifier of their party
+///
+#[derive(Clone)]
+pub struct PublicSession {
+    pub epoch: usize,
+    pub commitments: Vec<RistrettoPoint>,
+    pub z_list: Vec<(PartyId, RistrettoPoint)>,
+    pub x_list: Vec<(PartyId, RistrettoPoint)>,
+}
+
+impl PublicSession {
+    //fingerprint of the session, which binds the messages of a membership change to it
+    //the values are sorted by identifier, such that it does not depend on the order they were received in
+    fn fingerprint(&self) -> String {
+        let mut z_list = self.z_list.clone();
+        let mut x_list = self.x_list.clone();
+        z_list.sort_by_key(|(id, _)| *id);
+        x_list.sort_by_key(|(id, _)| *id);
+
+        let mut res_string = self.epoch.to_string();
+        for (id, z) in z_list.iter() {
+            res_string += &(id.to_string() + &ristretto_to_string(*z));
+        }
+        for (id, x) in x_list.iter() {
+            res_string += &(id.to_string() + &ristretto_to_string(*x));
+        }
+        hash_string(&res_string).encode_hex::<String>()
+    }
+}
+
+//re-key in progress after a membership change
+struct Rekey {
+    //fingerprint of the session before the change
+    context: String,
+    //members contributing a fresh Burmester-Desmedt value
+    refreshing: Vec<PartyId>,
+    //members whose Burmester-Desmedt value is fresh, once the refreshing members are received
+    fresh: Vec<PartyId>,
+}
+
+///
+/// Struct representing the state of a party in an established session
+/// the Burmester-Desmedt exponent is kept for the lifetime of the session, such that the key can be re-computed after a membership change,
+/// and is wiped when the exponent is refreshed or the session is dropped
+///
+pub struct GroupSession {
+    id: PartyId,
+    params: Arc<WakeParams<BulletproofsKeys>>,
+    epoch: usize,
+    ring: Ring,
+    //commitments of the members in statement order of the ring
+    commitments: Vec<RistrettoPoint>,
+    z_list: Vec<(PartyId, RistrettoPoint)>,
+    x_list: Vec<(PartyId, RistrettoPoint)>,
+    r: Zeroizing<Scalar>,
+    key: Option<RistrettoPoint>,
+    rekey: Option<Rekey>,
+}
+
+impl GroupSession {
+    //establishes the session from a completed key-exchange
+    pub(super) fn new(id: PartyId, params: Arc<WakeParams<BulletproofsKeys>>, commitments: Vec<RistrettoPoint>, z_list: &[(PartyId, RistrettoPoint)], x_list: &[(PartyId, RistrettoPoint)], r: Zeroizing<Scalar>) -> Result<GroupSession, String> {
+        let ring = Ring::from_commitments(&commitments)?;
+        let z_list = ring.ids().iter().copied().zip(ring.arrange(z_list)?).collect();
+        let x_list = ring.ids().iter().copied().zip(ring.arrange(x_list)?).collect();
+
+        let mut session = GroupSession { id, params, epoch: 0, ring, commitments, z_list, x_list, r, key: None, rekey: None };
+        session.key = Some(session.bd_key()?);
+        Ok(session)
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //number of completed membership changes
+    pub fn epoch(&self) -> usize {
+        self.epoch
+    }
+
+    pub fn members(&self) -> &[PartyId] {
+        self.ring.ids()
+    }
+
+    //key of the current epoch, none for a joining party until the re-key is complete
+    pub fn key(&self) -> Option<RistrettoPoint> {
+        self.key
+    }
+
+    pub fn public_session(&self) -> PublicSession {
+        PublicSession {
+            epoch: self.epoch,
+            commitments: self.ring.arrange_statements(&self.commitments),
+            z_list: self.z_list.clone(),
+            x_list: self.x_list.clone(),
+        }
+    }
+
+    ///
+    /// Join function, for a party joining the given session with its own balance and blinding factor
+    /// outputs the state of the joining party, and its request, signed for the darkpool relation, to be sent to the members
+    ///
+    pub fn join<R: RngCore + CryptoRng>(params: Arc<WakeParams<BulletproofsKeys>>, public_session: &PublicSession, balance: u64, blinding: Scalar, rng: &mut R) -> Result<(GroupSession, JoinRequest), String> {
+        if balance < params.min_bal() {
+            return Err("balance is smaller than minimum_balance".to_string())
+        }
+        let commitment = (params.g() * Scalar::from(params.upperbound() - balance)) + (params.pc_gens().B_blinding * blinding);
+        let id = PartyId::from_commitment(&commitment);
+
+        let ring = Ring::from_commitments(&public_session.commitments)?;
+        let mut session = GroupSession {
+            id,
+            params: params.clone(),
+            epoch: public_session.epoch,
+            ring,
+            commitments: public_session.commitments.clone(),
+            z_list: public_session.z_list.clone(),
+            x_list: public_session.x_list.clone(),
+            r: Zeroizing::new(Scalar::random(rng)),
+            key: None,
+            rekey: None,
+        };
+
+        let z = params.g() * *session.r;
+        let message = RekeyMessage::new(id, session.epoch, 1, z, public_session.fingerprint());
+        let signature = CompSignature::sign(&params, message.to_string(), balance, blinding, rng);
+        session.insert(commitment, z, message.context.clone())?;
+
+        Ok((session, JoinRequest { commitment, message, signature }))
+    }
+
+    //process a join request of another party, verifying its signature for the darkpool relation
+    pub fn accept_join(&mut self, request: &JoinRequest) -> Result<(), String> {
+        let message = &request.message;
+        if message.sender != PartyId::from_commitment(&request.commitment) {
+            return Err(format!("Identifier of party {} does not match its commitment", message.sender))
+        }
+        if message.epoch != self.epoch || message.round_idx != 1 || message.context != self.public_session().fingerprint() {
+            return Err(format!("Join request of party {} is not for this session", message.sender))
+        }
+
+        //assert darkpool relation of the joining party
+        let updated_bal_comm = request.commitment + (self.params.g() * Scalar::from(self.params.min_bal()));
+        if !request.signature.clone().verify_signature(&self.params, message.to_string(), &updated_bal_comm) {
+            return Err(format!("Verification of signature failed for party {}", message.sender))
+        }
+
+        self.insert(request.commitment, message.payload, message.context.clone())
+    }
+
+    //inserts a joining party into the ring, after which its ring neighbours refresh their values
+    fn insert(&mut self, commitment: RistrettoPoint, z: RistrettoPoint, context: String) -> Result<(), String> {
+        if self.rekey.is_some() {
+            return Err("A membership change is already in progress".to_string())
+        }
+        let id = PartyId::from_commitment(&commitment);
+        if self.ring.position(&id).is_some() {
+            return Err(format!("Party {} is already a member", id))
+        }
+
+        self.commitments.push(commitment);
+        self.ring = Ring::from_commitments(&self.commitments)?;
+        self.z_list.push((id, z));
+
+        let refreshing = self.neighbours(&id);
+        let mut fresh = refreshing.clone();
+        fresh.push(id);
+        self.rekey = Some(Rekey { context, refreshing, fresh });
+        Ok(())
+    }
+
+    //removes a leaving party from the ring, after which its former ring neighbours refresh their values
+    pub fn remove(&mut self, id: &PartyId) -> Result<(), String> {
+        if self.rekey.is_some() {
+            return Err("A membership change is already in progress".to_string())
+        }
+        if *id == self.id {
+            return Err("A party cannot remove itself".to_string())
+        }
+        let idx = self.ring.statement_idx(id).ok_or(format!("Unknown party {}", id))?;
+        if self.ring.len() <= 2 {
+            return Err("A session requires at least two members".to_string())
+        }
+
+        let context = self.public_session().fingerprint();
+        let refreshing = self.neighbours(id);
+
+        self.commitments.remove(idx);
+        self.ring = Ring::from_commitments(&self.commitments)?;
+        self.z_list.retain(|(sender, _)| sender != id);
+        self.x_list.retain(|(sender, _)| sender != id);
+
+        self.rekey = Some(Rekey { context, fresh: refreshing.clone(), refreshing });
+        Ok(())
+    }
+
+    //starts a re-key without a membership change, in which every member contributes a fresh Burmester-Desmedt value
+    //the members do not prove the darkpool relation again, as their fresh values are authenticated by their current exponents
+    pub fn start_rekey(&mut self) -> Result<(), String> {
+        if self.rekey.is_some() {
+            return Err("A membership change is already in progress".to_string())
+        }
+
+        let members = self.ring.ids().to_vec();
+        self.rekey = Some(Rekey { context: self.public_session().fingerprint(), refreshing: members.clone(), fresh: members });
+        Ok(())
+    }
+
+    //previous and next party of id in the ring
+    fn neighbours(&self, id: &PartyId) -> Vec<PartyId> {
+        let position = self.ring.position(id).unwrap();
+        let prev = self.ring.ids()[find_prev_idx(position, self.ring.len())];
+        let next = self.ring.ids()[(position + 1) % self.ring.len()];
+        if prev == next {
+            return vec![prev]
+        }
+        vec![prev, next]
+    }
+
+    //round 2 of a membership change: a ring neighbour of the joining or leaving party contributes a fresh Burmester-Desmedt value
+    //authenticated by a proof of knowledge of its previous exponent
+    pub fn refresh<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Result<Option<(RekeyMessage, DiscreteLogKnowledgeProof)>, String> {
+        let rekey = self.rekey.as_ref().ok_or("No membership change is in progress")?;
+        if !rekey.refreshing.contains(&self.id) {
+            return Ok(None)
+        }
+
+        let r = Zeroizing::new(Scalar::random(rng));
+        let z = self.params.g() * *r;
+        let message = RekeyMessage::new(self.id, self.epoch, 2, z, rekey.context.clone());
+        let proof = self.prove(&message, rng);
+
+        //the previous exponent is wiped when replaced
+        self.r = r;
+        self.set(true, self.id, z);
+        Ok(Some((message, proof)))
+    }
+
+    //process the fresh values of the refreshing members
+    pub fn refresh_recieve(&mut self, refresh_outputs: &[(RekeyMessage, DiscreteLogKnowledgeProof)]) -> Result<(), String> {
+        let rekey = self.rekey.as_ref().ok_or("No membership change is in progress")?;
+        let refreshing = rekey.refreshing.clone();
+        self.recieve(refresh_outputs, &refreshing, 2, true)
+    }
+
+    //round 3 of a membership change: every member next to a fresh value recomputes its Burmester-Desmedt X value
+    //authenticated by a proof of knowledge of its current exponent
+    pub fn update<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Result<Option<(RekeyMessage, DiscreteLogKnowledgeProof)>, String> {
+        let rekey = self.rekey.as_ref().ok_or("No membership change is in progress")?;
+        if !self.updating()?.contains(&self.id) {
+            return Ok(None)
+        }
+
+        let z_list = self.ring.arrange(&self.z_list)?;
+        let position = self.ring.position(&self.id).unwrap();
+        let (z_1, z_2) = get_adjacent_elements(&z_list, &position);
+        let x = (z_2 - z_1) * *self.r;
+
+        let message = RekeyMessage::new(self.id, self.epoch, 3, x, rekey.context.clone());
+        let proof = self.prove(&message, rng);
+
+        self.set(false, self.id, x);
+        Ok(Some((message, proof)))
+    }
+
+    //process the recomputed X values of the updating members
+    pub fn update_recieve(&mut self, update_outputs: &[(RekeyMessage, DiscreteLogKnowledgeProof)]) -> Result<(), String> {
+        let updating = self.updating()?;
+        self.recieve(update_outputs, &updating, 3, false)
+    }
+
+    //members whose X value changes: those with a fresh value themselves, or next to one
+    fn updating(&self) -> Result<Vec<PartyId>, String> {
+        let rekey = self.rekey.as_ref().ok_or("No membership change is in progress")?;
+        Ok(self.ring.ids().iter().copied().filter(|id| {
+            rekey.fresh.contains(id) || self.neighbours(id).iter().any(|neighbour| rekey.fresh.contains(neighbour))
+        }).collect())
+    }
+
+    //verifies the messages of the expected senders of a round, and stores their values
+    fn recieve(&mut self, outputs: &[(RekeyMessage, DiscreteLogKnowledgeProof)], expected: &[PartyId], round_idx: usize, is_z: bool) -> Result<(), String> {
+        let context = self.rekey.as_ref().unwrap().context.clone();
+
+        let mut received: Vec<PartyId> = Vec::new();
+        for (message, proof) in outputs {
+            if !expected.contains(&message.sender) {
+                return Err(format!("Unexpected message from party {}", message.sender))
+            }
+            if received.contains(&message.sender) {
+                return Err(format!("Duplicate message from party {}", message.sender))
+            }
+            received.push(message.sender);
+
+            if message.sender == self.id {
+                continue;
+            }
+            if message.epoch != self.epoch || message.round_idx != round_idx || message.context != context {
+                return Err(format!("Message of party {} is not for this membership change", message.sender))
+            }
+
+            //assert knowledge of the exponent of the sender's current Burmester-Desmedt value
+            let z = self.z_list.iter().find(|(id, _)| *id == message.sender).map(|(_, z)| *z).ok_or(format!("Unknown party {}", message.sender))?;
+            if !DiscreteLogKnowledgeProofStruct::new(z.compress()).verify(*proof, message.to_string()) {
+                return Err(format!("Verification of proof failed for party {}", message.sender))
+            }
+            self.set(is_z, message.sender, message.payload);
+        }
+
+        if received.len() != expected.len() {
+            return Err(format!("Invalid length expected: {}, found {}", expected.len(), received.len()))
+        }
+        Ok(())
+    }
+
+    //compute the Burmester-Desmedt key of the new epoch, once all updated values are received
+    pub fn compute_key(&mut self) -> Result<RistrettoPoint, String> {
+        if self.rekey.is_none() {
+            return Err("No membership change is in progress".to_string())
+        }
+        let key = self.bd_key()?;
+
+        self.epoch += 1;
+        self.key = Some(key);
+        self.rekey = None;
+        Ok(key)
+    }
+
+    fn prove<R: RngCore + CryptoRng>(&self, message: &RekeyMessage, rng: &mut R) -> DiscreteLogKnowledgeProof {
+        let z = self.params.g() * *self.r;
+        DiscreteLogKnowledgeProofStruct::new(z.compress()).prove(*self.r, message.to_string(), rng)
+    }
+
+    //replaces the Burmester-Desmedt value of a party, its z value or its X value
+    fn set(&mut self, is_z: bool, id: PartyId, value: RistrettoPoint) {
+        let list = if is_z { &mut self.z_list } else { &mut self.x_list };
+        match list.iter_mut().find(|(sender, _)| *sender == id) {
+            Some(entry) => entry.1 = value,
+            None => list.push((id, value)),
+        }
+    }
+
+    //Burmester-Desmedt key over the current ring
+    fn bd_key(&self) -> Result<RistrettoPoint, String> {
+        let z_list = self.ring.arrange(&self.z_list)?;
+        let x_list = self.ring.arrange(&self.x_list)?;
+        let num_parties = x_list.len();
+        let position = self.ring.position(&self.id).unwrap();
+
+        let z = z_list[find_prev_idx(position, num_parties)];
+        let exponent = Zeroizing::new(*self.r * Scalar::from(num_parties as u64));
+        let mut key = z * *exponent;
+
+        for idx in 0..num_parties - 1 {
+            let i = (position + idx) % num_parties;
+            key += x_list[i] * Scalar::from((num_parties - idx - 1) as u64);
+        }
+
+        Ok(key)
+    }
+}
+
+//runs the re-key of a membership change between the members of the session
+//outputs the fresh keys of the members, in order
+fn run_rekey<R: RngCore + CryptoRng>(sessions: &mut [GroupSession], rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    //round 2: fresh values of the ring neighbours
+    let mut refresh_outputs = Vec::new();
+    for session in sessions.iter_mut() {
+        refresh_outputs.extend(session.refresh(rng)?);
+    }
+    for session in sessions.iter_mut() {
+        session.refresh_recieve(&refresh_outputs)?;
+    }
+
+    //round 3: X values next to the fresh values
+    let mut update_outputs = Vec::new();
+    for session in sessions.iter_mut() {
+        update_outputs.extend(session.update(rng)?);
+    }
+    for session in sessions.iter_mut() {
+        session.update_recieve(&update_outputs)?;
+    }
+
+    //compute key
+    let mut keys: Vec<RistrettoPoint> = Vec::new();
+    for session in sessions.iter_mut() {
+        keys.push(session.compute_key()?);
+    }
+    Ok(keys)
+}
+
+///
+/// Adds a party with the given balance to an established session
+/// outputs the fresh keys of all members, including the joining party
+///
+pub fn run_join<R: RngCore + CryptoRng>(sessions: &mut Vec<GroupSession>, params: &Arc<WakeParams<BulletproofsKeys>>, balance: u64, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let public_session = sessions.first().ok_or("no established session")?.public_session();
+    let blinding = Scalar::random(rng);
+    let (session, request) = GroupSession::join(params.clone(), &public_session, balance, blinding, rng)?;
+
+    for member in sessions.iter_mut() {
+        member.accept_join(&request)?;
+    }
+    sessions.push(session);
+
+    run_rekey(sessions, rng)
+}
+
+///
+/// Removes a party from an established session
+/// outputs the fresh keys of the remaining members
+///
+pub fn run_leave<R: RngCore + CryptoRng>(sessions: &mut Vec<GroupSession>, id: &PartyId, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    //the leaving party wipes its exponent as its session is dropped
+    let position = sessions.iter().position(|session| session.id() == *id).ok_or(format!("unknown party {}", id))?;
+    sessions.remove(position);
+
+    for member in sessions.iter_mut() {
+        member.remove(id)?;
+    }
+
+    run_rekey(sessions, rng)
+}
+
+///
+/// Re-keys an established session among its members, with a fresh Burmester-Desmedt over the current ring
+/// outputs the fresh keys of the members
+///
+pub fn run_full_rekey<R: RngCore + CryptoRng>(sessions: &mut [GroupSession], rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    for session in sessions.iter_mut() {
+        session.start_rekey()?;
+    }
+
+    run_rekey(sessions, rng)
+}
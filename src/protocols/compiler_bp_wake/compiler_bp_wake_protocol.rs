@@ -1,43 +1,75 @@
 use crate::{
-    proof_systems::range_proof::RangeProofStruct, 
+    params::{WakeParams, BulletproofsKeys, SignatureBackend},
+    proof_systems::batch_verification::BatchVerifier, 
     protocols::compiler_bp_wake::compiler_bp_wake_signature::{
         CompMessage, 
         CompSignature
     }, 
+    protocols::compiler_bp_wake::compiler_bp_wake_membership::GroupSession,
+    protocols::session_transcript::SessionTranscript,
+    protocols::party_id::{PartyId, Ring},
+    protocols::session_id::{SessionContext, SessionId, SharedReplayCache},
+    protocols::robust::{Misbehaviour, RingMember, RobustConfig, RobustOutcome, run_robust_key_exchange},
+    protocols::broadcast::{BroadcastView, Conflict, Echo},
+    protocols::timeout::{Clock, RoundCollector, RoundDeadlines},
+    protocols::snapshot::{self, EncryptedSnapshot, SnapshotKey, scalar_from_state, session_fingerprint},
     utility::{
         get_adjacent_elements, 
         find_prev_idx, 
-        scalar_to_string
+        ristretto_to_string,
+        scalar_to_string,
+        map_items,
+        map_parties_mut,
+        map_parties_with_rng
     }
 };
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint}};
-use bulletproofs::{PedersenGens};
-use rand::rngs::OsRng;
-use std::error::Error;
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+use std::{error::Error, sync::Arc};
 
 /// 
 /// File for running Burmester Desmedt key-exchange, with compiler WAKE scheme, using Bulletproof as underlying proof, for the Darkpool transaction relation
 /// 
+//protocol label binding snapshots to the protocol
+const SNAPSHOT_PROTOCOL: &str = "compiler_bp";
 
 ///
 /// Struct responsible for holding nonces from the WAKE protocol
+/// together with the id of the session, derived from the nonces and the context of the session
 /// 
 #[derive(Clone)]
 pub struct Nonces {
-    idxs: Vec<usize>,
-    scalars: Vec<Scalar>
+    ids: Vec<PartyId>,
+    scalars: Vec<Scalar>,
+    session_id: SessionId
 }
 
 impl Nonces {
-    pub fn new(idxs: Vec<usize>, scalars: Vec<Scalar>) -> Nonces {
-        return Nonces { idxs, scalars };
+    //nonces of a session run in the default context
+    pub fn new(ids: Vec<PartyId>, scalars: Vec<Scalar>) -> Nonces {
+        Nonces::with_context(ids, scalars, &SessionContext::default())
+    }
+
+    pub fn with_context(ids: Vec<PartyId>, scalars: Vec<Scalar>, context: &SessionContext) -> Nonces {
+        let session_id = SessionId::derive(context, &ids, &scalars.iter().map(|scalar| scalar.to_bytes()).collect::<Vec<_>>());
+        Nonces { ids, scalars, session_id }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    //round 1 contributions of the parties
+    pub fn contributions(&self) -> Vec<[u8; 32]> {
+        self.scalars.iter().map(|scalar| scalar.to_bytes()).collect()
     }
 
     pub fn to_string(self) -> String {
-        let mut res_string = String::new();
+        let mut res_string = self.session_id.to_string();
 
-        for i in 0..self.idxs.len() {
-            res_string += &(self.idxs[i].to_string() + &scalar_to_string(self.scalars[i]));
+        for i in 0..self.ids.len() {
+            res_string += &(self.ids[i].to_string() + &scalar_to_string(self.scalars[i]));
         }
 
         return res_string;
@@ -47,176 +79,373 @@ impl Nonces {
 
 ///
 /// Struct responsible for holding Burmester Desmedt values
+/// the exponent r is secret, and wiped when dropped
 /// 
-#[derive(Clone)]
 pub struct BurmesterDesmedt {
-    r: Scalar,
+    r: Zeroizing<Scalar>,
 }
 
 ///
 /// Struct representing a single party
+/// idx is the index of the party's own commitment, from which its identifier is derived
 /// 
 pub struct Party {
     //public values
-    idx: usize,
-    min_bal: u64,
+    id: PartyId,
+    ring: Ring,
+    params: Arc<WakeParams<BulletproofsKeys>>,
     commitments: Vec<RistrettoPoint>,
     //secret values
-    balance: u64,
-    blinding: Scalar,
+    balance: Zeroizing<u64>,
+    blinding: Zeroizing<Scalar>,
+    //timeouts of the rounds
+    deadlines: RoundDeadlines,
+    //context of the session, and the cache of earlier sessions
+    context: SessionContext,
+    replay_cache: Option<SharedReplayCache>,
+    //signed messages received in rounds 2 and 3, when the reliable broadcast is enabled
+    broadcast: Option<BroadcastView<(CompMessage, CompSignature)>>,
     //optional values getting initialized during the protocol
     nonces: Option<Nonces>,
     bd: Option<BurmesterDesmedt>
 }
 
 impl Party {
-    pub fn new(idx: usize, commitments: Vec<RistrettoPoint>, min_bal: u64, balance: u64, blinding: Scalar) -> Party {
+    pub fn new(idx: usize, params: Arc<WakeParams<BulletproofsKeys>>, commitments: Vec<RistrettoPoint>, ring: Ring, balance: u64, blinding: Scalar) -> Party {
         return Party{ 
-            idx,
+            id: PartyId::from_commitment(&commitments[idx]),
+            ring,
+            params,
             commitments, 
-            min_bal, 
-            balance, 
-            blinding, 
+            balance: Zeroizing::new(balance), 
+            blinding: Zeroizing::new(blinding), 
+            deadlines: RoundDeadlines::default(),
+            context: SessionContext::default(),
+            replay_cache: None,
+            broadcast: None,
             nonces: None, 
             bd: None 
         };
     }
 
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //proof the party signs its messages with, as selected by the parameters of the session
+    pub fn signature_backend(&self) -> SignatureBackend {
+        self.params.keys().signature_backend()
+    }
+
+    //position of the party in the ring
+    fn position(&self) -> usize {
+        self.ring.position(&self.id).unwrap()
+    }
+
+    //sets the context the session is run in, from which the session id is derived together with the round 1 outputs
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    //sets the cache of earlier sessions, rejecting sessions that reuse their round 1 outputs
+    pub fn set_replay_cache(&mut self, replay_cache: SharedReplayCache) {
+        self.replay_cache = Some(replay_cache);
+    }
+
+    //id of the session, once the round 1 outputs are received
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.nonces.as_ref().map(|nonces| nonces.session_id())
+    }
+
+    //sets the timeouts of the rounds, after which the party stops waiting for the messages of the other parties
+    pub fn set_deadlines(&mut self, deadlines: RoundDeadlines) {
+        self.deadlines = deadlines;
+    }
+
+    //enables the reliable broadcast: the party records the signed messages it receives, such that it can echo them to the other members
+    //and check their echoes for conflicting messages (see broadcast)
+    pub fn enable_reliable_broadcast(&mut self) {
+        self.broadcast = Some(BroadcastView::new());
+    }
+
+    //records the signed messages received in round round_idx, if the reliable broadcast is enabled
+    pub fn record_broadcast(&mut self, round_idx: usize, round_outputs: &[(CompMessage, CompSignature)]) {
+        if let Some(broadcast) = self.broadcast.as_mut() {
+            for (message, signature) in round_outputs.iter() {
+                broadcast.record(round_idx, message.sender, &message.to_string(), (message.clone(), signature.clone()));
+            }
+        }
+    }
+
+    //echo of the messages received in round round_idx, if the reliable broadcast is enabled
+    pub fn echo(&self, round_idx: usize) -> Option<Echo> {
+        self.broadcast.as_ref().map(|broadcast| broadcast.echo(round_idx, self.id))
+    }
+
+    //message received from the sender of conflict, handed out as evidence for the echo of the party
+    pub fn evidence(&self, conflict: &Conflict) -> Option<(CompMessage, CompSignature)> {
+        self.broadcast.as_ref().and_then(|broadcast| broadcast.evidence(conflict.round, &conflict.sender))
+    }
+
+    //checks the echoes of the other members for conflicting messages, where evidence outputs the message handed out by the echoer of a conflict
+    //on failure, blames the parties that signed conflicting messages in this session, or the parties whose echoes are not backed by such a message
+    pub fn check_echoes<E: Fn(&Conflict) -> Option<(CompMessage, CompSignature)>>(&self, echoes: &[Echo], evidence: E) -> Result<(), Misbehaviour> {
+        let broadcast = self.broadcast.as_ref().ok_or(Misbehaviour::unattributable("Reliable broadcast is not enabled".to_string()))?;
+        let session_id = self.session_id().ok_or(Misbehaviour::unattributable("Round 1 outputs are not received".to_string()))?;
+
+        broadcast.check_echoes(echoes, evidence, |conflict, (message, signature)| {
+            let i = self.ring.statement_idx(&conflict.sender)?;
+            if message.sender != conflict.sender || message.round_idx != conflict.round || message.nonces.session_id() != session_id {
+                return None
+            }
+            let updated_bal_comm = self.commitments[i] + (self.params.g() * Scalar::from(self.params.min_bal()));
+            signature.clone().verify_signature(&self.params, message.to_string(), &updated_bal_comm).then(|| message.to_string())
+        })
+    }
+
+    //starts collecting the messages of round round_idx from the members of the ring, until the timeout of the round passes on clock
+    pub fn collect_round<T: Clone, C: Clock>(&self, round_idx: usize, clock: &C) -> RoundCollector<T> {
+        RoundCollector::new(round_idx, &self.ring, clock.now() + self.deadlines.timeout(round_idx))
+    }
+
+    //encrypted snapshot of the session state of the party between rounds, once the round 1 outputs are received
+    //bound to the party and to its session by the nonces
+    pub fn snapshot<R: RngCore + CryptoRng>(&self, key: &SnapshotKey, rng: &mut R) -> Result<EncryptedSnapshot, String> {
+        let nonces = self.nonces.clone().ok_or("Round 1 outputs were not received")?;
+
+        //the Burmester-Desmedt exponent, once round 2 is run
+        let mut state = Zeroizing::new(Vec::new());
+        if let Some(bd) = self.bd.as_ref() {
+            state.extend_from_slice(bd.r.as_bytes());
+        }
+
+        snapshot::seal(key, SNAPSHOT_PROTOCOL, &self.id, &session_fingerprint(&nonces.to_string()), &state, rng)
+    }
+
+    //restores the session state of a restarted party from a snapshot, given the round 1 outputs of its session
+    //snapshots taken in another session are refused
+    pub fn restore(&mut self, snapshot: &EncryptedSnapshot, key: &SnapshotKey, round_1_outputs: &[(PartyId, Scalar)]) -> Result<(), String> {
+        let nonces = Nonces::with_context(self.ring.ids().to_vec(), self.ring.arrange(round_1_outputs)?, &self.context);
+        let state = snapshot::open(key, SNAPSHOT_PROTOCOL, &self.id, &session_fingerprint(&nonces.clone().to_string()), snapshot)?;
+
+        self.bd = match state.len() {
+            0 => None,
+            _ => Some(BurmesterDesmedt { r: Zeroizing::new(scalar_from_state(&state)?) })
+        };
+        self.nonces = Some(nonces);
+        Ok(())
+    }
+
     //round 1 wake
-    pub fn round_1(&self, rng: &mut OsRng) -> (usize, Scalar) {
+    pub fn round_1<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (PartyId, Scalar) {
         let nonce = Scalar::random(rng);
 
-        return (self.idx, nonce)
+        return (self.id, nonce)
     }
 
     //process received round 1 messages
-    pub fn round1_recieve(&mut self, round_1_outputs: &Vec<(usize, Scalar)>) {
-        let (idxs, scalars): (Vec<usize>, Vec<Scalar>) = round_1_outputs.iter().map(|(x, y)| (*x, y.clone())).unzip();
+    //the nonces are ordered by the ring, such that all parties agree on them regardless of the order they are received in
+    pub fn round1_recieve(&mut self, round_1_outputs: &Vec<(PartyId, Scalar)>) -> Result<(), String> {
+        let scalars = self.ring.arrange(round_1_outputs)?;
+
+        let nonces = Nonces::with_context(self.ring.ids().to_vec(), scalars, &self.context);
 
-        let nonces = Nonces::new(idxs, scalars);
+        //reject sessions reusing the nonces of an earlier session
+        if let Some(replay_cache) = self.replay_cache.as_ref() {
+            replay_cache.lock().unwrap().admit(&nonces.session_id(), &nonces.contributions())?;
+        }
 
         self.nonces = Some(nonces);
+        Ok(())
     }
 
     //round 2 wake. round 1 Burmester-Desmedt
-    pub fn round_2(&mut self, g: RistrettoPoint, rng: &mut OsRng, upperbound: u64) -> (CompMessage, CompSignature) {
-        let r = Scalar::random(rng);
-        let z = g * r;
+    pub fn round_2<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> (CompMessage, CompSignature) {
+        let r = Zeroizing::new(Scalar::random(rng));
+        let z = self.params.g() * *r;
 
         let bd = BurmesterDesmedt { r };
         self.bd = Some(bd);
 
-        let message = CompMessage::new(self.idx, 2, z, self.nonces.clone().unwrap());
+        let message = CompMessage::new(self.id, 2, z, self.nonces.clone().unwrap());
 
         let signature = CompSignature::sign(
+            &self.params,
             message.to_string(), 
-            self.min_bal, 
-            self.balance, 
-            self.blinding,
-            upperbound);
+            *self.balance, 
+            *self.blinding,
+            rng);
 
         return (message, signature)
     }
 
     //round 3 wake. round 2 Burmester-Desmedt
-    pub fn round_3(&self, z_list: &Vec<RistrettoPoint>, upperbound: u64) -> (CompMessage, CompSignature) {
-        let (z_1, z_2) = get_adjacent_elements(&z_list, &self.idx);
-        let r = self.bd.clone().unwrap().r;
-        let x = (z_2 - z_1) * r;
+    pub fn round_3<R: RngCore + CryptoRng>(&self, z_list: &[(PartyId, RistrettoPoint)], rng: &mut R) -> Result<(CompMessage, CompSignature), String> {
+        let z_list = self.ring.arrange(z_list)?;
+        let position = self.position();
+        let (z_1, z_2) = get_adjacent_elements(&z_list, &position);
+        let x = (z_2 - z_1) * *self.bd.as_ref().ok_or("Burmester-Desmedt values were not computed")?.r;
 
-        let message = CompMessage::new(self.idx, 3, x, self.nonces.clone().unwrap());
+        let message = CompMessage::new(self.id, 3, x, self.nonces.clone().unwrap());
 
         let signature = CompSignature::sign(
+            &self.params,
             message.to_string(), 
-            self.min_bal, 
-            self.balance, 
-            self.blinding,
-            upperbound);
+            *self.balance, 
+            *self.blinding,
+            rng);
 
-        return (message, signature);
+        return Ok((message, signature));
     }
     
     //receive & process round 2/3 messages
-    pub fn round_recieve(&self, g: RistrettoPoint, prev_round_res: Vec<(CompMessage, CompSignature)>, round_idx: usize, upperbound: u64) -> Result<(), String> {
-        //check if un-equal vector lengths from input
-        if self.commitments.len() != prev_round_res.len() {
-            return Err(format!("Invalid length expected: {}, found {}", prev_round_res.len(), self.commitments.len()))
-        }
-    
-        //iterate over all other parties' round 2 results, and assert
-        for i in 0..prev_round_res.len() {
-            if i == self.idx {
+    //on failure, identifies the parties whose messages are missing or invalid (identifiable abort)
+    pub fn round_recieve<R: RngCore + CryptoRng>(&self, prev_round_res: Vec<(CompMessage, CompSignature)>, round_idx: usize, rng: &mut R) -> Result<(), Misbehaviour> {
+        let mut misbehaviour = Misbehaviour::default();
+
+        //signatures of all other parties are verified as one batch
+        let mut batch_verifier = BatchVerifier::new(self.params.keys().range_proof_struct());
+
+        //iterate over all other parties' round 2 results, matched to their sender by identifier, and assert
+        let mut received = vec![false; self.commitments.len()];
+        for (curr_message, signature) in prev_round_res.iter() {
+            let i = match self.ring.statement_idx(&curr_message.sender) {
+                Some(i) => i,
+                None => return Err(Misbehaviour::unattributable(format!("Unknown party {}", curr_message.sender)))
+            };
+            if received[i] {
+                misbehaviour.blame(curr_message.sender, format!("Duplicate message from party {}", curr_message.sender));
                 continue;
             }
+            received[i] = true;
 
-            let curr_message = &prev_round_res[i].0;
+            if curr_message.sender == self.id {
+                continue;
+            }
             
             //assert correct round number
             if curr_message.round_idx != round_idx {
-                return Err(format!("Incorrect round number {}", curr_message.round_idx))
+                misbehaviour.blame(curr_message.sender, format!("Incorrect round number {}", curr_message.round_idx));
+                continue;
             }
     
+            //assert same session
+            if curr_message.nonces.session_id() != self.nonces.as_ref().unwrap().session_id() {
+                misbehaviour.blame(curr_message.sender, "Invalid session id".to_string());
+                continue;
+            }
+
             //assert same nonces
             if curr_message.nonces.clone().to_string() != self.nonces.clone().unwrap().to_string() {
-                return Err("Invalid nonces".to_string())
+                misbehaviour.blame(curr_message.sender, "Invalid nonces".to_string());
+                continue;
             }
     
-            //assert correct signature
-            let range_proof_struct = RangeProofStruct::new(self.min_bal, upperbound);
-            let updated_bal_comm = self.commitments[i] + (g * Scalar::from(self.min_bal));
-            let verification_status = &prev_round_res[i].1.clone().verify_signature(prev_round_res[i].0.to_string(), &range_proof_struct, &updated_bal_comm);
-            if !verification_status {
-                return Err("Verification of signature failed".to_string())
+            //add signature to the batch
+            let updated_bal_comm = self.commitments[i] + (self.params.g() * Scalar::from(self.params.min_bal()));
+            if !signature.clone().add_to_batch(&self.params, &mut batch_verifier, i, curr_message.to_string(), &updated_bal_comm) {
+                misbehaviour.blame(curr_message.sender, "Verification of signature failed".to_string());
             }
         }   
-        return Ok(())
+
+        //assert a message from every member of the ring
+        for id in self.ring.ids() {
+            if !received[self.ring.statement_idx(id).unwrap()] {
+                misbehaviour.blame(*id, format!("Missing message from party {}", id));
+            }
+        }
+
+        //assert correct signatures
+        if let Err(invalid_idxs) = batch_verifier.verify(rng) {
+            for id in self.ring.ids_of_statements(&invalid_idxs) {
+                misbehaviour.blame(id, "Verification of signature failed".to_string());
+            }
+        }
+        return misbehaviour.into_result()
     }
     
+    //records the blinding and the Burmester-Desmedt exponent of the party in the transcript of a session, once the exponent is drawn
+    pub(crate) fn record_secret_inputs(&self, transcript: &mut SessionTranscript) {
+        if let Some(bd) = self.bd.as_ref() {
+            transcript.record_secret_inputs(&self.id, scalar_to_string(*self.blinding), scalar_to_string(*bd.r));
+        }
+    }
+
     //compute Burmester Desmedt key
-    pub fn compute_key(&self, z_list: &Vec<RistrettoPoint>, x_list: &Vec<RistrettoPoint>) -> RistrettoPoint {
+    pub fn compute_key(&mut self, z_list: &Vec<(PartyId, RistrettoPoint)>, x_list: &Vec<(PartyId, RistrettoPoint)>) -> Result<RistrettoPoint, String> {
+        let z_list = self.ring.arrange(z_list)?;
+        let x_list = self.ring.arrange(x_list)?;
         let num_parties = x_list.len();
+        let position = self.position();
 
-        let z = z_list[find_prev_idx(self.idx, num_parties)];
-        let exponent = self.bd.clone().unwrap().r * Scalar::from(num_parties as u64);
+        let z = z_list[find_prev_idx(position, num_parties)];
+        let mut exponent = *self.bd.as_ref().ok_or("Burmester-Desmedt exponent was erased")?.r * Scalar::from(num_parties as u64);
         let mut key = z * exponent;
 
         for idx in 0..num_parties - 1 {
-            let i = (self.idx + idx) % num_parties;
+            let i = (position + idx) % num_parties;
             key += x_list[i] * Scalar::from((num_parties - idx - 1) as u64);
         }
 
-        return key;
+        //erase the ephemeral Burmester-Desmedt exponent, such that the key cannot be recomputed from the party's state later on (forward secrecy)
+        exponent.zeroize();
+        self.bd = None;
+
+        return Ok(key);
+    }
+
+    //compute Burmester Desmedt key, and keep the exponent in an established session, such that parties can later join or leave without a full re-run
+    //the exponent is wiped once the session is dropped
+    pub fn establish_session(&mut self, z_list: &[(PartyId, RistrettoPoint)], x_list: &[(PartyId, RistrettoPoint)]) -> Result<GroupSession, String> {
+        let bd = self.bd.take().ok_or("Burmester-Desmedt values were not computed")?;
+        let commitments = self.ring.arrange_statements(&self.commitments);
+
+        GroupSession::new(self.id, self.params.clone(), commitments, z_list, x_list, bd.r.clone())
     }
 
 }
 
+impl RingMember for Party {
+    fn id(&self) -> PartyId {
+        self.id
+    }
+
+    fn exclude(&mut self, ids: &[PartyId]) {
+        self.ring = self.ring.without(ids);
+        self.nonces = None;
+        self.bd = None;
+        //messages of the failed session are no evidence in the next one
+        if self.broadcast.is_some() {
+            self.broadcast = Some(BroadcastView::new());
+        }
+    }
+}
+
 ///
 /// Setup function, for initializing the parties with their own secret values and public commitments
 /// 
-fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64) -> Result<Vec<Party>, Box<dyn Error>> {
+pub fn setup_parties<R: RngCore + CryptoRng>(rng: &mut R, party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>) -> Result<Vec<Party>, Box<dyn Error>> {
+    //the balances are wiped once the parties are set up
+    let balances = Zeroizing::new(balances);
+
     //check if length of balance list, is equal to the amount of parties denoted
     if balances.len() != party_amount {
         return Err("balances not correct length".into())
     }
-
-    //check if upperbound is among the valid options 2^8, 2^16, 2^32, 2^64
-    if upperbound != u8::MAX as u64 && upperbound != u16::MAX as u64 && upperbound != u32::MAX as u64 && upperbound != u64::MAX {
-        return Err(format!("upperbound was not among the valid values: {}, {}, {}, {}\n was: {}", u8::MAX, u16::MAX, u32::MAX, u64::MAX, upperbound).into());
-    }
     
     //public input generators (g, h)
-    let pedersen_gens = PedersenGens::default();
+    let pedersen_gens = params.pc_gens();
 
     //public statements
     let mut commitments: Vec<RistrettoPoint> = Vec::new();
 
     //witnesses
-    let mut blindings: Vec<Scalar> = Vec::new();
+    let mut blindings: Zeroizing<Vec<Scalar>> = Zeroizing::new(Vec::new());
 
     //populating statements and witnesses
     for i in 0..party_amount {
         //returrn error if insufficient balances 
-        if balances[i] < min_bal {
+        if balances[i] < params.min_bal() {
             return Err("one or more balances are smaller than minimum_balance".into())
         }
 
@@ -225,76 +454,161 @@ fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: V
         blindings.push(r);
 
         //compute pedersen commitment: g^(2^n-1-b)*h^(r)
-        let commitment = (pedersen_gens.B * Scalar::from(upperbound-balances[i])) + (pedersen_gens.B_blinding * r);
+        let commitment = (pedersen_gens.B * Scalar::from(params.upperbound()-balances[i])) + (pedersen_gens.B_blinding * r);
         commitments.push(commitment);
     }
 
+    //the ring is ordered by the identifiers of the parties, rather than by the order of the balances
+    let ring = Ring::from_commitments(&commitments)?;
+
     //create parties in the protocol, with their private inputs (witnesses), as well as all public information 
     let mut parties: Vec<Party> = Vec::new();
     for i in 0..party_amount {
-        parties.push( Party::new(i, commitments.clone(), min_bal, balances[i], blindings[i]));
+        parties.push( Party::new(i, params.clone(), commitments.clone(), ring.clone(), balances[i], blindings[i]));
     }
 
     return Ok(parties)
 }
 
 // Implements the compiler-version of the WAKE protocol 
-pub fn run_compiler_key_exchange(party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
-    let rng = &mut OsRng;
-    let pedersen_gens = PedersenGens::default();
-    let g = pedersen_gens.B;
+pub fn run_compiler_key_exchange<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let (keys, _) = run_compiler_key_exchange_with_transcript(party_amount, balances, params, rng)?;
+    Ok(keys)
+}
 
-    let mut parties = setup_parties(rng, party_amount, min_bal, balances, upperbound)?;
+// Implements the compiler-version of the WAKE protocol, additionally outputting the transcript of all public messages of the session
+pub fn run_compiler_key_exchange_with_transcript<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<(Vec<RistrettoPoint>, SessionTranscript), Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let mut transcript = SessionTranscript::new(parties.first().map(|party| party.commitments.iter().map(|commitment| ristretto_to_string(*commitment)).collect()).unwrap_or_default());
     
     //round 1
-    let mut round1_outputs: Vec<(usize, Scalar)> = Vec::new();
-    for i in 0..party_amount {
-        let r1_res = &parties[i].round_1(rng);
-        round1_outputs.push(*r1_res);
+    let round1_outputs: Vec<(PartyId, Scalar)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_1(rng));
+    for (sender, nonce) in round1_outputs.iter() {
+        transcript.record(1, sender, scalar_to_string(*nonce), &[]);
     }
     
     //round 1 receive
-    for i in 0..party_amount {
-        let _ = &parties[i].round1_recieve(&round1_outputs);
-    }
+    map_parties_mut(&mut parties, |party| party.round1_recieve(&round1_outputs)).into_iter().collect::<Result<Vec<()>, String>>()?;
 
     //round 2 execution and receive
-    let mut round2_outputs: Vec<(CompMessage, CompSignature)> = Vec::new();
-    let mut payload_list: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let (message, signature) = &parties[i].round_2(g, rng, upperbound);
-        payload_list.push(message.payload);
-        round2_outputs.push((message.clone(), signature.clone()));
+    let round2_outputs: Vec<(CompMessage, CompSignature)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_2(rng));
+    let mut payload_list: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for (message, signature) in round2_outputs.iter() {
+        payload_list.push((message.sender, message.payload));
+        transcript.record(2, &message.sender, message.to_string(), &signature.to_bytes());
     }
-    for i in 0..party_amount {
-        let round2_res = &parties[i].round_recieve(g, round2_outputs.clone(), 2, upperbound);
+    for round2_res in map_parties_with_rng(&mut parties, rng, |party, rng| party.round_recieve(round2_outputs.clone(), 2, rng)) {
         if let Err(round2_err) = round2_res {
-            return Err(round2_err.clone().into());
+            return Err(round2_err.into());
         }
     }
 
     //round 3 execution & receive
-    let mut round3_outputs: Vec<(CompMessage, CompSignature)> = Vec::new();
-    let mut x_list: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let (message, signature) = &parties[i].round_3(&payload_list, upperbound);
-        x_list.push(message.payload);
-        round3_outputs.push((message.clone(), signature.clone()));
+    let round3_outputs = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_3(&payload_list, rng)).into_iter().collect::<Result<Vec<(CompMessage, CompSignature)>, String>>()?;
+    let mut x_list: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for (message, signature) in round3_outputs.iter() {
+        x_list.push((message.sender, message.payload));
+        transcript.record(3, &message.sender, message.to_string(), &signature.to_bytes());
     }
-    for i in 0..party_amount {
-        let g = pedersen_gens.B;
-        let round3_res = &parties[i].round_recieve(g, round3_outputs.clone(), 3, upperbound);
+    for round3_res in map_parties_with_rng(&mut parties, rng, |party, rng| party.round_recieve(round3_outputs.clone(), 3, rng)) {
         if let Err(round3_err) = round3_res {
-            return Err(round3_err.clone().into());
+            return Err(round3_err.into());
         }
     }
     
+    //secret inputs, recorded before the Burmester-Desmedt exponents are erased
+    for party in parties.iter() {
+        party.record_secret_inputs(&mut transcript);
+    }
+
     //compute key
-    let mut keys: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let key = &parties[i].compute_key(&payload_list, &x_list);
-        keys.push(*key);
+    let keys = map_parties_mut(&mut parties, |party| party.compute_key(&payload_list, &x_list)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>()?;
+
+    return Ok((keys, transcript))
+}
+
+// Burmester-Desmedt z and X values of the parties of a session, listed by the identifier of their party
+type BurmesterDesmedtValues = (Vec<(PartyId, RistrettoPoint)>, Vec<(PartyId, RistrettoPoint)>);
+
+// Records the signed messages of round round_idx at the parties that enabled the reliable broadcast, and exchanges their echoes
+// the evidence for a conflicting echo is handed out by its echoer
+// outputs the misbehaviour identified by every party that found a conflict
+fn exchange_echoes(parties: &mut [Party], round_outputs: &[(CompMessage, CompSignature)], round_idx: usize) -> Result<(), Vec<Misbehaviour>> {
+    map_parties_mut(parties, |party| party.record_broadcast(round_idx, round_outputs));
+
+    let echoes: Vec<Echo> = parties.iter().filter_map(|party| party.echo(round_idx)).collect();
+    if echoes.is_empty() {
+        return Ok(())
+    }
+    let parties: &[Party] = parties;
+    let accusations: Vec<Misbehaviour> = map_items(parties, |party| party.check_echoes(&echoes, |conflict| parties.iter().find(|echoer| echoer.id == conflict.echoer).and_then(|echoer| echoer.evidence(conflict)))).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+    Ok(())
+}
+
+// Runs the rounds of a single session of the compiler-version of the WAKE protocol between the given parties
+// outputs the Burmester-Desmedt values of the parties, or the misbehaviour identified by every party in the first failing round
+fn run_identifiable_rounds<R: RngCore + CryptoRng>(parties: &mut [Party], rng: &mut R) -> Result<BurmesterDesmedtValues, Vec<Misbehaviour>> {
+    //round 1
+    let round1_outputs: Vec<(PartyId, Scalar)> = map_parties_with_rng(parties, rng, |party, rng| party.round_1(rng));
+    map_parties_mut(parties, |party| party.round1_recieve(&round1_outputs)).into_iter().collect::<Result<Vec<()>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+
+    //round 2 execution and receive
+    let round2_outputs: Vec<(CompMessage, CompSignature)> = map_parties_with_rng(parties, rng, |party, rng| party.round_2(rng));
+    let accusations: Vec<Misbehaviour> = map_parties_with_rng(parties, rng, |party, rng| party.round_recieve(round2_outputs.clone(), 2, rng)).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
     }
+    exchange_echoes(parties, &round2_outputs, 2)?;
+    let payload_list: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
 
-    return Ok(keys)
+    //round 3 execution & receive
+    let round3_outputs = map_parties_with_rng(parties, rng, |party, rng| party.round_3(&payload_list, rng)).into_iter().collect::<Result<Vec<(CompMessage, CompSignature)>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    let accusations: Vec<Misbehaviour> = map_parties_with_rng(parties, rng, |party, rng| party.round_recieve(round3_outputs.clone(), 3, rng)).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+    exchange_echoes(parties, &round3_outputs, 3)?;
+    let x_list: Vec<(PartyId, RistrettoPoint)> = round3_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+    Ok((payload_list, x_list))
+}
+
+// Runs a single session of the compiler-version of the WAKE protocol between the given parties
+// outputs the keys of the parties in order, or the misbehaviour identified by every party in the first failing round
+fn run_identifiable_session<R: RngCore + CryptoRng>(parties: &mut [Party], rng: &mut R) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>> {
+    let (payload_list, x_list) = run_identifiable_rounds(parties, rng)?;
+
+    //compute key
+    parties.iter_mut().map(|party| party.compute_key(&payload_list, &x_list)).collect::<Result<Vec<RistrettoPoint>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])
+}
+
+// Implements the robust compiler-version of the WAKE protocol
+// parties identified as misbehaving are excluded, and the key-exchange is restarted over the remaining parties, within the bounds of config
+// outputs the keys and the final member set
+pub fn run_robust_compiler_key_exchange<R: RngCore + CryptoRng>(parties: Vec<Party>, config: &RobustConfig, rng: &mut R) -> Result<RobustOutcome, Box<dyn Error>> {
+    run_robust_key_exchange(parties, config, |parties| run_identifiable_session(parties, rng))
+}
+
+// Implements the compiler-version of the WAKE protocol, establishing a session per party that parties can later join or leave
+// (see compiler_bp_wake_membership)
+pub fn run_compiler_group_session<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<GroupSession>, Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let (payload_list, x_list) = run_identifiable_rounds(&mut parties, rng).map_err(|accusations| accusations.into_iter().next().unwrap_or_default())?;
+
+    let sessions = parties.iter_mut().map(|party| party.establish_session(&payload_list, &x_list)).collect::<Result<Vec<GroupSession>, String>>()?;
+    Ok(sessions)
+}
+
+// Implements the compiler-version of the WAKE protocol over the reliable broadcast, where the parties echo the signed messages of every round
+// to each other, such that a party sending conflicting messages to different parties is blamed before the key is computed
+pub fn run_reliable_compiler_key_exchange<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    for party in parties.iter_mut() {
+        party.enable_reliable_broadcast();
+    }
+    let keys = run_identifiable_session(&mut parties, rng).map_err(|accusations| accusations.into_iter().next().unwrap_or_default())?;
+    Ok(keys)
 }
@@ -1,62 +1,117 @@
 use super::compiler_bp_wake_protocol::Nonces;
 use crate::{
-    proof_systems::range_proof::RangeProofStruct,
+    protocols::party_id::PartyId,
+    params::{WakeParams, BulletproofsKeys, SignatureBackend},
+    proof_systems::batch_verification::BatchVerifier,
     utility::ristretto_to_string
 };
-use bulletproofs::RangeProof;
+use bulletproofs::{RangeProof, r1cs::R1CSProof};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+use rand::{CryptoRng, RngCore};
 
 
 ///
 /// Corresponds to a WAKE compiler message
-/// holds party-index, round-index, the actual contents of the message, and the nonces
+/// holds the identifier of the sending party, round-index, the actual contents of the message, and the nonces
 /// 
 #[derive(Clone)]
 pub struct CompMessage {
-    pub idx: usize,
+    pub sender: PartyId,
     pub round_idx: usize,
     pub payload: RistrettoPoint,
     pub nonces: Nonces
 }
 
 impl CompMessage {
-    pub fn new(idx: usize, round_idx: usize, message: RistrettoPoint, nonces: Nonces) -> CompMessage {
-        return CompMessage { idx, round_idx, payload: message, nonces }
+    pub fn new(sender: PartyId, round_idx: usize, message: RistrettoPoint, nonces: Nonces) -> CompMessage {
+        return CompMessage { sender, round_idx, payload: message, nonces }
     }
 
     pub fn to_string(&self) -> String {
-        return self.idx.to_string() + "##" + &self.round_idx.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.nonces.clone().to_string();
+        return self.sender.to_string() + "##" + &self.round_idx.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.nonces.clone().to_string();
     }
 }
 
 ///
 /// Corresponds to a WAKE compiler signature
-/// holds a bulletproof rangeproof for the darkpool transaction relation
+/// holds a bulletproof rangeproof, or an R1CS signature, for the darkpool transaction relation, as selected by the signature backend of the parameters
 /// 
 #[derive(Clone)]
-pub struct CompSignature { 
-    pub range_proof: RangeProof, 
+pub enum CompSignature { 
+    RangeProof(Box<RangeProof>),
+    R1cs(Box<R1csCompSignature>),
 }
 
 impl CompSignature {
+    ///signing function
+    ///outputs a signature, with the signature backend of the parameters
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<BulletproofsKeys>, message_string: String, balance: u64, blinding_factor: Scalar, rng: &mut R) -> CompSignature {
+        match params.keys().signature_backend() {
+            //bulletproof proving: b >= min_bal
+            SignatureBackend::RangeProof => CompSignature::RangeProof(Box::new(params.keys().range_proof_struct().prove(balance, blinding_factor, message_string, rng).unwrap())),
+            SignatureBackend::R1cs => CompSignature::R1cs(Box::new(R1csCompSignature::sign(params, message_string, balance, blinding_factor).expect("r1cs proving failed"))),
+        }
+    }
+
+    ///verification function
+    ///outputs a boolean indicating the verification status, which is false for a signature of another backend than that of the parameters
+    pub fn verify_signature(self, params: &WakeParams<BulletproofsKeys>, message_string: String, commitment: &RistrettoPoint) -> bool {
+        match (self, params.keys().signature_backend()) {
+            (CompSignature::RangeProof(range_proof), SignatureBackend::RangeProof) => params.keys().range_proof_struct().verify(*range_proof, commitment.compress(), message_string),
+            (CompSignature::R1cs(signature), SignatureBackend::R1cs) => signature.verify_signature(params, message_string, commitment),
+            _ => false,
+        }
+    }
+
+    ///adds the proof of the signature to a batch, to be verified together with the signatures of the other parties
+    ///R1CS signatures cannot be batched, and are verified right away instead
+    ///outputs false if the signature is already known to be invalid
+    pub fn add_to_batch(self, params: &WakeParams<BulletproofsKeys>, batch_verifier: &mut BatchVerifier, idx: usize, message_string: String, commitment: &RistrettoPoint) -> bool {
+        match (self, params.keys().signature_backend()) {
+            (CompSignature::RangeProof(range_proof), SignatureBackend::RangeProof) => {
+                batch_verifier.add_range_proof(idx, *range_proof, commitment.compress(), message_string);
+                true
+            },
+            (signature, _) => signature.verify_signature(params, message_string, commitment),
+        }
+    }
+
+    ///serializes the signature, as the bytes of the range proof or the R1CS proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            CompSignature::RangeProof(range_proof) => range_proof.to_bytes(),
+            CompSignature::R1cs(signature) => signature.r1cs_proof.to_bytes(),
+        }
+    }
+}
+
+///
+/// Corresponds to a WAKE compiler signature
+/// holds a bulletproofs R1CS proof for the darkpool transaction relation, as an alternative to the rangeproof
+/// the parties sign with it, within CompSignature, when the parameters select the R1CS signature backend
+/// 
+#[derive(Clone)]
+pub struct R1csCompSignature {
+    pub r1cs_proof: R1CSProof,
+}
+
+impl R1csCompSignature {
     ///signing function
     ///outputs a signature
-    pub fn sign(message_string: String, min_bal: u64, balance: u64, blinding_factor: Scalar, upperbound: u64) -> CompSignature {
-        //bulletproof proving: b >= min_bal
-        //outputs new commitment to the witness
-        let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
-        let range_proof = range_proof_struct.prove(balance, blinding_factor, message_string.clone()).unwrap();
-    
-        //combine results
-        let signature = CompSignature { range_proof };
-    
-        return signature;
+    pub fn sign(params: &WakeParams<BulletproofsKeys>, message_string: String, balance: u64, blinding_factor: Scalar) -> Result<R1csCompSignature, String> {
+        if balance < params.min_bal() {
+            return Err("minimum balance requirement was not met".to_string());
+        }
+        let v = params.upperbound() - (balance - params.min_bal());
+
+        let (r1cs_proof, _) = params.keys().r1cs_proof_struct().prove(&[v], &[blinding_factor], message_string).map_err(|e| e.to_string())?;
+
+        Ok(R1csCompSignature { r1cs_proof })
     }
 
     ///verification function
     ///outputs a boolean indicating the verification status
-    pub fn verify_signature(self, message_string: String, range_proof_struct: &RangeProofStruct, commitment: &RistrettoPoint) -> bool {
-        let range_proof_verification = range_proof_struct.verify(self.range_proof.clone(), commitment.compress(), message_string.clone());
-        return range_proof_verification;
+    pub fn verify_signature(&self, params: &WakeParams<BulletproofsKeys>, message_string: String, commitment: &RistrettoPoint) -> bool {
+        params.keys().r1cs_proof_struct().verify(&self.r1cs_proof, &[commitment.compress()], message_string)
     }
-}
\ No newline at end of file
+}
@@ -1,2 +1,5 @@
 pub mod compiler_bp_wake_protocol;
-pub mod compiler_bp_wake_signature;
\ No newline at end of file
+pub mod compiler_bp_wake_signature;
+pub mod compiler_bp_wake_membership;
+#[cfg(feature = "async")]
+pub mod compiler_bp_wake_async;
\ No newline at end of file
@@ -1,14 +1,18 @@
 use crate::{
-    proof_systems::proof_system_gm17::Gm17Darkpool,
+    params::{WakeParams, Gm17Keys},
+    proof_systems::proof_system_gm17_aggregation::{AggregationSRS, AggregateProof},
     protocols::compiler_gm17_wake::compiler_gm17_wake_signature::{CompMessage, CompSignature},
-    utility::{get_adjacent_elements, find_prev_idx, scalar_to_string, mimc}
+    protocols::session_transcript::SessionTranscript,
+    protocols::party_id::{PartyId, Ring},
+    protocols::robust::{Misbehaviour, RingMember, RobustConfig, RobustOutcome, run_robust_key_exchange},
+    protocols::session_id::{SessionContext, SessionId, SharedReplayCache},
+    utility::{get_adjacent_elements, find_prev_idx, scalar_to_string, field_to_string, mimc, map_items, map_parties_mut, map_parties_with_rng}
 };
-use rand::{Rng, rngs::OsRng};
-use std::error::Error;
-use ark_bls12_377::{Fr, Parameters};
-use ark_ec::bls12::Bls12;
-use ark_gm17::{PreparedVerifyingKey, ProvingKey};
-use bulletproofs::PedersenGens;
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+use std::{error::Error, sync::Arc};
+use ark_ec::PairingEngine;
+use ark_ff::UniformRand;
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
 
 /// 
@@ -18,23 +22,40 @@ use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
 
 ///
 /// Struct responsible for holding nonces from the WAKE protocol
+/// together with the id of the session, derived from the nonces and the context of the session
 /// 
 #[derive(Clone)]
 pub struct Nonces {
-    idxs: Vec<usize>,
-    scalars: Vec<Scalar>
+    ids: Vec<PartyId>,
+    scalars: Vec<Scalar>,
+    session_id: SessionId
 }
 
 impl Nonces {
-    pub fn new(idxs: Vec<usize>, scalars: Vec<Scalar>) -> Nonces {
-        return Nonces { idxs, scalars };
+    //nonces of a session run in the default context
+    pub fn new(ids: Vec<PartyId>, scalars: Vec<Scalar>) -> Nonces {
+        Nonces::with_context(ids, scalars, &SessionContext::default())
+    }
+
+    pub fn with_context(ids: Vec<PartyId>, scalars: Vec<Scalar>, context: &SessionContext) -> Nonces {
+        let session_id = SessionId::derive(context, &ids, &scalars.iter().map(|scalar| scalar.to_bytes()).collect::<Vec<_>>());
+        Nonces { ids, scalars, session_id }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    //round 1 contributions of the parties
+    pub fn contributions(&self) -> Vec<[u8; 32]> {
+        self.scalars.iter().map(|scalar| scalar.to_bytes()).collect()
     }
 
     pub fn to_string(self) -> String {
-        let mut res_string = String::new();
+        let mut res_string = self.session_id.to_string();
 
-        for i in 0..self.idxs.len() {
-            res_string += &(self.idxs[i].to_string() + &scalar_to_string(self.scalars[i]));
+        for i in 0..self.ids.len() {
+            res_string += &(self.ids[i].to_string() + &scalar_to_string(self.scalars[i]));
         }
 
         return res_string;
@@ -43,267 +64,471 @@ impl Nonces {
 
 ///
 /// Struct responsible for holding Burmester Desmedt values
+/// the exponent r is secret, and wiped when dropped
 /// 
-
-#[derive(Clone)]
 pub struct BurmesterDesmedt {
-    r: Scalar,
+    r: Zeroizing<Scalar>,
 }
 
 ///
 /// Struct representing a single party
+/// idx is the index of the party's own image, from which its identifier is derived
 /// 
-pub struct Party {
+pub struct Party<E: PairingEngine> {
     //public values
     idx: usize,
-    min_bal: u64,
-    images: Vec<Fr>,
-    pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>>,
+    id: PartyId,
+    ring: Ring,
+    params: Arc<WakeParams<Gm17Keys<E>>>,
+    images: Vec<E::Fr>,
     //secret values
-    gm17_darkpool: Gm17Darkpool,
-    balance: u64,
-    blinding: Fr,
-    pk: ProvingKey<Bls12<ark_bls12_377::Parameters>>,
+    balance: Zeroizing<u64>,
+    blinding: Zeroizing<E::Fr>,
+    //context of the session, and the cache of earlier sessions
+    context: SessionContext,
+    replay_cache: Option<SharedReplayCache>,
     //optional values getting initialized during the protocol
     nonces: Option<Nonces>,
     bd: Option<BurmesterDesmedt>
 }
 
-impl Party {
-    pub fn new(idx: usize, gm17_darkpool: Gm17Darkpool, images: Vec<Fr>, min_bal: u64, balance: u64, blinding: Fr, pk: ProvingKey<Bls12<ark_bls12_377::Parameters>>, pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>>) -> Party {
+impl<E: PairingEngine> Party<E> {
+    pub fn new(idx: usize, params: Arc<WakeParams<Gm17Keys<E>>>, images: Vec<E::Fr>, ring: Ring, balance: u64, blinding: E::Fr) -> Party<E> {
         return Party{ 
             idx,
-            min_bal, 
+            id: PartyId::from_image(&images[idx]),
+            ring,
+            params, 
             images, 
-            pvk_list,
-            gm17_darkpool,
-            balance, 
-            blinding, 
-            pk,
+            balance: Zeroizing::new(balance), 
+            blinding: Zeroizing::new(blinding), 
+            context: SessionContext::default(),
+            replay_cache: None,
             nonces: None, 
             bd: None 
         };
     }
 
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //position of the party in the ring
+    fn position(&self) -> usize {
+        self.ring.position(&self.id).unwrap()
+    }
+
+    //sets the context the session is run in, from which the session id is derived together with the round 1 outputs
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    //sets the cache of earlier sessions, rejecting sessions that reuse their round 1 outputs
+    pub fn set_replay_cache(&mut self, replay_cache: SharedReplayCache) {
+        self.replay_cache = Some(replay_cache);
+    }
+
+    //id of the session, once the round 1 outputs are received
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.nonces.as_ref().map(|nonces| nonces.session_id())
+    }
+
     //round 1 wake
-    pub fn round_1(&self, rng: &mut OsRng) -> (usize, Scalar) {
+    pub fn round_1<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (PartyId, Scalar) {
         let nonce = Scalar::random(rng);
 
-        return (self.idx, nonce)
+        return (self.id, nonce)
     }
 
     //receive & process round 1 messages
-    pub fn round1_recieve(&mut self, round_1_outputs: &Vec<(usize, Scalar)>) {
-        let (idxs, scalars): (Vec<usize>, Vec<Scalar>) = round_1_outputs.iter().map(|(x, y)| (*x, y.clone())).unzip();
+    //the nonces are ordered by the ring, such that all parties agree on them regardless of the order they are received in
+    pub fn round1_recieve(&mut self, round_1_outputs: &Vec<(PartyId, Scalar)>) -> Result<(), String> {
+        let scalars = self.ring.arrange(round_1_outputs)?;
 
-        let nonces = Nonces::new(idxs, scalars);
+        let nonces = Nonces::with_context(self.ring.ids().to_vec(), scalars, &self.context);
+
+        //reject sessions reusing the nonces of an earlier session
+        if let Some(replay_cache) = self.replay_cache.as_ref() {
+            replay_cache.lock().unwrap().admit(&nonces.session_id(), &nonces.contributions())?;
+        }
 
         self.nonces = Some(nonces);
+        Ok(())
     }
 
     //round 2 wake. round 1 Burmester-Desmedt
-    pub fn round_2(&mut self, g: RistrettoPoint, rng: &mut OsRng, upperbound: u64) -> (CompMessage, CompSignature) {
-        let r = Scalar::random(rng);
-        let z = g * r;
+    pub fn round_2<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> (CompMessage, CompSignature<E>) {
+        let r = Zeroizing::new(Scalar::random(rng));
+        let z = self.params.g() * *r;
 
         let bd = BurmesterDesmedt { r };
         self.bd = Some(bd);
 
-        let message = CompMessage::new(self.idx, 2, z, self.nonces.clone().unwrap());
+        let message = CompMessage::new(self.id, 2, z, self.nonces.clone().unwrap());
 
         let signature = CompSignature::sign(
-            &self.gm17_darkpool, 
+            &self.params, 
+            self.idx, 
             message.to_string(), 
-            self.min_bal, 
-            self.balance, 
-            self.blinding, 
-            upperbound, 
-            &self.pk
+            *self.balance, 
+            *self.blinding,
+            rng
         );
 
         return (message, signature)
     }
 
     //round 3 wake. round 2 Burmester-Desmedt
-    pub fn round_3(&self, z_list: &Vec<RistrettoPoint>, upperbound: u64) -> (CompMessage, CompSignature) {
-        let (z_1, z_2) = get_adjacent_elements(&z_list, &self.idx);
-        let r = self.bd.clone().unwrap().r;
-        let x = (z_2 - z_1) * r;
+    pub fn round_3<R: RngCore + CryptoRng>(&self, z_list: &[(PartyId, RistrettoPoint)], rng: &mut R) -> Result<(CompMessage, CompSignature<E>), String> {
+        let z_list = self.ring.arrange(z_list)?;
+        let position = self.position();
+        let (z_1, z_2) = get_adjacent_elements(&z_list, &position);
+        let x = (z_2 - z_1) * *self.bd.as_ref().ok_or("Burmester-Desmedt values were not computed")?.r;
 
-        let message = CompMessage::new(self.idx, 3, x, self.nonces.clone().unwrap());
+        let message = CompMessage::new(self.id, 3, x, self.nonces.clone().unwrap());
 
         let signature = CompSignature::sign(
-            &self.gm17_darkpool, 
+            &self.params, 
+            self.idx, 
             message.to_string(), 
-            self.min_bal, 
-            self.balance, 
-            self.blinding,
-            upperbound, 
-            &self.pk
+            *self.balance, 
+            *self.blinding,
+            rng
         );
 
-        return (message, signature)
+        return Ok((message, signature))
     }
     
     //receive & process round 2/3 messages
-    pub fn round_recieve(&self, prev_round_res: Vec<(CompMessage, CompSignature)>, round_idx: usize) -> Result<(), String> {
-        //check if un-equal vector lengths from input
-        if self.images.len() != prev_round_res.len() {
-            return Err(format!("Invalid length expected: {}, found {}", prev_round_res.len(), self.images.len()))
-        }
-    
-        //iterate over all other parties' round 2 results, and assert
-        for i in 0..prev_round_res.len() {
-            if i == self.idx {
+    //on failure, identifies the parties whose messages are missing or invalid (identifiable abort)
+    pub fn round_recieve<R: RngCore + CryptoRng>(&self, prev_round_res: Vec<(CompMessage, CompSignature<E>)>, round_idx: usize, rng: &mut R) -> Result<(), Misbehaviour> {
+        let mut misbehaviour = Misbehaviour::default();
+
+        //signatures of all other parties are verified as one batch
+        let mut batch = Vec::new();
+
+        //iterate over all other parties' round 2 results, matched to their sender by identifier, and assert
+        let mut received = vec![false; self.images.len()];
+        for (curr_message, signature) in prev_round_res.iter() {
+            let i = match self.ring.statement_idx(&curr_message.sender) {
+                Some(i) => i,
+                None => return Err(Misbehaviour::unattributable(format!("Unknown party {}", curr_message.sender)))
+            };
+            if received[i] {
+                misbehaviour.blame(curr_message.sender, format!("Duplicate message from party {}", curr_message.sender));
                 continue;
             }
+            received[i] = true;
 
-            let curr_message = &prev_round_res[i].0;
+            if curr_message.sender == self.id {
+                continue;
+            }
             
             //assert correct round number
             if curr_message.round_idx != round_idx {
-                return Err(format!("Incorrect round number {}", curr_message.round_idx))
+                misbehaviour.blame(curr_message.sender, format!("Incorrect round number {}", curr_message.round_idx));
+                continue;
             }
     
+            //assert same session
+            if curr_message.nonces.session_id() != self.nonces.as_ref().unwrap().session_id() {
+                misbehaviour.blame(curr_message.sender, "Invalid session id".to_string());
+                continue;
+            }
+
             //assert same nonces
             if curr_message.nonces.clone().to_string() != self.nonces.clone().unwrap().to_string() {
-                return Err("Invalid nonces".to_string())
+                misbehaviour.blame(curr_message.sender, "Invalid nonces".to_string());
+                continue;
             }
             
-            //assert correct signature
-            let verification_status = &prev_round_res[i].1.clone().verify_signature(
-                &self.gm17_darkpool, 
-                &self.pvk_list[i],
-                self.images[i], 
-                curr_message.to_string(),
-            );
-            if !verification_status {
-                return Err("Verification of signature failed".to_string())
-            }
+            //add signature to the batch
+            batch.push(signature.clone().batch_item(i, self.params.keys().pvk(i), self.images[i], curr_message.to_string()));
         }   
-        return Ok(())
+
+        //assert a message from every member of the ring
+        for id in self.ring.ids() {
+            if !received[self.ring.statement_idx(id).unwrap()] {
+                misbehaviour.blame(*id, format!("Missing message from party {}", id));
+            }
+        }
+
+        //assert correct signatures
+        if let Err(invalid_idxs) = self.params.keys().gm17_darkpool().batch_verify(&batch, rng) {
+            for id in self.ring.ids_of_statements(&invalid_idxs) {
+                misbehaviour.blame(id, "Verification of signature failed".to_string());
+            }
+        }
+        return misbehaviour.into_result()
     }
     
+    //aggregate all signatures of a round 2/3 into a single proof, as the relaying party
+    //the signatures are aggregated in ring order, matched to the images of their senders
+    pub fn aggregate_round(&self, round_res: &[(CompMessage, CompSignature<E>)], srs: &AggregationSRS<E>) -> Result<AggregateProof<E>, Box<dyn Error>> {
+        if !self.shared_verifying_key() {
+            return Err("Aggregation requires all parties to share a verifying key".into())
+        }
+
+        let proofs = self.ring.arrange(&round_res.iter().map(|(message, signature)| (message.sender, signature.gm17_darkpool_proof.clone())).collect::<Vec<_>>())?;
+        let auxs = self.ring.arrange(&round_res.iter().map(|(message, _)| (message.sender, message.to_string())).collect::<Vec<_>>())?;
+        srs.aggregate(self.params.keys().pvk(0), &proofs, &self.ring.arrange_statements(&self.images), &auxs)
+    }
+
+    //receive & process round 2/3 messages, whose signatures are aggregated into a single proof by a relaying party
+    pub fn round_recieve_aggregated(&self, prev_round_messages: &[CompMessage], aggregate_proof: &AggregateProof<E>, srs: &AggregationSRS<E>, round_idx: usize) -> Result<(), String> {
+        if !self.shared_verifying_key() {
+            return Err("Aggregation requires all parties to share a verifying key".to_string())
+        }
+
+        //order the messages by the ring, in which the signatures were aggregated
+        let prev_round_messages = self.ring.arrange(&prev_round_messages.iter().map(|message| (message.sender, message.clone())).collect::<Vec<_>>())?;
+
+        for curr_message in prev_round_messages.iter() {
+            //assert correct round number
+            if curr_message.round_idx != round_idx {
+                return Err(format!("Incorrect round number {}", curr_message.round_idx))
+            }
+
+            //assert same session
+            if curr_message.nonces.session_id() != self.nonces.as_ref().unwrap().session_id() {
+                return Err("Invalid session id".to_string())
+            }
+
+            //assert same nonces
+            if curr_message.nonces.clone().to_string() != self.nonces.clone().unwrap().to_string() {
+                return Err("Invalid nonces".to_string())
+            }
+        }
+
+        //assert the aggregated signatures of all parties
+        let auxs: Vec<String> = prev_round_messages.iter().map(|message| message.to_string()).collect();
+        if !srs.verify(self.params.keys().pvk(0), &self.ring.arrange_statements(&self.images), &auxs, aggregate_proof) {
+            return Err("Verification of aggregated signatures failed".to_string())
+        }
+        Ok(())
+    }
+
+    //aggregated proofs are verified under a single verifying key
+    fn shared_verifying_key(&self) -> bool {
+        let pvk_list = self.params.keys().pvk_list();
+        pvk_list.iter().all(|pvk| pvk.vk == pvk_list[0].vk)
+    }
+
+    //records the blinding and the Burmester-Desmedt exponent of the party in the transcript of a session, once the exponent is drawn
+    pub(crate) fn record_secret_inputs(&self, transcript: &mut SessionTranscript) {
+        if let Some(bd) = self.bd.as_ref() {
+            transcript.record_secret_inputs(&self.id, field_to_string(*self.blinding), scalar_to_string(*bd.r));
+        }
+    }
+
     //compute Burmester Desmedt key
-    pub fn compute_key(&self, z_list: &Vec<RistrettoPoint>, x_list: &Vec<RistrettoPoint>) -> RistrettoPoint {
+    pub fn compute_key(&mut self, z_list: &Vec<(PartyId, RistrettoPoint)>, x_list: &Vec<(PartyId, RistrettoPoint)>) -> Result<RistrettoPoint, String> {
+        let z_list = self.ring.arrange(z_list)?;
+        let x_list = self.ring.arrange(x_list)?;
         let num_parties = x_list.len();
+        let position = self.position();
 
-        let z = z_list[find_prev_idx(self.idx, num_parties)];
-        let exponent = self.bd.clone().unwrap().r * Scalar::from(num_parties as u64);
+        let z = z_list[find_prev_idx(position, num_parties)];
+        let mut exponent = *self.bd.as_ref().ok_or("Burmester-Desmedt exponent was erased")?.r * Scalar::from(num_parties as u64);
         let mut key = z * exponent;
 
         for idx in 0..num_parties - 1 {
-            let i = (self.idx + idx) % num_parties;
+            let i = (position + idx) % num_parties;
             key += x_list[i] * Scalar::from((num_parties - idx - 1) as u64);
         }
 
-        return key;
+        //erase the ephemeral Burmester-Desmedt exponent, such that the key cannot be recomputed from the party's state later on (forward secrecy)
+        exponent.zeroize();
+        self.bd = None;
+
+        return Ok(key);
     }
 
 }
 
+impl<E: PairingEngine> RingMember for Party<E> {
+    fn id(&self) -> PartyId {
+        self.id
+    }
+
+    fn exclude(&mut self, ids: &[PartyId]) {
+        self.ring = self.ring.without(ids);
+        self.nonces = None;
+        self.bd = None;
+    }
+}
+
 ///
 /// Setup function, for initializing the parties with their own secret values and public images
 /// 
-fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64, gm17_darkpool: Gm17Darkpool, pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>>, pk_list: Vec<ProvingKey<Bls12<ark_bls12_377::Parameters>>>) -> Result<Vec<Party>, Box<dyn Error>> {
+pub fn setup_parties<E: PairingEngine, R: RngCore + CryptoRng>(rng: &mut R, party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>) -> Result<Vec<Party<E>>, Box<dyn Error>> {
+    //the balances are wiped once the parties are set up
+    let balances = Zeroizing::new(balances);
+
     //check if length of balance list, is equal to the amount of parties denoted
     if balances.len() != party_amount {
         return Err("balances not correct length".into())
     }
 
-    //check if upperbound is among the valid options 2^8, 2^16, 2^32, 2^64
-    if upperbound != u8::MAX as u64 && upperbound != u16::MAX as u64 && upperbound != u32::MAX as u64 && upperbound != u64::MAX {
-        return Err(format!("upperbound was not among the valid values: {}, {}, {}, {}\n was: {}", u8::MAX, u16::MAX, u32::MAX, u64::MAX, upperbound).into());
+    //check if there is a key pair for every party
+    if params.keys().party_amount() != party_amount {
+        return Err("amount of key pairs does not match the amount of parties".into())
     }
 
     //public images
-    let mut images: Vec<Fr> = Vec::new();
+    let mut images: Vec<E::Fr> = Vec::new();
 
     //blindings for hashing
-    let mut blindings: Vec<Fr> = Vec::new();
+    let mut blindings: Zeroizing<Vec<E::Fr>> = Zeroizing::new(Vec::new());
 
     //populating images and blindings
     for i in 0..party_amount {
         //returrn error if insufficient balances 
-        if balances[i] < min_bal {
+        if balances[i] < params.min_bal() {
             return Err("one or more balances are smaller than minimum_balance".into())
         }
         
         //generate random hash blinding
-        let r: Fr = rng.gen();
+        let r = E::Fr::rand(rng);
         blindings.push(r);
 
         //compute & hash: 2^n-1 - b + B
-        let v = Fr::from(upperbound - (balances[i] - min_bal));
-        let image = mimc(v, r, &gm17_darkpool.clone().mimc_constants);
+        let v = E::Fr::from(params.upperbound() - (balances[i] - params.min_bal()));
+        let image = mimc(v, r, &params.keys().gm17_darkpool().mimc_constants);
         images.push(image);
     }
 
+    //the ring is ordered by the identifiers of the parties, rather than by the order of the balances
+    let ring = Ring::from_images(&images)?;
+
     //create parties in the protocol, with their private inputs (witnesses), as well as all public information 
-    let mut parties: Vec<Party> = Vec::new();
+    let mut parties: Vec<Party<E>> = Vec::new();
     for i in 0..party_amount {
-        parties.push(Party::new(i, gm17_darkpool.clone(), images.clone(), min_bal, balances[i], blindings[i], pk_list[i].clone(), pvk_list.clone()));
+        parties.push(Party::new(i, params.clone(), images.clone(), ring.clone(), balances[i], blindings[i]));
     }
 
     return Ok(parties)
 }
 
 // Implements the compiler-version of the WAKE protocol 
-pub fn run_compiler_key_exchange(party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64, gm17_darkpool: &Gm17Darkpool, pvk_list: &Vec<PreparedVerifyingKey<Bls12<Parameters>>>, pk_list: &Vec<ProvingKey<Bls12<ark_bls12_377::Parameters>>>) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
-    let rng = &mut OsRng;
-    let pedersen_gens = PedersenGens::default();
-    let g = pedersen_gens.B;
+pub fn run_compiler_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let (keys, _) = run_compiler_key_exchange_with_transcript(party_amount, balances, params, rng)?;
+    Ok(keys)
+}
 
-    let mut parties = setup_parties(rng, party_amount, min_bal, balances, upperbound, gm17_darkpool.clone(), pvk_list.clone(), pk_list.clone())?;
+// Implements the compiler-version of the WAKE protocol, additionally outputting the transcript of all public messages of the session
+pub fn run_compiler_key_exchange_with_transcript<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>, rng: &mut R) -> Result<(Vec<RistrettoPoint>, SessionTranscript), Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let mut transcript = SessionTranscript::new(parties.first().map(|party| party.images.iter().map(|image| field_to_string(*image)).collect()).unwrap_or_default());
     
     //round 1
-    let mut round1_outputs: Vec<(usize, Scalar)> = Vec::new();
-    for i in 0..party_amount {
-        let r1_res = &parties[i].round_1(rng);
-        round1_outputs.push(*r1_res);
+    let round1_outputs: Vec<(PartyId, Scalar)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_1(rng));
+    for (sender, nonce) in round1_outputs.iter() {
+        transcript.record(1, sender, scalar_to_string(*nonce), &[]);
     }
     
     //round 1 receive
-    for i in 0..party_amount {
-        let _ = &parties[i].round1_recieve(&round1_outputs);
-    }
+    map_parties_mut(&mut parties, |party| party.round1_recieve(&round1_outputs)).into_iter().collect::<Result<Vec<()>, String>>()?;
 
     //round 2 execution and receive
-    let mut round2_outputs: Vec<(CompMessage, CompSignature)> = Vec::new();
-    let mut round2_outputs_payloads: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let (message, signature) = &parties[i].round_2(g, rng, upperbound);
-        round2_outputs_payloads.push(message.payload);
-        round2_outputs.push((message.clone(), signature.clone()));
+    let round2_outputs: Vec<(CompMessage, CompSignature<E>)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_2(rng));
+    let mut round2_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for (message, signature) in round2_outputs.iter() {
+        round2_outputs_payloads.push((message.sender, message.payload));
+        transcript.record(2, &message.sender, message.to_string(), &signature.to_bytes()?);
     }
-    for i in 0..party_amount {
-        let round2_res = &parties[i].round_recieve(round2_outputs.clone(), 2);
+    for round2_res in map_parties_with_rng(&mut parties, rng, |party, rng| party.round_recieve(round2_outputs.clone(), 2, rng)) {
         if let Err(round2_err) = round2_res {
-            return Err(round2_err.clone().into());
+            return Err(round2_err.into());
         }
     }
 
     //round 3 execution & receive
-    let mut round3_outputs: Vec<(CompMessage, CompSignature)> = Vec::new();
-    let mut round3_outputs_payloads: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let (message, signature) = &parties[i].round_3(&round2_outputs_payloads, upperbound);
-        round3_outputs_payloads.push(message.payload);
-        round3_outputs.push((message.clone(), signature.clone()));
+    let round3_outputs = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_3(&round2_outputs_payloads, rng)).into_iter().collect::<Result<Vec<(CompMessage, CompSignature<E>)>, String>>()?;
+    let mut round3_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for (message, signature) in round3_outputs.iter() {
+        round3_outputs_payloads.push((message.sender, message.payload));
+        transcript.record(3, &message.sender, message.to_string(), &signature.to_bytes()?);
     }
-    for i in 0..party_amount {
-        let round3_res = &parties[i].round_recieve(round3_outputs.clone(), 3);
+    for round3_res in map_parties_with_rng(&mut parties, rng, |party, rng| party.round_recieve(round3_outputs.clone(), 3, rng)) {
         if let Err(round3_err) = round3_res {
-            return Err(round3_err.clone().into());
+            return Err(round3_err.into());
         }
     }
     
+    //secret inputs, recorded before the Burmester-Desmedt exponents are erased
+    for party in parties.iter() {
+        party.record_secret_inputs(&mut transcript);
+    }
+
     //compute key
-    let mut keys: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let key = &parties[i].compute_key(&round2_outputs_payloads, &round3_outputs_payloads);
-        keys.push(*key);
+    let keys = map_parties_mut(&mut parties, |party| party.compute_key(&round2_outputs_payloads, &round3_outputs_payloads)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>()?;
+
+    return Ok((keys, transcript))
+}
+
+// Runs a single session of the compiler-version of the WAKE protocol between the given parties
+// outputs the keys of the parties in order, or the misbehaviour identified by every party in the first failing round
+fn run_identifiable_session<E: PairingEngine, R: RngCore + CryptoRng>(parties: &mut [Party<E>], rng: &mut R) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>> {
+    //round 1
+    let round1_outputs: Vec<(PartyId, Scalar)> = map_parties_with_rng(parties, rng, |party, rng| party.round_1(rng));
+    map_parties_mut(parties, |party| party.round1_recieve(&round1_outputs)).into_iter().collect::<Result<Vec<()>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+
+    //round 2 execution and receive
+    let round2_outputs: Vec<(CompMessage, CompSignature<E>)> = map_parties_with_rng(parties, rng, |party, rng| party.round_2(rng));
+    let accusations: Vec<Misbehaviour> = map_parties_with_rng(parties, rng, |party, rng| party.round_recieve(round2_outputs.clone(), 2, rng)).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+    let payload_list: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+    //round 3 execution & receive
+    let round3_outputs = map_parties_with_rng(parties, rng, |party, rng| party.round_3(&payload_list, rng)).into_iter().collect::<Result<Vec<(CompMessage, CompSignature<E>)>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    let accusations: Vec<Misbehaviour> = map_parties_with_rng(parties, rng, |party, rng| party.round_recieve(round3_outputs.clone(), 3, rng)).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
     }
+    let x_list: Vec<(PartyId, RistrettoPoint)> = round3_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+    //compute key
+    map_parties_mut(parties, |party| party.compute_key(&payload_list, &x_list)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])
+}
+
+// Implements the robust compiler-version of the WAKE protocol
+// parties identified as misbehaving are excluded, and the key-exchange is restarted over the remaining parties, within the bounds of config
+// outputs the keys and the final member set
+pub fn run_robust_compiler_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(parties: Vec<Party<E>>, config: &RobustConfig, rng: &mut R) -> Result<RobustOutcome, Box<dyn Error>> {
+    run_robust_key_exchange(parties, config, |parties| run_identifiable_session(parties, rng))
+}
+
+// Implements the compiler-version of the WAKE protocol, where all parties share a GM17 key pair (see Gm17Keys::shared), and party 0 relays a single aggregated proof of all signatures per round
+// unlike run_compiler_key_exchange, the per-party GM17 setup is replaced by one key pair for all parties, so the party that generated it
+// can forge the signature of any party. Likewise, the party that ran AggregationSRS::setup can forge aggregates, so both setups must be trusted
+pub fn run_aggregated_compiler_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>, srs: &AggregationSRS<E>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+
+    //round 1
+    let round1_outputs: Vec<(PartyId, Scalar)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_1(rng));
+
+    //round 1 receive
+    map_parties_mut(&mut parties, |party| party.round1_recieve(&round1_outputs)).into_iter().collect::<Result<Vec<()>, String>>()?;
+
+    //round 2 execution, aggregation & receive
+    let round2_outputs: Vec<(CompMessage, CompSignature<E>)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_2(rng));
+    let round2_aggregate = parties[0].aggregate_round(&round2_outputs, srs)?;
+    let round2_messages: Vec<CompMessage> = round2_outputs.into_iter().map(|(message, _)| message).collect();
+    let round2_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = round2_messages.iter().map(|message| (message.sender, message.payload)).collect();
+    map_items(&parties, |party| party.round_recieve_aggregated(&round2_messages, &round2_aggregate, srs, 2)).into_iter().collect::<Result<Vec<()>, String>>()?;
+
+    //round 3 execution, aggregation & receive
+    let round3_outputs: Vec<(CompMessage, CompSignature<E>)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_3(&round2_outputs_payloads, rng)).into_iter().collect::<Result<_, _>>()?;
+    let round3_aggregate = parties[0].aggregate_round(&round3_outputs, srs)?;
+    let round3_messages: Vec<CompMessage> = round3_outputs.into_iter().map(|(message, _)| message).collect();
+    let round3_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = round3_messages.iter().map(|message| (message.sender, message.payload)).collect();
+    map_items(&parties, |party| party.round_recieve_aggregated(&round3_messages, &round3_aggregate, srs, 3)).into_iter().collect::<Result<Vec<()>, String>>()?;
+
+    //compute key
+    let keys = map_parties_mut(&mut parties, |party| party.compute_key(&round2_outputs_payloads, &round3_outputs_payloads)).into_iter().collect::<Result<_, _>>()?;
 
-    return Ok(keys)
+    Ok(keys)
 }
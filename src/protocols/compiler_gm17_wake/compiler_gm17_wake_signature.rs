@@ -1,32 +1,35 @@
 use super::compiler_gm17_wake_protocol::Nonces;
 use crate::{
-    proof_systems::proof_system_gm17::Gm17Darkpool,
+    protocols::party_id::PartyId,
+    params::{WakeParams, Gm17Keys},
+    proof_systems::proof_system_gm17::Gm17BatchItem,
     utility::ristretto_to_string
 };
-use ark_bls12_377::{Parameters, Fr};
-use ark_ec::bls12::Bls12;
-use ark_gm17::{PreparedVerifyingKey, ProvingKey, Proof};
+use ark_ec::PairingEngine;
+use ark_gm17::{PreparedVerifyingKey, Proof};
+use ark_serialize::{CanonicalSerialize, SerializationError};
 use curve25519_dalek_ng::ristretto::{RistrettoPoint};
+use rand::{CryptoRng, RngCore};
 
 ///
 /// Corresponds to a WAKE compiler message
-/// holds party-index, round-index, the actual contents of the message, and the nonces
+/// holds the identifier of the sending party, round-index, the actual contents of the message, and the nonces
 /// 
 #[derive(Clone)]
 pub struct CompMessage {
-    pub idx: usize,
+    pub sender: PartyId,
     pub round_idx: usize,
     pub payload: RistrettoPoint,
     pub nonces: Nonces
 }
 
 impl CompMessage {
-    pub fn new(idx: usize, round_idx: usize, message: RistrettoPoint, nonces: Nonces) -> CompMessage {
-        return CompMessage { idx, round_idx, payload: message, nonces }
+    pub fn new(sender: PartyId, round_idx: usize, message: RistrettoPoint, nonces: Nonces) -> CompMessage {
+        return CompMessage { sender, round_idx, payload: message, nonces }
     }
 
     pub fn to_string(&self) -> String {
-        return self.idx.to_string() + "##" + &self.round_idx.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.nonces.clone().to_string();
+        return self.sender.to_string() + "##" + &self.round_idx.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.nonces.clone().to_string();
     }
 }
 
@@ -35,26 +38,38 @@ impl CompMessage {
 /// holds a GM17 proof for the darkpool transaction relation
 /// 
 #[derive(Clone)]
-pub struct CompSignature {
-    pub gm17_darkpool_proof: Proof<Bls12<Parameters>>,
+pub struct CompSignature<E: PairingEngine> {
+    pub gm17_darkpool_proof: Proof<E>,
 }
 
-impl CompSignature {
+impl<E: PairingEngine> CompSignature<E> {
     ///signing function
-    ///outputs a signature
-    pub fn sign(gm17_darkpool: &Gm17Darkpool, message_string: String, min_bal: u64, balance: u64, blinding_factor: Fr, upperbound: u64, pk: &ProvingKey<Bls12<Parameters>>) -> CompSignature {
+    ///outputs a signature, under the proving key of party idx
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<Gm17Keys<E>>, idx: usize, message_string: String, balance: u64, blinding_factor: E::Fr, rng: &mut R) -> CompSignature<E> {
         //compute updated balance and convert to group element
-        let v = Fr::from(upperbound - (balance - min_bal));
+        let v = E::Fr::from(params.upperbound() - (balance - params.min_bal()));
 
-        let gm17_darkpool_proof = gm17_darkpool.clone().prove(v, blinding_factor, message_string, pk.clone());
+        let gm17_darkpool_proof = params.keys().gm17_darkpool().prove(v, blinding_factor, message_string, params.keys().pk(idx), rng);
         return CompSignature { gm17_darkpool_proof }
     }
 
     ///verification function
-    ///outputs a boolean indicating the verification status
-    pub fn verify_signature(self, gm17_darkpool: &Gm17Darkpool, pvk: &PreparedVerifyingKey<Bls12<Parameters>>, image: Fr, message_string: String) -> bool {
-        let gm17_darkpool_verification = gm17_darkpool.clone().verify(image, message_string, pvk.clone(), self.gm17_darkpool_proof);
+    ///outputs a boolean indicating the verification status, under the verifying key of party idx
+    pub fn verify_signature(self, params: &WakeParams<Gm17Keys<E>>, idx: usize, image: E::Fr, message_string: String) -> bool {
+        let gm17_darkpool_verification = params.keys().gm17_darkpool().verify(image, message_string, params.keys().pvk(idx), &self.gm17_darkpool_proof);
 
         return gm17_darkpool_verification
     }
+
+    ///outputs the proof of the signature as an item of a batch, to be verified together with the signatures of the other parties
+    pub fn batch_item(self, idx: usize, pvk: &PreparedVerifyingKey<E>, image: E::Fr, message_string: String) -> Gm17BatchItem<'_, E> {
+        Gm17BatchItem { idx, image, aux: message_string, pvk, proof: self.gm17_darkpool_proof }
+    }
+
+    ///serializes the signature, as the compressed GM17 proof
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = Vec::new();
+        self.gm17_darkpool_proof.serialize(&mut bytes)?;
+        Ok(bytes)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,455 @@
+use crate::{
+    params::{WakeParams, MarlinKeys},
+    protocols::compiler_marlin_wake::compiler_marlin_wake_signature::{CompMessage, CompSignature},
+    protocols::session_transcript::SessionTranscript,
+    protocols::party_id::{PartyId, Ring},
+    protocols::robust::{Misbehaviour, RingMember, RobustConfig, RobustOutcome, run_robust_key_exchange},
+    protocols::session_id::{SessionContext, SessionId, SharedReplayCache},
+    utility::{get_adjacent_elements, find_prev_idx, scalar_to_string, field_to_string, mimc}
+};
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+use std::{error::Error, fmt, sync::Arc};
+use ark_ec::PairingEngine;
+use ark_ff::UniformRand;
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+
+// 
+// File for running Burmester Desmedt key-exchange, with compiler WAKE scheme, using Marlin SE zk-SNARK with a universal setup as underlying proof, for the Darkpool transaction relation
+// 
+
+///
+/// Struct responsible for holding nonces from the WAKE protocol
+/// together with the id of the session, derived from the nonces and the context of the session
+/// 
+#[derive(Clone)]
+pub struct Nonces {
+    ids: Vec<PartyId>,
+    scalars: Vec<Scalar>,
+    session_id: SessionId
+}
+
+impl Nonces {
+    //nonces of a session run in the default context
+    pub fn new(ids: Vec<PartyId>, scalars: Vec<Scalar>) -> Nonces {
+        Nonces::with_context(ids, scalars, &SessionContext::default())
+    }
+
+    pub fn with_context(ids: Vec<PartyId>, scalars: Vec<Scalar>, context: &SessionContext) -> Nonces {
+        let session_id = SessionId::derive(context, &ids, &scalars.iter().map(|scalar| scalar.to_bytes()).collect::<Vec<_>>());
+        Nonces { ids, scalars, session_id }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    //round 1 contributions of the parties
+    pub fn contributions(&self) -> Vec<[u8; 32]> {
+        self.scalars.iter().map(|scalar| scalar.to_bytes()).collect()
+    }
+}
+
+impl fmt::Display for Nonces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.session_id)?;
+
+        for (id, scalar) in self.ids.iter().zip(self.scalars.iter()) {
+            write!(f, "{}{}", id, scalar_to_string(*scalar))?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// Struct responsible for holding Burmester Desmedt values
+/// the exponent r is secret, and wiped when dropped
+/// 
+pub struct BurmesterDesmedt {
+    r: Zeroizing<Scalar>,
+}
+
+///
+/// Struct representing a single party
+/// idx is the index of the party's own image, from which its identifier is derived
+/// 
+pub struct Party<E: PairingEngine> {
+    //public values
+    id: PartyId,
+    ring: Ring,
+    params: Arc<WakeParams<MarlinKeys<E>>>,
+    images: Vec<E::Fr>,
+    //secret values
+    balance: Zeroizing<u64>,
+    blinding: Zeroizing<E::Fr>,
+    //context of the session, and the cache of earlier sessions
+    context: SessionContext,
+    replay_cache: Option<SharedReplayCache>,
+    //optional values getting initialized during the protocol
+    nonces: Option<Nonces>,
+    bd: Option<BurmesterDesmedt>
+}
+
+impl<E: PairingEngine> Party<E> {
+    pub fn new(idx: usize, params: Arc<WakeParams<MarlinKeys<E>>>, images: Vec<E::Fr>, ring: Ring, balance: u64, blinding: E::Fr) -> Party<E> {
+        Party{ 
+            id: PartyId::from_image(&images[idx]),
+            ring,
+            params, 
+            images, 
+            balance: Zeroizing::new(balance), 
+            blinding: Zeroizing::new(blinding), 
+            context: SessionContext::default(),
+            replay_cache: None,
+            nonces: None, 
+            bd: None 
+        }
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //position of the party in the ring
+    fn position(&self) -> usize {
+        self.ring.position(&self.id).unwrap()
+    }
+
+    //sets the context the session is run in, from which the session id is derived together with the round 1 outputs
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    //sets the cache of earlier sessions, rejecting sessions that reuse their round 1 outputs
+    pub fn set_replay_cache(&mut self, replay_cache: SharedReplayCache) {
+        self.replay_cache = Some(replay_cache);
+    }
+
+    //id of the session, once the round 1 outputs are received
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.nonces.as_ref().map(|nonces| nonces.session_id())
+    }
+
+    //round 1 wake
+    pub fn round_1<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (PartyId, Scalar) {
+        let nonce = Scalar::random(rng);
+
+        (self.id, nonce)
+    }
+
+    //receive & process round 1 messages
+    //the nonces are ordered by the ring, such that all parties agree on them regardless of the order they are received in
+    pub fn round1_recieve(&mut self, round_1_outputs: &[(PartyId, Scalar)]) -> Result<(), String> {
+        let scalars = self.ring.arrange(round_1_outputs)?;
+
+        let nonces = Nonces::with_context(self.ring.ids().to_vec(), scalars, &self.context);
+
+        //reject sessions reusing the nonces of an earlier session
+        if let Some(replay_cache) = self.replay_cache.as_ref() {
+            replay_cache.lock().unwrap().admit(&nonces.session_id(), &nonces.contributions())?;
+        }
+
+        self.nonces = Some(nonces);
+        Ok(())
+    }
+
+    //round 2 wake. round 1 Burmester-Desmedt
+    pub fn round_2<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> (CompMessage, CompSignature<E>) {
+        let r = Zeroizing::new(Scalar::random(rng));
+        let z = self.params.g() * *r;
+
+        let bd = BurmesterDesmedt { r };
+        self.bd = Some(bd);
+
+        let message = CompMessage::new(self.id, 2, z, self.nonces.clone().unwrap());
+
+        let signature = CompSignature::sign(
+            &self.params, 
+            message.to_string(), 
+            *self.balance, 
+            *self.blinding,
+            rng
+        );
+
+        (message, signature)
+    }
+
+    //round 3 wake. round 2 Burmester-Desmedt
+    pub fn round_3<R: RngCore + CryptoRng>(&self, z_list: &[(PartyId, RistrettoPoint)], rng: &mut R) -> Result<(CompMessage, CompSignature<E>), String> {
+        let z_list = self.ring.arrange(z_list)?;
+        let position = self.position();
+        let (z_1, z_2) = get_adjacent_elements(&z_list, &position);
+        let x = (z_2 - z_1) * *self.bd.as_ref().ok_or("Burmester-Desmedt values were not computed")?.r;
+
+        let message = CompMessage::new(self.id, 3, x, self.nonces.clone().unwrap());
+
+        let signature = CompSignature::sign(
+            &self.params, 
+            message.to_string(), 
+            *self.balance, 
+            *self.blinding,
+            rng
+        );
+
+        Ok((message, signature))
+    }
+    
+    //receive & process round 2/3 messages
+    //on failure, identifies the parties whose messages are missing or invalid (identifiable abort)
+    pub fn round_recieve<R: RngCore + CryptoRng>(&self, prev_round_res: Vec<(CompMessage, CompSignature<E>)>, round_idx: usize, rng: &mut R) -> Result<(), Misbehaviour> {
+        let mut misbehaviour = Misbehaviour::default();
+
+        //iterate over all other parties' round 2 results, matched to their sender by identifier, and assert
+        let mut received = vec![false; self.images.len()];
+        for (curr_message, signature) in prev_round_res.iter() {
+            let i = match self.ring.statement_idx(&curr_message.sender) {
+                Some(i) => i,
+                None => return Err(Misbehaviour::unattributable(format!("Unknown party {}", curr_message.sender)))
+            };
+            if received[i] {
+                misbehaviour.blame(curr_message.sender, format!("Duplicate message from party {}", curr_message.sender));
+                continue;
+            }
+            received[i] = true;
+
+            if curr_message.sender == self.id {
+                continue;
+            }
+            
+            //assert correct round number
+            if curr_message.round_idx != round_idx {
+                misbehaviour.blame(curr_message.sender, format!("Incorrect round number {}", curr_message.round_idx));
+                continue;
+            }
+    
+            //assert same session
+            if curr_message.nonces.session_id() != self.nonces.as_ref().unwrap().session_id() {
+                misbehaviour.blame(curr_message.sender, "Invalid session id".to_string());
+                continue;
+            }
+
+            //assert same nonces
+            if curr_message.nonces.clone().to_string() != self.nonces.clone().unwrap().to_string() {
+                misbehaviour.blame(curr_message.sender, "Invalid nonces".to_string());
+                continue;
+            }
+            
+            //assert correct signature
+            let verification_status = &signature.clone().verify_signature(
+                &self.params, 
+                self.images[i], 
+                curr_message.to_string(),
+                rng
+            );
+            if !verification_status {
+                misbehaviour.blame(curr_message.sender, "Verification of signature failed".to_string());
+            }
+        }   
+
+        //assert a message from every member of the ring
+        for id in self.ring.ids() {
+            if !received[self.ring.statement_idx(id).unwrap()] {
+                misbehaviour.blame(*id, format!("Missing message from party {}", id));
+            }
+        }
+        misbehaviour.into_result()
+    }
+    
+    //records the blinding and the Burmester-Desmedt exponent of the party in the transcript of a session, once the exponent is drawn
+    pub(crate) fn record_secret_inputs(&self, transcript: &mut SessionTranscript) {
+        if let Some(bd) = self.bd.as_ref() {
+            transcript.record_secret_inputs(&self.id, field_to_string(*self.blinding), scalar_to_string(*bd.r));
+        }
+    }
+
+    //compute Burmester Desmedt key
+    pub fn compute_key(&mut self, z_list: &[(PartyId, RistrettoPoint)], x_list: &[(PartyId, RistrettoPoint)]) -> Result<RistrettoPoint, String> {
+        let z_list = self.ring.arrange(z_list)?;
+        let x_list = self.ring.arrange(x_list)?;
+        let num_parties = x_list.len();
+        let position = self.position();
+
+        let z = z_list[find_prev_idx(position, num_parties)];
+        let mut exponent = *self.bd.as_ref().ok_or("Burmester-Desmedt exponent was erased")?.r * Scalar::from(num_parties as u64);
+        let mut key = z * exponent;
+
+        for idx in 0..num_parties - 1 {
+            let i = (position + idx) % num_parties;
+            key += x_list[i] * Scalar::from((num_parties - idx - 1) as u64);
+        }
+
+        //erase the ephemeral Burmester-Desmedt exponent, such that the key cannot be recomputed from the party's state later on (forward secrecy)
+        exponent.zeroize();
+        self.bd = None;
+
+        Ok(key)
+    }
+
+}
+
+impl<E: PairingEngine> RingMember for Party<E> {
+    fn id(&self) -> PartyId {
+        self.id
+    }
+
+    fn exclude(&mut self, ids: &[PartyId]) {
+        self.ring = self.ring.without(ids);
+        self.nonces = None;
+        self.bd = None;
+    }
+}
+
+///
+/// Setup function, for initializing the parties with their own secret values and public images
+/// 
+pub fn setup_parties<E: PairingEngine, R: RngCore + CryptoRng>(rng: &mut R, party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<MarlinKeys<E>>>) -> Result<Vec<Party<E>>, Box<dyn Error>> {
+    //the balances are wiped once the parties are set up
+    let balances = Zeroizing::new(balances);
+
+    //check if length of balance list, is equal to the amount of parties denoted
+    if balances.len() != party_amount {
+        return Err("balances not correct length".into())
+    }
+
+    //public images
+    let mut images: Vec<E::Fr> = Vec::new();
+
+    //blindings for hashing
+    let mut blindings: Zeroizing<Vec<E::Fr>> = Zeroizing::new(Vec::new());
+
+    //populating images and blindings
+    for i in 0..party_amount {
+        //returrn error if insufficient balances 
+        if balances[i] < params.min_bal() {
+            return Err("one or more balances are smaller than minimum_balance".into())
+        }
+        
+        //generate random hash blinding
+        let r = E::Fr::rand(rng);
+        blindings.push(r);
+
+        //compute & hash: 2^n-1 - b + B
+        let v = E::Fr::from(params.upperbound() - (balances[i] - params.min_bal()));
+        let image = mimc(v, r, &params.keys().marlin_darkpool().mimc_constants);
+        images.push(image);
+    }
+
+    //the ring is ordered by the identifiers of the parties, rather than by the order of the balances
+    let ring = Ring::from_images(&images)?;
+
+    //create parties in the protocol, with their private inputs (witnesses), as well as all public information 
+    let mut parties: Vec<Party<E>> = Vec::new();
+    for i in 0..party_amount {
+        parties.push(Party::new(i, params.clone(), images.clone(), ring.clone(), balances[i], blindings[i]));
+    }
+
+    Ok(parties)
+}
+
+// Implements the compiler-version of the WAKE protocol 
+pub fn run_compiler_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<MarlinKeys<E>>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let (keys, _) = run_compiler_key_exchange_with_transcript(party_amount, balances, params, rng)?;
+    Ok(keys)
+}
+
+// Implements the compiler-version of the WAKE protocol, additionally outputting the transcript of all public messages of the session
+pub fn run_compiler_key_exchange_with_transcript<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<MarlinKeys<E>>>, rng: &mut R) -> Result<(Vec<RistrettoPoint>, SessionTranscript), Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let mut transcript = SessionTranscript::new(parties.first().map(|party| party.images.iter().map(|image| field_to_string(*image)).collect()).unwrap_or_default());
+    
+    //round 1
+    let mut round1_outputs: Vec<(PartyId, Scalar)> = Vec::new();
+    for party in parties.iter() {
+        let r1_res = &party.round_1(rng);
+        round1_outputs.push(*r1_res);
+        transcript.record(1, &r1_res.0, scalar_to_string(r1_res.1), &[]);
+    }
+    
+    //round 1 receive
+    for party in parties.iter_mut() {
+        party.round1_recieve(&round1_outputs)?;
+    }
+
+    //round 2 execution and receive
+    let mut round2_outputs: Vec<(CompMessage, CompSignature<E>)> = Vec::new();
+    let mut round2_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for party in parties.iter_mut() {
+        let (message, signature) = &party.round_2(rng);
+        round2_outputs_payloads.push((message.sender, message.payload));
+        round2_outputs.push((message.clone(), signature.clone()));
+        transcript.record(2, &message.sender, message.to_string(), &signature.to_bytes()?);
+    }
+    for party in parties.iter() {
+        let round2_res = &party.round_recieve(round2_outputs.clone(), 2, rng);
+        if let Err(round2_err) = round2_res {
+            return Err(round2_err.clone().into());
+        }
+    }
+
+    //round 3 execution & receive
+    let mut round3_outputs: Vec<(CompMessage, CompSignature<E>)> = Vec::new();
+    let mut round3_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for party in parties.iter() {
+        let (message, signature) = &party.round_3(&round2_outputs_payloads, rng)?;
+        round3_outputs_payloads.push((message.sender, message.payload));
+        round3_outputs.push((message.clone(), signature.clone()));
+        transcript.record(3, &message.sender, message.to_string(), &signature.to_bytes()?);
+    }
+    for party in parties.iter() {
+        let round3_res = &party.round_recieve(round3_outputs.clone(), 3, rng);
+        if let Err(round3_err) = round3_res {
+            return Err(round3_err.clone().into());
+        }
+    }
+    
+    //secret inputs, recorded before the Burmester-Desmedt exponents are erased
+    for party in parties.iter() {
+        party.record_secret_inputs(&mut transcript);
+    }
+
+    //compute key
+    let mut keys: Vec<RistrettoPoint> = Vec::new();
+    for party in parties.iter_mut() {
+        let key = &party.compute_key(&round2_outputs_payloads, &round3_outputs_payloads)?;
+        keys.push(*key);
+    }
+
+    Ok((keys, transcript))
+}
+
+// Runs a single session of the compiler-version of the WAKE protocol between the given parties
+// outputs the keys of the parties in order, or the misbehaviour identified by every party in the first failing round
+fn run_identifiable_session<E: PairingEngine, R: RngCore + CryptoRng>(parties: &mut [Party<E>], rng: &mut R) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>> {
+    //round 1
+    let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+    for party in parties.iter_mut() {
+        party.round1_recieve(&round1_outputs).map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    }
+
+    //round 2 execution and receive
+    let round2_outputs: Vec<(CompMessage, CompSignature<E>)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+    let accusations: Vec<Misbehaviour> = parties.iter().filter_map(|party| party.round_recieve(round2_outputs.clone(), 2, rng).err()).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+    let round2_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+    //round 3 execution & receive
+    let round3_outputs = parties.iter().map(|party| party.round_3(&round2_outputs_payloads, rng)).collect::<Result<Vec<(CompMessage, CompSignature<E>)>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    let accusations: Vec<Misbehaviour> = parties.iter().filter_map(|party| party.round_recieve(round3_outputs.clone(), 3, rng).err()).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+    let round3_outputs_payloads: Vec<(PartyId, RistrettoPoint)> = round3_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+    //compute key
+    parties.iter_mut().map(|party| party.compute_key(&round2_outputs_payloads, &round3_outputs_payloads)).collect::<Result<Vec<RistrettoPoint>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])
+}
+
+// Implements the robust compiler-version of the WAKE protocol
+// parties identified as misbehaving are excluded, and the key-exchange is restarted over the remaining parties, within the bounds of config
+// outputs the keys and the final member set
+pub fn run_robust_compiler_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(parties: Vec<Party<E>>, config: &RobustConfig, rng: &mut R) -> Result<RobustOutcome, Box<dyn Error>> {
+    run_robust_key_exchange(parties, config, |parties| run_identifiable_session(parties, rng))
+}
@@ -0,0 +1,72 @@
+use super::compiler_marlin_wake_protocol::Nonces;
+use crate::{
+    protocols::party_id::PartyId,
+    params::{WakeParams, MarlinKeys},
+    proof_systems::proof_system_marlin::MarlinProof,
+    utility::ristretto_to_string
+};
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalSerialize, SerializationError};
+use std::{fmt, sync::Arc};
+use curve25519_dalek_ng::ristretto::{RistrettoPoint};
+use rand::{CryptoRng, RngCore};
+
+///
+/// Corresponds to a WAKE compiler message
+/// holds the identifier of the sending party, round-index, the actual contents of the message, and the nonces
+/// 
+#[derive(Clone)]
+pub struct CompMessage {
+    pub sender: PartyId,
+    pub round_idx: usize,
+    pub payload: RistrettoPoint,
+    pub nonces: Nonces
+}
+
+impl CompMessage {
+    pub fn new(sender: PartyId, round_idx: usize, message: RistrettoPoint, nonces: Nonces) -> CompMessage {
+        CompMessage { sender, round_idx, payload: message, nonces }
+    }
+}
+
+impl fmt::Display for CompMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}##{}##{}##{}", self.sender, self.round_idx, ristretto_to_string(self.payload), self.nonces)
+    }
+}
+
+///
+/// Corresponds to a WAKE compiler signature
+/// holds a Marlin proof for the darkpool transaction relation
+/// 
+#[derive(Clone)]
+pub struct CompSignature<E: PairingEngine> {
+    pub marlin_darkpool_proof: Arc<MarlinProof<E>>,
+}
+
+impl<E: PairingEngine> CompSignature<E> {
+    ///signing function
+    ///outputs a signature
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<MarlinKeys<E>>, message_string: String, balance: u64, blinding_factor: E::Fr, rng: &mut R) -> CompSignature<E> {
+        //compute updated balance and convert to group element
+        let v = E::Fr::from(params.upperbound() - (balance - params.min_bal()));
+
+        let marlin_darkpool_proof = Arc::new(params.keys().marlin_darkpool().prove(v, blinding_factor, message_string, params.keys().pk(), rng).expect("marlin proving failed"));
+        CompSignature { marlin_darkpool_proof }
+    }
+
+    ///verification function
+    ///outputs a boolean indicating the verification status
+    pub fn verify_signature<R: RngCore + CryptoRng>(self, params: &WakeParams<MarlinKeys<E>>, image: E::Fr, message_string: String, rng: &mut R) -> bool {
+        let marlin_darkpool_verification = params.keys().marlin_darkpool().verify(image, message_string, params.keys().vk(), &self.marlin_darkpool_proof, rng);
+
+        marlin_darkpool_verification
+    }
+
+    ///serializes the signature, as the compressed Marlin proof
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = Vec::new();
+        self.marlin_darkpool_proof.serialize(&mut bytes)?;
+        Ok(bytes)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod compiler_marlin_wake_protocol;
+pub mod compiler_marlin_wake_signature;
\ No newline at end of file
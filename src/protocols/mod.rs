@@ -2,4 +2,17 @@ pub mod compiler_bp_wake;
 pub mod optimized_bp_wake;
 pub mod compiler_gm17_wake;
 pub mod optimized_gm17_wake;
-pub mod burmester_desmedt;
\ No newline at end of file
+pub mod compiler_marlin_wake;
+pub mod optimized_marlin_wake;
+pub mod burmester_desmedt;
+pub mod session_transcript;
+pub mod party_id;
+pub mod robust;
+pub mod timeout;
+pub mod snapshot;
+pub mod session_id;
+pub mod channel;
+pub mod ratchet;
+#[cfg(feature = "async")]
+pub mod async_network;
+pub mod broadcast;
\ No newline at end of file
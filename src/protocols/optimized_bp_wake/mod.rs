@@ -1,2 +1,4 @@
 pub mod optimized_bp_wake_protocol;
-pub mod optimized_bp_wake_signature_and_session_authentication;
\ No newline at end of file
+pub mod optimized_bp_wake_signature_and_session_authentication;
+#[cfg(feature = "async")]
+pub mod optimized_bp_wake_async;
\ No newline at end of file
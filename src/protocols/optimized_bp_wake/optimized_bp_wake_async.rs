@@ -0,0 +1,146 @@
+use super::{optimized_bp_wake_protocol::{setup_parties, Party}, optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication}};
+use crate::{
+    params::{WakeParams, BulletproofsKeys},
+    protocols::async_network::{in_memory_network, run_blocking, Endpoint},
+    protocols::party_id::{PartyId, Ring},
+    utility::fork_rngs
+};
+use curve25519_dalek_ng::ristretto::RistrettoPoint;
+use rand::{CryptoRng, RngCore};
+use rand_chacha::ChaCha20Rng;
+use std::{error::Error, sync::Arc};
+
+///
+/// File for running a party of the optimized 2-round WAKE protocol as a tokio task. Every round sends the message of
+/// the party over its endpoint, and every receive awaits the messages of all members of the round. Signing with session
+/// authentication and verification run on the blocking thread pool of tokio, such that they do not stall the tasks of other parties
+///
+//amount of rounds of the optimized version
+pub const OPTIMIZED_ROUNDS: usize = 2;
+
+///
+/// Message of the optimized version as sent over the network: the Burmester-Desmedt z value of round 1,
+/// or the signed message of round 2
+///
+#[derive(Clone)]
+pub enum OptimizedNetworkMessage {
+    Point(RistrettoPoint),
+    Signed(Box<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>),
+}
+
+///
+/// Party of the optimized WAKE protocol, communicating over an endpoint
+/// holds the party and its rng, which are moved to the blocking thread pool while signing or verifying,
+/// and the Burmester-Desmedt values received in rounds 1 and 2
+///
+pub struct AsyncParty {
+    state: Option<(Party, ChaCha20Rng)>,
+    endpoint: Endpoint<OptimizedNetworkMessage>,
+    round_1_outputs: Vec<(PartyId, RistrettoPoint)>,
+    round2_payload: Vec<(PartyId, RistrettoPoint)>,
+}
+
+impl AsyncParty {
+    pub fn new(party: Party, endpoint: Endpoint<OptimizedNetworkMessage>, rng: ChaCha20Rng) -> Result<AsyncParty, String> {
+        if party.id() != endpoint.id() {
+            return Err("Endpoint does not belong to the party".to_string())
+        }
+        Ok(AsyncParty { state: Some((party, rng)), endpoint, round_1_outputs: Vec::new(), round2_payload: Vec::new() })
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.endpoint.id()
+    }
+
+    //the party is missing only if a blocking task panicked
+    fn state(&mut self) -> Result<&mut (Party, ChaCha20Rng), String> {
+        self.state.as_mut().ok_or("Party was lost in a failed blocking task".to_string())
+    }
+
+    //runs f over the party and its rng on the blocking thread pool
+    async fn blocking<T: Send + 'static, F: FnOnce(&mut Party, &mut ChaCha20Rng) -> T + Send + 'static>(&mut self, f: F) -> Result<T, String> {
+        let state = self.state.take().ok_or("Party was lost in a failed blocking task".to_string())?;
+        let (state, output) = run_blocking(state, move |(party, rng)| f(party, rng)).await?;
+        self.state = Some(state);
+        Ok(output)
+    }
+
+    //round 1: broadcast the Burmester-Desmedt z value
+    pub async fn round_1(&mut self) -> Result<(), String> {
+        let (party, rng) = self.state()?;
+        let (_, z_i) = party.round_1(rng);
+        self.endpoint.broadcast(1, OptimizedNetworkMessage::Point(z_i)).await
+    }
+
+    //round 1 receive: await the z values of all members
+    pub async fn round1_recieve(&mut self) -> Result<(), String> {
+        let clock = *self.endpoint.clock();
+        let collector = self.state()?.0.collect_round(1, &clock);
+        let messages = self.endpoint.recieve_round(collector).await?;
+        self.round_1_outputs = messages.into_iter().map(|(sender, message)| match message {
+            OptimizedNetworkMessage::Point(z_i) => Ok((sender, z_i)),
+            _ => Err(format!("Unexpected message of party {} in round 1", sender)),
+        }).collect::<Result<Vec<(PartyId, RistrettoPoint)>, String>>()?;
+        Ok(())
+    }
+
+    //round 2: sign and broadcast the Burmester-Desmedt X value, with session authentication
+    pub async fn round_2(&mut self) -> Result<(), String> {
+        let round_1_outputs = self.round_1_outputs.clone();
+        let (message, signature) = self.blocking(move |party, rng| party.round_2(&round_1_outputs, rng)).await??;
+        self.endpoint.broadcast(2, OptimizedNetworkMessage::Signed(Box::new((message, signature)))).await
+    }
+
+    //round 2 receive: await and verify the messages of all members, keeping their Burmester-Desmedt values
+    pub async fn round2_recieve(&mut self) -> Result<(), String> {
+        let clock = *self.endpoint.clock();
+        let collector = self.state()?.0.collect_round(2, &clock);
+        let messages = self.endpoint.recieve_round(collector).await?;
+        let round_2_outputs = messages.into_iter().map(|(sender, message)| match message {
+            OptimizedNetworkMessage::Signed(signed) if signed.0.sender == sender => Ok(*signed),
+            _ => Err(format!("Unexpected message of party {} in round 2", sender)),
+        }).collect::<Result<Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>, String>>()?;
+        let round2_payload: Vec<(PartyId, RistrettoPoint)> = round_2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+        let round_1_outputs = self.round_1_outputs.clone();
+        self.blocking(move |party, rng| party.verify_round_2_outputs(&round_1_outputs, round_2_outputs, party.commitments().to_vec(), rng)).await?.map_err(|misbehaviour| misbehaviour.to_string())?;
+        self.round2_payload = round2_payload;
+        Ok(())
+    }
+
+    //compute Burmester Desmedt key
+    pub fn compute_key(&mut self) -> Result<RistrettoPoint, String> {
+        let (round_1_outputs, round2_payload) = (self.round_1_outputs.clone(), self.round2_payload.clone());
+        self.state()?.0.compute_key(&round_1_outputs, &round2_payload)
+    }
+
+    //runs all rounds of a session
+    pub async fn run(mut self) -> Result<RistrettoPoint, String> {
+        self.round_1().await?;
+        self.round1_recieve().await?;
+        self.round_2().await?;
+        self.round2_recieve().await?;
+        self.compute_key()
+    }
+}
+
+///Optimized 2-round WAKE, running every party as a tokio task over a network of in-memory channels
+///outputs the keys of the parties in the order of their balances
+pub async fn run_async_optimized_key_exchange<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let parties = setup_parties(rng, party_amount, balances, params)?;
+    let ring = Ring::new(parties.iter().map(|party| party.id()).collect())?;
+    let mut endpoints: Vec<Option<Endpoint<OptimizedNetworkMessage>>> = in_memory_network(&ring, OPTIMIZED_ROUNDS).into_iter().map(Some).collect();
+
+    let mut tasks = Vec::new();
+    for (party, rng) in parties.into_iter().zip(fork_rngs(rng, party_amount)) {
+        let position = ring.position(&party.id()).ok_or("Party is not a member of the ring")?;
+        let endpoint = endpoints[position].take().ok_or("Endpoint is already taken")?;
+        tasks.push(tokio::spawn(AsyncParty::new(party, endpoint, rng)?.run()));
+    }
+
+    let mut keys = Vec::new();
+    for task in tasks {
+        keys.push(task.await??);
+    }
+    Ok(keys)
+}
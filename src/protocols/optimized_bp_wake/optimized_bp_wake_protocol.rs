@@ -1,41 +1,68 @@
 use super::optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
 use crate::{
-    utility::{get_adjacent_elements, find_prev_idx, ristretto_to_string},
+    params::{WakeParams, BulletproofsKeys, SignatureBackend},
+    protocols::session_transcript::SessionTranscript,
+    protocols::party_id::{PartyId, Ring},
+    protocols::session_id::{SessionContext, SessionId, SharedReplayCache},
+    protocols::robust::{Misbehaviour, RingMember, RobustConfig, RobustOutcome, run_robust_key_exchange},
+    protocols::broadcast::{BroadcastView, Conflict, Echo},
+    protocols::timeout::{Clock, RoundCollector, RoundDeadlines},
+    protocols::snapshot::{self, EncryptedSnapshot, SnapshotKey, scalar_from_state, session_fingerprint},
+    utility::{get_adjacent_elements, find_prev_idx, ristretto_to_string, scalar_to_string, map_items, map_parties_mut, map_parties_with_rng},
     proof_systems::{
         discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct,
-        range_proof::RangeProofStruct
+        batch_verification::BatchVerifier
     }
     
 };
-use bulletproofs::{PedersenGens};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint}};
-use rand::rngs::OsRng;
-use std::error::Error;
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+use std::{error::Error, sync::Arc};
 
 /// 
 /// File for running Burmester Desmedt key-exchange, with optimized WAKE scheme, using Bulletproof as underlying proof, for the Darkpool transaction relation
 /// 
+//protocol label binding snapshots to the protocol
+const SNAPSHOT_PROTOCOL: &str = "optimized_bp";
 
 
 ///
 /// Struct responsible for holding VK from the WAKE protocol
+/// together with the id of the session, derived from the VK and the context of the session
 /// 
 #[derive(Clone)]
 pub struct VK {
-    idxs: Vec<usize>,
-    z_is: Vec<RistrettoPoint>
+    ids: Vec<PartyId>,
+    z_is: Vec<RistrettoPoint>,
+    session_id: SessionId
 }
 
 impl VK {
-    pub fn new(idxs: Vec<usize>, z_is: Vec<RistrettoPoint>) -> VK {
-        return VK { idxs, z_is };
+    //VK of a session run in the default context
+    pub fn new(ids: Vec<PartyId>, z_is: Vec<RistrettoPoint>) -> VK {
+        VK::with_context(ids, z_is, &SessionContext::default())
+    }
+
+    pub fn with_context(ids: Vec<PartyId>, z_is: Vec<RistrettoPoint>, context: &SessionContext) -> VK {
+        let session_id = SessionId::derive(context, &ids, &z_is.iter().map(|z_i| z_i.compress().to_bytes()).collect::<Vec<_>>());
+        VK { ids, z_is, session_id }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    //round 1 contributions of the parties
+    pub fn contributions(&self) -> Vec<[u8; 32]> {
+        self.z_is.iter().map(|z_i| z_i.compress().to_bytes()).collect()
     }
 
     pub fn to_string(self) -> String {
-        let mut res_string = String::new();
+        let mut res_string = self.session_id.to_string();
 
-        for i in 0..self.idxs.len() {
-            res_string += &(self.idxs[i].to_string() + &ristretto_to_string(self.z_is[i]));
+        for i in 0..self.ids.len() {
+            res_string += &(self.ids[i].to_string() + &ristretto_to_string(self.z_is[i]));
         }
 
         return res_string;
@@ -44,155 +71,362 @@ impl VK {
 
 ///
 /// Struct responsible for holding Burmester Desmedt values
+/// the exponent x_i is secret, and wiped when dropped
 /// 
-#[derive(Clone)]
 pub struct BurmesterDesmedt {
     z_i: RistrettoPoint,
-    x_i: Scalar,
+    x_i: Zeroizing<Scalar>,
 }
 
 ///
 /// Struct representing a single party
+/// idx is the index of the party's own commitment, from which its identifier is derived
 /// 
 pub struct Party {
 
-    idx: usize,
+    id: PartyId,
+    ring: Ring,
+    params: Arc<WakeParams<BulletproofsKeys>>,
     commitments: Vec<RistrettoPoint>,
-    min_bal: u64,
-    blinding: Scalar,
-    balance: u64,
+    blinding: Zeroizing<Scalar>,
+    balance: Zeroizing<u64>,
+    deadlines: RoundDeadlines,
+    //context of the session, and the cache of earlier sessions
+    context: SessionContext,
+    replay_cache: Option<SharedReplayCache>,
+    //signed messages received in round 2, when the reliable broadcast is enabled
+    broadcast: Option<BroadcastView<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>>,
     vk: Option<VK>,
     bd: Option<BurmesterDesmedt>
 }
 
 impl Party {
-    pub fn new(idx: usize, commitments: Vec<RistrettoPoint>, min_bal: u64, balance: u64, blinding: Scalar) -> Party {
+    pub fn new(idx: usize, params: Arc<WakeParams<BulletproofsKeys>>, commitments: Vec<RistrettoPoint>, ring: Ring, balance: u64, blinding: Scalar) -> Party {
         return Party{ 
             //public values
-            idx, 
-            min_bal, 
+            id: PartyId::from_commitment(&commitments[idx]),
+            ring,
+            params, 
             commitments, 
             //secret values
-            balance, 
-            blinding, 
+            balance: Zeroizing::new(balance), 
+            blinding: Zeroizing::new(blinding), 
+            deadlines: RoundDeadlines::default(),
+            context: SessionContext::default(),
+            replay_cache: None,
+            broadcast: None,
             //optional values getting initialized during the protocol
             vk: None, 
             bd: None 
         };
     }
 
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //proof the party signs its messages with, as selected by the parameters of the session
+    pub fn signature_backend(&self) -> SignatureBackend {
+        self.params.keys().signature_backend()
+    }
+
+    //commitments of all parties, which the round 2 outputs are verified against
+    pub fn commitments(&self) -> &[RistrettoPoint] {
+        &self.commitments
+    }
+
+    //position of the party in the ring
+    fn position(&self) -> usize {
+        self.ring.position(&self.id).unwrap()
+    }
+
+    //sets the context the session is run in, from which the session id is derived together with the round 1 outputs
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    //sets the cache of earlier sessions, rejecting sessions that reuse their round 1 outputs
+    pub fn set_replay_cache(&mut self, replay_cache: SharedReplayCache) {
+        self.replay_cache = Some(replay_cache);
+    }
+
+    //id of the session, once the round 1 outputs are received
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.vk.as_ref().map(|vk| vk.session_id())
+    }
+
+    //sets the timeouts of the rounds, after which the party stops waiting for the messages of the other parties
+    pub fn set_deadlines(&mut self, deadlines: RoundDeadlines) {
+        self.deadlines = deadlines;
+    }
+
+    //enables the reliable broadcast: the party records the signed messages it receives, such that it can echo them to the other members
+    //and check their echoes for conflicting messages (see broadcast)
+    pub fn enable_reliable_broadcast(&mut self) {
+        self.broadcast = Some(BroadcastView::new());
+    }
+
+    //records the signed messages received in round 2, if the reliable broadcast is enabled
+    pub fn record_broadcast(&mut self, round_2_outputs: &[(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)]) {
+        if let Some(broadcast) = self.broadcast.as_mut() {
+            for (msg, sig_session_auth) in round_2_outputs.iter() {
+                broadcast.record(2, msg.sender, &msg.to_string(), (msg.clone(), sig_session_auth.clone()));
+            }
+        }
+    }
+
+    //echo of the messages received in round 2, if the reliable broadcast is enabled
+    pub fn echo(&self) -> Option<Echo> {
+        self.broadcast.as_ref().map(|broadcast| broadcast.echo(2, self.id))
+    }
+
+    //message received from the sender of conflict, handed out as evidence for the echo of the party
+    pub fn evidence(&self, conflict: &Conflict) -> Option<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)> {
+        self.broadcast.as_ref().and_then(|broadcast| broadcast.evidence(conflict.round, &conflict.sender))
+    }
+
+    //checks the echoes of the other members for conflicting messages, where evidence outputs the message handed out by the echoer of a conflict
+    //on failure, blames the parties that signed conflicting messages in this session, or the parties whose echoes are not backed by such a message
+    pub fn check_echoes<E: Fn(&Conflict) -> Option<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>>(&self, echoes: &[Echo], evidence: E) -> Result<(), Misbehaviour> {
+        let broadcast = self.broadcast.as_ref().ok_or(Misbehaviour::unattributable("Reliable broadcast is not enabled".to_string()))?;
+        let vk = self.vk.as_ref().ok_or(Misbehaviour::unattributable("Round 1 outputs are not received".to_string()))?;
+
+        broadcast.check_echoes(echoes, evidence, |conflict, (msg, sig_session_auth)| {
+            let (position, i) = (self.ring.position(&conflict.sender)?, self.ring.statement_idx(&conflict.sender)?);
+            if msg.sender != conflict.sender || msg.vk.session_id() != vk.session_id() {
+                return None
+            }
+            let discrete_log_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(vk.z_is[position].compress());
+            let updated_bal_comm = self.commitments[i] + (self.params.g() * Scalar::from(self.params.min_bal()));
+            sig_session_auth.clone().verify_signature(&self.params, &msg.to_string(), &discrete_log_knowledge_proof_struct, &updated_bal_comm).then(|| msg.to_string())
+        })
+    }
+
+    //starts collecting the messages of round round_idx from the members of the ring, until the timeout of the round passes on clock
+    pub fn collect_round<T: Clone, C: Clock>(&self, round_idx: usize, clock: &C) -> RoundCollector<T> {
+        RoundCollector::new(round_idx, &self.ring, clock.now() + self.deadlines.timeout(round_idx))
+    }
+
+    //encrypted snapshot of the session state of the party between rounds, once round 1 is run
+    //bound to the party and to its session by its own round 1 output, which is fresh in every session
+    pub fn snapshot<R: RngCore + CryptoRng>(&self, key: &SnapshotKey, rng: &mut R) -> Result<EncryptedSnapshot, String> {
+        let bd = self.bd.as_ref().ok_or("Round 1 was not run")?;
+
+        //the Burmester-Desmedt exponent, and whether round 2 is run
+        let mut state = Zeroizing::new(bd.x_i.as_bytes().to_vec());
+        state.push(self.vk.is_some() as u8);
+
+        snapshot::seal(key, SNAPSHOT_PROTOCOL, &self.id, &session_fingerprint(&ristretto_to_string(bd.z_i)), &state, rng)
+    }
+
+    //restores the session state of a restarted party from a snapshot, given the round 1 outputs of its session received so far
+    //snapshots taken in another session are refused
+    pub fn restore(&mut self, snapshot: &EncryptedSnapshot, key: &SnapshotKey, round_1_outputs: &[(PartyId, RistrettoPoint)]) -> Result<(), String> {
+        let z_i = round_1_outputs.iter().find(|(sender, _)| *sender == self.id).map(|(_, z_i)| *z_i).ok_or("Round 1 output of the party is missing")?;
+        let state = snapshot::open(key, SNAPSHOT_PROTOCOL, &self.id, &session_fingerprint(&ristretto_to_string(z_i)), snapshot)?;
+        if state.len() != 33 {
+            return Err("Invalid snapshot".to_string())
+        }
+
+        let x_i = Zeroizing::new(scalar_from_state(&state[..32])?);
+        if self.params.g() * *x_i != z_i {
+            return Err("Snapshot does not match the round 1 output of the party".to_string())
+        }
+
+        //once round 2 is run, the VK is ordered by the ring as in round 2
+        self.vk = match state[32] {
+            0 => None,
+            _ => Some(VK::with_context(self.ring.ids().to_vec(), self.ring.arrange(round_1_outputs)?, &self.context))
+        };
+        self.bd = Some(BurmesterDesmedt { x_i, z_i });
+        Ok(())
+    }
+
     //round 1 wake, round 1 Burmester-Desmedt
-    pub fn round_1(&mut self, g: RistrettoPoint, rng: &mut OsRng) -> (usize, RistrettoPoint) {
-        let x_i = Scalar::random(rng);
+    pub fn round_1<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> (PartyId, RistrettoPoint) {
+        let x_i = Zeroizing::new(Scalar::random(rng));
 
-        let z_i = g * x_i;
+        let z_i = self.params.g() * *x_i;
 
         self.bd = Some(BurmesterDesmedt { x_i, z_i });
 
-        return (self.idx, z_i);
+        return (self.id, z_i);
     }
 
     //round 2, round 2 Burmester-Desmedt
-    pub fn round_2(&mut self, round_1_outputs: &Vec<(usize, RistrettoPoint)>, upperbound: u64, idx: usize) -> (OptimizedMessage, OptimizedSignatureAndSessionAuthentication) {
-        //receiving stage
-        let idxs: Vec<usize> = round_1_outputs.iter().map(|&(x, _)| x).collect();
-        let z_i_list: Vec<RistrettoPoint> = round_1_outputs.iter().map(|&(_, y)| y).collect();
+    pub fn round_2<R: RngCore + CryptoRng>(&mut self, round_1_outputs: &Vec<(PartyId, RistrettoPoint)>, rng: &mut R) -> Result<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication), String> {
+        //receiving stage, the VK is ordered by the ring, such that all parties agree on it regardless of the order the messages are received in
+        let z_i_list = self.ring.arrange(round_1_outputs)?;
+
+        let vk = VK::with_context(self.ring.ids().to_vec(), z_i_list.clone(), &self.context);
 
-        let vk = VK::new(idxs, z_i_list.clone());
+        //reject sessions reusing the round 1 outputs of an earlier session
+        if let Some(replay_cache) = self.replay_cache.as_ref() {
+            replay_cache.lock().unwrap().admit(&vk.session_id(), &vk.contributions())?;
+        }
         self.vk = Some(vk);
 
         //creating & sending stage
-        let (z_1, z_2) = get_adjacent_elements(&z_i_list, &self.idx);
-        let x_i = self.bd.clone().unwrap().x_i;
+        let position = self.position();
+        let (z_1, z_2) = get_adjacent_elements(&z_i_list, &position);
+        let bd = self.bd.as_ref().ok_or("Burmester-Desmedt values were not computed")?;
 
         //compute message
-        let payload = (z_2 - z_1) * x_i;
-        let m_i = OptimizedMessage::new(self.idx, payload, self.vk.clone().unwrap());
+        let payload = (z_2 - z_1) * *bd.x_i;
+        let m_i = OptimizedMessage::new(self.id, payload, self.vk.clone().unwrap());
         
         //sign message
         let signature = OptimizedSignatureAndSessionAuthentication::sign(
+            &self.params,
             &m_i.to_string(), 
-            self.min_bal, 
-            self.balance, 
-            self.blinding,
-            (self.bd.clone().unwrap().z_i).compress(),
-            self.bd.clone().unwrap().x_i,
-            upperbound, &(self.commitments[idx] + (&PedersenGens::default().B * Scalar::from(self.min_bal))));
-
-        return (m_i, signature);
+            *self.balance, 
+            *self.blinding,
+            bd.z_i.compress(),
+            *bd.x_i,
+            &(self.commitments[self.ring.statement_idx(&self.id).unwrap()] + (self.params.g() * Scalar::from(self.params.min_bal()))),
+            rng);
+
+        return Ok((m_i, signature));
     }
 
     //verification of round 2 outputs
-    pub fn verify_round_2_outputs(&self, g: RistrettoPoint, round_1_outputs: &Vec<(usize, RistrettoPoint)>, round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>, commitments: Vec<RistrettoPoint>, upperbound: u64) -> Result<(), String> {
-        //iterate over all other parties' round 2 results, and assert
-        for i in 0..round_1_outputs.len() {
-            if i == self.idx {
+    //on failure, identifies the parties whose messages are missing or invalid (identifiable abort)
+    pub fn verify_round_2_outputs<R: RngCore + CryptoRng>(&self, round_1_outputs: &Vec<(PartyId, RistrettoPoint)>, round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>, commitments: Vec<RistrettoPoint>, rng: &mut R) -> Result<(), Misbehaviour> {
+        let mut misbehaviour = Misbehaviour::default();
+
+        //session authentications and signatures of all other parties are verified as one batch
+        let mut batch_verifier = BatchVerifier::new(self.params.keys().range_proof_struct());
+
+        //round 1 outputs in ring order
+        let z_i_list = self.ring.arrange(round_1_outputs).map_err(Misbehaviour::unattributable)?;
+
+        //iterate over all other parties' round 2 results, matched to their sender by identifier, and assert
+        let mut received = vec![false; z_i_list.len()];
+        for (msg, sig_session_auth) in round_2_outputs.iter() {
+            let position = match self.ring.position(&msg.sender) {
+                Some(position) => position,
+                None => return Err(Misbehaviour::unattributable(format!("Unknown party {}", msg.sender)))
+            };
+            if received[position] {
+                misbehaviour.blame(msg.sender, format!("Duplicate message from party {}", msg.sender));
+                continue;
+            }
+            received[position] = true;
+
+            if msg.sender == self.id {
+                continue;
+            }
+            let i = self.ring.statement_idx(&msg.sender).unwrap();
+
+            //assert same session
+            if msg.vk.session_id() != self.vk.as_ref().unwrap().session_id() {
+                misbehaviour.blame(msg.sender, "Invalid session id".to_string());
                 continue;
             }
-            let (msg, sig_session_auth) = &round_2_outputs[i];
 
             //assert same VKs
             let vk_verification = msg.vk.clone().to_string() == self.vk.clone().unwrap().to_string();
             if !vk_verification {
-                return Err("Invalid VK was send".to_string());
+                misbehaviour.blame(msg.sender, "Invalid VK was send".to_string());
+                continue;
             }
 
-            //assert session authentication, and signature verification
-            let discrete_log_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(round_1_outputs[i].1.compress());
-            let updated_bal_comm = commitments[i] + (g * Scalar::from(self.min_bal));
-            let range_proof_struct = RangeProofStruct::new(self.min_bal, upperbound);
-            let verification_status = &sig_session_auth.clone().verify_signature(&msg.to_string(), &discrete_log_knowledge_proof_struct, &range_proof_struct, &updated_bal_comm);
-            if !verification_status {
-                return Err("Verification of signature failed".to_string())
+            //add session authentication and signature to the batch
+            let discrete_log_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(z_i_list[position].compress());
+            let updated_bal_comm = commitments[i] + (self.params.g() * Scalar::from(self.params.min_bal()));
+            if !sig_session_auth.clone().add_to_batch(&self.params, &mut batch_verifier, i, &msg.to_string(), &discrete_log_knowledge_proof_struct, &updated_bal_comm) {
+                misbehaviour.blame(msg.sender, "Verification of signature failed".to_string());
             }
         }
-        return Ok(())
+
+        //assert a message from every member of the ring
+        for (position, id) in self.ring.ids().iter().enumerate() {
+            if !received[position] {
+                misbehaviour.blame(*id, format!("Missing message from party {}", id));
+            }
+        }
+
+        //assert session authentications, and signature verification
+        if let Err(invalid_idxs) = batch_verifier.verify(rng) {
+            for id in self.ring.ids_of_statements(&invalid_idxs) {
+                misbehaviour.blame(id, "Verification of signature failed".to_string());
+            }
+        }
+        return misbehaviour.into_result()
+    }
+
+    //records the blinding and the Burmester-Desmedt exponent of the party in the transcript of a session, once the exponent is drawn
+    pub(crate) fn record_secret_inputs(&self, transcript: &mut SessionTranscript) {
+        if let Some(bd) = self.bd.as_ref() {
+            transcript.record_secret_inputs(&self.id, scalar_to_string(*self.blinding), scalar_to_string(*bd.x_i));
+        }
     }
 
     //compute Burmester Desmedt key
-    pub fn compute_key(&self, round1_payload: &Vec<RistrettoPoint>, round2_payload: &Vec<RistrettoPoint>) -> RistrettoPoint {
+    pub fn compute_key(&mut self, round1_payload: &Vec<(PartyId, RistrettoPoint)>, round2_payload: &Vec<(PartyId, RistrettoPoint)>) -> Result<RistrettoPoint, String> {
+        let round1_payload = self.ring.arrange(round1_payload)?;
+        let round2_payload = self.ring.arrange(round2_payload)?;
         let num_parties = round1_payload.len();
+        let position = self.position();
 
-        let z = round1_payload[find_prev_idx(self.idx, num_parties)];
-        let exponent = self.bd.clone().unwrap().x_i * Scalar::from(num_parties as u64);
+        let z = round1_payload[find_prev_idx(position, num_parties)];
+        let mut exponent = *self.bd.as_ref().ok_or("Burmester-Desmedt exponent was erased")?.x_i * Scalar::from(num_parties as u64);
 
         let mut key = z * exponent;
         for idx in 0..num_parties - 1 {
-            let i = (self.idx + idx) % num_parties;
+            let i = (position + idx) % num_parties;
             key += round2_payload[i] * Scalar::from((num_parties - idx - 1) as u64);
         }
 
-        return key;
+        //erase the ephemeral Burmester-Desmedt exponent, such that the key cannot be recomputed from the party's state later on (forward secrecy)
+        exponent.zeroize();
+        self.bd = None;
+
+        return Ok(key);
+    }
+}
+
+impl RingMember for Party {
+    fn id(&self) -> PartyId {
+        self.id
+    }
+
+    fn exclude(&mut self, ids: &[PartyId]) {
+        self.ring = self.ring.without(ids);
+        self.vk = None;
+        self.bd = None;
+        //messages of the failed session are no evidence in the next one
+        if self.broadcast.is_some() {
+            self.broadcast = Some(BroadcastView::new());
+        }
     }
 }
 
 ///
 /// Setup function, for initializing the parties with their own secret values and public commitments
 /// 
-fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64) -> Result<Vec<Party>, Box<dyn Error>> {
+pub fn setup_parties<R: RngCore + CryptoRng>(rng: &mut R, party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>) -> Result<Vec<Party>, Box<dyn Error>> {
+    //the balances are wiped once the parties are set up
+    let balances = Zeroizing::new(balances);
+
     //check if length of balance list, is equal to the amount of parties denoted
     if balances.len() != party_amount {
         return Err("balances not correct length".into())
     }
-
-    //check if upperbound is among the valid options 2^8, 2^16, 2^32, 2^64
-    if upperbound != u8::MAX as u64 && upperbound != u16::MAX as u64 && upperbound != u32::MAX as u64 && upperbound != u64::MAX {
-        return Err(format!("upperbound was not among the valid values: {}, {}, {}, {}\n was: {}", u8::MAX, u16::MAX, u32::MAX, u64::MAX, upperbound).into());
-    }
     
     //public input (g, h)
-    let pedersen_gens = PedersenGens::default();
+    let pedersen_gens = params.pc_gens();
 
     //statements
     let mut commitments: Vec<RistrettoPoint> = Vec::new();
 
     //witnesses
-    let mut blindings: Vec<Scalar> = Vec::new();
+    let mut blindings: Zeroizing<Vec<Scalar>> = Zeroizing::new(Vec::new());
 
     //populating statements and witnesses
     for i in 0..party_amount {
         //returrn error if insufficient balances 
-        if balances[i] < min_bal {
+        if balances[i] < params.min_bal() {
             return Err("one or more balances are smaller than minimum_balance".into())
         }
 
@@ -201,14 +435,17 @@ fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: V
         blindings.push(r);
 
         //compute pedersen commitment: g^(2^n-1-b)*h^(r)
-        let commitment = (pedersen_gens.B * Scalar::from(upperbound-balances[i])) + (pedersen_gens.B_blinding * r);
+        let commitment = (pedersen_gens.B * Scalar::from(params.upperbound()-balances[i])) + (pedersen_gens.B_blinding * r);
         commitments.push(commitment);
     }
 
+    //the ring is ordered by the identifiers of the parties, rather than by the order of the balances
+    let ring = Ring::from_commitments(&commitments)?;
+
     //create parties in the protocol, with their private inputs (witnesses), as well as all public information 
     let mut parties: Vec<Party> = Vec::new();
     for i in 0..party_amount {
-        parties.push(Party::new(i, commitments.clone(), min_bal, balances[i], blindings[i]));
+        parties.push(Party::new(i, params.clone(), commitments.clone(), ring.clone(), balances[i], blindings[i]));
     }
 
     return Ok(parties)
@@ -217,46 +454,104 @@ fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: V
 ///Optimized 2-round WAKE
 ///runs a setup of key-exchange with random balances above or equal to the minimum balance
 ///outputs the list of keys for all the parties (which will be identical)
-pub fn run_optimized_key_exchange(party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
-    let rng = &mut OsRng;
-    let pedersen_gens = PedersenGens::default();
-    let g = pedersen_gens.B;
+pub fn run_optimized_key_exchange<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let (keys, _) = run_optimized_key_exchange_with_transcript(party_amount, balances, params, rng)?;
+    Ok(keys)
+}
 
-    let mut parties = setup_parties(rng, party_amount, min_bal, balances, upperbound)?;
+///Optimized 2-round WAKE
+///outputs the list of keys for all the parties, together with the transcript of all public messages of the session
+pub fn run_optimized_key_exchange_with_transcript<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<(Vec<RistrettoPoint>, SessionTranscript), Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let mut transcript = SessionTranscript::new(parties.first().map(|party| party.commitments.iter().map(|commitment| ristretto_to_string(*commitment)).collect()).unwrap_or_default());
 
     //round 1
-    let mut round_1_outputs: Vec<(usize, RistrettoPoint)> = Vec::new();
-    let mut round1_payload: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let r1_res = &parties[i].round_1(g, rng);
-        round_1_outputs.push(*r1_res);
-        round1_payload.push(r1_res.1);
+    let round_1_outputs: Vec<(PartyId, RistrettoPoint)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_1(rng));
+    for (sender, z_i) in round_1_outputs.iter() {
+        transcript.record(1, sender, ristretto_to_string(*z_i), &[]);
     }
 
     //round 2
-    let mut round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)> = Vec::new();
-    let mut round2_payload: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let (m_i, signature) = &parties[i].round_2(&round_1_outputs, upperbound, i);
-        round_2_outputs.push((m_i.clone(), signature.clone()));
-        round2_payload.push(m_i.payload);
+    let round_2_outputs = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_2(&round_1_outputs, rng)).into_iter().collect::<Result<Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>, String>>()?;
+    let mut round2_payload: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for (m_i, signature) in round_2_outputs.iter() {
+        round2_payload.push((m_i.sender, m_i.payload));
+        transcript.record(2, &m_i.sender, m_i.to_string(), &signature.to_bytes());
     }
 
     //verification
-    for i in 0..party_amount {
-        let round2_res = &parties[i].verify_round_2_outputs(g, &round_1_outputs, round_2_outputs.clone(), parties[i].commitments.clone(), upperbound);
+    for round2_res in map_parties_with_rng(&mut parties, rng, |party, rng| party.verify_round_2_outputs(&round_1_outputs, round_2_outputs.clone(), party.commitments.clone(), rng)) {
         if let Err(round2_err) = round2_res {
-            return Err(round2_err.clone().into());
+            return Err(round2_err.into());
         }
     }
 
+    //secret inputs, recorded before the Burmester-Desmedt exponents are erased
+    for party in parties.iter() {
+        party.record_secret_inputs(&mut transcript);
+    }
+
     //compute key
-    let mut keys: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let key = &parties[i].compute_key(&round1_payload, &round2_payload);
-        keys.push(*key);
+    let keys = map_parties_mut(&mut parties, |party| party.compute_key(&round_1_outputs, &round2_payload)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>()?;
+
+    return Ok((keys, transcript));
+
+}
+
+///Records the signed messages of round 2 at the parties that enabled the reliable broadcast, and exchanges their echoes
+///the evidence for a conflicting echo is handed out by its echoer
+///outputs the misbehaviour identified by every party that found a conflict
+fn exchange_echoes(parties: &mut [Party], round_2_outputs: &[(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)]) -> Result<(), Vec<Misbehaviour>> {
+    map_parties_mut(parties, |party| party.record_broadcast(round_2_outputs));
+
+    let echoes: Vec<Echo> = parties.iter().filter_map(|party| party.echo()).collect();
+    if echoes.is_empty() {
+        return Ok(())
+    }
+    let parties: &[Party] = parties;
+    let accusations: Vec<Misbehaviour> = map_items(parties, |party| party.check_echoes(&echoes, |conflict| parties.iter().find(|echoer| echoer.id == conflict.echoer).and_then(|echoer| echoer.evidence(conflict)))).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
     }
+    Ok(())
+}
+
+///Runs a single session of optimized WAKE between the given parties
+///outputs the keys of the parties in order, or the misbehaviour identified by every party
+fn run_identifiable_session<R: RngCore + CryptoRng>(parties: &mut [Party], rng: &mut R) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>> {
+    //round 1
+    let round_1_outputs: Vec<(PartyId, RistrettoPoint)> = map_parties_with_rng(parties, rng, |party, rng| party.round_1(rng));
 
-    return Ok(keys);
+    //round 2
+    let round_2_outputs = map_parties_with_rng(parties, rng, |party, rng| party.round_2(&round_1_outputs, rng)).into_iter().collect::<Result<Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    let round2_payload: Vec<(PartyId, RistrettoPoint)> = round_2_outputs.iter().map(|(m_i, _)| (m_i.sender, m_i.payload)).collect();
+
+    //verification
+    let accusations: Vec<Misbehaviour> = map_parties_with_rng(parties, rng, |party, rng| party.verify_round_2_outputs(&round_1_outputs, round_2_outputs.clone(), party.commitments.clone(), rng)).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+    exchange_echoes(parties, &round_2_outputs)?;
 
+    //compute key
+    map_parties_mut(parties, |party| party.compute_key(&round_1_outputs, &round2_payload)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])
+}
+
+///Robust optimized 2-round WAKE
+///parties identified as misbehaving are excluded, and the key-exchange is restarted over the remaining parties, within the bounds of config
+///outputs the keys and the final member set
+pub fn run_robust_optimized_key_exchange<R: RngCore + CryptoRng>(parties: Vec<Party>, config: &RobustConfig, rng: &mut R) -> Result<RobustOutcome, Box<dyn Error>> {
+    run_robust_key_exchange(parties, config, |parties| run_identifiable_session(parties, rng))
+}
+
+///Optimized 2-round WAKE over the reliable broadcast
+///the parties echo the signed messages of round 2 to each other, such that a party sending conflicting messages to different parties
+///is blamed before the key is computed
+pub fn run_reliable_optimized_key_exchange<R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<BulletproofsKeys>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    for party in parties.iter_mut() {
+        party.enable_reliable_broadcast();
+    }
+    let keys = run_identifiable_session(&mut parties, rng).map_err(|accusations| accusations.into_iter().next().unwrap_or_default())?;
+    Ok(keys)
 }
\ No newline at end of file
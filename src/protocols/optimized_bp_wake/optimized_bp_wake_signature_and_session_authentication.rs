@@ -1,68 +1,152 @@
 use super::optimized_bp_wake_protocol::VK;
 use crate::{
+    protocols::party_id::PartyId,
+    params::{WakeParams, BulletproofsKeys, SignatureBackend},
     utility::ristretto_to_string,
     proof_systems::{
-        range_proof::{RangeProofStruct},
+        batch_verification::BatchVerifier,
         discrete_log_knowledege_proof::{DiscreteLogKnowledgeProofStruct, DiscreteLogKnowledgeProof}
     },
 };
-use bulletproofs::RangeProof;
+use bulletproofs::{RangeProof, r1cs::R1CSProof};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint,CompressedRistretto}};
+use rand::{CryptoRng, RngCore};
 
 ///
 /// Corresponds to an optimized WAKE message
-/// holds party-index, round-index, the actual contents of the message, and the nonces
+/// holds the identifier of the sending party, round-index, the actual contents of the message, and the nonces
 /// 
 #[derive(Clone)]
 pub struct OptimizedMessage {
-    pub idx: usize,
+    pub sender: PartyId,
     pub payload: RistrettoPoint,
     pub vk: VK
 }
 
 impl OptimizedMessage {
-    pub fn new(idx: usize, message: RistrettoPoint, vk: VK) -> OptimizedMessage {
-        return OptimizedMessage { idx, payload: message, vk }
+    pub fn new(sender: PartyId, message: RistrettoPoint, vk: VK) -> OptimizedMessage {
+        return OptimizedMessage { sender, payload: message, vk }
     }
 
     pub fn to_string(&self) -> String {
-        return self.idx.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.vk.clone().to_string();
+        return self.sender.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.vk.clone().to_string();
     }
 }
 
 ///
 /// Corresponds to an optimized WAKE signature and session authentication proof
-/// holds a bulletproof rangeproof for the darkpool transaction relation, and a discrete logarithm knowledge proof
+/// holds a bulletproof rangeproof and a discrete logarithm knowledge proof, or an R1CS signature and session authentication,
+/// as selected by the signature backend of the parameters
 /// 
 #[derive(Clone)]
-pub struct OptimizedSignatureAndSessionAuthentication { 
-    pub discrete_log_knowledge_proof: DiscreteLogKnowledgeProof,
-    pub range_proof: RangeProof, 
+pub enum OptimizedSignatureAndSessionAuthentication { 
+    RangeProof {
+        discrete_log_knowledge_proof: DiscreteLogKnowledgeProof,
+        range_proof: Box<RangeProof>,
+    },
+    R1cs(Box<R1csOptimizedSignatureAndSessionAuthentication>),
 }
 
 impl OptimizedSignatureAndSessionAuthentication {
     ///signing function
-    ///outputs a signature
-    pub fn sign(message_string: &String, min_bal: u64, balance: u64, blinding_factor: Scalar, discrete_log_proof_statement: CompressedRistretto, discrete_log_proof_witness: Scalar, upperbound: u64, commitment: &RistrettoPoint) -> OptimizedSignatureAndSessionAuthentication {
+    ///outputs a signature, with the signature backend of the parameters
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<BulletproofsKeys>, message_string: &String, balance: u64, blinding_factor: Scalar, discrete_log_proof_statement: CompressedRistretto, discrete_log_proof_witness: Scalar, commitment: &RistrettoPoint, rng: &mut R) -> OptimizedSignatureAndSessionAuthentication {
+        if params.keys().signature_backend() == SignatureBackend::R1cs {
+            let signature = R1csOptimizedSignatureAndSessionAuthentication::sign(params, message_string, balance, blinding_factor, discrete_log_proof_statement, discrete_log_proof_witness, commitment, rng);
+            return OptimizedSignatureAndSessionAuthentication::R1cs(Box::new(signature.expect("r1cs proving failed")));
+        }
+
         //discrete log knowledge proof
         let discrete_log_knowledege_proof_struct = DiscreteLogKnowledgeProofStruct::new(discrete_log_proof_statement);
-        let discrete_log_knowledge_proof = discrete_log_knowledege_proof_struct.prove(discrete_log_proof_witness, message_string.clone() + &ristretto_to_string(*commitment));
+        let discrete_log_knowledge_proof = discrete_log_knowledege_proof_struct.prove(discrete_log_proof_witness, message_string.clone() + &ristretto_to_string(*commitment), rng);
 
         //range-proof
-        let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
-        let range_proof = range_proof_struct.prove(balance, blinding_factor, message_string.clone() + &discrete_log_knowledge_proof.to_string()).unwrap();
+        let range_proof = params.keys().range_proof_struct().prove(balance, blinding_factor, message_string.clone() + &discrete_log_knowledge_proof.to_string(), rng).unwrap();
 
         //combine results
-        let signature = OptimizedSignatureAndSessionAuthentication { discrete_log_knowledge_proof, range_proof };
+        OptimizedSignatureAndSessionAuthentication::RangeProof { discrete_log_knowledge_proof, range_proof: Box::new(range_proof) }
+    }
+
+    ///verification function
+    ///outputs a boolean indicating the verification status, which is false for a signature of another backend than that of the parameters
+    pub fn verify_signature(self, params: &WakeParams<BulletproofsKeys>, message_string: &String, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, commitment: &RistrettoPoint) -> bool {
+        match (self, params.keys().signature_backend()) {
+            (OptimizedSignatureAndSessionAuthentication::RangeProof { discrete_log_knowledge_proof, range_proof }, SignatureBackend::RangeProof) => {
+                let discrete_log_knowledege_proof_verification = discrete_log_knowledge_proof_struct.verify(discrete_log_knowledge_proof, message_string.clone() + &ristretto_to_string(*commitment));
+                let range_proof_verification = params.keys().range_proof_struct().verify(*range_proof, commitment.compress(), message_string.clone() + &discrete_log_knowledge_proof.to_string());
+                discrete_log_knowledege_proof_verification && range_proof_verification
+            },
+            (OptimizedSignatureAndSessionAuthentication::R1cs(signature), SignatureBackend::R1cs) => signature.verify_signature(params, message_string, discrete_log_knowledge_proof_struct, commitment),
+            _ => false,
+        }
+    }
+
+    ///adds the session authentication and the proof of the signature to a batch, to be verified together with those of the other parties
+    ///R1CS signatures cannot be batched, and are verified right away instead
+    ///outputs false if the signature is already known to be invalid
+    pub fn add_to_batch(self, params: &WakeParams<BulletproofsKeys>, batch_verifier: &mut BatchVerifier, idx: usize, message_string: &str, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, commitment: &RistrettoPoint) -> bool {
+        match (self, params.keys().signature_backend()) {
+            (OptimizedSignatureAndSessionAuthentication::RangeProof { discrete_log_knowledge_proof, range_proof }, SignatureBackend::RangeProof) => {
+                batch_verifier.add_discrete_log_knowledge_proof(idx, *discrete_log_knowledge_proof_struct, discrete_log_knowledge_proof, message_string.to_string() + &ristretto_to_string(*commitment));
+                batch_verifier.add_range_proof(idx, *range_proof, commitment.compress(), message_string.to_string() + &discrete_log_knowledge_proof.to_string());
+                true
+            },
+            (signature, _) => signature.verify_signature(params, &message_string.to_string(), discrete_log_knowledge_proof_struct, commitment),
+        }
+    }
+
+    ///serializes the signature, as the session authentication followed by the range proof or the R1CS proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            OptimizedSignatureAndSessionAuthentication::RangeProof { discrete_log_knowledge_proof, range_proof } => {
+                let mut bytes = discrete_log_knowledge_proof.to_bytes();
+                bytes.extend(range_proof.to_bytes());
+                bytes
+            },
+            OptimizedSignatureAndSessionAuthentication::R1cs(signature) => {
+                let mut bytes = signature.discrete_log_knowledge_proof.to_bytes();
+                bytes.extend(signature.r1cs_proof.to_bytes());
+                bytes
+            },
+        }
+    }
+}
+
+///
+/// Corresponds to an optimized WAKE signature and session authentication proof
+/// holds a bulletproofs R1CS proof for the darkpool transaction relation, as an alternative to the rangeproof, and a discrete logarithm knowledge proof
+/// the parties sign with it, within OptimizedSignatureAndSessionAuthentication, when the parameters select the R1CS signature backend
+/// 
+#[derive(Clone)]
+pub struct R1csOptimizedSignatureAndSessionAuthentication {
+    pub discrete_log_knowledge_proof: DiscreteLogKnowledgeProof,
+    pub r1cs_proof: R1CSProof,
+}
+
+impl R1csOptimizedSignatureAndSessionAuthentication {
+    ///signing function
+    ///outputs a signature
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<BulletproofsKeys>, message_string: &str, balance: u64, blinding_factor: Scalar, discrete_log_proof_statement: CompressedRistretto, discrete_log_proof_witness: Scalar, commitment: &RistrettoPoint, rng: &mut R) -> Result<R1csOptimizedSignatureAndSessionAuthentication, String> {
+        if balance < params.min_bal() {
+            return Err("minimum balance requirement was not met".to_string());
+        }
 
-        return signature;
+        //discrete log knowledge proof
+        let discrete_log_knowledege_proof_struct = DiscreteLogKnowledgeProofStruct::new(discrete_log_proof_statement);
+        let discrete_log_knowledge_proof = discrete_log_knowledege_proof_struct.prove(discrete_log_proof_witness, message_string.to_string() + &ristretto_to_string(*commitment), rng);
+
+        //r1cs proof
+        let v = params.upperbound() - (balance - params.min_bal());
+        let (r1cs_proof, _) = params.keys().r1cs_proof_struct().prove(&[v], &[blinding_factor], message_string.to_string() + &discrete_log_knowledge_proof.to_string()).map_err(|e| e.to_string())?;
+
+        Ok(R1csOptimizedSignatureAndSessionAuthentication { discrete_log_knowledge_proof, r1cs_proof })
     }
 
     ///verification function
     ///outputs a boolean indicating the verification status
-    pub fn verify_signature(self, message_string: &String, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, range_proof_struct: &RangeProofStruct, commitment: &RistrettoPoint) -> bool {
-        let discrete_log_knowledege_proof_verification = discrete_log_knowledge_proof_struct.verify(self.discrete_log_knowledge_proof.clone(), message_string.clone() + &ristretto_to_string(*commitment));
-        let range_proof_verification = range_proof_struct.verify(self.range_proof, commitment.compress(), message_string.clone() + &self.discrete_log_knowledge_proof.to_string());
-        return discrete_log_knowledege_proof_verification && range_proof_verification;
+    pub fn verify_signature(&self, params: &WakeParams<BulletproofsKeys>, message_string: &str, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, commitment: &RistrettoPoint) -> bool {
+        let discrete_log_knowledege_proof_verification = discrete_log_knowledge_proof_struct.verify(self.discrete_log_knowledge_proof, message_string.to_string() + &ristretto_to_string(*commitment));
+        let r1cs_proof_verification = params.keys().r1cs_proof_struct().verify(&self.r1cs_proof, &[commitment.compress()], message_string.to_string() + &self.discrete_log_knowledge_proof.to_string());
+        discrete_log_knowledege_proof_verification && r1cs_proof_verification
     }
-}
\ No newline at end of file
+}
@@ -1,41 +1,59 @@
 use super::optimized_gm17_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
 use crate::{
-    utility::{get_adjacent_elements, find_prev_idx, ristretto_to_string, mimc},
-    proof_systems::{
-        discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct,
-        proof_system_gm17::Gm17Darkpool
-    }
+    params::{WakeParams, Gm17Keys},
+    protocols::session_transcript::SessionTranscript,
+    protocols::party_id::{PartyId, Ring},
+    protocols::robust::{Misbehaviour, RingMember, RobustConfig, RobustOutcome, run_robust_key_exchange},
+    protocols::session_id::{SessionContext, SessionId, SharedReplayCache},
+    utility::{get_adjacent_elements, find_prev_idx, ristretto_to_string, scalar_to_string, field_to_string, mimc, map_parties_mut, map_parties_with_rng},
+    proof_systems::discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct
 };
-use std::error::Error;
-use rand::{rngs::OsRng, Rng};
-use bulletproofs::PedersenGens;
+use std::{error::Error, sync::Arc};
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
-use ark_bls12_377::{Fr, Parameters};
-use ark_ec::bls12::Bls12;
-use ark_gm17::{ProvingKey, PreparedVerifyingKey};
+use ark_ec::PairingEngine;
+use ark_ff::UniformRand;
 /// 
 /// File for running Burmester Desmedt key-exchange, with optimized WAKE scheme, using GM17 SE zk-SNARK as underlying proof, for the Darkpool transaction relation
 /// 
 
 ///
 /// Struct responsible for holding VK from the WAKE protocol
+/// together with the id of the session, derived from the VK and the context of the session
 /// 
 #[derive(Clone)]
 pub struct VK {
-    idxs: Vec<usize>,
-    z_is: Vec<RistrettoPoint>
+    ids: Vec<PartyId>,
+    z_is: Vec<RistrettoPoint>,
+    session_id: SessionId
 }
 
 impl VK {
-    pub fn new(idxs: Vec<usize>, z_is: Vec<RistrettoPoint>) -> VK {
-        return VK { idxs, z_is };
+    //VK of a session run in the default context
+    pub fn new(ids: Vec<PartyId>, z_is: Vec<RistrettoPoint>) -> VK {
+        VK::with_context(ids, z_is, &SessionContext::default())
+    }
+
+    pub fn with_context(ids: Vec<PartyId>, z_is: Vec<RistrettoPoint>, context: &SessionContext) -> VK {
+        let session_id = SessionId::derive(context, &ids, &z_is.iter().map(|z_i| z_i.compress().to_bytes()).collect::<Vec<_>>());
+        VK { ids, z_is, session_id }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    //round 1 contributions of the parties
+    pub fn contributions(&self) -> Vec<[u8; 32]> {
+        self.z_is.iter().map(|z_i| z_i.compress().to_bytes()).collect()
     }
 
     pub fn to_string(self) -> String {
-        let mut res_string = String::new();
+        let mut res_string = self.session_id.to_string();
 
-        for i in 0..self.idxs.len() {
-            res_string += &(self.idxs[i].to_string() + &ristretto_to_string(self.z_is[i]));
+        for i in 0..self.ids.len() {
+            res_string += &(self.ids[i].to_string() + &ristretto_to_string(self.z_is[i]));
         }
 
         return res_string;
@@ -44,172 +62,277 @@ impl VK {
 
 ///
 /// Struct responsible for holding Burmester Desmedt values
+/// the exponent x_i is secret, and wiped when dropped
 /// 
-#[derive(Clone)]
 pub struct BurmesterDesmedt {
     z_i: RistrettoPoint,
-    x_i: Scalar,
+    x_i: Zeroizing<Scalar>,
 }
 
 ///
 /// Struct representing a single party
+/// idx is the index of the party's own image, from which its identifier is derived
 /// 
-pub struct Party {
+pub struct Party<E: PairingEngine> {
     //public values
     idx: usize,
-    images: Vec<Fr>,
-    min_bal: u64,
-    gm17_darkpool: Gm17Darkpool,
-    pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>>,
+    id: PartyId,
+    ring: Ring,
+    params: Arc<WakeParams<Gm17Keys<E>>>,
+    images: Vec<E::Fr>,
     //secret values
-    balance: u64,
-    blinding: Fr,
-    pk: ProvingKey<Bls12<ark_bls12_377::Parameters>>,
+    balance: Zeroizing<u64>,
+    blinding: Zeroizing<E::Fr>,
+    //context of the session, and the cache of earlier sessions
+    context: SessionContext,
+    replay_cache: Option<SharedReplayCache>,
     //optional values getting initialized during the protocol
     vk: Option<VK>,
     bd: Option<BurmesterDesmedt>
 }
 
-impl Party {
-    pub fn new(idx: usize, gm17_darkpool: Gm17Darkpool, images: Vec<Fr>, min_bal: u64, balance: u64, blinding: Fr, pk: ProvingKey<Bls12<ark_bls12_377::Parameters>>, pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>>) -> Party {
+impl<E: PairingEngine> Party<E> {
+    pub fn new(idx: usize, params: Arc<WakeParams<Gm17Keys<E>>>, images: Vec<E::Fr>, ring: Ring, balance: u64, blinding: E::Fr) -> Party<E> {
         return Party{ 
             idx, 
+            id: PartyId::from_image(&images[idx]),
+            ring,
+            params, 
             images, 
-            gm17_darkpool, 
-            min_bal, 
-            balance, 
-            blinding, 
-            pk,
-            pvk_list,
+            balance: Zeroizing::new(balance), 
+            blinding: Zeroizing::new(blinding), 
+            context: SessionContext::default(),
+            replay_cache: None,
             vk: None, 
             bd: None 
         };
     }
 
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //position of the party in the ring
+    fn position(&self) -> usize {
+        self.ring.position(&self.id).unwrap()
+    }
+
+    //sets the context the session is run in, from which the session id is derived together with the round 1 outputs
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    //sets the cache of earlier sessions, rejecting sessions that reuse their round 1 outputs
+    pub fn set_replay_cache(&mut self, replay_cache: SharedReplayCache) {
+        self.replay_cache = Some(replay_cache);
+    }
+
+    //id of the session, once the round 1 outputs are received
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.vk.as_ref().map(|vk| vk.session_id())
+    }
+
     //round 1 wake, round 1 Burmester-Desmedt
-    pub fn round_1(&mut self, g: RistrettoPoint, rng: &mut OsRng) -> (usize, RistrettoPoint) {
-        let x_i = Scalar::random(rng);
+    pub fn round_1<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> (PartyId, RistrettoPoint) {
+        let x_i = Zeroizing::new(Scalar::random(rng));
 
-        let z_i = g * x_i;
+        let z_i = self.params.g() * *x_i;
 
         self.bd = Some(BurmesterDesmedt { x_i, z_i });
 
-        return (self.idx, z_i);
+        return (self.id, z_i);
     }
 
     //round 2, round 2 Burmester-Desmedt
-    pub fn round_2(&mut self, round_1_outputs: &Vec<(usize, RistrettoPoint)>, upperbound: u64) -> (OptimizedMessage, OptimizedSignatureAndSessionAuthentication) {
-        //receiving stage
-        let idxs: Vec<usize> = round_1_outputs.iter().map(|&(x, _)| x).collect();
-        let z_i_list: Vec<RistrettoPoint> = round_1_outputs.iter().map(|&(_, y)| y).collect();
+    pub fn round_2<R: RngCore + CryptoRng>(&mut self, round_1_outputs: &Vec<(PartyId, RistrettoPoint)>, rng: &mut R) -> Result<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>), String> {
+        //receiving stage, the VK is ordered by the ring, such that all parties agree on it regardless of the order the messages are received in
+        let z_i_list = self.ring.arrange(round_1_outputs)?;
 
-        let vk = VK::new(idxs, z_i_list.clone());
+        let vk = VK::with_context(self.ring.ids().to_vec(), z_i_list.clone(), &self.context);
+
+        //reject sessions reusing the round 1 outputs of an earlier session
+        if let Some(replay_cache) = self.replay_cache.as_ref() {
+            replay_cache.lock().unwrap().admit(&vk.session_id(), &vk.contributions())?;
+        }
         self.vk = Some(vk);
 
         //creating & sending stage
-        let (z_1, z_2) = get_adjacent_elements(&z_i_list, &self.idx);
-        let x_i = self.bd.clone().unwrap().x_i;
+        let position = self.position();
+        let (z_1, z_2) = get_adjacent_elements(&z_i_list, &position);
+        let bd = self.bd.as_ref().ok_or("Burmester-Desmedt values were not computed")?;
 
         //compute message
-        let payload = (z_2 - z_1) * x_i;
-        let m_i = OptimizedMessage::new(self.idx, payload, self.vk.clone().unwrap());
+        let payload = (z_2 - z_1) * *bd.x_i;
+        let m_i = OptimizedMessage::new(self.id, payload, self.vk.clone().unwrap());
 
         //sign message
         let signature = OptimizedSignatureAndSessionAuthentication::sign(
-            &self.gm17_darkpool,
+            &self.params,
+            self.idx,
             self.images[self.idx],
             &m_i.to_string(), 
-            self.min_bal, 
-            self.balance, 
-            self.blinding,
-            (self.bd.clone().unwrap().z_i).compress(),
-            self.bd.clone().unwrap().x_i,
-            upperbound,
-            &self.pk
+            *self.balance, 
+            *self.blinding,
+            bd.z_i.compress(),
+            *bd.x_i,
+            rng
         );
 
-        return (m_i, signature);
+        return Ok((m_i, signature));
     }
 
     //verification of round 2 outputs
-    pub fn verify_all_round_2_outputs(&self, round_1_outputs: &Vec<(usize, RistrettoPoint)>, round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>) -> Result<(), String> {
-        //iterate over all other parties' round 2 results, and assert
-        for i in 0..round_1_outputs.len() {
-            if i == self.idx {
+    //on failure, identifies the parties whose messages are missing or invalid (identifiable abort)
+    pub fn verify_all_round_2_outputs<R: RngCore + CryptoRng>(&self, round_1_outputs: &Vec<(PartyId, RistrettoPoint)>, round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>)>, rng: &mut R) -> Result<(), Misbehaviour> {
+        let mut misbehaviour = Misbehaviour::default();
+
+        //signatures of all other parties are verified as one batch
+        let mut batch = Vec::new();
+
+        //round 1 outputs in ring order
+        let z_i_list = self.ring.arrange(round_1_outputs).map_err(Misbehaviour::unattributable)?;
+
+        //iterate over all other parties' round 2 results, matched to their sender by identifier, and assert
+        let mut received = vec![false; z_i_list.len()];
+        for (msg, sig_session_auth) in round_2_outputs.iter() {
+            let position = match self.ring.position(&msg.sender) {
+                Some(position) => position,
+                None => return Err(Misbehaviour::unattributable(format!("Unknown party {}", msg.sender)))
+            };
+            if received[position] {
+                misbehaviour.blame(msg.sender, format!("Duplicate message from party {}", msg.sender));
+                continue;
+            }
+            received[position] = true;
+
+            if msg.sender == self.id {
+                continue;
+            }
+            let i = self.ring.statement_idx(&msg.sender).unwrap();
+
+            //assert same session
+            if msg.vk.session_id() != self.vk.as_ref().unwrap().session_id() {
+                misbehaviour.blame(msg.sender, "Invalid session id".to_string());
                 continue;
             }
-            let (msg, sig_session_auth) = &round_2_outputs[i];
 
             //assert same VKs
             let vk_verification = msg.vk.clone().to_string() == self.vk.clone().unwrap().to_string();
             if !vk_verification {
-                return Err("Invalid VK was send".to_string());
+                misbehaviour.blame(msg.sender, "Invalid VK was send".to_string());
+                continue;
+            }
+
+            //assert session authentication, and add signature to the batch
+            let discrete_log_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(z_i_list[position].compress());
+            match sig_session_auth.clone().batch_item(i, &msg.to_string(), &discrete_log_knowledge_proof_struct, self.params.keys().pvk(i), self.images[i]) {
+                Some(item) => batch.push(item),
+                None => misbehaviour.blame(msg.sender, "Verification of session authentication failed".to_string())
             }
+        }
 
-            //assert session authentication, and signature verification
-            let discrete_log_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(round_1_outputs[i].1.compress());
-            let verification_status = &sig_session_auth.clone().verify_signature(&msg.to_string(), &discrete_log_knowledge_proof_struct, &self.pvk_list[i], &self.gm17_darkpool, self.images[i]);
-            if !verification_status {
-                return Err("Verification of signature failed".to_string())
+        //assert a message from every member of the ring
+        for (position, id) in self.ring.ids().iter().enumerate() {
+            if !received[position] {
+                misbehaviour.blame(*id, format!("Missing message from party {}", id));
             }
         }
-        return Ok(())
+
+        //assert signature verification
+        if let Err(invalid_idxs) = self.params.keys().gm17_darkpool().batch_verify(&batch, rng) {
+            for id in self.ring.ids_of_statements(&invalid_idxs) {
+                misbehaviour.blame(id, "Verification of signature failed".to_string());
+            }
+        }
+        return misbehaviour.into_result()
+    }
+
+    //records the blinding and the Burmester-Desmedt exponent of the party in the transcript of a session, once the exponent is drawn
+    pub(crate) fn record_secret_inputs(&self, transcript: &mut SessionTranscript) {
+        if let Some(bd) = self.bd.as_ref() {
+            transcript.record_secret_inputs(&self.id, field_to_string(*self.blinding), scalar_to_string(*bd.x_i));
+        }
     }
 
     //compute Burmester Desmedt key
-    pub fn compute_key(&self, round1_payload: &Vec<RistrettoPoint>, round2_payload: &Vec<RistrettoPoint>) -> RistrettoPoint {
+    pub fn compute_key(&mut self, round1_payload: &Vec<(PartyId, RistrettoPoint)>, round2_payload: &Vec<(PartyId, RistrettoPoint)>) -> Result<RistrettoPoint, String> {
+        let round1_payload = self.ring.arrange(round1_payload)?;
+        let round2_payload = self.ring.arrange(round2_payload)?;
         let num_parties = round1_payload.len();
+        let position = self.position();
 
-        let z = round1_payload[find_prev_idx(self.idx, num_parties)];
-        let exponent = self.bd.clone().unwrap().x_i * Scalar::from(num_parties as u64);
+        let z = round1_payload[find_prev_idx(position, num_parties)];
+        let mut exponent = *self.bd.as_ref().ok_or("Burmester-Desmedt exponent was erased")?.x_i * Scalar::from(num_parties as u64);
 
         let mut key = z * exponent;
         for idx in 0..num_parties - 1 {
-            let i = (self.idx + idx) % num_parties;
+            let i = (position + idx) % num_parties;
             key += round2_payload[i] * Scalar::from((num_parties - idx - 1) as u64);
         }
 
-        return key;
+        //erase the ephemeral Burmester-Desmedt exponent, such that the key cannot be recomputed from the party's state later on (forward secrecy)
+        exponent.zeroize();
+        self.bd = None;
+
+        return Ok(key);
+    }
+}
+
+impl<E: PairingEngine> RingMember for Party<E> {
+    fn id(&self) -> PartyId {
+        self.id
+    }
+
+    fn exclude(&mut self, ids: &[PartyId]) {
+        self.ring = self.ring.without(ids);
+        self.vk = None;
+        self.bd = None;
     }
 }
 
 ///
 /// Setup function, for initializing the parties with their own secret values and public commitments
 /// 
-fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64, gm17_darkpool: Gm17Darkpool, pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>>, pk_list: Vec<ProvingKey<Bls12<ark_bls12_377::Parameters>>>) -> Result<Vec<Party>, Box<dyn Error>> {
+pub fn setup_parties<E: PairingEngine, R: RngCore + CryptoRng>(rng: &mut R, party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>) -> Result<Vec<Party<E>>, Box<dyn Error>> {
+    //the balances are wiped once the parties are set up
+    let balances = Zeroizing::new(balances);
+
     //check if length of balance list, is equal to the amount of parties denoted
     if balances.len() != party_amount {
         return Err("balances not correct length".into())
     }
 
-    //check if upperbound is among the valid options 2^8, 2^16, 2^32, 2^64
-    if upperbound != u8::MAX as u64 && upperbound != u16::MAX as u64 && upperbound != u32::MAX as u64 && upperbound != u64::MAX {
-        return Err(format!("upperbound was not among the valid values: {}, {}, {}, {}\n was: {}", u8::MAX, u16::MAX, u32::MAX, u64::MAX, upperbound).into());
+    //check if there is a key pair for every party
+    if params.keys().party_amount() != party_amount {
+        return Err("amount of key pairs does not match the amount of parties".into())
     }
 
     //statements
-    let mut images: Vec<Fr> = Vec::new();
+    let mut images: Vec<E::Fr> = Vec::new();
 
     //witnesses
-    let mut blindings: Vec<Fr> = Vec::new();
+    let mut blindings: Zeroizing<Vec<E::Fr>> = Zeroizing::new(Vec::new());
 
     //populating statements and witnesses
     for i in 0..party_amount {
-        if balances[i] < min_bal {
+        if balances[i] < params.min_bal() {
             return Err("one or more balances are smaller than minimum_balance".into())
         }
-        let r = rng.gen();
+        let r = E::Fr::rand(rng);
         blindings.push(r);
 
-        let v = Fr::from(upperbound - (balances[i] - min_bal));
-        let image = mimc(v, r, &gm17_darkpool.clone().mimc_constants);
+        let v = E::Fr::from(params.upperbound() - (balances[i] - params.min_bal()));
+        let image = mimc(v, r, &params.keys().gm17_darkpool().mimc_constants);
         images.push(image);
     }
 
+    //the ring is ordered by the identifiers of the parties, rather than by the order of the balances
+    let ring = Ring::from_images(&images)?;
+
     //create parties in the protocol, with their private inputs (witnesses), as well as all public information 
-    let mut parties: Vec<Party> = Vec::new();
+    let mut parties: Vec<Party<E>> = Vec::new();
     for i in 0..party_amount {
-        parties.push(Party::new(i, gm17_darkpool.clone(), images.clone(), min_bal, balances[i], blindings[i], pk_list[i].clone(), pvk_list.clone()));
+        parties.push(Party::new(i, params.clone(), images.clone(), ring.clone(), balances[i], blindings[i]));
     }
 
     return Ok(parties)
@@ -218,46 +341,73 @@ fn setup_parties(rng: &mut OsRng, party_amount: usize, min_bal: u64, balances: V
 ///Optimized 2-round WAKE
 ///runs a setup of key-exchange with random balances above or equal to the minimum balance
 ///outputs the list of keys for all the parties (which will be identical)
-pub fn run_optimized_key_exchange(party_amount: usize, min_bal: u64, balances: Vec<u64>, upperbound: u64, gm17_darkpool: &Gm17Darkpool, pvk_list: &Vec<PreparedVerifyingKey<Bls12<Parameters>>>, pk_list: &Vec<ProvingKey<Bls12<ark_bls12_377::Parameters>>>) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
-    let rng = &mut OsRng;
-    let pedersen_gens = PedersenGens::default();
-    let g = pedersen_gens.B;
+pub fn run_optimized_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let (keys, _) = run_optimized_key_exchange_with_transcript(party_amount, balances, params, rng)?;
+    Ok(keys)
+}
 
-    let mut parties = setup_parties(rng, party_amount, min_bal, balances, upperbound, gm17_darkpool.clone(), pvk_list.clone(), pk_list.clone())?;
+///Optimized 2-round WAKE
+///outputs the list of keys for all the parties, together with the transcript of all public messages of the session
+pub fn run_optimized_key_exchange_with_transcript<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<Gm17Keys<E>>>, rng: &mut R) -> Result<(Vec<RistrettoPoint>, SessionTranscript), Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let mut transcript = SessionTranscript::new(parties.first().map(|party| party.images.iter().map(|image| field_to_string(*image)).collect()).unwrap_or_default());
 
     //round 1
-    let mut round_1_outputs: Vec<(usize, RistrettoPoint)> = Vec::new();
-    let mut round1_payload: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let r1_res = &parties[i].round_1(g, rng);
-        round_1_outputs.push(*r1_res);
-        round1_payload.push(r1_res.1);
+    let round_1_outputs: Vec<(PartyId, RistrettoPoint)> = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_1(rng));
+    for (sender, z_i) in round_1_outputs.iter() {
+        transcript.record(1, sender, ristretto_to_string(*z_i), &[]);
     }
 
     //round 2
-    let mut round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)> = Vec::new();
-    let mut round2_payload: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let (m_i, signature) = &parties[i].round_2(&round_1_outputs, upperbound);
-        round_2_outputs.push((m_i.clone(), signature.clone()));
-        round2_payload.push(m_i.payload);
+    let round_2_outputs = map_parties_with_rng(&mut parties, rng, |party, rng| party.round_2(&round_1_outputs, rng)).into_iter().collect::<Result<Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>)>, String>>()?;
+    let mut round2_payload: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for (m_i, signature) in round_2_outputs.iter() {
+        round2_payload.push((m_i.sender, m_i.payload));
+        transcript.record(2, &m_i.sender, m_i.to_string(), &signature.to_bytes()?);
     }
 
     //verification
-    for i in 0..party_amount {
-        let round2_res = &parties[i].verify_all_round_2_outputs(&round_1_outputs, round_2_outputs.clone());
+    for round2_res in map_parties_with_rng(&mut parties, rng, |party, rng| party.verify_all_round_2_outputs(&round_1_outputs, round_2_outputs.clone(), rng)) {
         if let Err(round2_err) = round2_res {
-            return Err(round2_err.clone().into());
+            return Err(round2_err.into());
         }
     }
 
+    //secret inputs, recorded before the Burmester-Desmedt exponents are erased
+    for party in parties.iter() {
+        party.record_secret_inputs(&mut transcript);
+    }
+
     //compute key
-    let mut keys: Vec<RistrettoPoint> = Vec::new();
-    for i in 0..party_amount {
-        let key = &parties[i].compute_key(&round1_payload, &round2_payload);
-        keys.push(*key);
+    let keys = map_parties_mut(&mut parties, |party| party.compute_key(&round_1_outputs, &round2_payload)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>()?;
+
+    return Ok((keys, transcript));
+
+}
+
+///Runs a single session of optimized WAKE between the given parties
+///outputs the keys of the parties in order, or the misbehaviour identified by every party
+fn run_identifiable_session<E: PairingEngine, R: RngCore + CryptoRng>(parties: &mut [Party<E>], rng: &mut R) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>> {
+    //round 1
+    let round_1_outputs: Vec<(PartyId, RistrettoPoint)> = map_parties_with_rng(parties, rng, |party, rng| party.round_1(rng));
+
+    //round 2
+    let round_2_outputs = map_parties_with_rng(parties, rng, |party, rng| party.round_2(&round_1_outputs, rng)).into_iter().collect::<Result<Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>)>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    let round2_payload: Vec<(PartyId, RistrettoPoint)> = round_2_outputs.iter().map(|(m_i, _)| (m_i.sender, m_i.payload)).collect();
+
+    //verification
+    let accusations: Vec<Misbehaviour> = map_parties_with_rng(parties, rng, |party, rng| party.verify_all_round_2_outputs(&round_1_outputs, round_2_outputs.clone(), rng)).into_iter().filter_map(Result::err).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
     }
 
-    return Ok(keys);
+    //compute key
+    map_parties_mut(parties, |party| party.compute_key(&round_1_outputs, &round2_payload)).into_iter().collect::<Result<Vec<RistrettoPoint>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])
+}
 
+///Robust optimized 2-round WAKE
+///parties identified as misbehaving are excluded, and the key-exchange is restarted over the remaining parties, within the bounds of config
+///outputs the keys and the final member set
+pub fn run_robust_optimized_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(parties: Vec<Party<E>>, config: &RobustConfig, rng: &mut R) -> Result<RobustOutcome, Box<dyn Error>> {
+    run_robust_key_exchange(parties, config, |parties| run_identifiable_session(parties, rng))
 }
\ No newline at end of file
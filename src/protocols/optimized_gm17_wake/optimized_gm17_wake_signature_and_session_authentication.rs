@@ -1,35 +1,38 @@
 use super::optimized_gm17_wake_protocol::VK;
 use crate::{
+    protocols::party_id::PartyId,
+    params::{WakeParams, Gm17Keys},
     utility::ristretto_to_string,
     proof_systems::{
-        proof_system_gm17::Gm17Darkpool,
+        proof_system_gm17::Gm17BatchItem,
         discrete_log_knowledege_proof::{DiscreteLogKnowledgeProofStruct, DiscreteLogKnowledgeProof}
     }
 };
-use ark_bls12_377::{Parameters, Fr};
-use ark_ec::bls12::Bls12;
-use ark_gm17::{Proof, PreparedVerifyingKey, ProvingKey};
+use ark_ec::PairingEngine;
+use ark_gm17::{Proof, PreparedVerifyingKey};
+use ark_serialize::{CanonicalSerialize, SerializationError};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint, CompressedRistretto}};
+use rand::{CryptoRng, RngCore};
 
 
 ///
 /// Corresponds to an optimized WAKE message
-/// holds party-index, round-index, the actual contents of the message, and the nonces
+/// holds the identifier of the sending party, round-index, the actual contents of the message, and the nonces
 /// 
 #[derive(Clone)]
 pub struct OptimizedMessage {
-    pub idx: usize,
+    pub sender: PartyId,
     pub payload: RistrettoPoint,
     pub vk: VK
 }
 
 impl OptimizedMessage {
-    pub fn new(idx: usize, message: RistrettoPoint, vk: VK) -> OptimizedMessage {
-        return OptimizedMessage { idx, payload: message, vk }
+    pub fn new(sender: PartyId, message: RistrettoPoint, vk: VK) -> OptimizedMessage {
+        return OptimizedMessage { sender, payload: message, vk }
     }
 
     pub fn to_string(&self) -> String {
-        return self.idx.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.vk.clone().to_string();
+        return self.sender.to_string() + "##" + &ristretto_to_string(self.payload) + "##" +  &self.vk.clone().to_string();
     }
 }
 
@@ -38,22 +41,22 @@ impl OptimizedMessage {
 /// holds a GM17 proof for the darkpool transaction relation, and a discrete logarithm knowledge proof
 /// 
 #[derive(Clone)]
-pub struct OptimizedSignatureAndSessionAuthentication { 
+pub struct OptimizedSignatureAndSessionAuthentication<E: PairingEngine> { 
     pub discrete_log_knowledge_proof: DiscreteLogKnowledgeProof,
-    pub gm17_darkpool_proof: Proof<Bls12<Parameters>>,
+    pub gm17_darkpool_proof: Proof<E>,
 }
 
-impl OptimizedSignatureAndSessionAuthentication {
+impl<E: PairingEngine> OptimizedSignatureAndSessionAuthentication<E> {
     ///signing function
-    ///outputs a signature and the new commitment from the range proof
-    pub fn sign(gm17_darkpool: &Gm17Darkpool, image: Fr, message_string: &String, min_bal: u64, balance: u64, blinding_factor: Fr, discrete_log_proof_statement: CompressedRistretto, discrete_log_proof_witness: Scalar, upperbound: u64, pk: &ProvingKey<Bls12<Parameters>>) -> OptimizedSignatureAndSessionAuthentication {
+    ///outputs a signature and the new commitment from the range proof, under the proving key of party idx
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<Gm17Keys<E>>, idx: usize, image: E::Fr, message_string: &String, balance: u64, blinding_factor: E::Fr, discrete_log_proof_statement: CompressedRistretto, discrete_log_proof_witness: Scalar, rng: &mut R) -> OptimizedSignatureAndSessionAuthentication<E> {
         //discrete log knowledge proof
         let discrete_log_knowledege_proof_struct = DiscreteLogKnowledgeProofStruct::new(discrete_log_proof_statement);
-        let discrete_log_knowledge_proof = discrete_log_knowledege_proof_struct.prove(discrete_log_proof_witness, message_string.clone() + (&image.to_string()));
+        let discrete_log_knowledge_proof = discrete_log_knowledege_proof_struct.prove(discrete_log_proof_witness, message_string.clone() + (&image.to_string()), rng);
 
         //parse setup keys
-        let v = Fr::from(upperbound - (balance - min_bal));
-        let gm17_darkpool_proof = gm17_darkpool.clone().prove(v, blinding_factor, message_string.clone() + &discrete_log_knowledge_proof.to_string(), pk.clone());
+        let v = E::Fr::from(params.upperbound() - (balance - params.min_bal()));
+        let gm17_darkpool_proof = params.keys().gm17_darkpool().prove(v, blinding_factor, message_string.clone() + &discrete_log_knowledge_proof.to_string(), params.keys().pk(idx), rng);
 
         //combine results
         let signature = OptimizedSignatureAndSessionAuthentication { discrete_log_knowledge_proof, gm17_darkpool_proof};
@@ -62,12 +65,30 @@ impl OptimizedSignatureAndSessionAuthentication {
     }
 
     ///verification function
-    ///outputs a boolean indicating the verification status
-    pub fn verify_signature(self, message_string: &String, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, pvk: &PreparedVerifyingKey<Bls12<Parameters>>, gm17_darkpool: &Gm17Darkpool, image: Fr) -> bool {
+    ///outputs a boolean indicating the verification status, under the verifying key of party idx
+    pub fn verify_signature(self, params: &WakeParams<Gm17Keys<E>>, idx: usize, message_string: &String, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, image: E::Fr) -> bool {
         let discrete_log_knowledege_proof_verification = discrete_log_knowledge_proof_struct.verify(self.discrete_log_knowledge_proof.clone(), message_string.clone() + &image.to_string());
 
-        let gm17_darkpool_verification = gm17_darkpool.clone().verify(image, message_string.clone() + &self.discrete_log_knowledge_proof.to_string(), pvk.clone(), self.gm17_darkpool_proof);
+        let gm17_darkpool_verification = params.keys().gm17_darkpool().verify(image, message_string.clone() + &self.discrete_log_knowledge_proof.to_string(), params.keys().pvk(idx), &self.gm17_darkpool_proof);
         
         return discrete_log_knowledege_proof_verification && gm17_darkpool_verification;
     }
+
+    ///verifies the session authentication, and outputs the proof of the signature as an item of a batch, to be verified together with those of the other parties
+    ///outputs None if the session authentication fails
+    pub fn batch_item<'a>(self, idx: usize, message_string: &str, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, pvk: &'a PreparedVerifyingKey<E>, image: E::Fr) -> Option<Gm17BatchItem<'a, E>> {
+        if !discrete_log_knowledge_proof_struct.verify(self.discrete_log_knowledge_proof, message_string.to_string() + &image.to_string()) {
+            return None;
+        }
+
+        let aux = message_string.to_string() + &self.discrete_log_knowledge_proof.to_string();
+        Some(Gm17BatchItem { idx, image, aux, pvk, proof: self.gm17_darkpool_proof })
+    }
+
+    ///serializes the signature, as the session authentication followed by the compressed GM17 proof
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = self.discrete_log_knowledge_proof.to_bytes();
+        self.gm17_darkpool_proof.serialize(&mut bytes)?;
+        Ok(bytes)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod optimized_marlin_wake_protocol;
+pub mod optimized_marlin_wake_signature_and_session_authentication;
\ No newline at end of file
@@ -0,0 +1,405 @@
+use super::optimized_marlin_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
+use crate::{
+    params::{WakeParams, MarlinKeys},
+    protocols::session_transcript::SessionTranscript,
+    protocols::party_id::{PartyId, Ring},
+    protocols::robust::{Misbehaviour, RingMember, RobustConfig, RobustOutcome, run_robust_key_exchange},
+    protocols::session_id::{SessionContext, SessionId, SharedReplayCache},
+    utility::{get_adjacent_elements, find_prev_idx, ristretto_to_string, scalar_to_string, field_to_string, mimc},
+    proof_systems::discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct
+};
+use std::{error::Error, fmt, sync::Arc};
+use rand::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+use ark_ec::PairingEngine;
+use ark_ff::UniformRand;
+// 
+// File for running Burmester Desmedt key-exchange, with optimized WAKE scheme, using Marlin SE zk-SNARK with a universal setup as underlying proof, for the Darkpool transaction relation
+// 
+
+///
+/// Struct responsible for holding VK from the WAKE protocol
+/// together with the id of the session, derived from the VK and the context of the session
+/// 
+#[derive(Clone)]
+pub struct VK {
+    ids: Vec<PartyId>,
+    z_is: Vec<RistrettoPoint>,
+    session_id: SessionId
+}
+
+impl VK {
+    //VK of a session run in the default context
+    pub fn new(ids: Vec<PartyId>, z_is: Vec<RistrettoPoint>) -> VK {
+        VK::with_context(ids, z_is, &SessionContext::default())
+    }
+
+    pub fn with_context(ids: Vec<PartyId>, z_is: Vec<RistrettoPoint>, context: &SessionContext) -> VK {
+        let session_id = SessionId::derive(context, &ids, &z_is.iter().map(|z_i| z_i.compress().to_bytes()).collect::<Vec<_>>());
+        VK { ids, z_is, session_id }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    //round 1 contributions of the parties
+    pub fn contributions(&self) -> Vec<[u8; 32]> {
+        self.z_is.iter().map(|z_i| z_i.compress().to_bytes()).collect()
+    }
+}
+
+impl fmt::Display for VK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.session_id)?;
+
+        for (id, z_i) in self.ids.iter().zip(self.z_is.iter()) {
+            write!(f, "{}{}", id, ristretto_to_string(*z_i))?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// Struct responsible for holding Burmester Desmedt values
+/// the exponent x_i is secret, and wiped when dropped
+/// 
+pub struct BurmesterDesmedt {
+    z_i: RistrettoPoint,
+    x_i: Zeroizing<Scalar>,
+}
+
+///
+/// Struct representing a single party
+/// idx is the index of the party's own image, from which its identifier is derived
+/// 
+pub struct Party<E: PairingEngine> {
+    //public values
+    id: PartyId,
+    ring: Ring,
+    params: Arc<WakeParams<MarlinKeys<E>>>,
+    images: Vec<E::Fr>,
+    //secret values
+    balance: Zeroizing<u64>,
+    blinding: Zeroizing<E::Fr>,
+    //context of the session, and the cache of earlier sessions
+    context: SessionContext,
+    replay_cache: Option<SharedReplayCache>,
+    //optional values getting initialized during the protocol
+    vk: Option<VK>,
+    bd: Option<BurmesterDesmedt>
+}
+
+impl<E: PairingEngine> Party<E> {
+    pub fn new(idx: usize, params: Arc<WakeParams<MarlinKeys<E>>>, images: Vec<E::Fr>, ring: Ring, balance: u64, blinding: E::Fr) -> Party<E> {
+        Party{ 
+            id: PartyId::from_image(&images[idx]),
+            ring,
+            params, 
+            images, 
+            balance: Zeroizing::new(balance), 
+            blinding: Zeroizing::new(blinding), 
+            context: SessionContext::default(),
+            replay_cache: None,
+            vk: None, 
+            bd: None 
+        }
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    //position of the party in the ring
+    fn position(&self) -> usize {
+        self.ring.position(&self.id).unwrap()
+    }
+
+    //sets the context the session is run in, from which the session id is derived together with the round 1 outputs
+    pub fn set_context(&mut self, context: SessionContext) {
+        self.context = context;
+    }
+
+    //sets the cache of earlier sessions, rejecting sessions that reuse their round 1 outputs
+    pub fn set_replay_cache(&mut self, replay_cache: SharedReplayCache) {
+        self.replay_cache = Some(replay_cache);
+    }
+
+    //id of the session, once the round 1 outputs are received
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.vk.as_ref().map(|vk| vk.session_id())
+    }
+
+    //round 1 wake, round 1 Burmester-Desmedt
+    pub fn round_1<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> (PartyId, RistrettoPoint) {
+        let x_i = Zeroizing::new(Scalar::random(rng));
+
+        let z_i = self.params.g() * *x_i;
+
+        self.bd = Some(BurmesterDesmedt { x_i, z_i });
+
+        (self.id, z_i)
+    }
+
+    //round 2, round 2 Burmester-Desmedt
+    pub fn round_2<R: RngCore + CryptoRng>(&mut self, round_1_outputs: &[(PartyId, RistrettoPoint)], rng: &mut R) -> Result<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>), String> {
+        //receiving stage, the VK is ordered by the ring, such that all parties agree on it regardless of the order the messages are received in
+        let z_i_list = self.ring.arrange(round_1_outputs)?;
+
+        let vk = VK::with_context(self.ring.ids().to_vec(), z_i_list.clone(), &self.context);
+
+        //reject sessions reusing the round 1 outputs of an earlier session
+        if let Some(replay_cache) = self.replay_cache.as_ref() {
+            replay_cache.lock().unwrap().admit(&vk.session_id(), &vk.contributions())?;
+        }
+        self.vk = Some(vk);
+
+        //creating & sending stage
+        let position = self.position();
+        let (z_1, z_2) = get_adjacent_elements(&z_i_list, &position);
+        let bd = self.bd.as_ref().ok_or("Burmester-Desmedt values were not computed")?;
+
+        //compute message
+        let payload = (z_2 - z_1) * *bd.x_i;
+        let m_i = OptimizedMessage::new(self.id, payload, self.vk.clone().unwrap());
+
+        //sign message
+        let signature = OptimizedSignatureAndSessionAuthentication::sign(
+            &self.params,
+            &m_i.to_string(), 
+            *self.balance, 
+            *self.blinding,
+            bd.z_i.compress(),
+            *bd.x_i,
+            rng
+        );
+
+        Ok((m_i, signature))
+    }
+
+    //verification of round 2 outputs
+    //on failure, identifies the parties whose messages are missing or invalid (identifiable abort)
+    pub fn verify_all_round_2_outputs<R: RngCore + CryptoRng>(&self, round_1_outputs: &[(PartyId, RistrettoPoint)], round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>)>, rng: &mut R) -> Result<(), Misbehaviour> {
+        let mut misbehaviour = Misbehaviour::default();
+
+        //round 1 outputs in ring order
+        let z_i_list = self.ring.arrange(round_1_outputs).map_err(Misbehaviour::unattributable)?;
+
+        //iterate over all other parties' round 2 results, matched to their sender by identifier, and assert
+        let mut received = vec![false; z_i_list.len()];
+        for (msg, sig_session_auth) in round_2_outputs.iter() {
+            let position = match self.ring.position(&msg.sender) {
+                Some(position) => position,
+                None => return Err(Misbehaviour::unattributable(format!("Unknown party {}", msg.sender)))
+            };
+            if received[position] {
+                misbehaviour.blame(msg.sender, format!("Duplicate message from party {}", msg.sender));
+                continue;
+            }
+            received[position] = true;
+
+            if msg.sender == self.id {
+                continue;
+            }
+            let i = self.ring.statement_idx(&msg.sender).unwrap();
+
+            //assert same session
+            if msg.vk.session_id() != self.vk.as_ref().unwrap().session_id() {
+                misbehaviour.blame(msg.sender, "Invalid session id".to_string());
+                continue;
+            }
+
+            //assert same VKs
+            let vk_verification = msg.vk.clone().to_string() == self.vk.clone().unwrap().to_string();
+            if !vk_verification {
+                misbehaviour.blame(msg.sender, "Invalid VK was send".to_string());
+                continue;
+            }
+
+            //assert session authentication, and signature verification
+            let discrete_log_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(z_i_list[position].compress());
+            let verification_status = &sig_session_auth.clone().verify_signature(&self.params, &msg.to_string(), &discrete_log_knowledge_proof_struct, self.images[i], rng);
+            if !verification_status {
+                misbehaviour.blame(msg.sender, "Verification of signature failed".to_string());
+            }
+        }
+
+        //assert a message from every member of the ring
+        for (position, id) in self.ring.ids().iter().enumerate() {
+            if !received[position] {
+                misbehaviour.blame(*id, format!("Missing message from party {}", id));
+            }
+        }
+        misbehaviour.into_result()
+    }
+
+    //records the blinding and the Burmester-Desmedt exponent of the party in the transcript of a session, once the exponent is drawn
+    pub(crate) fn record_secret_inputs(&self, transcript: &mut SessionTranscript) {
+        if let Some(bd) = self.bd.as_ref() {
+            transcript.record_secret_inputs(&self.id, field_to_string(*self.blinding), scalar_to_string(*bd.x_i));
+        }
+    }
+
+    //compute Burmester Desmedt key
+    pub fn compute_key(&mut self, round1_payload: &[(PartyId, RistrettoPoint)], round2_payload: &[(PartyId, RistrettoPoint)]) -> Result<RistrettoPoint, String> {
+        let round1_payload = self.ring.arrange(round1_payload)?;
+        let round2_payload = self.ring.arrange(round2_payload)?;
+        let num_parties = round1_payload.len();
+        let position = self.position();
+
+        let z = round1_payload[find_prev_idx(position, num_parties)];
+        let mut exponent = *self.bd.as_ref().ok_or("Burmester-Desmedt exponent was erased")?.x_i * Scalar::from(num_parties as u64);
+
+        let mut key = z * exponent;
+        for idx in 0..num_parties - 1 {
+            let i = (position + idx) % num_parties;
+            key += round2_payload[i] * Scalar::from((num_parties - idx - 1) as u64);
+        }
+
+        //erase the ephemeral Burmester-Desmedt exponent, such that the key cannot be recomputed from the party's state later on (forward secrecy)
+        exponent.zeroize();
+        self.bd = None;
+
+        Ok(key)
+    }
+}
+
+impl<E: PairingEngine> RingMember for Party<E> {
+    fn id(&self) -> PartyId {
+        self.id
+    }
+
+    fn exclude(&mut self, ids: &[PartyId]) {
+        self.ring = self.ring.without(ids);
+        self.vk = None;
+        self.bd = None;
+    }
+}
+
+///
+/// Setup function, for initializing the parties with their own secret values and public commitments
+/// 
+pub fn setup_parties<E: PairingEngine, R: RngCore + CryptoRng>(rng: &mut R, party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<MarlinKeys<E>>>) -> Result<Vec<Party<E>>, Box<dyn Error>> {
+    //the balances are wiped once the parties are set up
+    let balances = Zeroizing::new(balances);
+
+    //check if length of balance list, is equal to the amount of parties denoted
+    if balances.len() != party_amount {
+        return Err("balances not correct length".into())
+    }
+
+    //statements
+    let mut images: Vec<E::Fr> = Vec::new();
+
+    //witnesses
+    let mut blindings: Zeroizing<Vec<E::Fr>> = Zeroizing::new(Vec::new());
+
+    //populating statements and witnesses
+    for i in 0..party_amount {
+        if balances[i] < params.min_bal() {
+            return Err("one or more balances are smaller than minimum_balance".into())
+        }
+        let r = E::Fr::rand(rng);
+        blindings.push(r);
+
+        let v = E::Fr::from(params.upperbound() - (balances[i] - params.min_bal()));
+        let image = mimc(v, r, &params.keys().marlin_darkpool().mimc_constants);
+        images.push(image);
+    }
+
+    //the ring is ordered by the identifiers of the parties, rather than by the order of the balances
+    let ring = Ring::from_images(&images)?;
+
+    //create parties in the protocol, with their private inputs (witnesses), as well as all public information 
+    let mut parties: Vec<Party<E>> = Vec::new();
+    for i in 0..party_amount {
+        parties.push(Party::new(i, params.clone(), images.clone(), ring.clone(), balances[i], blindings[i]));
+    }
+
+    Ok(parties)
+}
+
+///Optimized 2-round WAKE
+///runs a setup of key-exchange with random balances above or equal to the minimum balance
+///outputs the list of keys for all the parties (which will be identical)
+pub fn run_optimized_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<MarlinKeys<E>>>, rng: &mut R) -> Result<Vec<RistrettoPoint>, Box<dyn Error>> {
+    let (keys, _) = run_optimized_key_exchange_with_transcript(party_amount, balances, params, rng)?;
+    Ok(keys)
+}
+
+///Optimized 2-round WAKE
+///outputs the list of keys for all the parties, together with the transcript of all public messages of the session
+pub fn run_optimized_key_exchange_with_transcript<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, balances: Vec<u64>, params: &Arc<WakeParams<MarlinKeys<E>>>, rng: &mut R) -> Result<(Vec<RistrettoPoint>, SessionTranscript), Box<dyn Error>> {
+    let mut parties = setup_parties(rng, party_amount, balances, params)?;
+    let mut transcript = SessionTranscript::new(parties.first().map(|party| party.images.iter().map(|image| field_to_string(*image)).collect()).unwrap_or_default());
+
+    //round 1
+    let mut round_1_outputs: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for party in parties.iter_mut() {
+        let r1_res = &party.round_1(rng);
+        round_1_outputs.push(*r1_res);
+        transcript.record(1, &r1_res.0, ristretto_to_string(r1_res.1), &[]);
+    }
+
+    //round 2
+    let mut round_2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>)> = Vec::new();
+    let mut round2_payload: Vec<(PartyId, RistrettoPoint)> = Vec::new();
+    for party in parties.iter_mut() {
+        let (m_i, signature) = &party.round_2(&round_1_outputs, rng)?;
+        round_2_outputs.push((m_i.clone(), signature.clone()));
+        round2_payload.push((m_i.sender, m_i.payload));
+        transcript.record(2, &m_i.sender, m_i.to_string(), &signature.to_bytes()?);
+    }
+
+    //verification
+    for party in parties.iter() {
+        let round2_res = &party.verify_all_round_2_outputs(&round_1_outputs, round_2_outputs.clone(), rng);
+        if let Err(round2_err) = round2_res {
+            return Err(round2_err.clone().into());
+        }
+    }
+
+    //secret inputs, recorded before the Burmester-Desmedt exponents are erased
+    for party in parties.iter() {
+        party.record_secret_inputs(&mut transcript);
+    }
+
+    //compute key
+    let mut keys: Vec<RistrettoPoint> = Vec::new();
+    for party in parties.iter_mut() {
+        let key = &party.compute_key(&round_1_outputs, &round2_payload)?;
+        keys.push(*key);
+    }
+
+    Ok((keys, transcript))
+
+}
+
+///Runs a single session of optimized WAKE between the given parties
+///outputs the keys of the parties in order, or the misbehaviour identified by every party
+fn run_identifiable_session<E: PairingEngine, R: RngCore + CryptoRng>(parties: &mut [Party<E>], rng: &mut R) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>> {
+    //round 1
+    let round_1_outputs: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| party.round_1(rng)).collect();
+
+    //round 2
+    let round_2_outputs = parties.iter_mut().map(|party| party.round_2(&round_1_outputs, rng)).collect::<Result<Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication<E>)>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])?;
+    let round2_payload: Vec<(PartyId, RistrettoPoint)> = round_2_outputs.iter().map(|(m_i, _)| (m_i.sender, m_i.payload)).collect();
+
+    //verification
+    let accusations: Vec<Misbehaviour> = parties.iter().filter_map(|party| party.verify_all_round_2_outputs(&round_1_outputs, round_2_outputs.clone(), rng).err()).collect();
+    if !accusations.is_empty() {
+        return Err(accusations)
+    }
+
+    //compute key
+    parties.iter_mut().map(|party| party.compute_key(&round_1_outputs, &round2_payload)).collect::<Result<Vec<RistrettoPoint>, String>>().map_err(|err| vec![Misbehaviour::unattributable(err)])
+}
+
+///Robust optimized 2-round WAKE
+///parties identified as misbehaving are excluded, and the key-exchange is restarted over the remaining parties, within the bounds of config
+///outputs the keys and the final member set
+pub fn run_robust_optimized_key_exchange<E: PairingEngine, R: RngCore + CryptoRng>(parties: Vec<Party<E>>, config: &RobustConfig, rng: &mut R) -> Result<RobustOutcome, Box<dyn Error>> {
+    run_robust_key_exchange(parties, config, |parties| run_identifiable_session(parties, rng))
+}
\ No newline at end of file
@@ -0,0 +1,86 @@
+use super::optimized_marlin_wake_protocol::VK;
+use crate::{
+    protocols::party_id::PartyId,
+    params::{WakeParams, MarlinKeys},
+    utility::{ristretto_to_string, mimc},
+    proof_systems::{
+        proof_system_marlin::MarlinProof,
+        discrete_log_knowledege_proof::{DiscreteLogKnowledgeProofStruct, DiscreteLogKnowledgeProof}
+    }
+};
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalSerialize, SerializationError};
+use std::{fmt, sync::Arc};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint, CompressedRistretto}};
+use rand::{CryptoRng, RngCore};
+
+
+///
+/// Corresponds to an optimized WAKE message
+/// holds the identifier of the sending party, round-index, the actual contents of the message, and the nonces
+/// 
+#[derive(Clone)]
+pub struct OptimizedMessage {
+    pub sender: PartyId,
+    pub payload: RistrettoPoint,
+    pub vk: VK
+}
+
+impl OptimizedMessage {
+    pub fn new(sender: PartyId, message: RistrettoPoint, vk: VK) -> OptimizedMessage {
+        OptimizedMessage { sender, payload: message, vk }
+    }
+}
+
+impl fmt::Display for OptimizedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}##{}##{}", self.sender, ristretto_to_string(self.payload), self.vk)
+    }
+}
+
+///
+/// Corresponds to an optimized WAKE signature and session authentication proof
+/// holds a Marlin proof for the darkpool transaction relation, and a discrete logarithm knowledge proof
+/// 
+#[derive(Clone)]
+pub struct OptimizedSignatureAndSessionAuthentication<E: PairingEngine> { 
+    pub discrete_log_knowledge_proof: DiscreteLogKnowledgeProof,
+    pub marlin_darkpool_proof: Arc<MarlinProof<E>>,
+}
+
+impl<E: PairingEngine> OptimizedSignatureAndSessionAuthentication<E> {
+    ///signing function
+    ///outputs a signature and the new commitment from the range proof
+    ///the image of the signing party is recomputed from its balance and blinding
+    pub fn sign<R: RngCore + CryptoRng>(params: &WakeParams<MarlinKeys<E>>, message_string: &str, balance: u64, blinding_factor: E::Fr, discrete_log_proof_statement: CompressedRistretto, discrete_log_proof_witness: Scalar, rng: &mut R) -> OptimizedSignatureAndSessionAuthentication<E> {
+        //parse setup keys
+        let v = E::Fr::from(params.upperbound() - (balance - params.min_bal()));
+        let image = mimc(v, blinding_factor, &params.keys().marlin_darkpool().mimc_constants);
+
+        //discrete log knowledge proof
+        let discrete_log_knowledege_proof_struct = DiscreteLogKnowledgeProofStruct::new(discrete_log_proof_statement);
+        let discrete_log_knowledge_proof = discrete_log_knowledege_proof_struct.prove(discrete_log_proof_witness, message_string.to_string() + (&image.to_string()), rng);
+
+        let marlin_darkpool_proof = Arc::new(params.keys().marlin_darkpool().prove(v, blinding_factor, message_string.to_string() + &discrete_log_knowledge_proof.to_string(), params.keys().pk(), rng).expect("marlin proving failed"));
+
+        //combine results
+        OptimizedSignatureAndSessionAuthentication { discrete_log_knowledge_proof, marlin_darkpool_proof}
+    }
+
+    ///verification function
+    ///outputs a boolean indicating the verification status
+    pub fn verify_signature<R: RngCore + CryptoRng>(self, params: &WakeParams<MarlinKeys<E>>, message_string: &str, discrete_log_knowledge_proof_struct: &DiscreteLogKnowledgeProofStruct, image: E::Fr, rng: &mut R) -> bool {
+        let discrete_log_knowledege_proof_verification = discrete_log_knowledge_proof_struct.verify(self.discrete_log_knowledge_proof, message_string.to_string() + &image.to_string());
+
+        let marlin_darkpool_verification = params.keys().marlin_darkpool().verify(image, message_string.to_string() + &self.discrete_log_knowledge_proof.to_string(), params.keys().vk(), &self.marlin_darkpool_proof, rng);
+        
+        discrete_log_knowledege_proof_verification && marlin_darkpool_verification
+    }
+
+    ///serializes the signature, as the session authentication followed by the compressed Marlin proof
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = self.discrete_log_knowledge_proof.to_bytes();
+        self.marlin_darkpool_proof.serialize(&mut bytes)?;
+        Ok(bytes)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,160 @@
+use ark_ff::{BigInteger, PrimeField};
+use curve25519_dalek_ng::ristretto::RistrettoPoint;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+///
+/// File for identifying the parties of a WAKE session by their public statement, rather than by their position in the input,
+/// and for deriving the Burmester-Desmedt ring canonically from these identifiers
+///
+//domain separation tag for fingerprinting the statement of a party
+pub const PARTY_ID_DOMAIN: &[u8] = b"WAKE-PARTY-ID-V1";
+
+///
+/// Identifier of a party: the SHA-256 fingerprint of its public statement (its commitment or image)
+/// identifiers are ordered by their bytes, which fixes the ring order of a session
+///
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartyId([u8; 32]);
+
+impl PartyId {
+    pub fn from_statement(statement: &[u8]) -> PartyId {
+        let mut hasher = Sha256::new();
+        hasher.update(PARTY_ID_DOMAIN);
+        hasher.update(statement);
+        PartyId(hasher.finalize().into())
+    }
+
+    //identifier of a party with a Pedersen commitment as statement
+    pub fn from_commitment(commitment: &RistrettoPoint) -> PartyId {
+        PartyId::from_statement(commitment.compress().as_bytes())
+    }
+
+    //identifier of a party with a hash image as statement
+    pub fn from_image<F: PrimeField>(image: &F) -> PartyId {
+        PartyId::from_statement(&image.into_repr().to_bytes_le())
+    }
+
+    //identifier from its serialized bytes
+    pub fn from_bytes(bytes: [u8; 32]) -> PartyId {
+        PartyId(bytes)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl fmt::Display for PartyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.encode_hex::<String>())
+    }
+}
+
+impl fmt::Debug for PartyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PartyId({})", self)
+    }
+}
+
+///
+/// Burmester-Desmedt ring of a session: the parties sorted by their identifier
+/// every party derives the same ring from the public statements, regardless of the order in which the statements are given
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ring {
+    //identifiers in ring order
+    ids: Vec<PartyId>,
+    //for every position in the ring, the index of the party's statement
+    statement_idxs: Vec<usize>,
+}
+
+impl Ring {
+    //derives the ring from the identifiers of the statements, where ids[i] identifies statement i
+    pub fn new(ids: Vec<PartyId>) -> Result<Ring, String> {
+        let mut statement_idxs: Vec<usize> = (0..ids.len()).collect();
+        statement_idxs.sort_by_key(|&i| ids[i]);
+
+        //parties with the same statement cannot be told apart
+        if statement_idxs.windows(2).any(|pair| ids[pair[0]] == ids[pair[1]]) {
+            return Err("Duplicate party identifiers".to_string())
+        }
+
+        let sorted_ids = statement_idxs.iter().map(|&i| ids[i]).collect();
+        Ok(Ring { ids: sorted_ids, statement_idxs })
+    }
+
+    pub fn from_commitments(commitments: &[RistrettoPoint]) -> Result<Ring, String> {
+        Ring::new(commitments.iter().map(PartyId::from_commitment).collect())
+    }
+
+    pub fn from_images<F: PrimeField>(images: &[F]) -> Result<Ring, String> {
+        Ring::new(images.iter().map(PartyId::from_image).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn ids(&self) -> &[PartyId] {
+        &self.ids
+    }
+
+    //position of the party in the ring
+    pub fn position(&self, id: &PartyId) -> Option<usize> {
+        self.ids.binary_search(id).ok()
+    }
+
+    //index of the statement of the party
+    pub fn statement_idx(&self, id: &PartyId) -> Option<usize> {
+        self.position(id).map(|position| self.statement_idxs[position])
+    }
+
+    //identifier of the party holding statement idx
+    pub fn id_of_statement(&self, idx: usize) -> Option<PartyId> {
+        self.statement_idxs.iter().position(|&i| i == idx).map(|position| self.ids[position])
+    }
+
+    //identifiers of the parties holding the statements idxs, such as the parties of failing proofs in a batch
+    pub fn ids_of_statements(&self, idxs: &[usize]) -> Vec<PartyId> {
+        idxs.iter().filter_map(|&idx| self.id_of_statement(idx)).collect()
+    }
+
+    //the ring over the remaining parties, once the given parties are excluded
+    pub fn without(&self, excluded: &[PartyId]) -> Ring {
+        let (ids, statement_idxs) = self.ids.iter().zip(self.statement_idxs.iter())
+            .filter(|(id, _)| !excluded.contains(id))
+            .map(|(id, idx)| (*id, *idx))
+            .unzip();
+        Ring { ids, statement_idxs }
+    }
+
+    //orders the statements, given in statement order, by ring position
+    pub fn arrange_statements<T: Clone>(&self, statements: &[T]) -> Vec<T> {
+        self.statement_idxs.iter().map(|&i| statements[i].clone()).collect()
+    }
+
+    //orders values received from the parties by the ring position of their sender
+    //every member of the ring must have sent exactly one value
+    pub fn arrange<T: Clone>(&self, received: &[(PartyId, T)]) -> Result<Vec<T>, String> {
+        if received.len() != self.len() {
+            return Err(format!("Invalid length expected: {}, found {}", self.len(), received.len()))
+        }
+
+        let mut arranged: Vec<Option<T>> = vec![None; self.len()];
+        for (sender, value) in received {
+            let position = self.position(sender).ok_or(format!("Unknown party {}", sender))?;
+            if arranged[position].is_some() {
+                return Err(format!("Duplicate message from party {}", sender))
+            }
+            arranged[position] = Some(value.clone());
+        }
+
+        Ok(arranged.into_iter().flatten().collect())
+    }
+}
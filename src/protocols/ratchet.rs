@@ -0,0 +1,75 @@
+use curve25519_dalek_ng::ristretto::RistrettoPoint;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+///
+/// File for rotating the key of an established WAKE session without re-running the key-exchange. A symmetric ratchet derives
+/// a chain key from the session key, and from the chain key of every epoch the key of that epoch and the chain key of the next epoch.
+/// Advancing the ratchet wipes the chain key and epoch key of the previous epoch, such that compromising the ratchet does not reveal
+/// the keys of earlier epochs (forward secrecy across epochs)
+///
+//domain separation tag of the ratchet, and labels of the derived keys
+pub const RATCHET_DOMAIN: &[u8] = b"WAKE-RATCHET-V1";
+const CHAIN_LABEL: &[u8] = b"chain key";
+const EPOCH_LABEL: &[u8] = b"epoch key";
+
+///
+/// Key of an epoch, wiped when dropped
+///
+#[derive(Clone)]
+pub struct EpochKey {
+    epoch: u64,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl EpochKey {
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.key
+    }
+}
+
+///
+/// Symmetric ratchet of the keys of an established session
+/// the chain key of the current epoch is wiped when the ratchet advances or is dropped
+///
+pub struct KeyRatchet {
+    epoch: u64,
+    chain_key: Zeroizing<[u8; 32]>,
+}
+
+impl KeyRatchet {
+    //starts the ratchet at epoch 0, from the key established by a WAKE session
+    pub fn new(session_key: &RistrettoPoint) -> KeyRatchet {
+        let chain_key = derive(Some(RATCHET_DOMAIN), session_key.compress().as_bytes(), CHAIN_LABEL);
+        KeyRatchet { epoch: 0, chain_key }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    //key of the current epoch
+    pub fn epoch_key(&self) -> EpochKey {
+        EpochKey { epoch: self.epoch, key: derive(None, self.chain_key.as_ref(), EPOCH_LABEL) }
+    }
+
+    //advances the ratchet to the next epoch, replacing the chain key by the chain key of the next epoch
+    //outputs the key of the new epoch
+    pub fn advance(&mut self) -> EpochKey {
+        self.chain_key = derive(None, self.chain_key.as_ref(), CHAIN_LABEL);
+        self.epoch += 1;
+        self.epoch_key()
+    }
+}
+
+//HKDF-SHA256 of a 32 byte key from the input key, with the given label
+fn derive(salt: Option<&[u8]>, input_key: &[u8], label: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(salt, input_key).expand(label, key.as_mut()).expect("32 bytes is a valid HKDF output length");
+    key
+}
@@ -0,0 +1,161 @@
+use super::party_id::PartyId;
+use curve25519_dalek_ng::ristretto::RistrettoPoint;
+use std::{error::Error, fmt};
+
+///
+/// File for the robust mode of the WAKE protocols: parties whose messages are missing or invalid are identified (identifiable abort),
+/// excluded by agreement of the other parties, and the key-exchange is restarted with a fresh Burmester-Desmedt ring over the remaining parties
+/// all WAKE protocols support the robust mode, through run_robust_compiler_key_exchange and run_robust_optimized_key_exchange of the Bulletproofs, GM17 and Marlin protocols
+///
+/// Identifiable abort of a round: the parties whose messages are missing or invalid, and the reason of the first failure
+/// a failure that cannot be attributed to any party blames no parties
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Misbehaviour {
+    pub parties: Vec<PartyId>,
+    pub reason: String,
+}
+
+impl Misbehaviour {
+    pub fn unattributable(reason: String) -> Misbehaviour {
+        Misbehaviour { parties: Vec::new(), reason }
+    }
+
+    //blames a party, keeping the reason of the first failure
+    pub fn blame(&mut self, id: PartyId, reason: String) {
+        if self.reason.is_empty() {
+            self.reason = reason;
+        }
+        if !self.parties.contains(&id) {
+            self.parties.push(id);
+        }
+    }
+
+    //outputs the misbehaviour as an error, if any party was blamed
+    pub fn into_result(mut self) -> Result<(), Misbehaviour> {
+        if self.parties.is_empty() && self.reason.is_empty() {
+            return Ok(())
+        }
+        self.parties.sort();
+        Err(self)
+    }
+}
+
+impl fmt::Display for Misbehaviour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.parties.is_empty() {
+            return write!(f, "{}", self.reason)
+        }
+        write!(f, "{}, misbehaving parties: {:?}", self.reason, self.parties)
+    }
+}
+
+impl Error for Misbehaviour {}
+
+///
+/// Bounds on the restarts of a robust key-exchange: the number of restarts after the first attempt,
+/// and the minimum amount of parties that must remain for the key-exchange to be restarted
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RobustConfig {
+    max_retries: usize,
+    min_group_size: usize,
+}
+
+impl RobustConfig {
+    pub fn new(max_retries: usize, min_group_size: usize) -> Result<RobustConfig, Box<dyn Error>> {
+        //Burmester-Desmedt requires a ring of at least two parties
+        if min_group_size < 2 {
+            return Err("minimum group size should be at least 2".into())
+        }
+
+        Ok(RobustConfig { max_retries, min_group_size })
+    }
+
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    pub fn min_group_size(&self) -> usize {
+        self.min_group_size
+    }
+}
+
+///
+/// Result of a robust key-exchange: the keys of the final members, in ring order, the final member set,
+/// the excluded parties, and the amount of attempts it took
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RobustOutcome {
+    pub keys: Vec<RistrettoPoint>,
+    pub members: Vec<PartyId>,
+    pub excluded: Vec<PartyId>,
+    pub attempts: usize,
+}
+
+///
+/// A party of a WAKE protocol that supports the robust mode
+/// implemented by the parties of the compiler and optimized Bulletproofs, GM17 and Marlin protocols
+///
+pub trait RingMember {
+    fn id(&self) -> PartyId;
+
+    //removes the excluded parties from the ring of the party, and resets its session state, such that the key-exchange can be restarted
+    fn exclude(&mut self, ids: &[PartyId]);
+}
+
+///
+/// Agrees on the parties to exclude, from the misbehaviour identified by every party of a failed session
+/// a party is excluded when more than half of the members blame it, such that a minority of misbehaving parties cannot exclude honest ones
+///
+pub fn agree_on_exclusions(accusations: &[Misbehaviour], members: usize) -> Result<Vec<PartyId>, Box<dyn Error>> {
+    let mut excluded: Vec<PartyId> = accusations.iter().flat_map(|accusation| accusation.parties.iter().copied()).collect();
+    excluded.sort();
+    excluded.dedup();
+    excluded.retain(|id| accusations.iter().filter(|accusation| accusation.parties.contains(id)).count() * 2 > members);
+
+    if excluded.is_empty() {
+        let reason = accusations.first().map(|accusation| accusation.to_string()).unwrap_or_default();
+        return Err(format!("parties could not agree on whom to exclude: {}", reason).into())
+    }
+    Ok(excluded)
+}
+
+///
+/// Runs sessions of a WAKE protocol until one succeeds, excluding the misbehaving parties after every failed session
+/// session runs a single key-exchange between the given parties, and outputs their keys in order, or the misbehaviour identified by every party
+/// fails once the retry limit is reached, or fewer parties than the minimum group size remain
+///
+pub fn run_robust_key_exchange<P, F>(mut parties: Vec<P>, config: &RobustConfig, mut session: F) -> Result<RobustOutcome, Box<dyn Error>>
+where
+    P: RingMember,
+    F: FnMut(&mut [P]) -> Result<Vec<RistrettoPoint>, Vec<Misbehaviour>>,
+{
+    let mut excluded: Vec<PartyId> = Vec::new();
+
+    for attempt in 1..=config.max_retries() + 1 {
+        if parties.len() < config.min_group_size() {
+            return Err(format!("{} parties remain, fewer than the minimum group size of {}", parties.len(), config.min_group_size()).into())
+        }
+
+        //the parties are kept in ring order, such that the keys are output in the order of the members
+        parties.sort_by_key(|party| party.id());
+
+        match session(&mut parties) {
+            Ok(keys) => {
+                let members = parties.iter().map(|party| party.id()).collect();
+                return Ok(RobustOutcome { keys, members, excluded, attempts: attempt })
+            }
+            Err(accusations) => {
+                let blamed = agree_on_exclusions(&accusations, parties.len())?;
+                parties.retain(|party| !blamed.contains(&party.id()));
+                for party in parties.iter_mut() {
+                    party.exclude(&blamed);
+                }
+                excluded.extend(blamed);
+            }
+        }
+    }
+
+    Err(format!("key-exchange did not succeed within {} retries", config.max_retries()).into())
+}
@@ -0,0 +1,139 @@
+use super::party_id::PartyId;
+use hex::ToHex;
+use sha2::{Digest, Sha256};
+use std::{collections::HashSet, fmt, sync::{Arc, Mutex}};
+
+///
+/// File for identifying a WAKE session, such that its messages and proofs cannot be replayed into another session. The session id
+/// is derived from the round 1 contributions of all parties (their nonces or z-values) and the context the session is run in,
+/// and is bound into every signed message. A replay cache additionally rejects round 1 contributions seen in earlier sessions
+///
+//domain separation tag for deriving the session id
+pub const SESSION_ID_DOMAIN: &[u8] = b"WAKE-SESSION-ID-V1";
+//application of a session without a configured context
+pub const DEFAULT_APPLICATION: &str = "WAKE";
+
+///
+/// Context a session is run in: the application, the darkpool the parties prove membership of, and the epoch of the pool
+/// parties only agree on the session id when they run the session in the same context
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SessionContext {
+    pub application: String,
+    pub pool_id: String,
+    pub epoch: u64,
+}
+
+impl SessionContext {
+    pub fn new(application: &str, pool_id: &str, epoch: u64) -> SessionContext {
+        SessionContext { application: application.to_string(), pool_id: pool_id.to_string(), epoch }
+    }
+
+    //length-prefixed encoding, such that different contexts never encode the same
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for field in [self.application.as_bytes(), self.pool_id.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.extend_from_slice(&self.epoch.to_le_bytes());
+        bytes
+    }
+}
+
+impl Default for SessionContext {
+    fn default() -> SessionContext {
+        SessionContext::new(DEFAULT_APPLICATION, "", 0)
+    }
+}
+
+///
+/// Identifier of a session: the SHA-256 digest of the context, and the round 1 contributions of the parties in ring order
+///
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionId([u8; 32]);
+
+impl SessionId {
+    //derives the session id from the context, and the contribution of every party, where contributions[i] is sent by ids[i]
+    pub fn derive(context: &SessionContext, ids: &[PartyId], contributions: &[[u8; 32]]) -> SessionId {
+        let mut hasher = Sha256::new();
+        hasher.update(SESSION_ID_DOMAIN);
+        hasher.update(context.to_bytes());
+        for (id, contribution) in ids.iter().zip(contributions.iter()) {
+            hasher.update(id.to_bytes());
+            hasher.update(contribution);
+        }
+        SessionId(hasher.finalize().into())
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.encode_hex::<String>())
+    }
+}
+
+impl fmt::Debug for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SessionId({})", self)
+    }
+}
+
+///
+/// Cache of the sessions a party took part in, and of the round 1 contributions of those sessions
+/// a session reusing a contribution of an earlier session is rejected, as its messages may be replayed from that session
+///
+#[derive(Clone, Debug, Default)]
+pub struct ReplayCache {
+    sessions: HashSet<SessionId>,
+    contributions: HashSet<[u8; 32]>,
+}
+
+//replay cache shared by the parties of consecutive sessions
+pub type SharedReplayCache = Arc<Mutex<ReplayCache>>;
+
+impl ReplayCache {
+    pub fn new() -> ReplayCache {
+        ReplayCache::default()
+    }
+
+    pub fn shared() -> SharedReplayCache {
+        Arc::new(Mutex::new(ReplayCache::new()))
+    }
+
+    pub fn contains(&self, session_id: &SessionId) -> bool {
+        self.sessions.contains(session_id)
+    }
+
+    //amount of sessions in the cache
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    //admits a new session with the given round 1 contributions, rejecting sessions that were seen before,
+    //and sessions reusing a contribution of an earlier session, or of another party
+    pub fn admit(&mut self, session_id: &SessionId, contributions: &[[u8; 32]]) -> Result<(), String> {
+        if self.sessions.contains(session_id) {
+            return Err(format!("Session {} was already run", session_id))
+        }
+
+        let mut fresh = HashSet::new();
+        for contribution in contributions.iter() {
+            if self.contributions.contains(contribution) || !fresh.insert(*contribution) {
+                return Err(format!("Round 1 contribution of session {} was used before", session_id))
+            }
+        }
+
+        self.sessions.insert(*session_id);
+        self.contributions.extend(fresh);
+        Ok(())
+    }
+}
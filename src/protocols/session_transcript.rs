@@ -0,0 +1,55 @@
+use super::party_id::PartyId;
+use serde::{Serialize, Deserialize};
+
+///
+/// A single message sent in a round of the protocol, together with its signature.
+/// The sender is recorded by its party identifier, the message in the string encoding that is signed, and the signature as its hex encoded bytes,
+/// which is empty for unsigned messages
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundMessage {
+    pub round: usize,
+    pub sender: String,
+    pub message: String,
+    pub signature: String,
+}
+
+///
+/// Secret inputs of a party in a session, hex encoded: the blinding of its statement, and its ephemeral Burmester-Desmedt exponent.
+/// Together with the balances and the round 1 messages, they determine the statement, the Burmester-Desmedt values and the key of the party,
+/// such that other implementations can recompute them. The proofs are randomized, and can only be checked by verifying them
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretInputs {
+    pub party: String,
+    pub blinding: String,
+    pub exponent: String,
+}
+
+///
+/// Record of a WAKE session: the statements of the parties (their commitments or images), and every message sent,
+/// in the order the rounds were run, such that a session can be compared against known-answer test vectors
+/// it also holds the secret inputs of all parties, and is thus only meant for sessions simulating every party, as the runners do
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub statements: Vec<String>,
+    pub messages: Vec<RoundMessage>,
+    pub secret_inputs: Vec<SecretInputs>,
+}
+
+impl SessionTranscript {
+    pub fn new(statements: Vec<String>) -> SessionTranscript {
+        SessionTranscript { statements, messages: Vec::new(), secret_inputs: Vec::new() }
+    }
+
+    //records the secret inputs of party, in the order the parties were set up
+    pub fn record_secret_inputs(&mut self, party: &PartyId, blinding: String, exponent: String) {
+        self.secret_inputs.push(SecretInputs { party: party.to_string(), blinding, exponent });
+    }
+
+    //records a message of party sender in the given round
+    pub fn record(&mut self, round: usize, sender: &PartyId, message: String, signature: &[u8]) {
+        self.messages.push(RoundMessage { round, sender: sender.to_string(), message, signature: hex::encode(signature) });
+    }
+}
@@ -0,0 +1,115 @@
+use super::party_id::PartyId;
+use curve25519_dalek_ng::scalar::Scalar;
+use chacha20poly1305::{aead::{Aead, KeyInit, Payload}, ChaCha20Poly1305, Key, Nonce};
+use hex::ToHex;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+///
+/// File for encrypting the session state of a party between rounds, such that a restarted party can continue its session.
+/// A snapshot is encrypted with ChaCha20-Poly1305, under a key held by the party, and is bound to the party and to its session
+/// by the fingerprint of the round 1 outputs of the session. Restoring a snapshot into another session, or into another party,
+/// fails authentication and is refused.
+/// Only the parties of the compiler and optimized Bulletproofs protocols can be snapshotted and restored. The GM17 and Marlin
+/// parties hold no snapshot support, so a restarted GM17 or Marlin party has to take part in a new session instead
+///
+//domain separation tag of the associated data of a snapshot
+pub const SNAPSHOT_DOMAIN: &[u8] = b"WAKE-SNAPSHOT-V1";
+pub const SNAPSHOT_KEY_LEN: usize = 32;
+pub const SNAPSHOT_NONCE_LEN: usize = 12;
+
+///
+/// Key for encrypting snapshots, wiped when dropped
+///
+#[derive(Clone)]
+pub struct SnapshotKey(Zeroizing<[u8; SNAPSHOT_KEY_LEN]>);
+
+impl SnapshotKey {
+    pub fn new(bytes: [u8; SNAPSHOT_KEY_LEN]) -> SnapshotKey {
+        SnapshotKey(Zeroizing::new(bytes))
+    }
+
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> SnapshotKey {
+        let mut bytes = Zeroizing::new([0u8; SNAPSHOT_KEY_LEN]);
+        rng.fill_bytes(bytes.as_mut());
+        SnapshotKey(bytes)
+    }
+}
+
+///
+/// Encrypted session state of a party
+/// serialized as the nonce, followed by the ciphertext
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedSnapshot {
+    nonce: [u8; SNAPSHOT_NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedSnapshot {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.nonce.to_vec();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<EncryptedSnapshot, String> {
+        if bytes.len() < SNAPSHOT_NONCE_LEN {
+            return Err("Snapshot is too short".to_string())
+        }
+        let mut nonce = [0u8; SNAPSHOT_NONCE_LEN];
+        nonce.copy_from_slice(&bytes[..SNAPSHOT_NONCE_LEN]);
+        Ok(EncryptedSnapshot { nonce, ciphertext: bytes[SNAPSHOT_NONCE_LEN..].to_vec() })
+    }
+}
+
+//fingerprint of the round 1 outputs of a session, in their string encoding
+pub fn session_fingerprint(round_1_outputs: &str) -> String {
+    Sha256::digest(round_1_outputs.as_bytes()).encode_hex::<String>()
+}
+
+//associated data of a snapshot, binding it to the protocol, the party and the session
+fn associated_data(protocol: &str, id: &PartyId, session: &str) -> Vec<u8> {
+    let mut aad = SNAPSHOT_DOMAIN.to_vec();
+    aad.extend_from_slice(protocol.as_bytes());
+    aad.extend_from_slice(&id.to_bytes());
+    aad.extend_from_slice(session.as_bytes());
+    aad
+}
+
+///
+/// Encrypts the session state of party id in the given session of protocol
+///
+pub fn seal<R: RngCore + CryptoRng>(key: &SnapshotKey, protocol: &str, id: &PartyId, session: &str, state: &[u8], rng: &mut R) -> Result<EncryptedSnapshot, String> {
+    let mut nonce = [0u8; SNAPSHOT_NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.0.as_ref()));
+    let aad = associated_data(protocol, id, session);
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: state, aad: &aad }).map_err(|_| "Encryption of snapshot failed".to_string())?;
+
+    Ok(EncryptedSnapshot { nonce, ciphertext })
+}
+
+///
+/// Decrypts the session state of party id in the given session of protocol
+/// fails if the snapshot was taken by another party, in another session, or was altered
+///
+pub fn open(key: &SnapshotKey, protocol: &str, id: &PartyId, session: &str, snapshot: &EncryptedSnapshot) -> Result<Zeroizing<Vec<u8>>, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.0.as_ref()));
+    let aad = associated_data(protocol, id, session);
+    let state = cipher.decrypt(Nonce::from_slice(&snapshot.nonce), Payload { msg: &snapshot.ciphertext, aad: &aad }).map_err(|_| "Snapshot does not belong to this party and session".to_string())?;
+
+    Ok(Zeroizing::new(state))
+}
+
+//reads a scalar from the first 32 bytes of a decrypted state
+pub fn scalar_from_state(state: &[u8]) -> Result<Scalar, String> {
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    if state.len() < bytes.len() {
+        return Err("Invalid snapshot".to_string())
+    }
+    bytes.copy_from_slice(&state[..32]);
+    Scalar::from_canonical_bytes(*bytes).ok_or("Invalid snapshot".to_string())
+}
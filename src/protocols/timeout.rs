@@ -0,0 +1,195 @@
+use super::{party_id::{PartyId, Ring}, robust::Misbehaviour};
+use std::{error::Error, fmt, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+///
+/// File for bounding the time a party waits for the messages of a round. Every round has a deadline, after which the round
+/// ends with a timeout that lists the parties whose messages are missing. Time is read from a clock, which is either the
+/// system clock, or a simulated clock that is advanced by hand, such that timeouts can be tested deterministically.
+/// The deadlines are only wired into the parties of the Bulletproofs protocols, through collect_round. The GM17 and Marlin
+/// parties have no round collector, and process the messages of a round as they are handed to them
+///
+//timeout of a round without a configured timeout
+pub const DEFAULT_ROUND_TIMEOUT: Duration = Duration::from_secs(30);
+
+///
+/// Source of time for the deadlines of the rounds
+/// outputs the time passed since a fixed origin of the clock
+///
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+///
+/// Clock reading the monotonic system time
+///
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { origin: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+///
+/// Clock that only moves when advanced, shared by its clones
+///
+#[derive(Clone, Debug, Default)]
+pub struct SimulatedClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl SimulatedClock {
+    pub fn new() -> SimulatedClock {
+        SimulatedClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+///
+/// Timeouts of the rounds of a protocol: a timeout per round, and a default timeout for the other rounds
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundDeadlines {
+    default: Duration,
+    rounds: Vec<(usize, Duration)>,
+}
+
+impl RoundDeadlines {
+    pub fn new(default: Duration) -> RoundDeadlines {
+        RoundDeadlines { default, rounds: Vec::new() }
+    }
+
+    //sets the timeout of round round_idx
+    pub fn with_round(mut self, round_idx: usize, timeout: Duration) -> RoundDeadlines {
+        self.rounds.retain(|(idx, _)| *idx != round_idx);
+        self.rounds.push((round_idx, timeout));
+        self
+    }
+
+    pub fn timeout(&self, round_idx: usize) -> Duration {
+        self.rounds.iter().find(|(idx, _)| *idx == round_idx).map(|(_, timeout)| *timeout).unwrap_or(self.default)
+    }
+}
+
+impl Default for RoundDeadlines {
+    fn default() -> RoundDeadlines {
+        RoundDeadlines::new(DEFAULT_ROUND_TIMEOUT)
+    }
+}
+
+///
+/// Timeout of a round: the round, and the parties whose messages were not received before its deadline
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timeout {
+    pub round: usize,
+    pub silent: Vec<PartyId>,
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "round {} timed out, silent parties: {:?}", self.round, self.silent)
+    }
+}
+
+impl Error for Timeout {}
+
+//silent parties are blamed, such that a robust key-exchange excludes them
+impl From<Timeout> for Misbehaviour {
+    fn from(timeout: Timeout) -> Misbehaviour {
+        let mut misbehaviour = Misbehaviour::default();
+        for id in timeout.silent.iter() {
+            misbehaviour.blame(*id, format!("Round {} timed out", timeout.round));
+        }
+        misbehaviour
+    }
+}
+
+///
+/// Status of a round: still waiting for messages, complete with the messages of all members in ring order,
+/// or timed out
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoundStatus<T> {
+    Waiting,
+    Complete(Vec<(PartyId, T)>),
+    TimedOut(Timeout),
+}
+
+///
+/// Collects the messages of a round from the members of a ring, until the deadline of the round
+///
+#[derive(Clone, Debug)]
+pub struct RoundCollector<T> {
+    round: usize,
+    ring: Ring,
+    received: Vec<Option<T>>,
+    deadline: Duration,
+}
+
+impl<T: Clone> RoundCollector<T> {
+    //starts collecting the messages of round round_idx, with the deadline at the given time of the clock
+    pub fn new(round: usize, ring: &Ring, deadline: Duration) -> RoundCollector<T> {
+        RoundCollector { round, ring: ring.clone(), received: vec![None; ring.len()], deadline }
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn deadline(&self) -> Duration {
+        self.deadline
+    }
+
+    //receives the message of a member, rejecting messages of unknown parties and second messages of the same party
+    pub fn recieve(&mut self, sender: PartyId, message: T) -> Result<(), String> {
+        let position = self.ring.position(&sender).ok_or(format!("Unknown party {}", sender))?;
+        if self.received[position].is_some() {
+            return Err(format!("Duplicate message from party {}", sender))
+        }
+        self.received[position] = Some(message);
+        Ok(())
+    }
+
+    //members whose message was not received yet
+    pub fn silent(&self) -> Vec<PartyId> {
+        self.ring.ids().iter().zip(self.received.iter()).filter(|(_, message)| message.is_none()).map(|(id, _)| *id).collect()
+    }
+
+    //completes the round once all messages are received, or times it out once the deadline has passed on the clock
+    pub fn poll<C: Clock>(&self, clock: &C) -> RoundStatus<T> {
+        let silent = self.silent();
+        if silent.is_empty() {
+            let messages = self.ring.ids().iter().copied().zip(self.received.iter().flatten().cloned()).collect();
+            return RoundStatus::Complete(messages)
+        }
+        if clock.now() >= self.deadline {
+            return RoundStatus::TimedOut(Timeout { round: self.round, silent })
+        }
+        RoundStatus::Waiting
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    params::{WakeParams, WakeKeys, BulletproofsKeys, Gm17Keys, MarlinKeys},
+    protocols::{
+        session_transcript::SessionTranscript,
+        compiler_bp_wake::compiler_bp_wake_protocol,
+        compiler_gm17_wake::compiler_gm17_wake_protocol,
+        compiler_marlin_wake::compiler_marlin_wake_protocol,
+        optimized_bp_wake::optimized_bp_wake_protocol,
+        optimized_gm17_wake::optimized_gm17_wake_protocol,
+        optimized_marlin_wake::optimized_marlin_wake_protocol
+    },
+    utility::ristretto_to_string
+};
+use std::{error::Error, fs, path::Path, sync::Arc};
+use ark_bls12_377::Bls12_377;
+use ark_serialize::CanonicalSerialize;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+///
+/// File for generating known-answer test vectors of the WAKE protocols. A vector runs a session of a WAKE variant from a seeded RNG,
+/// and records its inputs, public parameters, every round message, the secret inputs of the parties and the derived keys, such that
+/// replaying the vector detects any change to the protocol, and other implementations can check interoperability against it
+///
+//WAKE variants with a test vector, named after their protocol and proof system
+pub const VARIANTS: [&str; 6] = ["compiler_bp", "optimized_bp", "compiler_gm17", "optimized_gm17", "compiler_marlin", "optimized_marlin"];
+
+//inputs of the sessions of the test vectors, small enough for the vectors to be generated and replayed quickly
+pub const SEED: u64 = 0x57414b45;
+pub const PARTY_AMOUNT: usize = 3;
+pub const MIN_BAL: u64 = 10;
+pub const UPPERBOUND: u64 = u8::MAX as u64;
+
+///
+/// Public parameters of a test vector: the ciphersuite, the generators of the key exchange, the serialized verifying keys,
+/// and a SHA-256 digest of the complete serialized parameters (see WakeParams::to_bytes), all hex encoded
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicParameters {
+    pub ciphersuite: String,
+    pub generators: Vec<String>,
+    pub verifying_keys: Vec<String>,
+    pub digest: String,
+}
+
+///
+/// Known-answer test vector of a single WAKE session
+/// the balances are drawn from the seeded RNG, before the parameters are set up and the protocol is run from the same RNG
+/// the RNG is ChaCha20 (as specified by rand_chacha), keyed with the 8 little-endian bytes of the seed followed by 24 zero bytes
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub variant: String,
+    pub seed: u64,
+    pub party_amount: usize,
+    pub min_bal: u64,
+    pub upperbound: u64,
+    pub balances: Vec<u64>,
+    pub public_parameters: PublicParameters,
+    pub transcript: SessionTranscript,
+    pub keys: Vec<String>,
+}
+
+impl TestVector {
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<TestVector, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn file_name(variant: &str) -> String {
+        format!("{}.json", variant)
+    }
+}
+
+//records the public parameters, with the given serialized verifying keys
+fn public_parameters<K: WakeKeys>(params: &WakeParams<K>, verifying_keys: Vec<String>) -> Result<PublicParameters, Box<dyn Error>> {
+    Ok(PublicParameters {
+        ciphersuite: params.ciphersuite().to_string(),
+        generators: vec![ristretto_to_string(params.pc_gens().B), ristretto_to_string(params.pc_gens().B_blinding)],
+        verifying_keys,
+        digest: hex::encode(Sha256::digest(params.to_bytes()?)),
+    })
+}
+
+//ChaCha20 RNG keyed with the little-endian bytes of seed, padded with zeros
+fn seeded_rng(seed: u64) -> ChaCha20Rng {
+    let mut key = [0u8; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    ChaCha20Rng::from_seed(key)
+}
+
+fn serialize_to_hex<T: CanonicalSerialize>(value: &T) -> Result<String, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    value.serialize(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+///
+/// Runs a session of the given WAKE variant from an RNG seeded with seed, and records it as a test vector
+/// GM17 and Marlin are instantiated over BLS12-377
+///
+pub fn generate_test_vector(variant: &str, seed: u64, party_amount: usize, min_bal: u64, upperbound: u64) -> Result<TestVector, Box<dyn Error>> {
+    let rng = &mut seeded_rng(seed);
+    let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(min_bal..=upperbound)).collect();
+
+    let (public_parameters, (keys, transcript)) = match variant {
+        "compiler_bp" | "optimized_bp" => {
+            let params = Arc::new(WakeParams::<BulletproofsKeys>::bulletproofs(min_bal, upperbound)?);
+            let session = if variant == "compiler_bp" {
+                compiler_bp_wake_protocol::run_compiler_key_exchange_with_transcript(party_amount, balances.clone(), &params, rng)?
+            } else {
+                optimized_bp_wake_protocol::run_optimized_key_exchange_with_transcript(party_amount, balances.clone(), &params, rng)?
+            };
+            (public_parameters(&params, Vec::new())?, session)
+        }
+        "compiler_gm17" | "optimized_gm17" => {
+            let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, min_bal, upperbound, rng)?);
+            let verifying_keys = params.keys().pvk_list().iter().map(|pvk| serialize_to_hex(&pvk.vk)).collect::<Result<Vec<String>, Box<dyn Error>>>()?;
+            let session = if variant == "compiler_gm17" {
+                compiler_gm17_wake_protocol::run_compiler_key_exchange_with_transcript(party_amount, balances.clone(), &params, rng)?
+            } else {
+                optimized_gm17_wake_protocol::run_optimized_key_exchange_with_transcript(party_amount, balances.clone(), &params, rng)?
+            };
+            (public_parameters(&params, verifying_keys)?, session)
+        }
+        "compiler_marlin" | "optimized_marlin" => {
+            let params: Arc<WakeParams<MarlinKeys<Bls12_377>>> = WakeParams::marlin(min_bal, upperbound, rng)?.into();
+            let verifying_keys = vec![serialize_to_hex(params.keys().vk())?];
+            let session = if variant == "compiler_marlin" {
+                compiler_marlin_wake_protocol::run_compiler_key_exchange_with_transcript(party_amount, balances.clone(), &params, rng)?
+            } else {
+                optimized_marlin_wake_protocol::run_optimized_key_exchange_with_transcript(party_amount, balances.clone(), &params, rng)?
+            };
+            (public_parameters(&params, verifying_keys)?, session)
+        }
+        _ => return Err(format!("unknown WAKE variant {}", variant).into())
+    };
+
+    Ok(TestVector {
+        variant: variant.to_string(),
+        seed,
+        party_amount,
+        min_bal,
+        upperbound,
+        balances,
+        public_parameters,
+        transcript,
+        keys: keys.iter().map(|key| ristretto_to_string(*key)).collect(),
+    })
+}
+
+///
+/// Regenerates the session of a test vector from its recorded inputs
+/// outputs the regenerated vector, which matches the given one as long as the protocol is unchanged
+///
+pub fn replay_test_vector(vector: &TestVector) -> Result<TestVector, Box<dyn Error>> {
+    generate_test_vector(&vector.variant, vector.seed, vector.party_amount, vector.min_bal, vector.upperbound)
+}
+
+///
+/// Generates the test vectors of all WAKE variants, and writes them as JSON files to dir
+///
+pub fn write_test_vectors(dir: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+    for variant in VARIANTS {
+        let vector = generate_test_vector(variant, SEED, PARTY_AMOUNT, MIN_BAL, UPPERBOUND)?;
+        fs::write(dir.join(TestVector::file_name(variant)), vector.to_json()? + "\n")?;
+    }
+    Ok(())
+}
@@ -1,45 +1,47 @@
-use crate::proof_systems::proof_system_gm17::Gm17Darkpool;
-use std::{error::Error, str::FromStr};
-use ark_ff::Field;
+use crate::proof_systems::{proof_system_gm17::Gm17Darkpool, proof_system_marlin::{MarlinDarkpool, MarlinProvingKey, MarlinVerifyingKey}};
+use std::error::Error;
+use ark_ff::{BigInteger, Field, PrimeField};
 use hex::ToHex;
-use num::Num;
-use num_bigint::BigInt;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use sha2::{Digest, Sha256};
-use ark_bls12_377::{Parameters, Fr};
-use ark_ec::bls12::Bls12;
+use ark_bls12_377::Fr;
+use ark_ec::PairingEngine;
 use ark_gm17::{PreparedVerifyingKey, ProvingKey};
 use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint, CompressedRistretto}};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 
 //run GM17 darkpool proof setup for a specified a single party
-pub fn setup_gm17_single_party(upperbound: u64) -> Result<(Gm17Darkpool, PreparedVerifyingKey<Bls12<Parameters>>, ProvingKey<Bls12<ark_bls12_377::Parameters>>), Box<dyn Error>>{
-    let gm17_darkpool = Gm17Darkpool::new(upperbound.try_into().unwrap()); 
-    let gm17_unwrapped: Gm17Darkpool;
+pub fn setup_gm17_single_party<E: PairingEngine, R: RngCore + CryptoRng>(upperbound: u64, rng: &mut R) -> Result<(Gm17Darkpool<E>, PreparedVerifyingKey<E>, ProvingKey<E>), Box<dyn Error>>{
+    let gm17_darkpool = Gm17Darkpool::new(upperbound.try_into().unwrap(), rng); 
+    let gm17_unwrapped: Gm17Darkpool<E>;
     match gm17_darkpool {
         Ok(val) => {gm17_unwrapped = val}
         Err(error) => {return Err(error)}
     }
 
-    let (pk, pvk) = gm17_unwrapped.clone().setup();
+    let (pk, pvk) = gm17_unwrapped.setup(rng);
 
     return Ok((gm17_unwrapped, pvk, pk))
 
 }
 
 //run GM17 darkpool proof setup for a specified amount of parties
-pub fn setup_gm17(party_amount: usize, upperbound: u64) -> Result<(Gm17Darkpool, Vec<PreparedVerifyingKey<Bls12<Parameters>>>, Vec<ProvingKey<Bls12<ark_bls12_377::Parameters>>>), Box<dyn Error>>{
-    let gm17_darkpool = Gm17Darkpool::new(upperbound.try_into().unwrap()); 
-    let gm17_unwrapped: Gm17Darkpool;
+pub fn setup_gm17<E: PairingEngine, R: RngCore + CryptoRng>(party_amount: usize, upperbound: u64, rng: &mut R) -> Result<(Gm17Darkpool<E>, Vec<PreparedVerifyingKey<E>>, Vec<ProvingKey<E>>), Box<dyn Error>>{
+    let gm17_darkpool = Gm17Darkpool::new(upperbound.try_into().unwrap(), rng); 
+    let gm17_unwrapped: Gm17Darkpool<E>;
     match gm17_darkpool {
         Ok(val) => {gm17_unwrapped = val}
         Err(error) => {return Err(error)}
     }
     
-    let mut pvk_list: Vec<PreparedVerifyingKey<Bls12<Parameters>>> = Vec::new();
-    let mut pk_list: Vec<ProvingKey<Bls12<ark_bls12_377::Parameters>>> = Vec::new();
+    let mut pvk_list: Vec<PreparedVerifyingKey<E>> = Vec::new();
+    let mut pk_list: Vec<ProvingKey<E>> = Vec::new();
     
     for _ in 0..party_amount {
-        let (pk, pvk) = gm17_unwrapped.clone().setup();
+        let (pk, pvk) = gm17_unwrapped.setup(rng);
         pvk_list.push(pvk);
         pk_list.push(pk);
     }
@@ -47,6 +49,16 @@ pub fn setup_gm17(party_amount: usize, upperbound: u64) -> Result<(Gm17Darkpool,
     return Ok((gm17_unwrapped, pvk_list, pk_list))
 }
 
+//run Marlin darkpool proof setup: a universal SRS, and the index keys of the darkpool circuit derived from it
+//the keys are deterministic in the SRS and the circuit, so they are shared by all parties rather than generated per party
+pub fn setup_marlin<E: PairingEngine, R: RngCore + CryptoRng>(upperbound: u64, rng: &mut R) -> Result<(MarlinDarkpool<E>, MarlinVerifyingKey<E>, MarlinProvingKey<E>), Box<dyn Error>>{
+    let marlin_darkpool = MarlinDarkpool::new(upperbound.try_into().unwrap(), rng)?;
+    let srs = marlin_darkpool.universal_setup(rng)?;
+    let (pk, vk) = marlin_darkpool.index(&srs)?;
+
+    Ok((marlin_darkpool, vk, pk))
+}
+
 //compute the logarithm of an upperbound among valid inputs: 2^8, 2^16, 2^32, 2^64
 pub fn upperbound_log(upperbound: u64) -> Result<usize, Box<dyn Error>> {
     let u32_max = u32::MAX as u64;
@@ -98,6 +110,11 @@ pub fn scalar_to_string(scalar: Scalar) -> String {
     return scalar_string;
 }
 
+//converts a prime field element to a string, as the hex encoding of its little-endian bytes
+pub fn field_to_string<F: PrimeField>(v: F) -> String {
+    v.into_repr().to_bytes_le().encode_hex::<String>()
+}
+
 //get adjacent elements in from list of group elements
 pub fn get_adjacent_elements<'a>(z_list: &'a [RistrettoPoint], idx: &'a usize) -> (&'a RistrettoPoint, &'a RistrettoPoint) {
     let len = z_list.len();
@@ -129,34 +146,43 @@ pub fn hash_string(input_string: &str) -> [u8; 32] {
     return hash_result.into()
 }
 
+//domain separation tag for hashing auxilliary strings into the GM17 circuit field
+pub const GM17_AUX_DOMAIN: &[u8] = b"WAKE-GM17-AUX-V1";
+
+//domain separation tag for hashing auxilliary strings into the Marlin circuit field, such that aux inputs are not shared between the two proof systems
+pub const MARLIN_AUX_DOMAIN: &[u8] = b"WAKE-MARLIN-AUX-V1";
+
+//hash a string to Fr, domain separated for use as the auxilliary input of GM17 proofs
 pub fn hash_string_to_fr(input_str: String) -> Fr {
-    //hash string to 256 bits (32 bytes)
-    let mut hasher = Sha256::new();
-    hasher.update(input_str.as_bytes());
-    let hash = hasher.finalize();
-
-    //convert byte-array to decimal string<
-    let big_int_val = BigInt::from_bytes_be(num_bigint::Sign::Plus, &hash);
-    let decimal_string = big_int_val.to_str_radix(10);
-
-    //convert decimal string to Fr
-    let fr_val = Fr::from_str(&decimal_string).unwrap();
-    return fr_val;
-}
-
-pub fn fr_to_bits<F: Field>(v: F) -> Vec<Option<F>> {
-    let fr_string = v.to_string()[8..v.to_string().len() - 2].to_string();
-    let fr_string_base_10 = BigInt::from_str_radix(&fr_string, 16).unwrap();
-    let skrt = format!("{:0>64}", fr_string_base_10.to_str_radix(2));
-    let mut fr_vec = Vec::new();
-    for c in skrt.chars() {
-        match c {
-            '0' => fr_vec.push(Some(F::from(0 as u64))),
-            '1' => fr_vec.push(Some(F::from(1 as u64))),
-            _ => panic!("Invalid value"),
-        }
+    hash_to_field(GM17_AUX_DOMAIN, input_str.as_bytes())
+}
+
+//hash bytes to a prime field element, domain separated by the given tag
+//expands the input to 512 bits with SHA-256, such that the modular reduction is statistically close to uniform for fields up to 256 bits
+pub fn hash_to_field<F: PrimeField>(domain: &[u8], input: &[u8]) -> F {
+    let mut expanded: Vec<u8> = Vec::with_capacity(64);
+    for counter in 0..2u8 {
+        let mut hasher = Sha256::new();
+        hasher.update([domain.len() as u8]);
+        hasher.update(domain);
+        hasher.update([counter]);
+        hasher.update(input);
+        expanded.extend_from_slice(&hasher.finalize());
     }
-    return fr_vec;
+
+    F::from_be_bytes_mod_order(&expanded)
+}
+
+//decompose a field element into its lowest bit_len bits, most significant bit first
+pub fn fr_to_bits<F: PrimeField>(v: F, bit_len: usize) -> Vec<Option<F>> {
+    let bits = v.into_repr().to_bits_le();
+
+    (0..bit_len).rev().map(|i| {
+        match bits.get(i) {
+            Some(true) => Some(F::one()),
+            _ => Some(F::zero()),
+        }
+    }).collect()
 }
 
 pub fn mimc<F: Field>(mut v: F, mut r: F, constants: &[F]) -> F {
@@ -174,4 +200,51 @@ pub fn mimc<F: Field>(mut v: F, mut r: F, constants: &[F]) -> F {
     }
 
     v
+}
+
+//derive an independent rng per party from rng, such that the output of parties running concurrently does not depend on their scheduling
+//the forked rngs are ChaCha20, whose output is fixed, such that seeded sessions (and the test vectors) are reproducible across versions of rand
+pub fn fork_rngs<R: RngCore + CryptoRng>(rng: &mut R, amount: usize) -> Vec<ChaCha20Rng> {
+    (0..amount).map(|_| {
+        let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+        ChaCha20Rng::from_seed(seed)
+    }).collect()
+}
+
+//apply f to every item, concurrently with the parallel feature, outputs in the order of the items
+pub fn map_items<P: Sync, T: Send, F: Fn(&P) -> T + Sync + Send>(items: &[P], f: F) -> Vec<T> {
+    #[cfg(feature = "parallel")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+//apply f to every party, concurrently with the parallel feature, outputs in the order of the parties
+pub fn map_parties_mut<P: Send, T: Send, F: Fn(&mut P) -> T + Sync + Send>(parties: &mut [P], f: F) -> Vec<T> {
+    #[cfg(feature = "parallel")]
+    {
+        parties.par_iter_mut().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        parties.iter_mut().map(f).collect()
+    }
+}
+
+//apply f to every party together with its own rng forked from rng, concurrently with the parallel feature, outputs in the order of the parties
+pub fn map_parties_with_rng<P: Send, T: Send, R: RngCore + CryptoRng, F: Fn(&mut P, &mut ChaCha20Rng) -> T + Sync + Send>(parties: &mut [P], rng: &mut R, f: F) -> Vec<T> {
+    let mut rngs = fork_rngs(rng, parties.len());
+    #[cfg(feature = "parallel")]
+    {
+        parties.par_iter_mut().zip(rngs.par_iter_mut()).map(|(party, rng)| f(party, rng)).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        parties.iter_mut().zip(rngs.iter_mut()).map(|(party, rng)| f(party, rng)).collect()
+    }
 }
\ No newline at end of file
@@ -1,2 +1,5 @@
+pub mod params;
 pub mod proof_systems;
-pub mod protocols;
\ No newline at end of file
+pub mod protocols;
+pub mod test_vectors;
+pub mod utility;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod test_params;
\ No newline at end of file
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::params::{WakeParams, BulletproofsKeys, Gm17Keys, MarlinKeys, SignatureBackend};
+    use wake_implementation::proof_systems::proof_system_gm17::Gm17Darkpool;
+    use wake_implementation::protocols::{
+        compiler_gm17_wake::compiler_gm17_wake_protocol::run_compiler_key_exchange as run_gm17_compiler_key_exchange,
+        optimized_bp_wake::optimized_bp_wake_protocol::run_optimized_key_exchange,
+        optimized_marlin_wake::optimized_marlin_wake_protocol::run_optimized_key_exchange as run_marlin_optimized_key_exchange
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bn254::Bn254;
+    use std::sync::Arc;
+    use rand::rngs::OsRng;
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref MIN_BAL: u64 = 10;
+        static ref UPPER_U8: u64 = u8::MAX as u64;
+        static ref UPPER_U16: u64 = u16::MAX as u64;
+    }
+
+    ///
+    /// Serializes and deserializes Bulletproofs parameters, and runs a key-exchange with the deserialized parameters
+    ///
+    #[test]
+    fn test_bulletproofs_round_trip() {
+        let rng = &mut OsRng;
+        let party_amount = 3;
+        let params = WakeParams::bulletproofs(*MIN_BAL, *UPPER_U8).unwrap();
+        assert_eq!(params.ciphersuite(), "WAKE-BULLETPROOFS-RISTRETTO255");
+
+        let deserialized = WakeParams::<BulletproofsKeys>::from_bytes(&params.to_bytes().unwrap()).unwrap();
+        assert_eq!(deserialized.ciphersuite(), params.ciphersuite());
+        assert_eq!(deserialized.min_bal(), *MIN_BAL);
+        assert_eq!(deserialized.upperbound(), *UPPER_U8);
+        assert_eq!(deserialized.keys().signature_backend(), SignatureBackend::RangeProof);
+
+        let keys = run_optimized_key_exchange(party_amount, vec![*MIN_BAL, 100, *UPPER_U8], &Arc::new(deserialized), rng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Serializes and deserializes Bulletproofs parameters selecting the R1CS signature backend, and runs a key-exchange with the deserialized parameters
+    ///
+    #[test]
+    fn test_bulletproofs_r1cs_backend_round_trip() {
+        let rng = &mut OsRng;
+        let party_amount = 3;
+        let params = WakeParams::bulletproofs_with_signature_backend(*MIN_BAL, *UPPER_U8, SignatureBackend::R1cs).unwrap();
+        let bytes = params.to_bytes().unwrap();
+
+        let deserialized = WakeParams::<BulletproofsKeys>::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.keys().signature_backend(), SignatureBackend::R1cs);
+        assert_eq!(deserialized.to_bytes().unwrap(), bytes);
+
+        let keys = run_optimized_key_exchange(party_amount, vec![*MIN_BAL, 100, *UPPER_U8], &Arc::new(deserialized), rng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+
+        //unknown signature backend
+        let mut unknown_backend = bytes.clone();
+        *unknown_backend.last_mut().unwrap() = 2;
+        assert!(WakeParams::<BulletproofsKeys>::from_bytes(&unknown_backend).err().unwrap().to_string() == "unknown signature backend 2");
+    }
+
+    ///
+    /// Serializes and deserializes GM17 parameters, and runs a key-exchange with the deserialized parameters
+    ///
+    #[test]
+    fn test_gm17_round_trip() {
+        let rng = &mut OsRng;
+        let party_amount = 3;
+        let params = WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, *MIN_BAL, *UPPER_U8, rng).unwrap();
+        assert_eq!(params.ciphersuite(), "WAKE-GM17-BLS12-377");
+
+        let bytes = params.to_bytes().unwrap();
+        let deserialized = WakeParams::<Gm17Keys<Bls12_377>>::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_bytes().unwrap(), bytes);
+        assert_eq!(deserialized.keys().party_amount(), party_amount);
+
+        let keys = run_gm17_compiler_key_exchange(party_amount, vec![*MIN_BAL, 100, *UPPER_U8], &Arc::new(deserialized), rng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Serializes and deserializes GM17 parameters with a key pair shared by all parties
+    /// Asserts the deserialized parties share a single copy of the key pair
+    ///
+    #[test]
+    fn test_gm17_shared_keys_round_trip() {
+        let rng = &mut OsRng;
+        let party_amount = 3;
+        let gm17_darkpool = Gm17Darkpool::<Bls12_377>::new(*UPPER_U8 as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
+        let params = WakeParams::new(*MIN_BAL, *UPPER_U8, Gm17Keys::shared(gm17_darkpool, pvk, pk, party_amount)).unwrap();
+        assert!(Arc::ptr_eq(&params.keys().pvk_list()[0], &params.keys().pvk_list()[party_amount - 1]));
+
+        let deserialized = WakeParams::<Gm17Keys<Bls12_377>>::from_bytes(&params.to_bytes().unwrap()).unwrap();
+        assert_eq!(deserialized.keys().party_amount(), party_amount);
+        for i in 1..party_amount {
+            assert!(Arc::ptr_eq(&deserialized.keys().pvk_list()[0], &deserialized.keys().pvk_list()[i]));
+            assert!(std::ptr::eq(deserialized.keys().pk(0), deserialized.keys().pk(i)));
+        }
+    }
+
+    ///
+    /// Serializes and deserializes Marlin parameters, and runs a key-exchange with the deserialized parameters
+    ///
+    #[test]
+    fn test_marlin_round_trip() {
+        let rng = &mut OsRng;
+        let party_amount = 3;
+        let params = WakeParams::<MarlinKeys<Bls12_377>>::marlin(*MIN_BAL, *UPPER_U8, rng).unwrap();
+        assert_eq!(params.ciphersuite(), "WAKE-MARLIN-BLS12-377");
+
+        let bytes = params.to_bytes().unwrap();
+        let deserialized = WakeParams::<MarlinKeys<Bls12_377>>::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_bytes().unwrap(), bytes);
+
+        let keys = run_marlin_optimized_key_exchange(party_amount, vec![*MIN_BAL, 100, *UPPER_U8], &Arc::new(deserialized), rng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Parameters with invalid bounds, or keys inconsistent with the bounds or each other, are rejected
+    ///
+    #[test]
+    fn test_validation_neg() {
+        let rng = &mut OsRng;
+        //minimum balance above the upperbound
+        let params = WakeParams::bulletproofs(*UPPER_U8 + 1, *UPPER_U8);
+        assert!(params.err().unwrap().to_string() == "minimum balance exceeds the upperbound");
+
+        //bulletproofs keys of other bounds
+        let params = WakeParams::new(*MIN_BAL, *UPPER_U16, BulletproofsKeys::new(*MIN_BAL, *UPPER_U8));
+        assert!(params.err().unwrap().to_string() == "range proof statement does not match the bounds");
+
+        //circuit of another upperbound
+        let gm17_darkpool = Gm17Darkpool::<Bls12_377>::new(*UPPER_U8 as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
+        let params = WakeParams::new(*MIN_BAL, *UPPER_U16, Gm17Keys::shared(gm17_darkpool.clone(), pvk.clone(), pk.clone(), 2));
+        assert!(params.err().unwrap().to_string() == "GM17 circuit does not match the upperbound");
+
+        //verifying key of another setup
+        let (_, other_pvk) = gm17_darkpool.setup(rng);
+        let params = WakeParams::new(*MIN_BAL, *UPPER_U8, Gm17Keys::new(gm17_darkpool.clone(), vec![pvk, other_pvk], vec![pk.clone(), pk.clone()]));
+        assert!(params.err().unwrap().to_string() == "GM17 verifying key does not match proving key");
+
+        //no key pairs
+        let params = WakeParams::new(*MIN_BAL, *UPPER_U8, Gm17Keys::new(gm17_darkpool, Vec::new(), Vec::new()));
+        assert!(params.err().unwrap().to_string() == "expected a GM17 key pair for every party");
+    }
+
+    ///
+    /// *Negative test*
+    /// Deserialization fails for parameters of another ciphersuite, another version, or with trailing bytes
+    ///
+    #[test]
+    fn test_deserialization_neg() {
+        let rng = &mut OsRng;
+        let params = WakeParams::<Gm17Keys<Bls12_377>>::gm17(1, *MIN_BAL, *UPPER_U8, rng).unwrap();
+        let bytes = params.to_bytes().unwrap();
+
+        //other curve
+        let other_curve = WakeParams::<Gm17Keys<Bn254>>::from_bytes(&bytes);
+        assert!(other_curve.err().unwrap().to_string() == "expected ciphersuite WAKE-GM17-BN254, found WAKE-GM17-BLS12-377");
+
+        //other proof system
+        let other_system = WakeParams::<BulletproofsKeys>::from_bytes(&bytes);
+        assert!(other_system.err().unwrap().to_string().starts_with("expected ciphersuite WAKE-BULLETPROOFS-RISTRETTO255"));
+
+        //other version
+        let mut other_version = bytes.clone();
+        other_version[0] += 1;
+        assert!(WakeParams::<Gm17Keys<Bls12_377>>::from_bytes(&other_version).err().unwrap().to_string().starts_with("unsupported parameters version"));
+
+        //trailing bytes
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(WakeParams::<Gm17Keys<Bls12_377>>::from_bytes(&trailing).err().unwrap().to_string() == "trailing bytes after parameters");
+
+        //truncated
+        assert!(WakeParams::<Gm17Keys<Bls12_377>>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(WakeParams::<Gm17Keys<Bls12_377>>::from_bytes(&[]).is_err());
+    }
+}
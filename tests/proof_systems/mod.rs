@@ -1,3 +1,8 @@
+pub mod test_batch_verification;
 pub mod test_dl_knowledge_proof;
 pub mod test_proof_system_gm17;
+pub mod test_proof_system_gm17_aggregation;
+pub mod test_proof_system_gm17_pedersen;
+pub mod test_proof_system_marlin;
+pub mod test_r1cs_proof;
 pub mod test_range_proof;
\ No newline at end of file
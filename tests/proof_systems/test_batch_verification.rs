@@ -0,0 +1,219 @@
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::{RistrettoPoint, CompressedRistretto}};
+    use rand::rngs::OsRng;
+    use wake_implementation::proof_systems::{
+        batch_verification::BatchVerifier,
+        discrete_log_knowledege_proof::{DiscreteLogKnowledgeProofStruct, DiscreteLogKnowledgeProof},
+        range_proof::RangeProofStruct
+    };
+    use wake_implementation::utility::{hash_string_to_scalar, ristretto_to_string, compressed_ristretto_to_string};
+    use bulletproofs::{PedersenGens, RangeProof};
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref G: RistrettoPoint = PedersenGens::default().B;
+        static ref H: RistrettoPoint = PedersenGens::default().B_blinding;
+        static ref MIN_BAL: u64 = 100;
+    }
+
+    //range proof of a balance, with the commitment to v = upperbound - (b - min_bal) it is verified against
+    fn range_proof(range_proof_struct: &RangeProofStruct, b: u64, upperbound: u64, aux: &str) -> (RangeProof, CompressedRistretto) {
+        let rng = &mut OsRng;
+        let r = Scalar::random(rng);
+        let comm = (*G)*Scalar::from(upperbound - (b - *MIN_BAL)) + (*H)*r;
+        let proof = range_proof_struct.prove(b, r, aux.to_string(), rng).unwrap();
+        return (proof, comm.compress())
+    }
+
+    ///
+    /// Test that a batch of valid range proofs verifies, for each of the supported upperbounds
+    ///
+    #[test]
+    fn batch_range_proofs() {
+        for upperbound in [u8::MAX as u64, u16::MAX as u64, u32::MAX as u64, u64::MAX] {
+            let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+            let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+            for i in 0..4 {
+                let aux = format!("party {}", i);
+                let (proof, comm) = range_proof(&range_proof_struct, *MIN_BAL + 10 * i as u64, upperbound, &aux);
+                batch_verifier.add_range_proof(i, proof, comm, aux);
+            }
+
+            assert_eq!(batch_verifier.verify(&mut OsRng), Ok(()));
+        }
+    }
+
+    ///
+    /// Test that an empty batch verifies
+    ///
+    #[test]
+    fn empty_batch() {
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, u32::MAX as u64);
+        assert_eq!(BatchVerifier::new(&range_proof_struct).verify(&mut OsRng), Ok(()));
+    }
+
+    ///
+    /// Test that range proofs and discrete log knowledge proofs verify together
+    ///
+    #[test]
+    fn batch_range_and_discrete_log_proofs() {
+        let rng = &mut OsRng;
+        let upperbound = u32::MAX as u64;
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+        let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+        for i in 0..3 {
+            let x = Scalar::random(rng);
+            let dl_proof_struct = DiscreteLogKnowledgeProofStruct::new(((*G)*x).compress());
+            batch_verifier.add_discrete_log_knowledge_proof(i, dl_proof_struct, dl_proof_struct.prove(x, "dl".to_string(), rng), "dl".to_string());
+
+            let (proof, comm) = range_proof(&range_proof_struct, 5000, upperbound, "rp");
+            batch_verifier.add_range_proof(i, proof, comm, "rp".to_string());
+        }
+
+        assert_eq!(batch_verifier.verify(rng), Ok(()));
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that the batch fails, and the party of a range proof under a different aux string or commitment is identified
+    ///
+    #[test]
+    fn batch_identifies_invalid_range_proofs() {
+        let upperbound = u16::MAX as u64;
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+        let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+
+        let (proof, comm) = range_proof(&range_proof_struct, 1000, upperbound, "test123");
+        batch_verifier.add_range_proof(0, proof, comm, "test123".to_string());
+
+        //different aux string
+        let (proof, comm) = range_proof(&range_proof_struct, 1000, upperbound, "test123");
+        batch_verifier.add_range_proof(3, proof, comm, "different string".to_string());
+
+        //different commitment
+        let (proof, _) = range_proof(&range_proof_struct, 1000, upperbound, "test123");
+        let (_, other_comm) = range_proof(&range_proof_struct, 1000, upperbound, "test123");
+        batch_verifier.add_range_proof(1, proof, other_comm, "test123".to_string());
+
+        assert_eq!(batch_verifier.verify(&mut OsRng), Err(vec![1, 3]));
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that the party of a forged discrete log knowledge proof is identified, where the commitment is solved for
+    /// as g^response * statement^(-challenge) under a challenge that does not hash it
+    ///
+    #[test]
+    fn batch_identifies_forged_discrete_log_proof() {
+        let rng = &mut OsRng;
+        let upperbound = u8::MAX as u64;
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+        let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+
+        let x = Scalar::random(rng);
+        let dl_proof_struct = DiscreteLogKnowledgeProofStruct::new(((*G)*x).compress());
+        batch_verifier.add_discrete_log_knowledge_proof(0, dl_proof_struct, dl_proof_struct.prove(x, "aux".to_string(), rng), "aux".to_string());
+
+        //forgery for a statement whose discrete logarithm is unknown
+        let statement = (*G)*Scalar::random(rng);
+        let challenge = hash_string_to_scalar(ristretto_to_string(*G) + &compressed_ristretto_to_string(statement.compress()) + "aux");
+        let response = Scalar::random(rng);
+        let forged_proof = DiscreteLogKnowledgeProof { statement: ((*G)*response - statement*challenge).compress(), challenge, response };
+        batch_verifier.add_discrete_log_knowledge_proof(1, DiscreteLogKnowledgeProofStruct::new(statement.compress()), forged_proof, "aux".to_string());
+
+        assert_eq!(batch_verifier.verify(rng), Err(vec![1]));
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that the party of a discrete log knowledge proof for another statement is identified
+    ///
+    #[test]
+    fn batch_identifies_invalid_discrete_log_proof() {
+        let rng = &mut OsRng;
+        let upperbound = u32::MAX as u64;
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+        let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+
+        let (proof, comm) = range_proof(&range_proof_struct, 5000, upperbound, "");
+        batch_verifier.add_range_proof(0, proof, comm, String::new());
+
+        let x = Scalar::random(rng);
+        let dl_proof_struct = DiscreteLogKnowledgeProofStruct::new(((*G)*x).compress());
+        let dl_proof = dl_proof_struct.prove(x, String::new(), rng);
+        batch_verifier.add_discrete_log_knowledge_proof(0, dl_proof_struct, dl_proof, String::new());
+
+        //proof of knowledge of x, verified against the statement of another party
+        let other_dl_proof_struct = DiscreteLogKnowledgeProofStruct::new(((*G)*Scalar::random(rng)).compress());
+        batch_verifier.add_discrete_log_knowledge_proof(2, other_dl_proof_struct, dl_proof, String::new());
+
+        assert_eq!(batch_verifier.verify(rng), Err(vec![2]));
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that a range proof for a balance below the minimum balance is identified
+    ///
+    #[test]
+    fn batch_identifies_balance_below_min_bal() {
+        let rng = &mut OsRng;
+        let upperbound = u8::MAX as u64;
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+        let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+
+        let (proof, comm) = range_proof(&range_proof_struct, 200, upperbound, "");
+        batch_verifier.add_range_proof(0, proof, comm, String::new());
+
+        //proof of v = upperbound - (b - min_bal), against a commitment with a balance 1 below the minimum balance
+        let r = Scalar::random(rng);
+        let proof = range_proof_struct.prove(*MIN_BAL, r, String::new(), rng).unwrap();
+        let comm = (*G)*(Scalar::from(upperbound) + Scalar::one()) + (*H)*r;
+        batch_verifier.add_range_proof(1, proof, comm.compress(), String::new());
+
+        assert_eq!(batch_verifier.verify(rng), Err(vec![1]));
+    }
+
+    //copy of the proof with the 32 byte element at position i replaced, where the proof layout is A, S, T_1, T_2, t_x, t_x_blinding,
+    //e_blinding, the pairs L, R of the inner product proof, and its scalars a, b
+    fn tamper(proof: &RangeProof, i: usize, replacement: [u8; 32]) -> RangeProof {
+        let mut bytes = proof.to_bytes();
+        bytes[32 * i..32 * (i + 1)].copy_from_slice(&replacement);
+        RangeProof::from_bytes(&bytes).unwrap()
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that the batch verification equation agrees with verify_single of bulletproofs: a valid proof is accepted by both,
+    /// and a proof with A, S, T_1, t_x, the first L or the first R replaced is rejected by both
+    ///
+    #[test]
+    fn batch_agrees_with_verify_single() {
+        let rng = &mut OsRng;
+        let upperbound = u8::MAX as u64;
+        let range_proof_struct = RangeProofStruct::new(*MIN_BAL, upperbound);
+        let (proof, comm) = range_proof(&range_proof_struct, 200, upperbound, "test123");
+
+        let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+        batch_verifier.add_range_proof(0, proof.clone(), comm, "test123".to_string());
+        assert!(range_proof_struct.verify(proof.clone(), comm, "test123".to_string()));
+        assert_eq!(batch_verifier.verify(rng), Ok(()));
+
+        let random_point = || RistrettoPoint::random(&mut OsRng).compress().to_bytes();
+        let tampered_proofs = [
+            ("A", tamper(&proof, 0, random_point())),
+            ("S", tamper(&proof, 1, random_point())),
+            ("T_1", tamper(&proof, 2, random_point())),
+            ("t_x", tamper(&proof, 4, Scalar::random(rng).to_bytes())),
+            ("L", tamper(&proof, 7, random_point())),
+            ("R", tamper(&proof, 8, random_point())),
+        ];
+        for (element, tampered_proof) in tampered_proofs {
+            let mut batch_verifier = BatchVerifier::new(&range_proof_struct);
+            batch_verifier.add_range_proof(0, tampered_proof.clone(), comm, "test123".to_string());
+            assert!(!range_proof_struct.verify(tampered_proof, comm, "test123".to_string()), "verify_single accepted a proof with another {}", element);
+            assert_eq!(batch_verifier.verify(rng), Err(vec![0]), "batch accepted a proof with another {}", element);
+        }
+    }
+}
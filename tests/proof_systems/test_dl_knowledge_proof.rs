@@ -2,7 +2,8 @@
 mod tests {
     use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
     use rand::rngs::OsRng;
-    use wake_implementation::proof_systems::discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct;
+    use wake_implementation::proof_systems::discrete_log_knowledege_proof::{DiscreteLogKnowledgeProofStruct, DiscreteLogKnowledgeProof};
+    use wake_implementation::utility::{hash_string_to_scalar, ristretto_to_string, compressed_ristretto_to_string};
     use bulletproofs::PedersenGens;
     use lazy_static::lazy_static;
 
@@ -11,11 +12,6 @@ mod tests {
     lazy_static!{
         static ref G: RistrettoPoint = PedersenGens::default().B;
         static ref H: RistrettoPoint = PedersenGens::default().B_blinding;
-        static ref RNG: &'static mut OsRng = {
-            let mut rng = OsRng;
-            let rng_ptr: *mut OsRng = &mut rng;
-            unsafe { &mut *rng_ptr }
-        };
     }
 
     ///
@@ -23,12 +19,13 @@ mod tests {
     /// 
     #[test]
     fn discrete_log_knowledege_proof_test() {        
-        let witness = Scalar::random(&mut RNG.clone());
+        let rng = &mut OsRng;
+        let witness = Scalar::random(rng);
         let public_val = (*G) * witness;
         
         let dl_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(public_val.compress());
         
-        let proof = dl_knowledge_proof_struct.prove(witness, String::new());
+        let proof = dl_knowledge_proof_struct.prove(witness, String::new(), rng);
         
         let verification = dl_knowledge_proof_struct.verify(proof, String::new());
         
@@ -40,12 +37,13 @@ mod tests {
     /// 
     #[test]
     fn discrete_log_knowledege_proof_test_challenge_neg() {
-        let witness = Scalar::random(&mut RNG.clone());
+        let rng = &mut OsRng;
+        let witness = Scalar::random(rng);
         let public_val = (*G) * witness;
         
         let dl_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(public_val.compress());
         
-        let proof = dl_knowledge_proof_struct.prove(witness, "prover string".to_string());
+        let proof = dl_knowledge_proof_struct.prove(witness, "prover string".to_string(), rng);
         
         let verification = dl_knowledge_proof_struct.verify(proof, "verifier string".to_string());
         
@@ -57,13 +55,14 @@ mod tests {
     /// 
     #[test]
     fn discrete_log_knowledege_proof_test_witness_neg() {        
-        let witness = Scalar::random(&mut RNG.clone());
+        let rng = &mut OsRng;
+        let witness = Scalar::random(rng);
         let public_val = (*G) * witness;
         
         let dl_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(public_val.compress());
         
         //prove with witness + 1, instead of just witness
-        let proof = dl_knowledge_proof_struct.prove(witness + Scalar::from(1 as u64), String::new());
+        let proof = dl_knowledge_proof_struct.prove(witness + Scalar::from(1 as u64), String::new(), rng);
         
         let verification = dl_knowledge_proof_struct.verify(proof, String::new());
         
@@ -75,12 +74,13 @@ mod tests {
     /// 
     #[test]
     fn discrete_log_knowledege_proof_test_proof_neg() {        
-        let witness = Scalar::random(&mut RNG.clone());
+        let rng = &mut OsRng;
+        let witness = Scalar::random(rng);
         let public_val = (*G) * witness;
         
         let dl_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(public_val.compress());
         
-        let real_proof = dl_knowledge_proof_struct.prove(witness, String::new());
+        let real_proof = dl_knowledge_proof_struct.prove(witness, String::new(), rng);
         
         // create copies of the properly constructed proof
         let mut proof_1 = real_proof.clone();
@@ -101,4 +101,29 @@ mod tests {
         assert!(!verification_3);
     }
 
+    //proof for a statement, without knowing its discrete logarithm, under a challenge that does not hash the commitment:
+    //the response is picked first, and the commitment solved for as g^response * statement^(-challenge)
+    fn forged_proof(statement: RistrettoPoint, auxilliary_challenge_string: &str) -> DiscreteLogKnowledgeProof {
+        let challenge = hash_string_to_scalar(ristretto_to_string(*G) + &compressed_ristretto_to_string(statement.compress()) + auxilliary_challenge_string);
+        let response = Scalar::random(&mut OsRng);
+        let commitment = (*G) * response - statement * challenge;
+        DiscreteLogKnowledgeProof { statement: commitment.compress(), challenge, response }
+    }
+
+    ///
+    /// *Negative test*
+    /// Test verification false, for a proof whose commitment is solved for after picking the response
+    /// 
+    #[test]
+    fn discrete_log_knowledege_proof_test_forgery_neg() {
+        let statement = (*G) * Scalar::random(&mut OsRng);
+        let dl_knowledge_proof_struct = DiscreteLogKnowledgeProofStruct::new(statement.compress());
+
+        let proof = forged_proof(statement, "message");
+
+        //the forgery satisfies the verification equation, but not the challenge binding the commitment
+        assert_eq!((*G) * proof.response, proof.statement.decompress().unwrap() + statement * proof.challenge);
+        assert!(!dl_knowledge_proof_struct.verify(proof, "message".to_string()));
+    }
+
 }
\ No newline at end of file
@@ -1,145 +1,494 @@
 #[cfg(test)]
 mod tests {
-    use ark_bls12_377::Fr;
-    use wake_implementation::proof_systems::proof_system_gm17::{Gm17Darkpool};
-    use wake_implementation::utility::mimc;
-    use rand::{rngs::OsRng, Rng};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::PairingEngine;
+    use ark_ff::{Field, PrimeField, UniformRand};
+    use ark_relations::{lc, ns, r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, Variable}};
+    use wake_implementation::proof_systems::proof_system_gm17::{Gm17Darkpool, Gm17BatchItem};
+    use wake_implementation::utility::{fr_to_bits, mimc};
+    use rand::rngs::OsRng;
     use lazy_static::lazy_static;
     
     //setup of shared variables
     lazy_static!{
-        static ref RNG: &'static mut OsRng = {
-            let mut rng = OsRng;
-            let rng_ptr: *mut OsRng = &mut rng;
-            unsafe { &mut *rng_ptr }
-        };
         static ref STANDARD_BALANCE: u64 = 100000;
         static ref STANDARD_MIN_BAL: u64 = 100;
         static ref MIMC_ROUNDS: usize = 322;
     }
     
-    #[test]
     /// Tests proof correctly validates with correct inputs to prover and verifier
     /// Performed with different combinations of v
-    fn test_positive_behaviour(){
+    fn positive_behaviour<E: PairingEngine>(){
+        let rng = &mut OsRng;
         //reusable setup
         let upperbound = u64::MAX;
-        let r: Fr = RNG.clone().gen();
-        let gm17_darkpool =  Gm17Darkpool::new(upperbound as usize).unwrap();
-        let (pk, pvk) = gm17_darkpool.clone().setup();
+        let r = E::Fr::rand(rng);
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
         
         //test with no balance or minimum balance
         let balance = 0;
         let min_bal = 0;
-        let v = Fr::from(upperbound - balance + min_bal);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let v = E::Fr::from(upperbound - balance + min_bal);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(verification_status);
         
         //test with equal balance and minimum balance
         let balance = 10000;
         let min_bal = 10000;
-        let v = Fr::from(upperbound - balance + min_bal);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let v = E::Fr::from(upperbound - balance + min_bal);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(verification_status);
         
         //test with no minimum balance
         let balance = 10000;
         let min_bal = 0;
-        let v = Fr::from(upperbound - balance + min_bal);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let v = E::Fr::from(upperbound - balance + min_bal);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(verification_status);
         
         //test with very large values 
         let balance = u64::MAX;
         let min_bal = u64::MAX;
-        let v = Fr::from(upperbound - balance + min_bal);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let v = E::Fr::from(upperbound - balance + min_bal);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(verification_status);
         
         //test with very large values and no minimum balance
         let balance = u64::MAX;
         let min_bal = 0;
-        let v = Fr::from(upperbound - balance + min_bal);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let v = E::Fr::from(upperbound - balance + min_bal);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(verification_status);
         
         //test with non-empty aux strings
-        let v = Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
-        let proof = gm17_darkpool.clone().prove(v, r, "test123".to_string(), pk.clone());
+        let v = E::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let proof = gm17_darkpool.prove(v, r, "test123".to_string(), &pk, rng);
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "test123".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "test123".to_string(), &pvk, &proof);
         assert!(verification_status);
     }
-    
+
     #[test]
-    fn negative_incorrect_image(){
+    fn test_positive_behaviour_bls12_377(){
+        positive_behaviour::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_positive_behaviour_bls12_381(){
+        positive_behaviour::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_positive_behaviour_bn254(){
+        positive_behaviour::<Bn254>();
+    }
+
+    fn negative_incorrect_image<E: PairingEngine>(){
+        let rng = &mut OsRng;
         //reusable setup
         let upperbound = u64::MAX;
-        let gm17_darkpool =  Gm17Darkpool::new(upperbound as usize).unwrap();
-        let (pk, pvk) = gm17_darkpool.clone().setup();
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
         
-        let r: Fr = RNG.clone().gen();
-        let v = Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
         
         //test with image not produced by correct "v"
-        let incorrect_v = Fr::from(123456789);
+        let incorrect_v = E::Fr::from(123456789u64);
         let image = mimc(incorrect_v, r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof.clone());
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(!verification_status);
 
         //test with image not produced by correct "r"
-        let incorrect_r = Fr::from(987654321);
+        let incorrect_r = E::Fr::from(987654321u64);
         let image = mimc(v, incorrect_r, &gm17_darkpool.mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof.clone());
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(!verification_status);
 
     }
 
     #[test]
-    fn negative_incorrect_mimc_constants(){
+    fn negative_incorrect_image_bls12_377(){
+        negative_incorrect_image::<Bls12_377>();
+    }
+
+    #[test]
+    fn negative_incorrect_image_bls12_381(){
+        negative_incorrect_image::<Bls12_381>();
+    }
+
+    #[test]
+    fn negative_incorrect_image_bn254(){
+        negative_incorrect_image::<Bn254>();
+    }
+
+    fn negative_incorrect_mimc_constants<E: PairingEngine>(){
+        let rng = &mut OsRng;
         //reusable setup
         let upperbound = u64::MAX;
-        let gm17_darkpool =  Gm17Darkpool::new(upperbound as usize).unwrap();
-        let (pk, pvk) = gm17_darkpool.clone().setup();
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
         
         //test with newly generated mimc constants
-        let r: Fr = RNG.clone().gen();
-        let v = Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
-        let proof = gm17_darkpool.clone().prove(v, r, "".to_string(), pk.clone());
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
 
-        let incorrect_mimc_constants = (0..*MIMC_ROUNDS).map(|_| RNG.clone().gen()).collect::<Vec<_>>();
+        let incorrect_mimc_constants = (0..*MIMC_ROUNDS).map(|_| E::Fr::rand(rng)).collect::<Vec<_>>();
         let image = mimc(v, r, &incorrect_mimc_constants);
-        let verification_status = gm17_darkpool.clone().verify(image, "".to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, "".to_string(), &pvk, &proof);
         assert!(!verification_status);
     }
 
     #[test]
-    fn negative_unequal_aux_strings(){
+    fn negative_incorrect_mimc_constants_bls12_377(){
+        negative_incorrect_mimc_constants::<Bls12_377>();
+    }
+
+    #[test]
+    fn negative_incorrect_mimc_constants_bls12_381(){
+        negative_incorrect_mimc_constants::<Bls12_381>();
+    }
+
+    #[test]
+    fn negative_incorrect_mimc_constants_bn254(){
+        negative_incorrect_mimc_constants::<Bn254>();
+    }
+
+    fn negative_unequal_aux_strings<E: PairingEngine>(){
+        let rng = &mut OsRng;
         //reusable setup
         let upperbound = u64::MAX;
-        let gm17_darkpool =  Gm17Darkpool::new(upperbound as usize).unwrap();
-        let (pk, pvk) = gm17_darkpool.clone().setup();
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
         
         //test with different aux strings for prover and verifier
-        let r: Fr = RNG.clone().gen();
-        let v = Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
         let prover_aux_string = "test123";
-        let proof = gm17_darkpool.clone().prove(v, r, prover_aux_string.to_string(), pk.clone());
+        let proof = gm17_darkpool.prove(v, r, prover_aux_string.to_string(), &pk, rng);
 
         let image = mimc(v, r, &gm17_darkpool.mimc_constants);
         let verifier_aux_string = "different string";
-        let verification_status = gm17_darkpool.clone().verify(image, verifier_aux_string.to_string(), pvk.clone(), proof);
+        let verification_status = gm17_darkpool.verify(image, verifier_aux_string.to_string(), &pvk, &proof);
         assert!(!verification_status);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn negative_unequal_aux_strings_bls12_377(){
+        negative_unequal_aux_strings::<Bls12_377>();
+    }
+
+    #[test]
+    fn negative_unequal_aux_strings_bls12_381(){
+        negative_unequal_aux_strings::<Bls12_381>();
+    }
+
+    #[test]
+    fn negative_unequal_aux_strings_bn254(){
+        negative_unequal_aux_strings::<Bn254>();
+    }
+
+    /// Tests that inspecting the circuit with a valid witness reports a satisfied constraint system of the expected size
+    fn inspect_satisfied<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        let upperbound = u64::MAX;
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let image = mimc(v, r, &gm17_darkpool.mimc_constants);
+        let inspection = gm17_darkpool.inspect(v, r, "test123".to_string(), image).unwrap();
+
+        //one constraint per bit, one for reconstructing v, and two per MiMC round
+        assert_eq!(inspection.num_constraints, 64 + 1 + 2 * *MIMC_ROUNDS);
+        //the one-variable, aux and the image
+        assert_eq!(inspection.num_instance_variables, 3);
+        assert!(inspection.is_satisfied);
+        assert!(inspection.first_unsatisfied.is_none());
+    }
+
+    #[test]
+    fn test_inspect_satisfied_bls12_377(){
+        inspect_satisfied::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_inspect_satisfied_bls12_381(){
+        inspect_satisfied::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_inspect_satisfied_bn254(){
+        inspect_satisfied::<Bn254>();
+    }
+
+    /// Tests that inspecting the circuit against an image of another witness reports the MiMC rounds as unsatisfied
+    fn inspect_incorrect_image<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        let upperbound = u16::MAX as u64;
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound - 1000 + *STANDARD_MIN_BAL);
+        let image = mimc(E::Fr::from(123456789u64), r, &gm17_darkpool.mimc_constants);
+        let inspection = gm17_darkpool.inspect(v, r, "".to_string(), image).unwrap();
+
+        assert!(!inspection.is_satisfied);
+        assert!(inspection.first_unsatisfied.unwrap().contains("round"));
+    }
+
+    #[test]
+    fn test_inspect_incorrect_image_bls12_377(){
+        inspect_incorrect_image::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_inspect_incorrect_image_bls12_381(){
+        inspect_incorrect_image::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_inspect_incorrect_image_bn254(){
+        inspect_incorrect_image::<Bn254>();
+    }
+
+    /// Tests that inspecting the circuit with a v too large for the upperbound reports the reconstruction of v as unsatisfied
+    fn inspect_v_out_of_range<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        let upperbound = u8::MAX as u64;
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+
+        //balance below the minimum balance, such that v exceeds 2^8-1
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound + 1);
+        let image = mimc(v, r, &gm17_darkpool.mimc_constants);
+        let inspection = gm17_darkpool.inspect(v, r, "".to_string(), image).unwrap();
+
+        assert_eq!(inspection.num_constraints, 8 + 1 + 2 * *MIMC_ROUNDS);
+        assert!(!inspection.is_satisfied);
+        assert!(inspection.first_unsatisfied.unwrap().contains("reconstruction"));
+    }
+
+    #[test]
+    fn test_inspect_v_out_of_range_bls12_377(){
+        inspect_v_out_of_range::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_inspect_v_out_of_range_bls12_381(){
+        inspect_v_out_of_range::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_inspect_v_out_of_range_bn254(){
+        inspect_v_out_of_range::<Bn254>();
+    }
+
+    /// Tests a batch of proofs under per-party verifying keys, where two proofs share a verifying key
+    fn batch_verify_positive<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        let upperbound = u32::MAX;
+        let gm17_darkpool = Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let keys: Vec<_> = (0..3).map(|_| gm17_darkpool.setup(rng)).collect();
+
+        let mut batch = Vec::new();
+        for (idx, key_idx) in [0, 1, 2, 2].into_iter().enumerate() {
+            let (pk, pvk) = &keys[key_idx];
+            let r = E::Fr::rand(rng);
+            let v = E::Fr::from(upperbound as u64 - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+            let aux = format!("party {}", idx);
+            let proof = gm17_darkpool.prove(v, r, aux.clone(), &pk, rng);
+            let image = mimc(v, r, &gm17_darkpool.mimc_constants);
+            batch.push(Gm17BatchItem { idx, image, aux, pvk, proof });
+        }
+
+        assert_eq!(gm17_darkpool.batch_verify(&batch, rng), Ok(()));
+        assert_eq!(gm17_darkpool.batch_verify(&[], rng), Ok(()));
+    }
+
+    #[test]
+    fn test_batch_verify_positive_bls12_377(){
+        batch_verify_positive::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_batch_verify_positive_bls12_381(){
+        batch_verify_positive::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_batch_verify_positive_bn254(){
+        batch_verify_positive::<Bn254>();
+    }
+
+    /// Tests that a failing batch identifies the parties whose proof has an incorrect image, aux string or verifying key
+    fn batch_verify_identifies_invalid<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        let upperbound = u16::MAX;
+        let gm17_darkpool = Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let keys: Vec<_> = (0..2).map(|_| gm17_darkpool.setup(rng)).collect();
+
+        let mut batch = Vec::new();
+        for idx in 0..5 {
+            let (pk, pvk) = &keys[idx % 2];
+            let r = E::Fr::rand(rng);
+            let v = E::Fr::from(upperbound as u64 - 1000 + *STANDARD_MIN_BAL);
+            let proof = gm17_darkpool.prove(v, r, "test123".to_string(), &pk, rng);
+            let image = mimc(v, r, &gm17_darkpool.mimc_constants);
+            batch.push(Gm17BatchItem { idx, image, aux: "test123".to_string(), pvk, proof });
+        }
+
+        //incorrect image, aux string, and verifying key
+        batch[1].image = mimc(E::Fr::from(123456789u64), E::Fr::from(1u64), &gm17_darkpool.mimc_constants);
+        batch[2].aux = "different string".to_string();
+        batch[4].pvk = &keys[1].1;
+
+        assert_eq!(gm17_darkpool.batch_verify(&batch, rng), Err(vec![1, 2, 4]));
+    }
+
+    #[test]
+    fn test_batch_verify_identifies_invalid_bls12_377(){
+        batch_verify_identifies_invalid::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_batch_verify_identifies_invalid_bls12_381(){
+        batch_verify_identifies_invalid::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_batch_verify_identifies_invalid_bn254(){
+        batch_verify_identifies_invalid::<Bn254>();
+    }
+
+    ///
+    /// darkpool circuit as it was before the bit decomposition was linked to v: the first partial sum is an unconstrained witness,
+    /// and v is allocated without any constraint to the sum of its bits. v holds the assignment of v chosen by the prover
+    /// 
+    struct UnlinkedDarkpoolCircuit<'a, F: Field> {
+        aux: F,
+        v: F,
+        v_bits: &'a Vec<Option<F>>,
+        r: F,
+        constants: &'a [F],
+        upperbound: usize,
+    }
+
+    impl<'a, F: Field> ConstraintSynthesizer<F> for UnlinkedDarkpoolCircuit<'a, F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            cs.new_input_variable(|| Ok(self.aux))?;
+            let mut r_value = self.r;
+            let mut r = cs.new_witness_variable(|| Ok(r_value))?;
+
+            let mut v_bits_val: Vec<F> = self.v_bits.iter().map(|b| b.unwrap()).collect();
+            v_bits_val.reverse();
+
+            let mut curr_exp = F::one();
+            let mut curr_sum = F::one();
+            let mut curr_sum_var = cs.new_witness_variable(|| Ok(curr_exp))?;
+            for b_i in v_bits_val.into_iter().take(self.upperbound) {
+                let b_i_var = cs.new_witness_variable(|| Ok(b_i))?;
+                let a_i_var = cs.new_witness_variable(|| Ok(F::one() - b_i))?;
+                cs.enforce_constraint(lc!() + b_i_var, lc!() + a_i_var, lc!())?;
+
+                let curr_val = curr_exp * b_i;
+                let curr_val_var = cs.new_witness_variable(|| Ok(curr_val))?;
+                let new_curr_sum = curr_sum + curr_val;
+                let new_curr_sum_var = cs.new_witness_variable(|| Ok(new_curr_sum))?;
+                cs.enforce_constraint(lc!() + curr_sum_var, lc!() + Variable::One, lc!() + new_curr_sum_var - curr_val_var)?;
+
+                curr_sum = new_curr_sum;
+                curr_sum_var = new_curr_sum_var;
+                curr_exp.double_in_place();
+            }
+
+            let mut v_value = self.v;
+            let mut v = cs.new_witness_variable(|| Ok(v_value))?;
+            for (i, constant) in self.constants.iter().enumerate() {
+                let ns = ns!(cs, "round");
+                let cs = ns.cs();
+
+                let tmp_value = (v_value + constant).square();
+                let tmp = cs.new_witness_variable(|| Ok(tmp_value))?;
+                cs.enforce_constraint(lc!() + v + (*constant, Variable::One), lc!() + v + (*constant, Variable::One), lc!() + tmp)?;
+
+                let new_v_value = (v_value + constant) * tmp_value + r_value;
+                let new_v = if i == self.constants.len() - 1 {
+                    cs.new_input_variable(|| Ok(new_v_value))?
+                } else {
+                    cs.new_witness_variable(|| Ok(new_v_value))?
+                };
+                cs.enforce_constraint(lc!() + tmp, lc!() + v + (*constant, Variable::One), lc!() + new_v - r)?;
+
+                r = v;
+                r_value = v_value;
+                v = new_v;
+                v_value = new_v_value;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// *Negative test*
+    /// Regression test for the soundness of the range check: a v exceeding the upperbound, with the bits of v modulo the upperbound,
+    /// satisfies the constraints of the circuit before v was linked to its bits, while the current circuit rejects it
+    fn unlinked_v_out_of_range<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        let upperbound = u8::MAX as u64;
+        let gm17_darkpool =  Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let upperbound_exp = gm17_darkpool.upperbound_exp();
+
+        //v = 2^8, whose 8 lowest bits are all zero
+        let r = E::Fr::rand(rng);
+        let v = E::Fr::from(upperbound + 1);
+        let v_bits = fr_to_bits(v, upperbound_exp);
+        let image = mimc(v, r, &gm17_darkpool.mimc_constants);
+
+        let cs = ConstraintSystem::<E::Fr>::new_ref();
+        let c = UnlinkedDarkpoolCircuit {
+            aux: E::Fr::from_be_bytes_mod_order(b"test123"),
+            v,
+            v_bits: &v_bits,
+            r,
+            constants: &gm17_darkpool.mimc_constants,
+            upperbound: upperbound_exp,
+        };
+        c.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let inspection = gm17_darkpool.inspect(v, r, "test123".to_string(), image).unwrap();
+        assert!(!inspection.is_satisfied);
+        assert!(inspection.first_unsatisfied.unwrap().contains("reconstruction"));
+    }
+
+    #[test]
+    fn test_unlinked_v_out_of_range_bls12_377(){
+        unlinked_v_out_of_range::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_unlinked_v_out_of_range_bls12_381(){
+        unlinked_v_out_of_range::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_unlinked_v_out_of_range_bn254(){
+        unlinked_v_out_of_range::<Bn254>();
+    }
+}
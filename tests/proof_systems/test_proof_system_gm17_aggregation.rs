@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+    use ark_ff::UniformRand;
+    use ark_gm17::{PreparedVerifyingKey, Proof};
+    use wake_implementation::proof_systems::{proof_system_gm17::Gm17Darkpool, proof_system_gm17_aggregation::AggregationSRS};
+    use wake_implementation::utility::mimc;
+    use rand::rngs::OsRng;
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref STANDARD_BALANCE: u64 = 100000;
+        static ref STANDARD_MIN_BAL: u64 = 100;
+    }
+
+    //proofs of proof_amount parties under the same verifying key, with their images and aux strings
+    fn proofs<E: PairingEngine>(proof_amount: usize) -> (PreparedVerifyingKey<E>, Vec<Proof<E>>, Vec<E::Fr>, Vec<String>) {
+        let rng = &mut OsRng;
+        let upperbound = u32::MAX as u64;
+        let gm17_darkpool = Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
+
+        let mut proofs = Vec::new();
+        let mut images = Vec::new();
+        let mut auxs = Vec::new();
+        for i in 0..proof_amount {
+            let v = E::Fr::from(upperbound - (*STANDARD_BALANCE + i as u64) + *STANDARD_MIN_BAL);
+            let r = E::Fr::rand(rng);
+            let aux = format!("party {}", i);
+            proofs.push(gm17_darkpool.prove(v, r, aux.clone(), &pk, rng));
+            images.push(mimc(v, r, &gm17_darkpool.mimc_constants));
+            auxs.push(aux);
+        }
+        (pvk, proofs, images, auxs)
+    }
+
+    ///
+    /// Test that aggregated proofs verify, for amounts of proofs which are and are not a power of two
+    ///
+    fn positive_behaviour<E: PairingEngine>() {
+        let rng = &mut OsRng;
+        let (pvk, proofs, images, auxs) = proofs::<E>(5);
+        let srs = AggregationSRS::<E>::setup(8, rng).unwrap();
+
+        for proof_amount in [1, 2, 3, 4, 5] {
+            let aggregate_proof = srs.aggregate(&pvk, &proofs[..proof_amount], &images[..proof_amount], &auxs[..proof_amount]).unwrap();
+            assert!(srs.verify(&pvk, &images[..proof_amount], &auxs[..proof_amount], &aggregate_proof));
+        }
+    }
+
+    ///
+    /// Test that the aggregate proof grows logarithmically in the amount of proofs
+    ///
+    fn logarithmic_size<E: PairingEngine>() {
+        let rng = &mut OsRng;
+        let (pvk, proofs, images, auxs) = proofs::<E>(4);
+        let srs = AggregationSRS::<E>::setup(4, rng).unwrap();
+
+        let size_2 = srs.aggregate(&pvk, &proofs[..2], &images[..2], &auxs[..2]).unwrap().serialized_size();
+        let size_3 = srs.aggregate(&pvk, &proofs[..3], &images[..3], &auxs[..3]).unwrap().serialized_size();
+        let size_4 = srs.aggregate(&pvk, &proofs, &images, &auxs).unwrap().serialized_size();
+        let size_1 = srs.aggregate(&pvk, &proofs[..1], &images[..1], &auxs[..1]).unwrap().serialized_size();
+        assert_eq!(size_3, size_4);
+        assert_eq!(size_2 - size_1, size_4 - size_2);
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that aggregation verification fails for a wrong image, aux string, or an invalid aggregated proof
+    ///
+    fn negative_behaviour<E: PairingEngine>() {
+        let rng = &mut OsRng;
+        let (pvk, proofs, images, auxs) = proofs::<E>(3);
+        let srs = AggregationSRS::<E>::setup(4, rng).unwrap();
+        let aggregate_proof = srs.aggregate(&pvk, &proofs, &images, &auxs).unwrap();
+        assert!(srs.verify(&pvk, &images, &auxs, &aggregate_proof));
+
+        //wrong image
+        let mut wrong_images = images.clone();
+        wrong_images[1] = E::Fr::rand(rng);
+        assert!(!srs.verify(&pvk, &wrong_images, &auxs, &aggregate_proof));
+
+        //wrong aux string
+        let mut wrong_auxs = auxs.clone();
+        wrong_auxs[2] = "different string".to_string();
+        assert!(!srs.verify(&pvk, &images, &wrong_auxs, &aggregate_proof));
+
+        //swapped public inputs
+        let swapped_images = vec![images[1], images[0], images[2]];
+        let swapped_auxs = vec![auxs[1].clone(), auxs[0].clone(), auxs[2].clone()];
+        assert!(!srs.verify(&pvk, &swapped_images, &swapped_auxs, &aggregate_proof));
+
+        //missing proof
+        assert!(!srs.verify(&pvk, &images[..2], &auxs[..2], &aggregate_proof));
+
+        //aggregate of a proof with a tampered element
+        let mut tampered_proofs = proofs.clone();
+        tampered_proofs[0].c = (tampered_proofs[0].c.into_projective() + E::G1Affine::prime_subgroup_generator().into_projective()).into_affine();
+        let tampered_aggregate_proof = srs.aggregate(&pvk, &tampered_proofs, &images, &auxs).unwrap();
+        assert!(!srs.verify(&pvk, &images, &auxs, &tampered_aggregate_proof));
+
+        //aggregate under a different SRS
+        let other_srs = AggregationSRS::<E>::setup(4, rng).unwrap();
+        assert!(!other_srs.verify(&pvk, &images, &auxs, &aggregate_proof));
+
+        //aggregate of more proofs than the SRS supports
+        let small_srs = AggregationSRS::<E>::setup(2, rng).unwrap();
+        assert!(small_srs.aggregate(&pvk, &proofs, &images, &auxs).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Test that aggregation verification fails if a single proof among valid ones was created for another aux string
+    ///
+    fn invalid_proof_among_valid<E: PairingEngine>() {
+        let rng = &mut OsRng;
+        let upperbound = u32::MAX as u64;
+        let gm17_darkpool = Gm17Darkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let (pk, pvk) = gm17_darkpool.setup(rng);
+        let srs = AggregationSRS::<E>::setup(4, rng).unwrap();
+
+        let mut proofs = Vec::new();
+        let mut images = Vec::new();
+        let mut auxs = Vec::new();
+        for i in 0..4 {
+            let v = E::Fr::from(upperbound - (*STANDARD_BALANCE + i as u64) + *STANDARD_MIN_BAL);
+            let r = E::Fr::rand(rng);
+            let aux = format!("party {}", i);
+            //party 2 proves its statement for the aux string of party 3
+            let proof_aux = if i == 2 { "party 3".to_string() } else { aux.clone() };
+            proofs.push(gm17_darkpool.prove(v, r, proof_aux, &pk, rng));
+            images.push(mimc(v, r, &gm17_darkpool.mimc_constants));
+            auxs.push(aux);
+        }
+        assert!(!gm17_darkpool.verify(images[2], auxs[2].clone(), &pvk, &proofs[2]));
+
+        let aggregate_proof = srs.aggregate(&pvk, &proofs, &images, &auxs).unwrap();
+        assert!(!srs.verify(&pvk, &images, &auxs, &aggregate_proof));
+    }
+
+    #[test]
+    fn aggregation_positive_behaviour_bls12_377() {
+        positive_behaviour::<Bls12_377>();
+    }
+
+    #[test]
+    fn aggregation_positive_behaviour_bls12_381() {
+        positive_behaviour::<Bls12_381>();
+    }
+
+    #[test]
+    fn aggregation_positive_behaviour_bn254() {
+        positive_behaviour::<Bn254>();
+    }
+
+    #[test]
+    fn aggregation_logarithmic_size() {
+        logarithmic_size::<Bls12_377>();
+    }
+
+    #[test]
+    fn aggregation_negative_behaviour_bls12_377() {
+        negative_behaviour::<Bls12_377>();
+    }
+
+    #[test]
+    fn aggregation_negative_behaviour_bn254() {
+        negative_behaviour::<Bn254>();
+    }
+
+    #[test]
+    fn aggregation_invalid_proof_among_valid_bls12_377() {
+        invalid_proof_among_valid::<Bls12_377>();
+    }
+
+    #[test]
+    fn aggregation_invalid_proof_among_valid_bn254() {
+        invalid_proof_among_valid::<Bn254>();
+    }
+}
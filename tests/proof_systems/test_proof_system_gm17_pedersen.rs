@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_377::Fr as EdFr;
+    use wake_implementation::proof_systems::proof_system_gm17_pedersen::Gm17PedersenDarkpool;
+    use rand::{rngs::OsRng, Rng};
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref STANDARD_BALANCE: u64 = 100000;
+        static ref STANDARD_MIN_BAL: u64 = 100;
+    }
+
+    #[test]
+    /// Tests proof correctly validates with correct inputs to prover and verifier
+    /// Performed with different combinations of v
+    fn test_positive_behaviour(){
+        let rng = &mut OsRng;
+        //reusable setup
+        let upperbound = u64::MAX;
+        let r: EdFr = rng.gen();
+        let gm17_pedersen_darkpool = Gm17PedersenDarkpool::new(upperbound as usize).unwrap();
+        let (pk, pvk) = gm17_pedersen_darkpool.setup(rng);
+
+        //test with no balance or minimum balance
+        let v = upperbound;
+        let proof = gm17_pedersen_darkpool.prove(v, r, "".to_string(), &pk, rng);
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+        assert!(gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+
+        //test with equal balance and minimum balance
+        let v = upperbound - (10000 - 10000);
+        let proof = gm17_pedersen_darkpool.prove(v, r, "".to_string(), &pk, rng);
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+        assert!(gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+
+        //test with very large balance and no minimum balance
+        let v = upperbound - u64::MAX;
+        let proof = gm17_pedersen_darkpool.prove(v, r, "".to_string(), &pk, rng);
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+        assert!(gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+
+        //test with non-empty aux strings
+        let v = upperbound - (*STANDARD_BALANCE - *STANDARD_MIN_BAL);
+        let proof = gm17_pedersen_darkpool.prove(v, r, "test123".to_string(), &pk, rng);
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+        assert!(gm17_pedersen_darkpool.verify(commitment, "test123".to_string(), &pvk, &proof));
+    }
+
+    #[test]
+    /// Tests that a homomorphically updated commitment can be proven with the updated balance and the same blinding
+    fn test_homomorphic_balance_update(){
+        let rng = &mut OsRng;
+        let upperbound = u32::MAX as u64;
+        let r: EdFr = rng.gen();
+        let gm17_pedersen_darkpool = Gm17PedersenDarkpool::new(upperbound as usize).unwrap();
+        let (pk, pvk) = gm17_pedersen_darkpool.setup(rng);
+
+        let v = upperbound - (*STANDARD_BALANCE - *STANDARD_MIN_BAL);
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+
+        //increase balance by 500, decrease it by 200
+        let updated_commitment = gm17_pedersen_darkpool.add_to_balance(commitment, 500);
+        let updated_commitment = gm17_pedersen_darkpool.subtract_from_balance(updated_commitment, 200);
+        let updated_v = upperbound - (*STANDARD_BALANCE + 500 - 200 - *STANDARD_MIN_BAL);
+        assert_eq!(updated_commitment, gm17_pedersen_darkpool.commit(updated_v, r));
+
+        let proof = gm17_pedersen_darkpool.prove(updated_v, r, "".to_string(), &pk, rng);
+        assert!(gm17_pedersen_darkpool.verify(updated_commitment, "".to_string(), &pvk, &proof));
+        assert!(!gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+    }
+
+    #[test]
+    fn negative_incorrect_commitment(){
+        let rng = &mut OsRng;
+        //reusable setup
+        let upperbound = u64::MAX;
+        let gm17_pedersen_darkpool = Gm17PedersenDarkpool::new(upperbound as usize).unwrap();
+        let (pk, pvk) = gm17_pedersen_darkpool.setup(rng);
+
+        let r: EdFr = rng.gen();
+        let v = upperbound - (*STANDARD_BALANCE - *STANDARD_MIN_BAL);
+        let proof = gm17_pedersen_darkpool.prove(v, r, "".to_string(), &pk, rng);
+
+        //test with commitment not produced by correct "v"
+        let commitment = gm17_pedersen_darkpool.commit(123456789, r);
+        assert!(!gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+
+        //test with commitment not produced by correct "r"
+        let commitment = gm17_pedersen_darkpool.commit(v, EdFr::from(987654321u64));
+        assert!(!gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+    }
+
+    #[test]
+    fn negative_v_out_of_range(){
+        let rng = &mut OsRng;
+        //reusable setup
+        let upperbound = u8::MAX as u64;
+        let gm17_pedersen_darkpool = Gm17PedersenDarkpool::new(upperbound as usize).unwrap();
+        let (pk, pvk) = gm17_pedersen_darkpool.setup(rng);
+
+        //v does not fit in 8 bits, so the commitment cannot be opened inside the circuit
+        let r: EdFr = rng.gen();
+        let v = upperbound + 1;
+        let proof = gm17_pedersen_darkpool.prove(v, r, "".to_string(), &pk, rng);
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+        assert!(!gm17_pedersen_darkpool.verify(commitment, "".to_string(), &pvk, &proof));
+    }
+
+    #[test]
+    fn negative_unequal_aux_strings(){
+        let rng = &mut OsRng;
+        //reusable setup
+        let upperbound = u64::MAX;
+        let gm17_pedersen_darkpool = Gm17PedersenDarkpool::new(upperbound as usize).unwrap();
+        let (pk, pvk) = gm17_pedersen_darkpool.setup(rng);
+
+        //test with different aux strings for prover and verifier
+        let r: EdFr = rng.gen();
+        let v = upperbound - (*STANDARD_BALANCE - *STANDARD_MIN_BAL);
+        let proof = gm17_pedersen_darkpool.prove(v, r, "test123".to_string(), &pk, rng);
+
+        let commitment = gm17_pedersen_darkpool.commit(v, r);
+        assert!(!gm17_pedersen_darkpool.verify(commitment, "different string".to_string(), &pvk, &proof));
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use ark_bls12_377::Bls12_377;
+    use ark_bn254::Bn254;
+    use ark_ec::PairingEngine;
+    use ark_ff::UniformRand;
+    use wake_implementation::proof_systems::proof_system_marlin::MarlinDarkpool;
+    use wake_implementation::utility::mimc;
+    use rand::rngs::OsRng;
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref STANDARD_BALANCE: u64 = 100000;
+        static ref STANDARD_MIN_BAL: u64 = 100;
+    }
+
+    /// Tests proof correctly validates with correct inputs to prover and verifier
+    /// Performed with different combinations of v
+    fn positive_behaviour<E: PairingEngine>(){
+        let rng = &mut OsRng;
+        //reusable setup
+        let upperbound = u64::MAX;
+        let r = E::Fr::rand(rng);
+        let marlin_darkpool = MarlinDarkpool::<E>::new(upperbound as usize, rng).unwrap();
+        let srs = marlin_darkpool.universal_setup(rng).unwrap();
+        let (pk, vk) = marlin_darkpool.index(&srs).unwrap();
+
+        //test with no balance or minimum balance
+        let v = E::Fr::from(upperbound);
+        let proof = marlin_darkpool.prove(v, r, "".to_string(), &pk, rng).unwrap();
+        let image = mimc(v, r, &marlin_darkpool.mimc_constants);
+        assert!(marlin_darkpool.verify(image, "".to_string(), &vk, &proof, rng));
+
+        //test with very large values and no minimum balance
+        let v = E::Fr::from(upperbound - u64::MAX);
+        let proof = marlin_darkpool.prove(v, r, "".to_string(), &pk, rng).unwrap();
+        let image = mimc(v, r, &marlin_darkpool.mimc_constants);
+        assert!(marlin_darkpool.verify(image, "".to_string(), &vk, &proof, rng));
+
+        //test with non-empty aux strings
+        let v = E::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let proof = marlin_darkpool.prove(v, r, "test123".to_string(), &pk, rng).unwrap();
+        let image = mimc(v, r, &marlin_darkpool.mimc_constants);
+        assert!(marlin_darkpool.verify(image, "test123".to_string(), &vk, &proof, rng));
+    }
+
+    #[test]
+    fn test_positive_behaviour_bls12_377(){
+        positive_behaviour::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_positive_behaviour_bn254(){
+        positive_behaviour::<Bn254>();
+    }
+
+    #[test]
+    /// Tests that a single universal SRS supports the darkpool circuit for different upperbounds
+    fn test_universal_srs_reused_across_upperbounds(){
+        let rng = &mut OsRng;
+        let r = <Bls12_377 as PairingEngine>::Fr::rand(rng);
+        let srs = MarlinDarkpool::<Bls12_377>::new(u64::MAX as usize, rng).unwrap().universal_setup(rng).unwrap();
+
+        for upperbound in [u8::MAX as u64, u16::MAX as u64, u32::MAX as u64] {
+            let marlin_darkpool = MarlinDarkpool::<Bls12_377>::new(upperbound as usize, rng).unwrap();
+            let (pk, vk) = marlin_darkpool.index(&srs).unwrap();
+
+            let v = <Bls12_377 as PairingEngine>::Fr::from(upperbound - (*STANDARD_MIN_BAL - *STANDARD_MIN_BAL));
+            let proof = marlin_darkpool.prove(v, r, "".to_string(), &pk, rng).unwrap();
+            let image = mimc(v, r, &marlin_darkpool.mimc_constants);
+            assert!(marlin_darkpool.verify(image, "".to_string(), &vk, &proof, rng));
+        }
+    }
+
+    #[test]
+    fn negative_incorrect_image(){
+        let rng = &mut OsRng;
+        let upperbound = u64::MAX;
+        let r = <Bls12_377 as PairingEngine>::Fr::rand(rng);
+        let marlin_darkpool = MarlinDarkpool::<Bls12_377>::new(upperbound as usize, rng).unwrap();
+        let srs = marlin_darkpool.universal_setup(rng).unwrap();
+        let (pk, vk) = marlin_darkpool.index(&srs).unwrap();
+
+        let v = <Bls12_377 as PairingEngine>::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let proof = marlin_darkpool.prove(v, r, "".to_string(), &pk, rng).unwrap();
+
+        //test with image not produced by correct "v"
+        let image = mimc(<Bls12_377 as PairingEngine>::Fr::from(123456789u64), r, &marlin_darkpool.mimc_constants);
+        assert!(!marlin_darkpool.verify(image, "".to_string(), &vk, &proof, rng));
+
+        //test with image not produced by correct "r"
+        let image = mimc(v, <Bls12_377 as PairingEngine>::Fr::from(987654321u64), &marlin_darkpool.mimc_constants);
+        assert!(!marlin_darkpool.verify(image, "".to_string(), &vk, &proof, rng));
+    }
+
+    #[test]
+    fn negative_v_out_of_range(){
+        let rng = &mut OsRng;
+        let upperbound = u8::MAX as u64;
+        let r = <Bls12_377 as PairingEngine>::Fr::rand(rng);
+        let marlin_darkpool = MarlinDarkpool::<Bls12_377>::new(upperbound as usize, rng).unwrap();
+        let srs = marlin_darkpool.universal_setup(rng).unwrap();
+        let (pk, vk) = marlin_darkpool.index(&srs).unwrap();
+
+        //v does not fit in 8 bits, so no valid proof can be produced for its image
+        let v = <Bls12_377 as PairingEngine>::Fr::from(upperbound + 1);
+        let image = mimc(v, r, &marlin_darkpool.mimc_constants);
+        if let Ok(proof) = marlin_darkpool.prove(v, r, "".to_string(), &pk, rng) {
+            assert!(!marlin_darkpool.verify(image, "".to_string(), &vk, &proof, rng));
+        }
+    }
+
+    #[test]
+    fn negative_unequal_aux_strings(){
+        let rng = &mut OsRng;
+        let upperbound = u64::MAX;
+        let r = <Bls12_377 as PairingEngine>::Fr::rand(rng);
+        let marlin_darkpool = MarlinDarkpool::<Bls12_377>::new(upperbound as usize, rng).unwrap();
+        let srs = marlin_darkpool.universal_setup(rng).unwrap();
+        let (pk, vk) = marlin_darkpool.index(&srs).unwrap();
+
+        //test with different aux strings for prover and verifier
+        let v = <Bls12_377 as PairingEngine>::Fr::from(upperbound - *STANDARD_BALANCE + *STANDARD_MIN_BAL);
+        let proof = marlin_darkpool.prove(v, r, "test123".to_string(), &pk, rng).unwrap();
+        let image = mimc(v, r, &marlin_darkpool.mimc_constants);
+        assert!(!marlin_darkpool.verify(image, "different string".to_string(), &vk, &proof, rng));
+    }
+}
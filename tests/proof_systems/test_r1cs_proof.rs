@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use rand::rngs::OsRng;
+    use wake_implementation::proof_systems::r1cs_proof::{R1csProofStruct, DarkpoolConstraint};
+    use bulletproofs::PedersenGens;
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref G: RistrettoPoint = PedersenGens::default().B;
+        static ref H: RistrettoPoint = PedersenGens::default().B_blinding;
+    }
+
+    fn random_blindings(amount: usize) -> Vec<Scalar> {
+        let rng = &mut OsRng;
+        (0..amount).map(|_| Scalar::random(rng)).collect()
+    }
+
+    ///
+    /// Test that the darkpool statement verifies against a commitment to v = upperbound - (b - min_bal)
+    /// for each of the upperbounds supported by the range proof
+    ///
+    #[test]
+    fn darkpool_positive() {
+        let rng = &mut OsRng;
+        let b = 5000;
+        let min_bal = 100;
+        for upperbound in [u16::MAX as u64, u32::MAX as u64, u64::MAX] {
+            let r = Scalar::random(rng);
+            let v = upperbound - (b - min_bal);
+            let comm = (*G)*Scalar::from(v) + (*H)*r;
+            let r1cs_proof_struct = R1csProofStruct::darkpool(upperbound);
+
+            let (proof, commitments) = r1cs_proof_struct.prove(&[v], &[r], String::new()).unwrap();
+
+            assert_eq!(commitments[0], comm.compress());
+            assert!(r1cs_proof_struct.verify(&proof, &[comm.compress()], String::new()));
+        }
+    }
+
+    ///
+    /// Test that verification fails for v above a bound, which is not a power of two
+    ///
+    #[test]
+    fn darkpool_out_of_bound_neg() {
+        let rng = &mut OsRng;
+        let upperbound = 1000;
+        let v = 1001;
+        let r = Scalar::random(rng);
+        let r1cs_proof_struct = R1csProofStruct::darkpool(upperbound);
+
+        let (proof, commitments) = r1cs_proof_struct.prove(&[v], &[r], String::new()).unwrap();
+
+        assert!(!r1cs_proof_struct.verify(&proof, &commitments, String::new()));
+    }
+
+    ///
+    /// Test a two-sided bound, at and beyond both of its ends
+    ///
+    #[test]
+    fn two_sided_bound() {
+        let r1cs_proof_struct = R1csProofStruct::new(1, vec![DarkpoolConstraint::Bounded { idx: 0, lower: 100, upper: 200 }]).unwrap();
+
+        for (v, expected) in [(100, true), (200, true), (150, true), (99, false), (201, false)] {
+            let (proof, commitments) = r1cs_proof_struct.prove(&[v], &random_blindings(1), String::new()).unwrap();
+            assert_eq!(r1cs_proof_struct.verify(&proof, &commitments, String::new()), expected);
+        }
+    }
+
+    ///
+    /// Test a statement combining a sum, an equality and bounds across commitments:
+    /// two balances summing to a total, where the first balance equals a fourth committed value
+    ///
+    #[test]
+    fn sum_and_equality() {
+        let constraints = vec![
+            DarkpoolConstraint::Sum { idxs: vec![0, 1], total: 2 },
+            DarkpoolConstraint::Equal { a: 0, b: 3 },
+            DarkpoolConstraint::Bounded { idx: 0, lower: 10, upper: u32::MAX as u64 },
+            DarkpoolConstraint::Bounded { idx: 1, lower: 10, upper: u32::MAX as u64 },
+        ];
+        let r1cs_proof_struct = R1csProofStruct::new(4, constraints).unwrap();
+
+        //satisfying values
+        let (proof, commitments) = r1cs_proof_struct.prove(&[300, 700, 1000, 300], &random_blindings(4), "aux".to_string()).unwrap();
+        assert!(r1cs_proof_struct.verify(&proof, &commitments, "aux".to_string()));
+
+        //sum does not hold
+        let (proof, commitments) = r1cs_proof_struct.prove(&[300, 700, 1001, 300], &random_blindings(4), "aux".to_string()).unwrap();
+        assert!(!r1cs_proof_struct.verify(&proof, &commitments, "aux".to_string()));
+
+        //equality does not hold
+        let (proof, commitments) = r1cs_proof_struct.prove(&[300, 700, 1000, 301], &random_blindings(4), "aux".to_string()).unwrap();
+        assert!(!r1cs_proof_struct.verify(&proof, &commitments, "aux".to_string()));
+    }
+
+    ///
+    /// Test that a proof is bound to the auxilliary string, the commitments and the statement
+    ///
+    #[test]
+    fn binding_neg() {
+        let rng = &mut OsRng;
+        let r1cs_proof_struct = R1csProofStruct::darkpool(u32::MAX as u64);
+        let (proof, commitments) = r1cs_proof_struct.prove(&[42], &random_blindings(1), "test123".to_string()).unwrap();
+
+        //different aux string
+        assert!(!r1cs_proof_struct.verify(&proof, &commitments, "different string".to_string()));
+
+        //different commitment
+        let other_comm = ((*G)*Scalar::from(42u64) + (*H)*Scalar::random(rng)).compress();
+        assert!(!r1cs_proof_struct.verify(&proof, &[other_comm], "test123".to_string()));
+
+        //different statement over the same commitment
+        let other_struct = R1csProofStruct::darkpool(u16::MAX as u64);
+        assert!(!other_struct.verify(&proof, &commitments, "test123".to_string()));
+
+        //wrong amount of commitments
+        assert!(!r1cs_proof_struct.verify(&proof, &[commitments[0], commitments[0]], "test123".to_string()));
+    }
+
+    ///
+    /// Test that malformed statements and inputs are rejected
+    ///
+    #[test]
+    fn invalid_statement_neg() {
+        assert!(R1csProofStruct::new(1, vec![DarkpoolConstraint::Bounded { idx: 1, lower: 0, upper: 10 }]).is_err());
+        assert!(R1csProofStruct::new(1, vec![DarkpoolConstraint::Bounded { idx: 0, lower: 11, upper: 10 }]).is_err());
+        assert!(R1csProofStruct::new(2, vec![DarkpoolConstraint::Sum { idxs: vec![], total: 1 }]).is_err());
+        assert!(R1csProofStruct::new(2, vec![DarkpoolConstraint::Equal { a: 0, b: 2 }]).is_err());
+
+        let r1cs_proof_struct = R1csProofStruct::darkpool(u8::MAX as u64);
+        assert!(r1cs_proof_struct.prove(&[1, 2], &random_blindings(2), String::new()).is_err());
+    }
+}
@@ -10,11 +10,6 @@ mod tests {
     lazy_static!{
         static ref G: RistrettoPoint = PedersenGens::default().B;
         static ref H: RistrettoPoint = PedersenGens::default().B_blinding;
-        static ref RNG: &'static mut OsRng = {
-            let mut rng = OsRng;
-            let rng_ptr: *mut OsRng = &mut rng;
-            unsafe { &mut *rng_ptr }
-        };
     }
 
     ///
@@ -23,16 +18,17 @@ mod tests {
     /// 
     #[test]
     fn range_proof_u8() {
+        let rng = &mut OsRng;
         //arrange
         let b = u8::MAX as u64;
         let min_bal = 100 as u64;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u8::MAX as u64;
         let comm = (*G)*(Scalar::from(upperbound - (b - min_bal))) + ((*H)*r);
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
         
         //act
-        let proof = range_proof_struct.prove(b, r, String::new()).unwrap();
+        let proof = range_proof_struct.prove(b, r, String::new(), rng).unwrap();
         
         //assert
         assert!(range_proof_struct.verify(proof, comm.compress(), String::new()))
@@ -44,16 +40,17 @@ mod tests {
     /// 
     #[test]
     fn range_proof_u16() {
+        let rng = &mut OsRng;
         //arrange
         let b = u16::MAX as u64;
         let min_bal = 1000;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u16::MAX as u64;
         let comm = (*G)*(Scalar::from(upperbound - (b - min_bal))) + ((*H)*r);
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
         
         //act
-        let proof = range_proof_struct.prove(b, r, String::new()).unwrap();
+        let proof = range_proof_struct.prove(b, r, String::new(), rng).unwrap();
         
         //assert
         assert!(range_proof_struct.verify(proof, comm.compress(), String::new()))
@@ -65,16 +62,17 @@ mod tests {
     /// 
     #[test]
     fn range_proof_u32() {
+        let rng = &mut OsRng;
         //arrange
         let b = u32::MAX as u64;
         let min_bal = 1000;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u32::MAX as u64;
         let comm = (*G)*(Scalar::from(upperbound - (b - min_bal))) + ((*H)*r);
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
         
         //act
-        let proof = range_proof_struct.prove(b, r, String::new()).unwrap();
+        let proof = range_proof_struct.prove(b, r, String::new(), rng).unwrap();
         
         //assert
         assert!(range_proof_struct.verify(proof, comm.compress(), String::new()))
@@ -86,16 +84,17 @@ mod tests {
     /// 
     #[test]
     fn range_proof_u64() {
+        let rng = &mut OsRng;
         //arrange
         let b = u64::MAX;
         let min_bal = 1000;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u64::MAX;
         let comm = (*G)*(Scalar::from(upperbound - (b - min_bal))) + ((*H)*r);
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
         
         //act
-        let proof = range_proof_struct.prove(b, r, String::new()).unwrap();
+        let proof = range_proof_struct.prove(b, r, String::new(), rng).unwrap();
         
         //assert
         assert!(range_proof_struct.verify(proof, comm.compress(), String::new()))
@@ -106,16 +105,17 @@ mod tests {
     /// 
     #[test]
     fn range_proof_eq() {
+        let rng = &mut OsRng;
         //arrange
         let b = 4294967296u64;
         let min_bal = 4294967296u64;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u64::MAX;
         let comm = (*G)*(Scalar::from(upperbound - (b - min_bal))) + ((*H)*r);
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
         
         //act
-        let proof = range_proof_struct.prove(b, r, String::new()).unwrap();
+        let proof = range_proof_struct.prove(b, r, String::new(), rng).unwrap();
         
         //assert
         assert!(range_proof_struct.verify(proof, comm.compress(), String::new()))
@@ -127,15 +127,16 @@ mod tests {
     /// 
     #[test]
     fn test_range_proof_neg() {
+        let rng = &mut OsRng;
         //arrange
         let b = 4294967296u64;
         let min_bal = 4294967297u64;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u64::MAX;
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
         
         //act
-        let proof = range_proof_struct.prove(b, r, String::new());
+        let proof = range_proof_struct.prove(b, r, String::new(), rng);
 
         assert!(proof.err().unwrap().to_string() == "minimum balance requirement was not met");
     }
@@ -146,10 +147,11 @@ mod tests {
     /// 
     #[test]
     fn test_incorrect_axilliary_challenge_strings_fails() {
+        let rng = &mut OsRng;
         //arrange
         let b = 4294967296u64;
         let min_bal = 0u64;
-        let r = Scalar::random(&mut RNG.clone());
+        let r = Scalar::random(rng);
         let upperbound = u64::MAX;
         let comm = (*G)*(Scalar::from(upperbound - (b - min_bal))) + ((*H)*r);
         let range_proof_struct = RangeProofStruct::new(min_bal, upperbound);
@@ -157,7 +159,7 @@ mod tests {
         let prover_aux_string = "prover auxilliary challenge string".to_string();
         let verifier_aux_string = "verifier auxilliary challenge string".to_string();
 
-        let proof = range_proof_struct.prove(b, r, prover_aux_string).unwrap();
+        let proof = range_proof_struct.prove(b, r, prover_aux_string, rng).unwrap();
         assert!(!range_proof_struct.verify(proof, comm.compress(), verifier_aux_string))
     }
 }
@@ -1,5 +1,21 @@
 pub mod test_bp_compiler_wake;
 pub mod test_bp_optimized_wake;
+pub mod test_bp_r1cs_signatures;
 pub mod test_gm17_compiler_wake;
 pub mod test_gm17_optimized_wake;
-pub mod test_key_exchange_dalek;
\ No newline at end of file
+pub mod test_marlin_compiler_wake;
+pub mod test_marlin_optimized_wake;
+pub mod test_key_exchange_dalek;
+pub mod test_deterministic_sessions;
+
+pub mod test_party_ids;
+pub mod test_robust_wake;
+pub mod test_bp_compiler_membership;
+pub mod test_round_deadlines;
+pub mod test_snapshots;
+pub mod test_session_ids;
+pub mod test_group_channel;
+pub mod test_key_ratchet;
+pub mod test_parallel_execution;
+pub mod test_async_parties;
+pub mod test_reliable_broadcast;
\ No newline at end of file
@@ -0,0 +1,107 @@
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use wake_implementation::protocols::async_network::in_memory_network;
+    use wake_implementation::protocols::compiler_bp_wake::{compiler_bp_wake_protocol, compiler_bp_wake_async};
+    use wake_implementation::protocols::optimized_bp_wake::{optimized_bp_wake_protocol, optimized_bp_wake_async};
+    use wake_implementation::protocols::party_id::Ring;
+    use wake_implementation::protocols::timeout::RoundDeadlines;
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use wake_implementation::utility::fork_rngs;
+    use compiler_bp_wake_async::{AsyncParty, CompilerNetworkMessage, COMPILER_ROUNDS};
+    use std::{sync::Arc, time::Duration};
+    use rand::rngs::OsRng;
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn params() -> Arc<WakeParams<BulletproofsKeys>> {
+        Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap())
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs with every party as a tokio task
+    /// Asserts all parties compute the same key
+    ///
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_compiler_key_exchange() {
+        let keys = compiler_bp_wake_async::run_async_compiler_key_exchange(4, vec![20, 30, 40, 50], &params(), &mut OsRng).await.unwrap();
+        assert_eq!(keys.len(), 4);
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Runs optimized WAKE using Bulletproofs with every party as a tokio task
+    /// Asserts all parties compute the same key
+    ///
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_optimized_key_exchange() {
+        let keys = optimized_bp_wake_async::run_async_optimized_key_exchange(4, vec![20, 30, 40, 50], &params(), &mut OsRng).await.unwrap();
+        assert_eq!(keys.len(), 4);
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Runs async compiler WAKE on a current-thread runtime, such that all parties share one thread
+    /// Asserts the session completes, as no party blocks the runtime while proving
+    ///
+    #[tokio::test]
+    async fn test_async_single_threaded_runtime() {
+        let keys = compiler_bp_wake_async::run_async_compiler_key_exchange(3, vec![20, 30, 40], &params(), &mut OsRng).await.unwrap();
+        assert_eq!(keys[0], keys[2]);
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs async compiler WAKE where the last party holds its endpoint but never sends, with a short round 1 deadline
+    /// Asserts the other parties fail with a timeout of round 1 naming the silent party
+    ///
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_silent_party_times_out() {
+        let rng = &mut OsRng;
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+        let ring = Ring::new(parties.iter().map(|party| party.id()).collect()).unwrap();
+        let mut endpoints = in_memory_network::<CompilerNetworkMessage>(&ring, COMPILER_ROUNDS);
+
+        let silent = parties.pop().unwrap();
+        let position = ring.position(&silent.id()).unwrap();
+        let _silent_endpoint = endpoints.remove(position);
+
+        let mut tasks = Vec::new();
+        for (mut party, rng) in parties.into_iter().zip(fork_rngs(rng, 2)) {
+            party.set_deadlines(RoundDeadlines::new(Duration::from_secs(60)).with_round(1, Duration::from_millis(200)));
+            let endpoint = endpoints.remove(endpoints.iter().position(|endpoint| endpoint.id() == party.id()).unwrap());
+            tasks.push(tokio::spawn(AsyncParty::new(party, endpoint, rng).unwrap().run()));
+        }
+
+        for task in tasks {
+            let err = task.await.unwrap().unwrap_err();
+            assert!(err.contains("round 1 timed out"));
+            assert!(err.contains(&format!("{:?}", silent.id())));
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Creates async parties of both variants with the endpoint of another party
+    /// Asserts the parties are rejected
+    ///
+    #[tokio::test]
+    async fn test_async_endpoint_of_other_party() {
+        let rng = &mut OsRng;
+        let parties = compiler_bp_wake_protocol::setup_parties(rng, 2, vec![20, 30], &params()).unwrap();
+        let ring = Ring::new(parties.iter().map(|party| party.id()).collect()).unwrap();
+        let mut endpoints = in_memory_network(&ring, COMPILER_ROUNDS);
+        let other = endpoints.remove(1 - ring.position(&parties[0].id()).unwrap());
+        assert!(AsyncParty::new(parties.into_iter().next().unwrap(), other, fork_rngs(rng, 1).remove(0)).is_err());
+
+        let parties = optimized_bp_wake_protocol::setup_parties(rng, 2, vec![20, 30], &params()).unwrap();
+        let ring = Ring::new(parties.iter().map(|party| party.id()).collect()).unwrap();
+        let mut endpoints = in_memory_network(&ring, optimized_bp_wake_async::OPTIMIZED_ROUNDS);
+        let other = endpoints.remove(1 - ring.position(&parties[0].id()).unwrap());
+        assert!(optimized_bp_wake_async::AsyncParty::new(parties.into_iter().next().unwrap(), other, fork_rngs(rng, 1).remove(0)).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol::run_compiler_group_session;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_membership::{GroupSession, RekeyMessage, run_join, run_leave};
+    use wake_implementation::protocols::party_id::PartyId;
+    use wake_implementation::proof_systems::discrete_log_knowledege_proof::DiscreteLogKnowledgeProof;
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use wake_implementation::utility::{hash_string_to_scalar, ristretto_to_string, compressed_ristretto_to_string};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::rngs::OsRng;
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn setup_sessions(balances: Vec<u64>, rng: &mut OsRng) -> (Arc<WakeParams<BulletproofsKeys>>, Vec<GroupSession>) {
+        let params: Arc<WakeParams<BulletproofsKeys>> = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        let sessions = run_compiler_group_session(balances.len(), balances, &params, rng).unwrap();
+        (params, sessions)
+    }
+
+    //proof of knowledge of the exponent of statement, forged without it: the response is picked first, and the commitment solved for
+    //as g^response * statement^(-challenge), under a challenge that does not hash the commitment
+    fn forged_proof(params: &WakeParams<BulletproofsKeys>, statement: RistrettoPoint, message: &RekeyMessage) -> DiscreteLogKnowledgeProof {
+        let challenge = hash_string_to_scalar(ristretto_to_string(params.g()) + &compressed_ristretto_to_string(statement.compress()) + &message.to_string());
+        let response = Scalar::random(&mut OsRng);
+        DiscreteLogKnowledgeProof { statement: (params.g() * response - statement * challenge).compress(), challenge, response }
+    }
+
+    //current Burmester-Desmedt value of id, as known to session
+    fn z_of(session: &GroupSession, id: &PartyId) -> RistrettoPoint {
+        session.public_session().z_list.iter().find(|(sender, _)| sender == id).unwrap().1
+    }
+
+    fn assert_same_keys(keys: &[RistrettoPoint]) {
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Establishes a session using compiler WAKE with Bulletproofs, after which a party joins
+    /// Asserts all members, including the joining party, produce similar keys, which differ from the key before the join
+    ///
+    #[test]
+    fn test_join() {
+        let rng = &mut OsRng;
+        let (params, mut sessions) = setup_sessions(vec![20, 30, 40, 50], rng);
+        let old_key = sessions[0].key().unwrap();
+
+        let keys = run_join(&mut sessions, &params, 60, rng).unwrap();
+        assert_eq!(keys.len(), 5);
+        assert_same_keys(&keys);
+        assert_ne!(keys[0], old_key);
+
+        for session in sessions.iter() {
+            assert_eq!(session.epoch(), 1);
+            assert_eq!(session.members().len(), 5);
+            assert_eq!(session.key(), Some(keys[0]));
+        }
+    }
+
+    ///
+    /// Establishes a session using compiler WAKE with Bulletproofs, after which a party leaves
+    /// Asserts the remaining members produce similar keys, which differ from the key before the leave
+    ///
+    #[test]
+    fn test_leave() {
+        let rng = &mut OsRng;
+        let (_, mut sessions) = setup_sessions(vec![20, 30, 40, 50], rng);
+        let old_key = sessions[0].key().unwrap();
+        let leaving = sessions[2].id();
+
+        let keys = run_leave(&mut sessions, &leaving, rng).unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_same_keys(&keys);
+        assert_ne!(keys[0], old_key);
+        assert!(sessions.iter().all(|session| !session.members().contains(&leaving)));
+    }
+
+    ///
+    /// Establishes a session using compiler WAKE with Bulletproofs, after which parties repeatedly join and leave, down to two members
+    /// Asserts every membership change produces similar and fresh keys
+    ///
+    #[test]
+    fn test_join_and_leave() {
+        let rng = &mut OsRng;
+        let (params, mut sessions) = setup_sessions(vec![20, 30, 40], rng);
+        let mut previous_keys = vec![sessions[0].key().unwrap()];
+
+        let keys = run_join(&mut sessions, &params, 70, rng).unwrap();
+        assert_same_keys(&keys);
+        previous_keys.push(keys[0]);
+
+        for _ in 0..2 {
+            let leaving = sessions[0].id();
+            let keys = run_leave(&mut sessions, &leaving, rng).unwrap();
+            assert_same_keys(&keys);
+            assert!(!previous_keys.contains(&keys[0]));
+            previous_keys.push(keys[0]);
+        }
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].epoch(), 3);
+    }
+
+    ///
+    /// *Negative test*
+    /// Establishes a session, after which a party with a balance below the minimum balance requests to join
+    /// Should fail, as the joining party cannot prove the darkpool relation
+    ///
+    #[test]
+    fn test_join_insufficient_balance_neg() {
+        let rng = &mut OsRng;
+        let (params, mut sessions) = setup_sessions(vec![20, 30, 40], rng);
+
+        let public_session = sessions[0].public_session();
+        assert!(GroupSession::join(params.clone(), &public_session, MIN_BAL - 1, Scalar::random(rng), rng).is_err());
+
+        //a party proving a balance it does not hold is rejected by the members
+        let (_, mut request) = GroupSession::join(params.clone(), &public_session, 50, Scalar::random(rng), rng).unwrap();
+        request.commitment = params.g() * Scalar::from(UPPERBOUND - 50) + params.pc_gens().B_blinding * Scalar::random(rng);
+        request.message.sender = PartyId::from_commitment(&request.commitment);
+        assert!(sessions[0].accept_join(&request).is_err());
+
+        //the join request is bound to the epoch of the session
+        let (_, request) = GroupSession::join(params.clone(), &public_session, 50, Scalar::random(rng), rng).unwrap();
+        let leaving = sessions[2].id();
+        run_leave(&mut sessions, &leaving, rng).unwrap();
+        assert!(sessions[0].accept_join(&request).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Establishes a session, after which a party leaves, and the departed party sends a fresh value in place of a ring neighbour
+    /// Should fail, as the departed party does not know the exponent of the neighbour
+    ///
+    #[test]
+    fn test_leave_forged_refresh_neg() {
+        let rng = &mut OsRng;
+        let (params, mut sessions) = setup_sessions(vec![20, 30, 40, 50], rng);
+        let leaving = sessions[0].id();
+        sessions.remove(0);
+        for session in sessions.iter_mut() {
+            session.remove(&leaving).unwrap();
+        }
+
+        let mut refresh_outputs: Vec<(RekeyMessage, DiscreteLogKnowledgeProof)> = Vec::new();
+        for session in sessions.iter_mut() {
+            refresh_outputs.extend(session.refresh(rng).unwrap());
+        }
+
+        //replace the fresh value of a neighbour with a value of the departed party, reusing the proof
+        let forged = params.g() * Scalar::random(rng);
+        let (message, _) = &mut refresh_outputs[0];
+        message.payload = forged;
+        let honest = sessions.iter().position(|session| session.id() != refresh_outputs[0].0.sender).unwrap();
+        assert!(sessions[honest].refresh_recieve(&refresh_outputs).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Establishes a session, after which a party leaves, and the departed party injects re-key messages under the identity of a ring neighbour,
+    /// a fresh value in the refresh round and an X value in the update round, each with a proof forged for the current value of the neighbour
+    /// Should fail, as the challenge of the proof hashes the commitment of the prover, such that it cannot be solved for
+    ///
+    #[test]
+    fn test_leave_injected_rekey_neg() {
+        let rng = &mut OsRng;
+        let (params, mut sessions) = setup_sessions(vec![20, 30, 40, 50], rng);
+        let leaving = sessions[0].id();
+        sessions.remove(0);
+        for session in sessions.iter_mut() {
+            session.remove(&leaving).unwrap();
+        }
+
+        let mut refresh_outputs: Vec<(RekeyMessage, DiscreteLogKnowledgeProof)> = Vec::new();
+        for session in sessions.iter_mut() {
+            refresh_outputs.extend(session.refresh(rng).unwrap());
+        }
+        let neighbour = refresh_outputs[0].0.sender;
+        let honest = sessions.iter().position(|session| session.id() != neighbour && !refresh_outputs.iter().any(|(message, _)| message.sender == session.id())).unwrap();
+
+        //refresh round: a fresh value of the departed party, sent in place of the neighbour
+        let mut injected = refresh_outputs.clone();
+        let message = RekeyMessage::new(neighbour, injected[0].0.epoch, 2, params.g() * Scalar::random(rng), injected[0].0.context.clone());
+        injected[0] = (message.clone(), forged_proof(&params, z_of(&sessions[honest], &neighbour), &message));
+        assert!(sessions[honest].refresh_recieve(&injected).is_err());
+
+        //update round: the refresh is completed honestly, after which an X value is sent in place of the neighbour
+        for session in sessions.iter_mut() {
+            session.refresh_recieve(&refresh_outputs).unwrap();
+        }
+        let mut update_outputs: Vec<(RekeyMessage, DiscreteLogKnowledgeProof)> = Vec::new();
+        for session in sessions.iter_mut() {
+            update_outputs.extend(session.update(rng).unwrap());
+        }
+        let idx = update_outputs.iter().position(|(message, _)| message.sender == neighbour).unwrap();
+        let message = RekeyMessage::new(neighbour, update_outputs[idx].0.epoch, 3, RistrettoPoint::random(rng), update_outputs[idx].0.context.clone());
+        update_outputs[idx] = (message.clone(), forged_proof(&params, z_of(&sessions[honest], &neighbour), &message));
+        assert!(sessions[honest].update_recieve(&update_outputs).is_err());
+    }
+}
@@ -1,17 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol::run_optimized_key_exchange;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol::{run_optimized_key_exchange, setup_parties};
+    use wake_implementation::protocols::party_id::PartyId;
+    use curve25519_dalek_ng::ristretto::RistrettoPoint;
+    use wake_implementation::params::WakeParams;
+    use std::sync::Arc;
     use rand::{rngs::OsRng, Rng};
     use lazy_static::lazy_static;
 
     //setup of shared variables
     lazy_static!{
         static ref LARGE_MIN_BAL: u64 = 100000000;
-        static ref RNG: &'static mut OsRng = {
-            let mut rng = OsRng;
-            let rng_ptr: *mut OsRng = &mut rng;
-            unsafe { &mut *rng_ptr }
-        };
         static ref UPPER_U8: u64 = u8::MAX as u64;
         static ref UPPER_U16: u64 = u16::MAX as u64;
         static ref UPPER_U32: u64 = u32::MAX as u64;
@@ -24,17 +23,19 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u8() {
+        let rng = &mut OsRng;
         let min_bal = 10;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(min_bal..=*UPPER_U8);
+            let balance = rng.gen_range(min_bal..=*UPPER_U8);
             balances.push(balance);
         }
         
-        let keys = run_optimized_key_exchange(party_amount, min_bal, balances, *UPPER_U8).unwrap();
+        let params = Arc::new(WakeParams::bulletproofs(min_bal, *UPPER_U8).unwrap());
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -47,17 +48,19 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u16() {
+        let rng = &mut OsRng;
         let min_bal = 1000;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(min_bal..=*UPPER_U16);
+            let balance = rng.gen_range(min_bal..=*UPPER_U16);
             balances.push(balance);
         }
         
-        let keys = run_optimized_key_exchange(party_amount, min_bal, balances, *UPPER_U16).unwrap();
+        let params = Arc::new(WakeParams::bulletproofs(min_bal, *UPPER_U16).unwrap());
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -70,17 +73,19 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u32() {
+        let rng = &mut OsRng;
         let min_bal = 1000;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(min_bal..=*UPPER_U32);
+            let balance = rng.gen_range(min_bal..=*UPPER_U32);
             balances.push(balance);
         }
         
-        let keys = run_optimized_key_exchange(party_amount, min_bal, balances, *UPPER_U32).unwrap();
+        let params = Arc::new(WakeParams::bulletproofs(min_bal, *UPPER_U32).unwrap());
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -93,16 +98,18 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u64() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(*LARGE_MIN_BAL..=*UPPER_U64);
+            let balance = rng.gen_range(*LARGE_MIN_BAL..=*UPPER_U64);
             balances.push(balance);
         }
         
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances, *UPPER_U64).unwrap();
+        let params = Arc::new(WakeParams::bulletproofs(*LARGE_MIN_BAL, *UPPER_U64).unwrap());
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -116,11 +123,13 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_balance_neg() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         
         let balances: Vec<u64> = vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18];
         
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances, *UPPER_U64);
+        let params = Arc::new(WakeParams::bulletproofs(*LARGE_MIN_BAL, *UPPER_U64).unwrap());
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng);
         
         assert!(keys.err().unwrap().to_string() == "one or more balances are smaller than minimum_balance");
     }
@@ -132,14 +141,16 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_balance_len_neg() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         
         let balances_one: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18];
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances_one, *UPPER_U64);
+        let params = Arc::new(WakeParams::bulletproofs(*LARGE_MIN_BAL, *UPPER_U64).unwrap());
+        let keys = run_optimized_key_exchange(party_amount, balances_one, &params, rng);
         assert!(keys.err().unwrap().to_string() == "balances not correct length");
         
         let balances_two: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20];
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances_two, *UPPER_U64);
+        let keys = run_optimized_key_exchange(party_amount, balances_two, &params, rng);
         assert!(keys.err().unwrap().to_string() == "balances not correct length");
     }
     
@@ -150,14 +161,33 @@ mod tests {
     /// 
     #[test]
     fn test_upperbound_neg() {
-        let party_amount = 10;
         let upperbound = 9;
         
-        let balances: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19];
-        
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances, upperbound);
+        let params = WakeParams::bulletproofs(*LARGE_MIN_BAL, upperbound);
         
-        assert!(keys.err().unwrap().to_string().starts_with("upperbound was not among the valid values:"));
+        assert!(params.err().unwrap().to_string().starts_with("upperbound was not among the valid values:"));
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs the rounds of optimized WAKE for two parties, and computes the key of the first party twice
+    /// Should return error the second time, as the ephemeral Burmester-Desmedt exponent is erased once the key is computed
+    /// 
+    #[test]
+    fn test_compute_key_erases_exponent() {
+        let rng = &mut OsRng;
+        let params = Arc::new(WakeParams::bulletproofs(10, *UPPER_U8).unwrap());
+
+        let mut parties = setup_parties(rng, 2, vec![100, 200], &params).unwrap();
+        let round1_payload: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| party.round_1(rng)).collect();
+        let round2_payload: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| {
+            let (message, _) = party.round_2(&round1_payload, rng).unwrap();
+            (message.sender, message.payload)
+        }).collect();
+
+        let key = parties[0].compute_key(&round1_payload, &round2_payload).unwrap();
+        assert_eq!(key, parties[1].compute_key(&round1_payload, &round2_payload).unwrap());
+
+        assert_eq!(parties[0].compute_key(&round1_payload, &round2_payload), Err("Burmester-Desmedt exponent was erased".to_string()));
     }
-    
 }
\ No newline at end of file
@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::{
+        params::{WakeParams, SignatureBackend},
+        proof_systems::discrete_log_knowledege_proof::DiscreteLogKnowledgeProofStruct,
+        protocols::{
+            compiler_bp_wake::{compiler_bp_wake_protocol::{self, Nonces, run_compiler_key_exchange, run_robust_compiler_key_exchange}, compiler_bp_wake_signature::{CompMessage, CompSignature, R1csCompSignature}},
+            optimized_bp_wake::{optimized_bp_wake_protocol::{self, VK, run_optimized_key_exchange}, optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, R1csOptimizedSignatureAndSessionAuthentication}},
+            party_id::{PartyId, Ring},
+            robust::RobustConfig,
+        },
+    };
+    use bulletproofs::PedersenGens;
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::{rngs::OsRng, Rng};
+    use lazy_static::lazy_static;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref G: RistrettoPoint = PedersenGens::default().B;
+        static ref H: RistrettoPoint = PedersenGens::default().B_blinding;
+        static ref MIN_BAL: u64 = 100;
+        static ref UPPER_U32: u64 = u32::MAX as u64;
+    }
+
+    ///
+    /// Signs a compiler message with the R1CS signature, and verifies it against the commitment of the signer
+    ///
+    #[test]
+    fn r1cs_compiler_signature() {
+        let rng = &mut OsRng;
+        let balance = 5000;
+        let r = Scalar::random(rng);
+        let commitment = (*G)*Scalar::from(*UPPER_U32 - (balance - *MIN_BAL)) + (*H)*r;
+        let id = PartyId::from_commitment(&commitment);
+        let message = CompMessage::new(id, 2, *G, Nonces::new(vec![id], vec![Scalar::one()]));
+        let params = WakeParams::bulletproofs(*MIN_BAL, *UPPER_U32).unwrap();
+
+        let signature = R1csCompSignature::sign(&params, message.to_string(), balance, r).unwrap();
+
+        assert!(signature.verify_signature(&params, message.to_string(), &commitment));
+        assert!(!signature.verify_signature(&params, CompMessage::new(id, 3, *G, Nonces::new(vec![id], vec![Scalar::one()])).to_string(), &commitment));
+    }
+
+    ///
+    /// *Negative test*
+    /// Signing fails when the balance is below the minimum balance
+    ///
+    #[test]
+    fn r1cs_compiler_signature_balance_neg() {
+        let rng = &mut OsRng;
+        let r = Scalar::random(rng);
+        let params = WakeParams::bulletproofs(*MIN_BAL, *UPPER_U32).unwrap();
+        assert!(R1csCompSignature::sign(&params, String::new(), *MIN_BAL - 1, r).is_err());
+    }
+
+    ///
+    /// Signs an optimized message with the R1CS signature and session authentication, and verifies it
+    ///
+    #[test]
+    fn r1cs_optimized_signature() {
+        let rng = &mut OsRng;
+        let balance = 5000;
+        let r = Scalar::random(rng);
+        let commitment = (*G)*Scalar::from(*UPPER_U32 - (balance - *MIN_BAL)) + (*H)*r;
+        let x_i = Scalar::random(rng);
+        let z_i = (*G)*x_i;
+        let id = PartyId::from_commitment(&commitment);
+        let message = OptimizedMessage::new(id, *G, VK::new(vec![id], vec![z_i]));
+        let params = WakeParams::bulletproofs(*MIN_BAL, *UPPER_U32).unwrap();
+        let dl_proof_struct = DiscreteLogKnowledgeProofStruct::new(z_i.compress());
+
+        let signature = R1csOptimizedSignatureAndSessionAuthentication::sign(&params, &message.to_string(), balance, r, z_i.compress(), x_i, &commitment, rng).unwrap();
+
+        assert!(signature.verify_signature(&params, &message.to_string(), &dl_proof_struct, &commitment));
+
+        //session authentication under another key fails
+        let other_dl_proof_struct = DiscreteLogKnowledgeProofStruct::new(((*G)*Scalar::random(rng)).compress());
+        assert!(!signature.verify_signature(&params, &message.to_string(), &other_dl_proof_struct, &commitment));
+    }
+
+    ///
+    /// Runs compiler WAKE key-exchange, where the parties sign their messages with the R1CS signature
+    /// Asserts similar keys produced
+    ///
+    #[test]
+    fn r1cs_compiler_key_exchange() {
+        let rng = &mut OsRng;
+        let party_amount = 4;
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(*MIN_BAL..=*UPPER_U32)).collect();
+        let params = Arc::new(WakeParams::bulletproofs_with_signature_backend(*MIN_BAL, *UPPER_U32, SignatureBackend::R1cs).unwrap());
+
+        let parties = compiler_bp_wake_protocol::setup_parties(rng, party_amount, balances.clone(), &params).unwrap();
+        assert!(parties.iter().all(|party| party.signature_backend() == SignatureBackend::R1cs));
+
+        let keys = run_compiler_key_exchange(party_amount, balances, &params, rng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Runs optimized WAKE key-exchange, where the parties sign their messages with the R1CS signature and session authentication
+    /// Asserts similar keys produced
+    ///
+    #[test]
+    fn r1cs_optimized_key_exchange() {
+        let rng = &mut OsRng;
+        let party_amount = 4;
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(*MIN_BAL..=*UPPER_U32)).collect();
+        let params = Arc::new(WakeParams::bulletproofs_with_signature_backend(*MIN_BAL, *UPPER_U32, SignatureBackend::R1cs).unwrap());
+
+        let parties = optimized_bp_wake_protocol::setup_parties(rng, party_amount, balances.clone(), &params).unwrap();
+        assert!(parties.iter().all(|party| party.signature_backend() == SignatureBackend::R1cs));
+
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Signs a compiler message under one signature backend, and verifies it under the other
+    /// Should fail, as the parties of a session must select the same backend
+    ///
+    #[test]
+    fn r1cs_compiler_signature_backend_mismatch_neg() {
+        let rng = &mut OsRng;
+        let balance = 5000;
+        let r = Scalar::random(rng);
+        let commitment = (*G)*Scalar::from(*UPPER_U32 - (balance - *MIN_BAL)) + (*H)*r;
+        let id = PartyId::from_commitment(&commitment);
+        let message = CompMessage::new(id, 2, *G, Nonces::new(vec![id], vec![Scalar::one()]));
+        let range_proof_params = WakeParams::bulletproofs(*MIN_BAL, *UPPER_U32).unwrap();
+        let r1cs_params = WakeParams::bulletproofs_with_signature_backend(*MIN_BAL, *UPPER_U32, SignatureBackend::R1cs).unwrap();
+
+        let range_proof_signature = CompSignature::sign(&range_proof_params, message.to_string(), balance, r, rng);
+        assert!(range_proof_signature.clone().verify_signature(&range_proof_params, message.to_string(), &commitment));
+        assert!(!range_proof_signature.verify_signature(&r1cs_params, message.to_string(), &commitment));
+
+        let r1cs_signature = CompSignature::sign(&r1cs_params, message.to_string(), balance, r, rng);
+        assert!(r1cs_signature.clone().verify_signature(&r1cs_params, message.to_string(), &commitment));
+        assert!(!r1cs_signature.verify_signature(&range_proof_params, message.to_string(), &commitment));
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs robust compiler WAKE, where one party signs with the range proof while the others sign with the R1CS signature
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn r1cs_compiler_mixed_signature_backends_neg() {
+        let rng = &mut OsRng;
+        let party_amount = 4;
+        let range_proof_params = Arc::new(WakeParams::bulletproofs(*MIN_BAL, *UPPER_U32).unwrap());
+        let r1cs_params = Arc::new(WakeParams::bulletproofs_with_signature_backend(*MIN_BAL, *UPPER_U32, SignatureBackend::R1cs).unwrap());
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(*MIN_BAL..=*UPPER_U32)).collect();
+        let blindings: Vec<Scalar> = (0..party_amount).map(|_| Scalar::random(rng)).collect();
+        let commitments: Vec<RistrettoPoint> = (0..party_amount).map(|i| (*G)*Scalar::from(*UPPER_U32 - balances[i]) + (*H)*blindings[i]).collect();
+        let ring = Ring::from_commitments(&commitments).unwrap();
+
+        //party 0 selects the range proof backend
+        let parties: Vec<compiler_bp_wake_protocol::Party> = (0..party_amount).map(|i| {
+            let params = if i == 0 { range_proof_params.clone() } else { r1cs_params.clone() };
+            compiler_bp_wake_protocol::Party::new(i, params, commitments.clone(), ring.clone(), balances[i], blindings[i])
+        }).collect();
+
+        let outcome = run_robust_compiler_key_exchange(parties, &RobustConfig::new(1, 2).unwrap(), rng).unwrap();
+        assert_eq!(outcome.excluded, vec![PartyId::from_commitment(&commitments[0])]);
+        assert_eq!(outcome.attempts, 2);
+        for i in 1..outcome.keys.len() {
+            assert_eq!(outcome.keys[i-1], outcome.keys[i])
+        }
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol;
+    use wake_implementation::protocols::optimized_gm17_wake::optimized_gm17_wake_protocol;
+    use wake_implementation::protocols::compiler_marlin_wake::compiler_marlin_wake_protocol;
+    use wake_implementation::proof_systems::proof_system_gm17::Gm17Darkpool;
+    use wake_implementation::params::{WakeParams, Gm17Keys, MarlinKeys};
+    use curve25519_dalek_ng::ristretto::RistrettoPoint;
+    use std::sync::Arc;
+    use ark_bls12_377::Bls12_377;
+    use ark_ff::UniformRand;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const SEED: u64 = 2023;
+    const OTHER_SEED: u64 = 2024;
+
+    //draws balances from the session rng so a seed fixes the whole session
+    fn balances(rng: &mut StdRng, party_amount: usize, min_bal: u64, upperbound: u64) -> Vec<u64> {
+        (0..party_amount).map(|_| rng.gen_range(min_bal..=upperbound)).collect()
+    }
+
+    fn compiler_bp_session(seed: u64) -> Vec<RistrettoPoint> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (party_amount, min_bal, upperbound) = (5, 10, u8::MAX as u64);
+        let balances = balances(rng, party_amount, min_bal, upperbound);
+        let params = Arc::new(WakeParams::bulletproofs(min_bal, upperbound).unwrap());
+        compiler_bp_wake_protocol::run_compiler_key_exchange(party_amount, balances, &params, rng).unwrap()
+    }
+
+    fn optimized_bp_session(seed: u64) -> Vec<RistrettoPoint> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (party_amount, min_bal, upperbound) = (5, 10, u8::MAX as u64);
+        let balances = balances(rng, party_amount, min_bal, upperbound);
+        let params = Arc::new(WakeParams::bulletproofs(min_bal, upperbound).unwrap());
+        optimized_bp_wake_protocol::run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap()
+    }
+
+    fn optimized_gm17_session(seed: u64) -> Vec<RistrettoPoint> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (party_amount, min_bal, upperbound) = (3, 10, u8::MAX as u64);
+        let balances = balances(rng, party_amount, min_bal, upperbound);
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, min_bal, upperbound, rng).unwrap());
+        optimized_gm17_wake_protocol::run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap()
+    }
+
+    fn compiler_marlin_session(seed: u64) -> Vec<RistrettoPoint> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (party_amount, min_bal, upperbound) = (3, 10, u8::MAX as u64);
+        let balances = balances(rng, party_amount, min_bal, upperbound);
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(min_bal, upperbound, rng).unwrap());
+        compiler_marlin_wake_protocol::run_compiler_key_exchange(party_amount, balances, &params, rng).unwrap()
+    }
+
+    //runs a session twice with the same seed and once with another,
+    //the first two must agree key for key while the third must not
+    fn assert_reproducible(session: fn(u64) -> Vec<RistrettoPoint>) {
+        let first = session(SEED);
+        let second = session(SEED);
+        let other = session(OTHER_SEED);
+
+        assert_eq!(first, second);
+        for i in 1..first.len() {
+            assert_eq!(first[i-1], first[i])
+        }
+        assert_ne!(first[0], other[0]);
+    }
+
+    ///
+    /// Runs seeded compiler WAKE key-exchanges using Bulletproofs
+    /// Asserts the same seed reproduces the same keys
+    ///
+    #[test]
+    fn test_compiler_bp_seeded_session() {
+        assert_reproducible(compiler_bp_session);
+    }
+
+    ///
+    /// Runs seeded optimized WAKE key-exchanges using Bulletproofs
+    /// Asserts the same seed reproduces the same keys
+    ///
+    #[test]
+    fn test_optimized_bp_seeded_session() {
+        assert_reproducible(optimized_bp_session);
+    }
+
+    ///
+    /// Runs seeded optimized WAKE key-exchanges using GM17, including the key setup
+    /// Asserts the same seed reproduces the same keys
+    ///
+    #[test]
+    fn test_optimized_gm17_seeded_session() {
+        assert_reproducible(optimized_gm17_session);
+    }
+
+    ///
+    /// Runs seeded compiler WAKE key-exchanges using Marlin, including the universal setup
+    /// Asserts the same seed reproduces the same keys
+    ///
+    #[test]
+    fn test_compiler_marlin_seeded_session() {
+        assert_reproducible(compiler_marlin_session);
+    }
+
+    ///
+    /// Runs the GM17 setup and prover twice from the same seed
+    /// Asserts the keys and proofs are byte-for-byte identical
+    ///
+    #[test]
+    fn test_gm17_seeded_proof() {
+        let prove = |seed: u64| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            let gm17_darkpool = Gm17Darkpool::<Bls12_377>::new(u8::MAX as usize, rng).unwrap();
+            let (pk, pvk) = gm17_darkpool.setup(rng);
+            let r = <Bls12_377 as ark_ec::PairingEngine>::Fr::rand(rng);
+            let v = <Bls12_377 as ark_ec::PairingEngine>::Fr::from(100u64);
+            let proof = gm17_darkpool.prove(v, r, "".to_string(), &pk, rng);
+            (pvk, proof)
+        };
+
+        let (pvk_a, proof_a) = prove(SEED);
+        let (pvk_b, proof_b) = prove(SEED);
+        assert!(pvk_a.vk == pvk_b.vk);
+        assert!(proof_a == proof_b);
+    }
+}
@@ -1,6 +1,12 @@
 #[cfg(test)]
 mod tests {
     use wake_implementation::protocols::optimized_gm17_wake::optimized_gm17_wake_protocol::run_optimized_key_exchange;
+    use wake_implementation::params::{WakeParams, Gm17Keys};
+    use std::sync::Arc;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::PairingEngine;
     use rand::{rngs::OsRng, Rng};
     use lazy_static::lazy_static;
 
@@ -9,11 +15,6 @@ mod tests {
     //setup of shared variables
     lazy_static!{
         static ref LARGE_MIN_BAL: u64 = 100000000;
-        static ref RNG: &'static mut OsRng = {
-            let mut rng = OsRng;
-            let rng_ptr: *mut OsRng = &mut rng;
-            unsafe { &mut *rng_ptr }
-        };
         static ref UPPER_U8: u64 = u8::MAX as u64;
         static ref UPPER_U16: u64 = u16::MAX as u64;
         static ref UPPER_U32: u64 = u32::MAX as u64;
@@ -26,19 +27,20 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u8() {
+        let rng = &mut OsRng;
         let min_bal = 10;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(min_bal..=*UPPER_U8);
+            let balance = rng.gen_range(min_bal..=*UPPER_U8);
             balances.push(balance);
         }
         
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U8).unwrap();
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, min_bal, *UPPER_U8, rng).unwrap());
         
-        let keys = run_optimized_key_exchange(party_amount, min_bal, balances, *UPPER_U8, &gm17_darkpool, &pvk_list, &pk_list).unwrap();
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -51,19 +53,20 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u16() {
+        let rng = &mut OsRng;
         let min_bal = 1000;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(min_bal..=*UPPER_U16);
+            let balance = rng.gen_range(min_bal..=*UPPER_U16);
             balances.push(balance);
         }
         
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U16).unwrap();
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, min_bal, *UPPER_U16, rng).unwrap());
 
-        let keys = run_optimized_key_exchange(party_amount, min_bal, balances, *UPPER_U16, &gm17_darkpool, &pvk_list, &pk_list).unwrap();
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -76,19 +79,20 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_u32() {
+        let rng = &mut OsRng;
         let min_bal = 1000;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(min_bal..=*UPPER_U32);
+            let balance = rng.gen_range(min_bal..=*UPPER_U32);
             balances.push(balance);
         }
 
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U32).unwrap();
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, min_bal, *UPPER_U32, rng).unwrap());
         
-        let keys = run_optimized_key_exchange(party_amount, min_bal, balances, *UPPER_U32, &gm17_darkpool, &pvk_list, &pk_list).unwrap();
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
@@ -99,25 +103,40 @@ mod tests {
     /// Runs optimized WAKE key-exchange with a range-bound upperbound of 2^64-1
     /// Asserts similar keys produced
     /// 
-    #[test]
-    fn test_key_exchange_u64() {
+    fn key_exchange_u64<E: PairingEngine>() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         
         let mut balances: Vec<u64> = Vec::new();
         for _ in 0..party_amount {
             //generate random balance larger than or equal, to the minimum balance
-            let balance = RNG.clone().gen_range(*LARGE_MIN_BAL..=*UPPER_U64);
+            let balance = rng.gen_range(*LARGE_MIN_BAL..=*UPPER_U64);
             balances.push(balance);
         }
 
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U64).unwrap();
+        let params = Arc::new(WakeParams::<Gm17Keys<E>>::gm17(party_amount, *LARGE_MIN_BAL, *UPPER_U64, rng).unwrap());
         
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances, *UPPER_U64, &gm17_darkpool, &pvk_list, &pk_list).unwrap();
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
         
         for i in 1..keys.len() {
             assert_eq!(keys[i-1], keys[i])
         }
     }
+
+    #[test]
+    fn test_key_exchange_u64_bls12_377() {
+        key_exchange_u64::<Bls12_377>();
+    }
+
+    #[test]
+    fn test_key_exchange_u64_bls12_381() {
+        key_exchange_u64::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_key_exchange_u64_bn254() {
+        key_exchange_u64::<Bn254>();
+    }
     
     ///
     /// *Negative test*
@@ -126,13 +145,14 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_balance_neg() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         
         let balances: Vec<u64> = vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18];
         
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U64).unwrap();
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, *LARGE_MIN_BAL, *UPPER_U64, rng).unwrap());
 
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances, *UPPER_U64, &gm17_darkpool, &pvk_list, &pk_list);
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng);
         
         assert!(keys.err().unwrap().to_string() == "one or more balances are smaller than minimum_balance");
     }
@@ -144,16 +164,17 @@ mod tests {
     /// 
     #[test]
     fn test_key_exchange_balance_len_neg() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U64).unwrap();
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, *LARGE_MIN_BAL, *UPPER_U64, rng).unwrap());
 
         let balances_one: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18];
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances_one, *UPPER_U64, &gm17_darkpool, &pvk_list, &pk_list);
+        let keys = run_optimized_key_exchange(party_amount, balances_one, &params, rng);
         assert!(keys.err().unwrap().to_string() == "balances not correct length");
         
         let balances_two: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20];
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances_two, *UPPER_U64, &gm17_darkpool, &pvk_list, &pk_list);
+        let keys = run_optimized_key_exchange(party_amount, balances_two, &params, rng);
         assert!(keys.err().unwrap().to_string() == "balances not correct length");
     }
     
@@ -164,19 +185,16 @@ mod tests {
     /// 
     #[test]
     fn test_upperbound_neg() {
+        let rng = &mut OsRng;
         let party_amount = 10;
         let upperbound = 9;
         
         //assert wrong upperbound given to setup function fails
-        assert!(setup_gm17(party_amount, upperbound).err().unwrap().to_string().starts_with("Upperbound should be either 2^8, 2^16, 2^32 or 2^64"));
+        assert!(setup_gm17::<Bls12_377, _>(party_amount, upperbound, rng).err().unwrap().to_string().starts_with("Upperbound should be either 2^8, 2^16, 2^32 or 2^64"));
 
-        //create proper setup values
-        let balances: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19];
-        let (gm17_darkpool, pvk_list, pk_list) = setup_gm17(party_amount, *UPPER_U64).unwrap();
-        
-        //assert wrong upperbound given to key exchange functions fails
-        let keys = run_optimized_key_exchange(party_amount, *LARGE_MIN_BAL, balances, upperbound, &gm17_darkpool, &pvk_list, &pk_list);
-        assert!(keys.err().unwrap().to_string().starts_with("upperbound was not among the valid values:"));
+        //assert wrong upperbound given to the public parameters fails
+        let params = WakeParams::<Gm17Keys<Bls12_377>>::gm17(party_amount, *LARGE_MIN_BAL, upperbound, rng);
+        assert!(params.err().unwrap().to_string().starts_with("upperbound was not among the valid values:"));
     }
     
 }
\ No newline at end of file
@@ -0,0 +1,249 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol::run_compiler_group_session;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
+    use wake_implementation::protocols::channel::{ChannelKeyAnnouncement, ChannelMessage, ChannelSigningKey, GroupChannel, CHANNEL_DOMAIN, CHANNEL_HEADER_LEN, CHANNEL_SIGNATURE_LEN};
+    use wake_implementation::utility::{hash_string_to_scalar, ristretto_to_string, compressed_ristretto_to_string};
+    use bulletproofs::PedersenGens;
+    use wake_implementation::protocols::party_id::{PartyId, Ring};
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol::{self, Party};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::rngs::OsRng;
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn params() -> Arc<WakeParams<BulletproofsKeys>> {
+        Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap())
+    }
+
+    //opens the channel of every member with the given key, with a fresh signing key per member
+    fn open_channels(keys: &[RistrettoPoint], ids: &[PartyId], rng: &mut OsRng) -> Vec<GroupChannel> {
+        let signing_keys: Vec<ChannelSigningKey> = ids.iter().map(|_| ChannelSigningKey::generate(rng)).collect();
+        let members: Vec<(PartyId, RistrettoPoint)> = ids.iter().copied().zip(signing_keys.iter().map(|signing_key| signing_key.verifying_key())).collect();
+        keys.iter().zip(ids.iter()).zip(signing_keys).map(|((key, id), signing_key)| GroupChannel::new(key, *id, signing_key, &members).unwrap()).collect()
+    }
+
+    //runs compiler WAKE using Bulletproofs, and opens the channel of every party with the established key
+    fn setup_channels(balances: Vec<u64>, rng: &mut OsRng) -> Vec<GroupChannel> {
+        let sessions = run_compiler_group_session(balances.len(), balances, &params(), rng).unwrap();
+        let keys: Vec<RistrettoPoint> = sessions.iter().map(|session| session.key().unwrap()).collect();
+        let ids: Vec<PartyId> = sessions.iter().map(|session| session.id()).collect();
+        open_channels(&keys, &ids, rng)
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, after which every party broadcasts encrypted messages to the group
+    /// Asserts every other party decrypts the messages of every sender, in the order they were sent
+    ///
+    #[test]
+    fn test_compiler_encrypted_exchange() {
+        let rng = &mut OsRng;
+        let mut channels = setup_channels(vec![20, 30, 40, 50], rng);
+
+        for sender in 0..channels.len() {
+            for round in 0..2 {
+                let plaintext = format!("message {} of sender {}", round, sender).into_bytes();
+                let message = channels[sender].send(&plaintext, rng).unwrap();
+                assert_eq!(message.sequence, round);
+
+                //the message is broadcast in its serialized form
+                let bytes = message.to_bytes();
+                for (i, channel) in channels.iter_mut().enumerate() {
+                    if i != sender {
+                        assert_eq!(*channel.recieve(&ChannelMessage::from_bytes(&bytes).unwrap()).unwrap(), plaintext);
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Runs optimized WAKE using Bulletproofs, after which the parties exchange encrypted messages
+    /// Asserts the parties decrypt the messages of each other
+    ///
+    #[test]
+    fn test_optimized_encrypted_exchange() {
+        let rng = &mut OsRng;
+        let mut parties = optimized_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+        let round1_outputs: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| party.round_1(rng)).collect();
+        let round2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)> = parties.iter_mut().map(|party| party.round_2(&round1_outputs, rng).unwrap()).collect();
+        let x_list: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+        let members: Vec<PartyId> = parties.iter().map(|party| party.id()).collect();
+
+        let keys: Vec<RistrettoPoint> = parties.iter_mut().map(|party| party.compute_key(&round1_outputs, &x_list).unwrap()).collect();
+        let mut channels = open_channels(&keys, &members, rng);
+
+        let message = channels[2].send(b"order book update", rng).unwrap();
+        assert_eq!(*channels[0].recieve(&message).unwrap(), b"order book update".to_vec());
+        assert_eq!(*channels[1].recieve(&message).unwrap(), b"order book update".to_vec());
+
+        let reply = channels[0].send(b"acknowledged", rng).unwrap();
+        assert_eq!(*channels[2].recieve(&reply).unwrap(), b"acknowledged".to_vec());
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, after which a message is received twice, and an older message after a newer one
+    /// Should fail, as the sequence numbers of a sender must increase
+    ///
+    #[test]
+    fn test_replayed_message_neg() {
+        let rng = &mut OsRng;
+        let mut channels = setup_channels(vec![20, 30, 40], rng);
+
+        let first = channels[0].send(b"first", rng).unwrap();
+        let second = channels[0].send(b"second", rng).unwrap();
+
+        channels[1].recieve(&first).unwrap();
+        assert!(channels[1].recieve(&first).is_err());
+
+        channels[2].recieve(&second).unwrap();
+        assert!(channels[2].recieve(&first).is_err());
+
+        //a message of the party itself is not received
+        assert!(channels[0].recieve(&second).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, after which a message is altered, attributed to another member, or sent by a party outside the group
+    /// Should fail, as messages are authenticated under the sender key of their sender, derived from the key of the group
+    ///
+    #[test]
+    fn test_forged_message_neg() {
+        let rng = &mut OsRng;
+        let mut channels = setup_channels(vec![20, 30, 40], rng);
+        let message = channels[0].send(b"transfer", rng).unwrap();
+
+        //altered ciphertext or sequence number
+        let mut altered = message.to_bytes();
+        let last = altered.len() - 1;
+        altered[last] ^= 1;
+        assert!(channels[1].recieve(&ChannelMessage::from_bytes(&altered).unwrap()).is_err());
+        let mut resequenced = message.clone();
+        resequenced.sequence += 1;
+        assert!(channels[1].recieve(&resequenced).is_err());
+
+        //attributed to another member
+        let mut reattributed = message.clone();
+        reattributed.sender = channels[2].id();
+        assert!(channels[1].recieve(&reattributed).is_err());
+
+        //sent by a party of another group, claiming to be a member
+        let mut outsiders = setup_channels(vec![20, 30], rng);
+        let mut outside = outsiders[0].send(b"transfer", rng).unwrap();
+        assert!(channels[1].recieve(&outside).is_err());
+        outside.sender = channels[0].id();
+        assert!(channels[1].recieve(&outside).is_err());
+
+        //the genuine message is still accepted, and too short messages are rejected
+        assert_eq!(*channels[1].recieve(&message).unwrap(), b"transfer".to_vec());
+        assert!(ChannelMessage::from_bytes(&[0u8; 16]).is_err());
+        let members: Vec<(PartyId, RistrettoPoint)> = channels[0].members().iter().map(|id| (*id, RistrettoPoint::random(rng))).collect();
+        assert!(GroupChannel::new(&RistrettoPoint::random(rng), outsiders[0].id(), ChannelSigningKey::generate(rng), &members).is_err());
+        assert!(GroupChannel::new(&RistrettoPoint::random(rng), channels[0].id(), ChannelSigningKey::generate(rng), &members).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, after which member A sends a message in the name of member B. A holds the session key,
+    /// such that it encrypts the message under the sender key of B, but signs it with its own signing key
+    /// Should fail, as the message is not signed with the signing key of B
+    ///
+    #[test]
+    fn test_member_forging_other_member_neg() {
+        let rng = &mut OsRng;
+        let sessions = run_compiler_group_session(3, vec![20, 30, 40], &params(), rng).unwrap();
+        let signing_keys: Vec<ChannelSigningKey> = sessions.iter().map(|_| ChannelSigningKey::generate(rng)).collect();
+        let members: Vec<(PartyId, RistrettoPoint)> = sessions.iter().zip(signing_keys.iter()).map(|(session, signing_key)| (session.id(), signing_key.verifying_key())).collect();
+        let mut channels: Vec<GroupChannel> = sessions.iter().zip(signing_keys).map(|(session, signing_key)| GroupChannel::new(&session.key().unwrap(), session.id(), signing_key, &members).unwrap()).collect();
+
+        //member A opens a channel as member B, listing its own verifying key for B
+        let forging_key = ChannelSigningKey::generate(rng);
+        let mut forged_members = members.clone();
+        forged_members[1].1 = forging_key.verifying_key();
+        let mut forger = GroupChannel::new(&sessions[0].key().unwrap(), sessions[1].id(), forging_key, &forged_members).unwrap();
+
+        let forged = forger.send(b"cancel all orders", rng).unwrap();
+        assert_eq!(forged.sender, sessions[1].id());
+        let err = channels[2].recieve(&forged).unwrap_err();
+        assert!(err.contains("Signature"));
+
+        //a genuine message of B is still accepted
+        let genuine = channels[1].send(b"cancel all orders", rng).unwrap();
+        assert_eq!(*channels[2].recieve(&genuine).unwrap(), b"cancel all orders".to_vec());
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, after which member A sends a message in the name of member B, with a signature forged
+    /// for the verifying key of B without its signing key: the response s is picked first, and the commitment solved for as R = g^s * X^(-c),
+    /// under a challenge c hashing the verifying key X and the message, but not R
+    /// Should fail, as the challenge of the signature hashes R
+    ///
+    #[test]
+    fn test_forged_signature_neg() {
+        let rng = &mut OsRng;
+        let sessions = run_compiler_group_session(3, vec![20, 30, 40], &params(), rng).unwrap();
+        let signing_keys: Vec<ChannelSigningKey> = sessions.iter().map(|_| ChannelSigningKey::generate(rng)).collect();
+        let members: Vec<(PartyId, RistrettoPoint)> = sessions.iter().zip(signing_keys.iter()).map(|(session, signing_key)| (session.id(), signing_key.verifying_key())).collect();
+        let mut channels: Vec<GroupChannel> = sessions.iter().zip(signing_keys).map(|(session, signing_key)| GroupChannel::new(&session.key().unwrap(), session.id(), signing_key, &members).unwrap()).collect();
+
+        //member A encrypts a message under the sender key of B, which it derives from the session key
+        let forging_key = ChannelSigningKey::generate(rng);
+        let mut forged_members = members.clone();
+        forged_members[1].1 = forging_key.verifying_key();
+        let mut forger = GroupChannel::new(&sessions[0].key().unwrap(), sessions[1].id(), forging_key, &forged_members).unwrap();
+        let mut bytes = forger.send(b"cancel all orders", rng).unwrap().to_bytes();
+
+        //signed bytes: the domain, the header and the ciphertext
+        let mut signed_bytes = CHANNEL_DOMAIN.to_vec();
+        signed_bytes.extend_from_slice(&bytes[..CHANNEL_HEADER_LEN]);
+        signed_bytes.extend_from_slice(&bytes[CHANNEL_HEADER_LEN + CHANNEL_SIGNATURE_LEN..]);
+
+        //signature for the verifying key of B, with R = g^s * X^(-c)
+        let g = PedersenGens::default().B;
+        let verifying_key = members[1].1;
+        let challenge = hash_string_to_scalar(ristretto_to_string(g) + &compressed_ristretto_to_string(verifying_key.compress()) + &hex::encode(&signed_bytes));
+        let response = Scalar::random(rng);
+        let commitment = g * response - verifying_key * challenge;
+        bytes[CHANNEL_HEADER_LEN..CHANNEL_HEADER_LEN + 32].copy_from_slice(commitment.compress().as_bytes());
+        bytes[CHANNEL_HEADER_LEN + 32..CHANNEL_HEADER_LEN + CHANNEL_SIGNATURE_LEN].copy_from_slice(response.as_bytes());
+
+        let forged = ChannelMessage::from_bytes(&bytes).unwrap();
+        assert_eq!(forged.sender, sessions[1].id());
+        let err = channels[2].recieve(&forged).unwrap_err();
+        assert!(err.contains("Signature"));
+
+        //a genuine message of B is still accepted
+        let genuine = channels[1].send(b"cancel all orders", rng).unwrap();
+        assert_eq!(*channels[2].recieve(&genuine).unwrap(), b"cancel all orders".to_vec());
+    }
+
+    ///
+    /// Announces the verifying key of a member with its WAKE signature
+    /// Asserts the announcement verifies under the commitment of the member, and fails under the commitment of another member
+    /// or for another verifying key
+    ///
+    #[test]
+    fn test_channel_key_announcement() {
+        let rng = &mut OsRng;
+        let params = params();
+        let (balances, blindings): (Vec<u64>, Vec<Scalar>) = (vec![20, 30], vec![Scalar::random(rng), Scalar::random(rng)]);
+        let commitments: Vec<RistrettoPoint> = (0..2).map(|i| (params.pc_gens().B * Scalar::from(UPPERBOUND - balances[i])) + (params.pc_gens().B_blinding * blindings[i])).collect();
+        let party: Party = compiler_bp_wake_protocol::Party::new(0, params.clone(), commitments.clone(), Ring::from_commitments(&commitments).unwrap(), balances[0], blindings[0]);
+
+        let signing_key = ChannelSigningKey::generate(rng);
+        let announcement = ChannelKeyAnnouncement::new(&params, party.id(), &signing_key, balances[0], blindings[0], rng);
+        assert_eq!(announcement.verify(&params, &commitments[0]).unwrap(), (party.id(), signing_key.verifying_key()));
+        assert!(announcement.verify(&params, &commitments[1]).is_err());
+
+        let mut replaced = announcement.clone();
+        replaced.verifying_key = ChannelSigningKey::generate(rng).verifying_key();
+        assert!(replaced.verify(&params, &commitments[0]).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol::run_compiler_group_session;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_membership::{GroupSession, run_full_rekey, run_leave};
+    use wake_implementation::protocols::channel::{ChannelSigningKey, GroupChannel};
+    use wake_implementation::protocols::ratchet::KeyRatchet;
+    use wake_implementation::protocols::party_id::PartyId;
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::ristretto::RistrettoPoint;
+    use std::sync::Arc;
+    use rand::rngs::OsRng;
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn setup_sessions(balances: Vec<u64>, rng: &mut OsRng) -> Vec<GroupSession> {
+        let params: Arc<WakeParams<BulletproofsKeys>> = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        run_compiler_group_session(balances.len(), balances, &params, rng).unwrap()
+    }
+
+    //opens the channel of every session, with a fresh signing key per member
+    fn setup_channels(sessions: &[GroupSession]) -> Vec<GroupChannel> {
+        let signing_keys: Vec<ChannelSigningKey> = sessions.iter().map(|_| ChannelSigningKey::generate(&mut OsRng)).collect();
+        let members: Vec<(PartyId, RistrettoPoint)> = sessions.iter().zip(signing_keys.iter()).map(|(session, signing_key)| (session.id(), signing_key.verifying_key())).collect();
+        sessions.iter().zip(signing_keys).map(|(session, signing_key)| GroupChannel::new(&session.key().unwrap(), session.id(), signing_key, &members).unwrap()).collect()
+    }
+
+    fn assert_same_keys(keys: &[RistrettoPoint]) {
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Runs ratchets from the same session key, and from another key
+    /// Asserts ratchets from the same key derive the same key in every epoch, and the keys of different epochs and session keys differ
+    ///
+    #[test]
+    fn test_ratchet_epoch_keys() {
+        let session_key = RistrettoPoint::random(&mut OsRng);
+        let mut ratchet = KeyRatchet::new(&session_key);
+        let mut other = KeyRatchet::new(&session_key);
+        let mut previous_keys = Vec::new();
+
+        for epoch in 0..4 {
+            let key = ratchet.epoch_key();
+            assert_eq!(key.epoch(), epoch);
+            assert_eq!(key.as_bytes(), other.epoch_key().as_bytes());
+            assert!(!previous_keys.contains(key.as_bytes()));
+            previous_keys.push(*key.as_bytes());
+
+            assert_eq!(ratchet.advance().as_bytes(), other.advance().as_bytes());
+        }
+        assert_eq!(ratchet.epoch(), 4);
+        assert_ne!(KeyRatchet::new(&RistrettoPoint::random(&mut OsRng)).epoch_key().as_bytes(), KeyRatchet::new(&session_key).epoch_key().as_bytes());
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, after which the parties exchange encrypted messages, rotate their channels, and continue
+    /// Asserts the messages of every epoch are received, with sequence numbers starting over in every epoch
+    ///
+    #[test]
+    fn test_channel_rotation() {
+        let rng = &mut OsRng;
+        let sessions = setup_sessions(vec![20, 30, 40], rng);
+        let mut channels = setup_channels(&sessions);
+
+        for epoch in 0..3 {
+            for sender in 0..channels.len() {
+                let message = channels[sender].send(b"pool update", rng).unwrap();
+                assert_eq!((message.epoch, message.sequence), (epoch, 0));
+                for (i, channel) in channels.iter_mut().enumerate() {
+                    if i != sender {
+                        assert_eq!(*channel.recieve(&message).unwrap(), b"pool update".to_vec());
+                    }
+                }
+            }
+            for channel in channels.iter_mut() {
+                channel.rotate().unwrap();
+            }
+        }
+        assert!(channels.iter().all(|channel| channel.epoch() == 3));
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, after which a message of an epoch is received once the receiver has rotated its channel,
+    /// and a message of a sender that has rotated is received by a party that has not
+    /// Should fail, as the keys of the earlier epoch are wiped, and the receiver does not know the keys of the later epoch yet
+    ///
+    #[test]
+    fn test_message_of_other_epoch_neg() {
+        let rng = &mut OsRng;
+        let sessions = setup_sessions(vec![20, 30, 40], rng);
+        let mut channels = setup_channels(&sessions);
+
+        let old_message = channels[0].send(b"before rotation", rng).unwrap();
+        channels[1].rotate().unwrap();
+        assert!(channels[1].recieve(&old_message).is_err());
+
+        //claiming a later epoch does not help, as the message is authenticated together with its epoch
+        let mut relabeled = old_message.clone();
+        relabeled.epoch = 1;
+        assert!(channels[1].recieve(&relabeled).is_err());
+
+        let new_message = channels[1].send(b"after rotation", rng).unwrap();
+        assert!(channels[2].recieve(&new_message).is_err());
+        channels[2].rotate().unwrap();
+        assert_eq!(*channels[2].recieve(&new_message).unwrap(), b"after rotation".to_vec());
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, after which the members re-key the session with a fresh Burmester-Desmedt, twice
+    /// Asserts the members produce similar and fresh keys, without a change of members, and open a channel with the fresh key
+    ///
+    #[test]
+    fn test_full_rekey() {
+        let rng = &mut OsRng;
+        let mut sessions = setup_sessions(vec![20, 30, 40, 50], rng);
+        let members = sessions[0].members().to_vec();
+        let mut previous_keys = vec![sessions[0].key().unwrap()];
+
+        for epoch in 1..3 {
+            let keys = run_full_rekey(&mut sessions, rng).unwrap();
+            assert_eq!(keys.len(), 4);
+            assert_same_keys(&keys);
+            assert!(!previous_keys.contains(&keys[0]));
+            previous_keys.push(keys[0]);
+            assert!(sessions.iter().all(|session| session.epoch() == epoch && session.members() == members.as_slice()));
+        }
+
+        let mut channels = setup_channels(&sessions);
+        let message = channels[3].send(b"fresh key", rng).unwrap();
+        assert_eq!(*channels[0].recieve(&message).unwrap(), b"fresh key".to_vec());
+
+        //a re-key also follows a membership change
+        let leaving = sessions[1].id();
+        run_leave(&mut sessions, &leaving, rng).unwrap();
+        assert_same_keys(&run_full_rekey(&mut sessions, rng).unwrap());
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, after which the fresh values of a completed re-key are replayed into the next re-key,
+    /// and a fresh value is sent under the identifier of another member
+    /// Should fail, as re-key messages are bound to the session before the re-key, and authenticated by the exponent of their sender
+    ///
+    #[test]
+    fn test_full_rekey_replay_and_forgery_neg() {
+        let rng = &mut OsRng;
+        let mut sessions = setup_sessions(vec![20, 30, 40], rng);
+
+        let mut old_outputs = Vec::new();
+        for session in sessions.iter_mut() {
+            session.start_rekey().unwrap();
+        }
+        for session in sessions.iter_mut() {
+            old_outputs.extend(session.refresh(rng).unwrap());
+        }
+        for session in sessions.iter_mut() {
+            session.refresh_recieve(&old_outputs).unwrap();
+        }
+        let mut update_outputs = Vec::new();
+        for session in sessions.iter_mut() {
+            update_outputs.extend(session.update(rng).unwrap());
+        }
+        for session in sessions.iter_mut() {
+            session.update_recieve(&update_outputs).unwrap();
+            session.compute_key().unwrap();
+        }
+
+        //replay into the next re-key
+        for session in sessions.iter_mut() {
+            session.start_rekey().unwrap();
+            assert!(session.start_rekey().is_err());
+        }
+        let mut refresh_outputs = Vec::new();
+        for session in sessions.iter_mut().skip(1) {
+            refresh_outputs.extend(session.refresh(rng).unwrap());
+        }
+        let mut replayed = refresh_outputs.clone();
+        replayed.push(old_outputs[0].clone());
+        assert!(sessions[1].refresh_recieve(&replayed).is_err());
+
+        //fresh value of party 0, sent by party 2 under the identifier of party 0
+        let mut forged = refresh_outputs[1].clone();
+        forged.0.sender = sessions[0].id();
+        refresh_outputs.push(forged);
+        assert!(sessions[1].refresh_recieve(&refresh_outputs).is_err());
+    }
+}
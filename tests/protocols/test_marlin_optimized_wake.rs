@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::optimized_marlin_wake::optimized_marlin_wake_protocol::run_optimized_key_exchange;
+    use wake_implementation::params::{WakeParams, MarlinKeys};
+    use std::sync::Arc;
+    use ark_bls12_377::Bls12_377;
+    use ark_ec::PairingEngine;
+    use rand::{rngs::OsRng, Rng};
+    use lazy_static::lazy_static;
+
+    use wake_implementation::utility::setup_marlin;
+
+    //setup of shared variables
+    lazy_static!{
+        static ref LARGE_MIN_BAL: u64 = 100000000;
+        static ref UPPER_U8: u64 = u8::MAX as u64;
+        static ref UPPER_U16: u64 = u16::MAX as u64;
+        static ref UPPER_U32: u64 = u32::MAX as u64;
+        static ref UPPER_U64: u64 = u64::MAX;
+    }
+    
+    ///
+    /// Runs Optimized WAKE key-exchange with a range-bound upperbound of 2^8-1
+    /// Asserts similar keys produced
+    /// 
+    #[test]
+    fn test_key_exchange_u8() {
+        let rng = &mut OsRng;
+        let min_bal = 10;
+        let party_amount = 10;
+        
+        let mut balances: Vec<u64> = Vec::new();
+        for _ in 0..party_amount {
+            //generate random balance larger than or equal, to the minimum balance
+            let balance = rng.gen_range(min_bal..=*UPPER_U8);
+            balances.push(balance);
+        }
+        
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(min_bal, *UPPER_U8, rng).unwrap());
+        
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
+        
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+    
+    ///
+    /// Runs optimized WAKE key-exchange with a range-bound upperbound of 2^16-1
+    /// Asserts similar keys produced
+    /// 
+    #[test]
+    fn test_key_exchange_u16() {
+        let rng = &mut OsRng;
+        let min_bal = 1000;
+        let party_amount = 10;
+        
+        let mut balances: Vec<u64> = Vec::new();
+        for _ in 0..party_amount {
+            //generate random balance larger than or equal, to the minimum balance
+            let balance = rng.gen_range(min_bal..=*UPPER_U16);
+            balances.push(balance);
+        }
+        
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(min_bal, *UPPER_U16, rng).unwrap());
+
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
+        
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+    
+    ///
+    /// Runs optimized WAKE key-exchange with a range-bound upperbound of 2^32-1
+    /// Asserts similar keys produced
+    /// 
+    #[test]
+    fn test_key_exchange_u32() {
+        let rng = &mut OsRng;
+        let min_bal = 1000;
+        let party_amount = 10;
+        
+        let mut balances: Vec<u64> = Vec::new();
+        for _ in 0..party_amount {
+            //generate random balance larger than or equal, to the minimum balance
+            let balance = rng.gen_range(min_bal..=*UPPER_U32);
+            balances.push(balance);
+        }
+
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(min_bal, *UPPER_U32, rng).unwrap());
+        
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
+        
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+    
+    ///
+    /// Runs optimized WAKE key-exchange with a range-bound upperbound of 2^64-1
+    /// Asserts similar keys produced
+    /// 
+    fn key_exchange_u64<E: PairingEngine>() {
+        let rng = &mut OsRng;
+        let party_amount = 10;
+        
+        let mut balances: Vec<u64> = Vec::new();
+        for _ in 0..party_amount {
+            //generate random balance larger than or equal, to the minimum balance
+            let balance = rng.gen_range(*LARGE_MIN_BAL..=*UPPER_U64);
+            balances.push(balance);
+        }
+
+        let params = Arc::new(WakeParams::<MarlinKeys<E>>::marlin(*LARGE_MIN_BAL, *UPPER_U64, rng).unwrap());
+        
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng).unwrap();
+        
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    #[test]
+    fn test_key_exchange_u64_bls12_377() {
+        key_exchange_u64::<Bls12_377>();
+    }
+
+
+    
+    ///
+    /// *Negative test*
+    /// Runs optimized WAKE key-exchange
+    /// Should return error, as balances are not above the minimum required balance
+    /// 
+    #[test]
+    fn test_key_exchange_balance_neg() {
+        let rng = &mut OsRng;
+        let party_amount = 10;
+        
+        let balances: Vec<u64> = vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18];
+        
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(*LARGE_MIN_BAL, *UPPER_U64, rng).unwrap());
+
+        let keys = run_optimized_key_exchange(party_amount, balances, &params, rng);
+        
+        assert!(keys.err().unwrap().to_string() == "one or more balances are smaller than minimum_balance");
+    }
+    
+    ///
+    /// *Negative test*
+    /// Runs optimized WAKE key-exchange
+    /// Should return error, as not enough balances supplied, or too many supplied
+    /// 
+    #[test]
+    fn test_key_exchange_balance_len_neg() {
+        let rng = &mut OsRng;
+        let party_amount = 10;
+        
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(*LARGE_MIN_BAL, *UPPER_U64, rng).unwrap());
+
+        let balances_one: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18];
+        let keys = run_optimized_key_exchange(party_amount, balances_one, &params, rng);
+        assert!(keys.err().unwrap().to_string() == "balances not correct length");
+        
+        let balances_two: Vec<u64> = vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20];
+        let keys = run_optimized_key_exchange(party_amount, balances_two, &params, rng);
+        assert!(keys.err().unwrap().to_string() == "balances not correct length");
+    }
+    
+    ///
+    /// *Negative test*
+    /// Runs optimized WAKE key-exchange
+    /// Should return error, as upppbound is not valid
+    /// 
+    #[test]
+    fn test_upperbound_neg() {
+        let rng = &mut OsRng;
+        let upperbound = 9;
+        
+        //assert wrong upperbound given to setup function fails
+        assert!(setup_marlin::<Bls12_377, _>(upperbound, rng).err().unwrap().to_string().starts_with("Upperbound should be either 2^8, 2^16, 2^32 or 2^64"));
+
+        //assert wrong upperbound given to the public parameters fails
+        let params = WakeParams::<MarlinKeys<Bls12_377>>::marlin(*LARGE_MIN_BAL, upperbound, rng);
+        assert!(params.err().unwrap().to_string().starts_with("upperbound was not among the valid values:"));
+    }
+    
+}
\ No newline at end of file
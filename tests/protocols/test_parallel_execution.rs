@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol;
+    use wake_implementation::params::WakeParams;
+    use wake_implementation::utility::{fork_rngs, map_items, map_parties_mut, map_parties_with_rng};
+    use curve25519_dalek_ng::ristretto::RistrettoPoint;
+    use std::sync::Arc;
+    use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+    const SEED: u64 = 2023;
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn compiler_bp_session(seed: u64) -> Vec<RistrettoPoint> {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let params = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        compiler_bp_wake_protocol::run_compiler_key_exchange(4, vec![20, 30, 40, 50], &params, rng).unwrap()
+    }
+
+    ///
+    /// Forks an rng per party twice from the same seed
+    /// Asserts the forked rngs are reproducible, and independent of each other
+    ///
+    #[test]
+    fn test_fork_rngs() {
+        let first: Vec<u64> = fork_rngs(&mut StdRng::seed_from_u64(SEED), 8).iter_mut().map(|rng| rng.next_u64()).collect();
+        let second: Vec<u64> = fork_rngs(&mut StdRng::seed_from_u64(SEED), 8).iter_mut().map(|rng| rng.next_u64()).collect();
+        assert_eq!(first, second);
+
+        let mut distinct = first.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), first.len());
+    }
+
+    ///
+    /// Maps over parties, with and without an rng per party
+    /// Asserts the outputs are in the order of the parties, and every party uses its own forked rng
+    ///
+    #[test]
+    fn test_map_parties_order() {
+        let mut parties: Vec<usize> = (0..64).collect();
+        assert_eq!(map_items(&parties, |party| party * 2), (0..64).map(|party| party * 2).collect::<Vec<usize>>());
+
+        map_parties_mut(&mut parties, |party| *party += 1);
+        assert_eq!(parties, (1..65).collect::<Vec<usize>>());
+
+        let outputs: Vec<(usize, u64)> = map_parties_with_rng(&mut parties, &mut StdRng::seed_from_u64(SEED), |party, rng| (*party, rng.gen()));
+        let expected: Vec<u64> = fork_rngs(&mut StdRng::seed_from_u64(SEED), parties.len()).iter_mut().map(|rng| rng.gen()).collect();
+        assert_eq!(outputs.iter().map(|(party, _)| *party).collect::<Vec<usize>>(), parties);
+        assert_eq!(outputs.iter().map(|(_, value)| *value).collect::<Vec<u64>>(), expected);
+    }
+
+    ///
+    /// Runs a seeded compiler WAKE session using Bulletproofs on a single thread, and on several threads
+    /// Asserts the keys are equal, as the output does not depend on the scheduling of the parties
+    ///
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_single_thread() {
+        let single = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap().install(|| compiler_bp_session(SEED));
+        let multi = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap().install(|| compiler_bp_session(SEED));
+        assert_eq!(single, multi);
+    }
+
+    ///
+    /// Runs a seeded compiler WAKE session using Bulletproofs twice
+    /// Asserts the keys are reproducible, and similar for all parties
+    ///
+    #[test]
+    fn test_seeded_session_with_forked_rngs() {
+        let keys = compiler_bp_session(SEED);
+        assert_eq!(keys, compiler_bp_session(SEED));
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+}
@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol::Party;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_signature::{CompMessage, CompSignature};
+    use wake_implementation::protocols::party_id::{PartyId, Ring};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::{rngs::OsRng, Rng};
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    //sets up compiler WAKE parties with random balances, outside of the protocol runner
+    fn setup_parties(party_amount: usize, rng: &mut OsRng) -> Vec<Party> {
+        let params: Arc<WakeParams<BulletproofsKeys>> = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(MIN_BAL..=UPPERBOUND)).collect();
+        let blindings: Vec<Scalar> = (0..party_amount).map(|_| Scalar::random(rng)).collect();
+        let commitments: Vec<RistrettoPoint> = (0..party_amount).map(|i| (params.pc_gens().B * Scalar::from(UPPERBOUND - balances[i])) + (params.pc_gens().B_blinding * blindings[i])).collect();
+        let ring = Ring::from_commitments(&commitments).unwrap();
+
+        (0..party_amount).map(|i| Party::new(i, params.clone(), commitments.clone(), ring.clone(), balances[i], blindings[i])).collect()
+    }
+
+    //delivers the messages to party i in a different order for every party
+    fn delivery_order<T: Clone>(messages: &[T], i: usize) -> Vec<T> {
+        let mut delivered = messages.to_vec();
+        delivered.rotate_left(i);
+        if i % 2 == 1 {
+            delivered.reverse();
+        }
+        delivered
+    }
+
+    ///
+    /// Derives the ring from the same commitments, given in two different orders
+    /// Asserts the ring order and the statements of the parties are the same
+    ///
+    #[test]
+    fn test_ring_independent_of_statement_order() {
+        let rng = &mut OsRng;
+        let commitments: Vec<RistrettoPoint> = (0..5).map(|_| RistrettoPoint::random(rng)).collect();
+        let reversed: Vec<RistrettoPoint> = commitments.iter().rev().cloned().collect();
+
+        let ring = Ring::from_commitments(&commitments).unwrap();
+        let reversed_ring = Ring::from_commitments(&reversed).unwrap();
+        assert_eq!(ring.ids(), reversed_ring.ids());
+
+        for id in ring.ids() {
+            let idx = ring.statement_idx(id).unwrap();
+            assert_eq!(commitments[idx], reversed[reversed_ring.statement_idx(id).unwrap()]);
+            assert_eq!(ring.id_of_statement(idx), Some(*id));
+        }
+        assert!(ring.ids().windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    ///
+    /// *Negative test*
+    /// Derives a ring from two equal commitments
+    /// Should fail, as the parties cannot be told apart
+    ///
+    #[test]
+    fn test_ring_duplicate_ids_neg() {
+        let commitment = RistrettoPoint::random(&mut OsRng);
+        assert!(Ring::from_commitments(&[commitment, commitment]).is_err());
+    }
+
+    ///
+    /// Arranges messages of the ring members, given in reverse order
+    /// Asserts the messages are ordered by ring position, and that unknown, duplicate or missing senders are rejected
+    ///
+    #[test]
+    fn test_arrange_by_sender() {
+        let rng = &mut OsRng;
+        let commitments: Vec<RistrettoPoint> = (0..4).map(|_| RistrettoPoint::random(rng)).collect();
+        let ring = Ring::from_commitments(&commitments).unwrap();
+
+        let received: Vec<(PartyId, usize)> = ring.ids().iter().enumerate().rev().map(|(position, id)| (*id, position)).collect();
+        assert_eq!(ring.arrange(&received).unwrap(), vec![0, 1, 2, 3]);
+
+        let mut unknown = received.clone();
+        unknown[0].0 = PartyId::from_commitment(&RistrettoPoint::random(rng));
+        assert!(ring.arrange(&unknown).is_err());
+
+        let mut duplicate = received.clone();
+        duplicate[0].0 = duplicate[1].0;
+        assert!(ring.arrange(&duplicate).is_err());
+
+        assert!(ring.arrange(&received[1..]).is_err());
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, where every party receives the messages of each round in a different order
+    /// Asserts similar keys produced
+    ///
+    #[test]
+    fn test_key_exchange_out_of_order_delivery() {
+        let rng = &mut OsRng;
+        let party_amount = 5;
+        let mut parties = setup_parties(party_amount, rng);
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        for (i, party) in parties.iter_mut().enumerate() {
+            party.round1_recieve(&delivery_order(&round1_outputs, i)).unwrap();
+        }
+
+        let round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+        let z_list: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+        for (i, party) in parties.iter().enumerate() {
+            party.round_recieve(delivery_order(&round2_outputs, i), 2, rng).unwrap();
+        }
+
+        let round3_outputs: Vec<(CompMessage, CompSignature)> = parties.iter().enumerate().map(|(i, party)| party.round_3(&delivery_order(&z_list, i), rng).unwrap()).collect();
+        let x_list: Vec<(PartyId, RistrettoPoint)> = round3_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+        for (i, party) in parties.iter().enumerate() {
+            party.round_recieve(delivery_order(&round3_outputs, i), 3, rng).unwrap();
+        }
+
+        let keys: Vec<RistrettoPoint> = parties.iter_mut().enumerate().map(|(i, party)| party.compute_key(&delivery_order(&z_list, i), &delivery_order(&x_list, i)).unwrap()).collect();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, where a round 2 message is delivered twice in place of another party's message
+    /// Should fail, as messages are matched to their sender by identifier
+    ///
+    #[test]
+    fn test_round_recieve_duplicate_sender_neg() {
+        let rng = &mut OsRng;
+        let mut parties = setup_parties(3, rng);
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        for party in parties.iter_mut() {
+            party.round1_recieve(&round1_outputs).unwrap();
+        }
+
+        let mut round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+        round2_outputs[2] = round2_outputs[1].clone();
+        assert!(parties[0].round_recieve(round2_outputs, 2, rng).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_signature::{CompMessage, CompSignature};
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
+    use wake_implementation::protocols::broadcast::{BroadcastView, Echo};
+    use wake_implementation::protocols::party_id::{PartyId, Ring};
+    use wake_implementation::protocols::robust::{Misbehaviour, RobustConfig};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::{rngs::{OsRng, StdRng}, Rng, SeedableRng};
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+    const SEED: u64 = 2023;
+
+    //round 2 messages of optimized WAKE, as received by a party
+    type OptimizedOutputs = Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)>;
+
+    //public statements and witnesses of the parties
+    struct Inputs {
+        params: Arc<WakeParams<BulletproofsKeys>>,
+        commitments: Vec<RistrettoPoint>,
+        ring: Ring,
+        balances: Vec<u64>,
+        blindings: Vec<Scalar>,
+    }
+
+    fn setup_inputs(party_amount: usize, rng: &mut OsRng) -> Inputs {
+        let params: Arc<WakeParams<BulletproofsKeys>> = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(MIN_BAL..=UPPERBOUND)).collect();
+        let blindings: Vec<Scalar> = (0..party_amount).map(|_| Scalar::random(rng)).collect();
+        let commitments: Vec<RistrettoPoint> = (0..party_amount).map(|i| (params.pc_gens().B * Scalar::from(UPPERBOUND - balances[i])) + (params.pc_gens().B_blinding * blindings[i])).collect();
+        let ring = Ring::from_commitments(&commitments).unwrap();
+
+        Inputs { params, commitments, ring, balances, blindings }
+    }
+
+    fn compiler_parties(inputs: &Inputs) -> Vec<compiler_bp_wake_protocol::Party> {
+        (0..inputs.balances.len()).map(|i| {
+            let mut party = compiler_bp_wake_protocol::Party::new(i, inputs.params.clone(), inputs.commitments.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i]);
+            party.enable_reliable_broadcast();
+            party
+        }).collect()
+    }
+
+    fn optimized_parties(inputs: &Inputs) -> Vec<optimized_bp_wake_protocol::Party> {
+        (0..inputs.balances.len()).map(|i| {
+            let mut party = optimized_bp_wake_protocol::Party::new(i, inputs.params.clone(), inputs.commitments.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i]);
+            party.enable_reliable_broadcast();
+            party
+        }).collect()
+    }
+
+    //runs round 1 and round 2 of compiler WAKE, where party 0 signs two different z values, and sends the second to the parties from split onwards
+    //outputs the round 2 messages as received by every party
+    fn compiler_equivocation(parties: &mut [compiler_bp_wake_protocol::Party], split: usize, rng: &mut OsRng) -> Vec<Vec<(CompMessage, CompSignature)>> {
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        for party in parties.iter_mut() {
+            party.round1_recieve(&round1_outputs).unwrap();
+        }
+
+        let round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+        let conflicting = parties[0].round_2(rng);
+        let received: Vec<Vec<(CompMessage, CompSignature)>> = (0..parties.len()).map(|i| {
+            let mut outputs = round2_outputs.clone();
+            if i >= split {
+                outputs[0] = conflicting.clone();
+            }
+            outputs
+        }).collect();
+
+        //both messages of party 0 are validly signed, such that every party accepts the round on its own
+        for (party, outputs) in parties.iter_mut().zip(received.iter()) {
+            party.round_recieve(outputs.clone(), 2, rng).unwrap();
+            party.record_broadcast(2, outputs);
+        }
+        received
+    }
+
+    //runs round 1 and round 2 of optimized WAKE, where party 0 signs two different X values, and sends the second to the parties from split onwards
+    //outputs the round 2 messages as received by every party
+    fn optimized_equivocation(inputs: &Inputs, parties: &mut [optimized_bp_wake_protocol::Party], split: usize, rng: &mut OsRng) -> Vec<OptimizedOutputs> {
+        //party 0 draws its Burmester-Desmedt exponent from a seeded rng, such that it can sign a second message
+        let x_0 = Scalar::random(&mut StdRng::seed_from_u64(SEED));
+        let mut round_1_outputs: Vec<(PartyId, RistrettoPoint)> = vec![parties[0].round_1(&mut StdRng::seed_from_u64(SEED))];
+        round_1_outputs.extend(parties[1..].iter_mut().map(|party| party.round_1(rng)));
+
+        let round_2_outputs: OptimizedOutputs = parties.iter_mut().map(|party| party.round_2(&round_1_outputs, rng).unwrap()).collect();
+        let (message, _) = round_2_outputs[0].clone();
+        let conflicting_message = OptimizedMessage::new(message.sender, message.payload + inputs.params.g(), message.vk.clone());
+        let conflicting_signature = OptimizedSignatureAndSessionAuthentication::sign(
+            &inputs.params,
+            &conflicting_message.to_string(),
+            inputs.balances[0],
+            inputs.blindings[0],
+            (inputs.params.g() * x_0).compress(),
+            x_0,
+            &(inputs.commitments[0] + (inputs.params.g() * Scalar::from(MIN_BAL))),
+            rng);
+
+        let received: Vec<OptimizedOutputs> = (0..parties.len()).map(|i| {
+            let mut outputs = round_2_outputs.clone();
+            if i >= split {
+                outputs[0] = (conflicting_message.clone(), conflicting_signature.clone());
+            }
+            outputs
+        }).collect();
+
+        for (party, outputs) in parties.iter_mut().zip(received.iter()) {
+            party.verify_round_2_outputs(&round_1_outputs, outputs.clone(), inputs.commitments.clone(), rng).unwrap();
+            party.record_broadcast(outputs);
+        }
+        received
+    }
+
+    ///
+    /// Records conflicting messages of a sender in the views of two parties
+    /// Asserts the echoes of the views conflict on the message of the sender only, and are consistent with the view they are taken from
+    ///
+    #[test]
+    fn test_broadcast_view_conflicts() {
+        let (first, second, sender) = (PartyId::from_bytes([1; 32]), PartyId::from_bytes([2; 32]), PartyId::from_bytes([3; 32]));
+        let (mut view_1, mut view_2): (BroadcastView<String>, BroadcastView<String>) = (BroadcastView::new(), BroadcastView::new());
+        for (view, message) in [(&mut view_1, "first"), (&mut view_2, "second")] {
+            view.record(2, first, "message of first", "message of first".to_string());
+            view.record(2, sender, message, message.to_string());
+        }
+
+        assert!(view_1.conflicts(&view_1.echo(2, first)).is_empty());
+        let conflicts = view_1.conflicts(&view_2.echo(2, second));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].sender, conflicts[0].echoer), (sender, second));
+        assert_eq!(view_2.evidence(2, &sender), Some("second".to_string()));
+        assert!(view_1.conflicts(&view_2.echo(3, second)).is_empty());
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs over the reliable broadcast, with honest parties
+    /// Asserts all parties compute the same key
+    ///
+    #[test]
+    fn test_reliable_compiler_key_exchange() {
+        let params = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        let keys = compiler_bp_wake_protocol::run_reliable_compiler_key_exchange(4, vec![20, 30, 40, 50], &params, &mut OsRng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Runs optimized WAKE using Bulletproofs over the reliable broadcast, with honest parties
+    /// Asserts all parties compute the same key
+    ///
+    #[test]
+    fn test_reliable_optimized_key_exchange() {
+        let params = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        let keys = optimized_bp_wake_protocol::run_reliable_optimized_key_exchange(4, vec![20, 30, 40, 50], &params, &mut OsRng).unwrap();
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs round 2 of compiler WAKE using Bulletproofs, where party 0 sends differently signed z values to two halves of the ring
+    /// Asserts every party accepts the round on its own, while the exchange of echoes blames party 0 at every other party
+    ///
+    #[test]
+    fn test_compiler_equivocation_blamed() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(4, rng);
+        let mut parties = compiler_parties(&inputs);
+        compiler_equivocation(&mut parties, 2, rng);
+
+        let echoes: Vec<Echo> = parties.iter().map(|party| party.echo(2).unwrap()).collect();
+        let equivocator = parties[0].id();
+        for party in parties[1..].iter() {
+            let misbehaviour = party.check_echoes(&echoes, |conflict| parties.iter().find(|other| other.id() == conflict.echoer).and_then(|other| other.evidence(conflict))).unwrap_err();
+            assert_eq!(misbehaviour.parties, vec![equivocator]);
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs round 2 of optimized WAKE using Bulletproofs, where party 0 sends differently signed X values to two halves of the ring
+    /// Asserts the exchange of echoes blames party 0 at every other party
+    ///
+    #[test]
+    fn test_optimized_equivocation_blamed() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(4, rng);
+        let mut parties = optimized_parties(&inputs);
+        optimized_equivocation(&inputs, &mut parties, 2, rng);
+
+        let echoes: Vec<Echo> = parties.iter().map(|party| party.echo().unwrap()).collect();
+        let equivocator = parties[0].id();
+        for party in parties[1..].iter() {
+            let misbehaviour = party.check_echoes(&echoes, |conflict| parties.iter().find(|other| other.id() == conflict.echoer).and_then(|other| other.evidence(conflict))).unwrap_err();
+            assert_eq!(misbehaviour.parties, vec![equivocator]);
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs round 2 of compiler WAKE using Bulletproofs with honest parties, where party 1 echoes a forged digest for the message of party 0,
+    /// and a second echo missing the message of party 0
+    /// Asserts party 1 is blamed rather than party 0, as its echoes are not backed by a signed message of party 0
+    ///
+    #[test]
+    fn test_forged_echo_blames_echoer() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(3, rng);
+        let mut parties = compiler_parties(&inputs);
+        compiler_equivocation(&mut parties, 3, rng);
+        let (sender, forger) = (parties[0].id(), parties[1].id());
+
+        let mut forged = parties[1].echo(2).unwrap();
+        forged.digests.iter_mut().filter(|(id, _)| *id == sender).for_each(|(_, digest)| digest[0] ^= 1);
+        let evidence = |conflict: &_| parties[1].evidence(conflict);
+        let misbehaviour: Misbehaviour = parties[2].check_echoes(&[parties[0].echo(2).unwrap(), forged], evidence).unwrap_err();
+        assert_eq!(misbehaviour.parties, vec![forger]);
+
+        let mut incomplete = parties[1].echo(2).unwrap();
+        incomplete.digests.retain(|(id, _)| *id != sender);
+        let misbehaviour = parties[2].check_echoes(&[incomplete], evidence).unwrap_err();
+        assert_eq!(misbehaviour.parties, vec![forger]);
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs without enabling the reliable broadcast
+    /// Asserts the parties neither echo their messages nor check echoes
+    ///
+    #[test]
+    fn test_broadcast_not_enabled() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(2, rng);
+        let party = compiler_bp_wake_protocol::Party::new(0, inputs.params.clone(), inputs.commitments.clone(), inputs.ring.clone(), inputs.balances[0], inputs.blindings[0]);
+        assert!(party.echo(2).is_none());
+        assert!(party.check_echoes(&[], |_| None).is_err());
+    }
+
+    ///
+    /// Runs robust optimized WAKE using Bulletproofs over the reliable broadcast, with honest parties
+    /// Asserts the session succeeds in a single attempt
+    ///
+    #[test]
+    fn test_robust_reliable_optimized() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(4, rng);
+        let outcome = optimized_bp_wake_protocol::run_robust_optimized_key_exchange(optimized_parties(&inputs), &RobustConfig::new(1, 2).unwrap(), rng).unwrap();
+        assert_eq!(outcome.attempts, 1);
+        assert!(outcome.excluded.is_empty());
+    }
+}
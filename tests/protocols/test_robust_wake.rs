@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol::{self, run_robust_compiler_key_exchange};
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol::{self, run_robust_optimized_key_exchange};
+    use wake_implementation::protocols::{compiler_gm17_wake::compiler_gm17_wake_protocol, optimized_gm17_wake::optimized_gm17_wake_protocol};
+    use wake_implementation::protocols::{compiler_marlin_wake::compiler_marlin_wake_protocol, optimized_marlin_wake::optimized_marlin_wake_protocol};
+    use wake_implementation::protocols::party_id::{PartyId, Ring};
+    use wake_implementation::protocols::robust::{RobustConfig, RobustOutcome};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys, Gm17Keys, MarlinKeys};
+    use wake_implementation::utility::mimc;
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use ark_bls12_377::{Bls12_377, Fr};
+    use ark_ff::PrimeField;
+    use std::sync::Arc;
+    use rand::{rngs::OsRng, Rng};
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    //public statements and witnesses of the parties, where party 0 holds a blinding that does not open its commitment
+    struct Inputs {
+        params: Arc<WakeParams<BulletproofsKeys>>,
+        commitments: Vec<RistrettoPoint>,
+        ring: Ring,
+        balances: Vec<u64>,
+        blindings: Vec<Scalar>,
+    }
+
+    fn setup_inputs(party_amount: usize, rng: &mut OsRng) -> Inputs {
+        let params: Arc<WakeParams<BulletproofsKeys>> = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(MIN_BAL..=UPPERBOUND)).collect();
+        let mut blindings: Vec<Scalar> = (0..party_amount).map(|_| Scalar::random(rng)).collect();
+        let commitments: Vec<RistrettoPoint> = (0..party_amount).map(|i| (params.pc_gens().B * Scalar::from(UPPERBOUND - balances[i])) + (params.pc_gens().B_blinding * blindings[i])).collect();
+        blindings[0] = Scalar::random(rng);
+        let ring = Ring::from_commitments(&commitments).unwrap();
+
+        Inputs { params, commitments, ring, balances, blindings }
+    }
+
+    fn compiler_parties(inputs: &Inputs) -> Vec<compiler_bp_wake_protocol::Party> {
+        (0..inputs.balances.len()).map(|i| compiler_bp_wake_protocol::Party::new(i, inputs.params.clone(), inputs.commitments.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i])).collect()
+    }
+
+    fn optimized_parties(inputs: &Inputs) -> Vec<optimized_bp_wake_protocol::Party> {
+        (0..inputs.balances.len()).map(|i| optimized_bp_wake_protocol::Party::new(i, inputs.params.clone(), inputs.commitments.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i])).collect()
+    }
+
+    //public images and witnesses of the parties of the GM17 and Marlin protocols, where party 0 holds a blinding that does not open its image
+    struct ImageInputs<F: PrimeField> {
+        images: Vec<F>,
+        ring: Ring,
+        balances: Vec<u64>,
+        blindings: Vec<F>,
+    }
+
+    fn setup_image_inputs<F: PrimeField>(party_amount: usize, mimc_constants: &[F], rng: &mut OsRng) -> ImageInputs<F> {
+        let balances: Vec<u64> = (0..party_amount).map(|_| rng.gen_range(MIN_BAL..=UPPERBOUND)).collect();
+        let mut blindings: Vec<F> = (0..party_amount).map(|_| F::rand(rng)).collect();
+        let images: Vec<F> = (0..party_amount).map(|i| mimc(F::from(UPPERBOUND - (balances[i] - MIN_BAL)), blindings[i], mimc_constants)).collect();
+        blindings[0] = F::rand(rng);
+        let ring = Ring::from_images(&images).unwrap();
+
+        ImageInputs { images, ring, balances, blindings }
+    }
+
+    //asserts the cheating party 0 was excluded in a single restart, and the remaining parties agree on a key
+    fn assert_cheater_excluded(inputs: &Inputs, outcome: &RobustOutcome) {
+        assert_excluded(&inputs.commitments.iter().map(PartyId::from_commitment).collect::<Vec<_>>(), outcome);
+    }
+
+    //asserts the cheating party 0 of the GM17 or Marlin protocols was excluded in a single restart, and the remaining parties agree on a key
+    fn assert_image_cheater_excluded<F: PrimeField>(inputs: &ImageInputs<F>, outcome: &RobustOutcome) {
+        assert_excluded(&inputs.images.iter().map(PartyId::from_image).collect::<Vec<_>>(), outcome);
+    }
+
+    fn assert_excluded(ids: &[PartyId], outcome: &RobustOutcome) {
+        let cheater = ids[0];
+        let mut honest: Vec<PartyId> = ids[1..].to_vec();
+        honest.sort();
+
+        assert_eq!(outcome.excluded, vec![cheater]);
+        assert_eq!(outcome.members, honest);
+        assert_eq!(outcome.attempts, 2);
+        assert_eq!(outcome.keys.len(), honest.len());
+        for i in 1..outcome.keys.len() {
+            assert_eq!(outcome.keys[i-1], outcome.keys[i])
+        }
+    }
+
+    ///
+    /// Runs robust compiler WAKE using Bulletproofs, where one party cannot prove its balance
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn test_robust_compiler_excludes_cheater() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(4, rng);
+        let config = RobustConfig::new(2, 2).unwrap();
+
+        let outcome = run_robust_compiler_key_exchange(compiler_parties(&inputs), &config, rng).unwrap();
+        assert_cheater_excluded(&inputs, &outcome);
+    }
+
+    ///
+    /// Runs robust optimized WAKE using Bulletproofs, where one party cannot prove its balance
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn test_robust_optimized_excludes_cheater() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(4, rng);
+        let config = RobustConfig::new(2, 2).unwrap();
+
+        let outcome = run_robust_optimized_key_exchange(optimized_parties(&inputs), &config, rng).unwrap();
+        assert_cheater_excluded(&inputs, &outcome);
+    }
+
+    ///
+    /// Runs robust compiler WAKE using GM17, where one party cannot prove its balance
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn test_robust_gm17_compiler_excludes_cheater() {
+        let rng = &mut OsRng;
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(4, MIN_BAL, UPPERBOUND, rng).unwrap());
+        let inputs = setup_image_inputs::<Fr>(4, &params.keys().gm17_darkpool().mimc_constants, rng);
+        let parties = (0..4).map(|i| compiler_gm17_wake_protocol::Party::new(i, params.clone(), inputs.images.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i])).collect();
+
+        let outcome = compiler_gm17_wake_protocol::run_robust_compiler_key_exchange(parties, &RobustConfig::new(2, 2).unwrap(), rng).unwrap();
+        assert_image_cheater_excluded(&inputs, &outcome);
+    }
+
+    ///
+    /// Runs robust optimized WAKE using GM17, where one party cannot prove its balance
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn test_robust_gm17_optimized_excludes_cheater() {
+        let rng = &mut OsRng;
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(4, MIN_BAL, UPPERBOUND, rng).unwrap());
+        let inputs = setup_image_inputs::<Fr>(4, &params.keys().gm17_darkpool().mimc_constants, rng);
+        let parties = (0..4).map(|i| optimized_gm17_wake_protocol::Party::new(i, params.clone(), inputs.images.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i])).collect();
+
+        let outcome = optimized_gm17_wake_protocol::run_robust_optimized_key_exchange(parties, &RobustConfig::new(2, 2).unwrap(), rng).unwrap();
+        assert_image_cheater_excluded(&inputs, &outcome);
+    }
+
+    ///
+    /// Runs robust compiler WAKE using Marlin, where one party cannot prove its balance
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn test_robust_marlin_compiler_excludes_cheater() {
+        let rng = &mut OsRng;
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(MIN_BAL, UPPERBOUND, rng).unwrap());
+        let inputs = setup_image_inputs::<Fr>(4, &params.keys().marlin_darkpool().mimc_constants, rng);
+        let parties = (0..4).map(|i| compiler_marlin_wake_protocol::Party::new(i, params.clone(), inputs.images.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i])).collect();
+
+        let outcome = compiler_marlin_wake_protocol::run_robust_compiler_key_exchange(parties, &RobustConfig::new(2, 2).unwrap(), rng).unwrap();
+        assert_image_cheater_excluded(&inputs, &outcome);
+    }
+
+    ///
+    /// Runs robust optimized WAKE using Marlin, where one party cannot prove its balance
+    /// Asserts the party is excluded, and the remaining parties produce similar keys after a restart
+    ///
+    #[test]
+    fn test_robust_marlin_optimized_excludes_cheater() {
+        let rng = &mut OsRng;
+        let params = Arc::new(WakeParams::<MarlinKeys<Bls12_377>>::marlin(MIN_BAL, UPPERBOUND, rng).unwrap());
+        let inputs = setup_image_inputs::<Fr>(4, &params.keys().marlin_darkpool().mimc_constants, rng);
+        let parties = (0..4).map(|i| optimized_marlin_wake_protocol::Party::new(i, params.clone(), inputs.images.clone(), inputs.ring.clone(), inputs.balances[i], inputs.blindings[i])).collect();
+
+        let outcome = optimized_marlin_wake_protocol::run_robust_optimized_key_exchange(parties, &RobustConfig::new(2, 2).unwrap(), rng).unwrap();
+        assert_image_cheater_excluded(&inputs, &outcome);
+    }
+
+    ///
+    /// Runs robust compiler WAKE using GM17 between honest parties
+    /// Asserts no party is excluded, and similar keys are produced in the first attempt
+    ///
+    #[test]
+    fn test_robust_gm17_compiler_honest_parties() {
+        let rng = &mut OsRng;
+        let params = Arc::new(WakeParams::<Gm17Keys<Bls12_377>>::gm17(3, MIN_BAL, UPPERBOUND, rng).unwrap());
+        let parties = compiler_gm17_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params).unwrap();
+
+        let outcome = compiler_gm17_wake_protocol::run_robust_compiler_key_exchange(parties, &RobustConfig::new(1, 2).unwrap(), rng).unwrap();
+        assert!(outcome.excluded.is_empty());
+        assert_eq!(outcome.attempts, 1);
+        for i in 1..outcome.keys.len() {
+            assert_eq!(outcome.keys[i-1], outcome.keys[i])
+        }
+    }
+
+    ///
+    /// Runs robust compiler WAKE using Bulletproofs between honest parties
+    /// Asserts no party is excluded, and similar keys are produced in the first attempt
+    ///
+    #[test]
+    fn test_robust_compiler_honest_parties() {
+        let rng = &mut OsRng;
+        let parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap())).unwrap();
+        let config = RobustConfig::new(1, 2).unwrap();
+
+        let outcome = run_robust_compiler_key_exchange(parties, &config, rng).unwrap();
+        assert!(outcome.excluded.is_empty());
+        assert_eq!(outcome.members.len(), 3);
+        assert_eq!(outcome.attempts, 1);
+        for i in 1..outcome.keys.len() {
+            assert_eq!(outcome.keys[i-1], outcome.keys[i])
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs robust compiler WAKE using Bulletproofs with a cheating party, where no restarts are allowed
+    /// Should fail, as the key-exchange cannot be restarted without the cheating party
+    ///
+    #[test]
+    fn test_robust_compiler_no_retries_neg() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(3, rng);
+        let config = RobustConfig::new(0, 2).unwrap();
+
+        assert!(run_robust_compiler_key_exchange(compiler_parties(&inputs), &config, rng).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs robust compiler WAKE using Bulletproofs, where excluding the cheating parties leaves fewer parties than the minimum group size
+    /// Should fail, as the key-exchange is not restarted with too few parties
+    ///
+    #[test]
+    fn test_robust_compiler_min_group_size_neg() {
+        let rng = &mut OsRng;
+        let inputs = setup_inputs(3, rng);
+        let config = RobustConfig::new(2, 3).unwrap();
+
+        assert!(run_robust_compiler_key_exchange(compiler_parties(&inputs), &config, rng).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Creates a robust configuration with a minimum group size of a single party
+    /// Should fail, as Burmester-Desmedt requires at least two parties
+    ///
+    #[test]
+    fn test_robust_config_group_size_neg() {
+        assert!(RobustConfig::new(1, 1).is_err());
+    }
+}
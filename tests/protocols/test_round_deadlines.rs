@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_signature::{CompMessage, CompSignature};
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol;
+    use wake_implementation::protocols::party_id::PartyId;
+    use wake_implementation::protocols::robust::Misbehaviour;
+    use wake_implementation::protocols::timeout::{RoundCollector, RoundDeadlines, RoundStatus, SimulatedClock, SystemClock, Timeout};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::{sync::Arc, time::Duration};
+    use rand::rngs::OsRng;
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn params() -> Arc<WakeParams<BulletproofsKeys>> {
+        Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap())
+    }
+
+    ///
+    /// Runs round 1 of compiler WAKE using Bulletproofs, where all parties deliver their nonces before the deadline
+    /// Asserts the round completes with the nonces in ring order, which the parties accept
+    ///
+    #[test]
+    fn test_round_complete_before_deadline() {
+        let rng = &mut OsRng;
+        let clock = SimulatedClock::new();
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        let mut collector: RoundCollector<Scalar> = parties[0].collect_round(1, &clock);
+        for (sender, nonce) in round1_outputs.iter().rev() {
+            assert_eq!(collector.poll(&clock), RoundStatus::Waiting);
+            collector.recieve(*sender, *nonce).unwrap();
+        }
+
+        clock.advance(Duration::from_secs(1));
+        match collector.poll(&clock) {
+            RoundStatus::Complete(nonces) => parties[0].round1_recieve(&nonces).unwrap(),
+            status => panic!("round did not complete: {:?}", status),
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs round 2 of compiler WAKE using Bulletproofs, where one party stays silent
+    /// Should time out once the deadline of the round passes, listing the silent party
+    ///
+    #[test]
+    fn test_compiler_round_timeout_neg() {
+        let rng = &mut OsRng;
+        let clock = SimulatedClock::new();
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+        let deadlines = RoundDeadlines::new(Duration::from_secs(30)).with_round(2, Duration::from_secs(5));
+        for party in parties.iter_mut() {
+            party.set_deadlines(deadlines.clone());
+        }
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        for party in parties.iter_mut() {
+            party.round1_recieve(&round1_outputs).unwrap();
+        }
+
+        let mut collector: RoundCollector<(CompMessage, CompSignature)> = parties[0].collect_round(2, &clock);
+        let silent = parties[2].id();
+        for party in parties.iter_mut().take(2) {
+            let (message, signature) = party.round_2(rng);
+            collector.recieve(message.sender, (message, signature)).unwrap();
+        }
+
+        clock.advance(Duration::from_secs(4));
+        assert!(matches!(collector.poll(&clock), RoundStatus::Waiting));
+        clock.advance(Duration::from_secs(1));
+        match collector.poll(&clock) {
+            RoundStatus::TimedOut(timeout) => assert_eq!(timeout, Timeout { round: 2, silent: vec![silent] }),
+            _ => panic!("round did not time out"),
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs round 1 of optimized WAKE using Bulletproofs, where two parties stay silent
+    /// Should time out once the default deadline passes, listing the silent parties in ring order
+    ///
+    #[test]
+    fn test_optimized_round_timeout_neg() {
+        let rng = &mut OsRng;
+        let clock = SimulatedClock::new();
+        let mut parties = optimized_bp_wake_protocol::setup_parties(rng, 4, vec![20, 30, 40, 50], &params()).unwrap();
+
+        let mut collector: RoundCollector<RistrettoPoint> = parties[0].collect_round(1, &clock);
+        let (sender, z) = parties[0].round_1(rng);
+        collector.recieve(sender, z).unwrap();
+        let (sender, z) = parties[3].round_1(rng);
+        collector.recieve(sender, z).unwrap();
+
+        let mut silent = vec![parties[1].id(), parties[2].id()];
+        silent.sort();
+        clock.advance(RoundDeadlines::default().timeout(1));
+        assert_eq!(collector.poll(&clock), RoundStatus::TimedOut(Timeout { round: 1, silent: silent.clone() }));
+        assert_eq!(collector.silent(), silent);
+    }
+
+    ///
+    /// Times out a round, and converts the timeout into misbehaviour
+    /// Asserts the silent parties are blamed, such that a robust key-exchange excludes them
+    ///
+    #[test]
+    fn test_timeout_blames_silent_parties() {
+        let silent: Vec<PartyId> = (0..2).map(|_| PartyId::from_commitment(&RistrettoPoint::random(&mut OsRng))).collect();
+        let misbehaviour = Misbehaviour::from(Timeout { round: 3, silent: silent.clone() });
+
+        let mut expected = silent;
+        expected.sort();
+        assert_eq!(misbehaviour.clone().into_result().unwrap_err().parties, expected);
+        assert!(misbehaviour.reason.contains("Round 3"));
+    }
+
+    ///
+    /// *Negative test*
+    /// Collects a round using the system clock, with a timeout of zero, where no party sends a message
+    /// Should time out immediately, listing all parties
+    ///
+    #[test]
+    fn test_system_clock_timeout_neg() {
+        let rng = &mut OsRng;
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 2, vec![20, 30], &params()).unwrap();
+        parties[1].set_deadlines(RoundDeadlines::new(Duration::ZERO));
+
+        let clock = SystemClock::new();
+        let collector: RoundCollector<Scalar> = parties[1].collect_round(1, &clock);
+        match collector.poll(&clock) {
+            RoundStatus::TimedOut(timeout) => assert_eq!(timeout.silent.len(), 2),
+            status => panic!("round did not time out: {:?}", status),
+        }
+    }
+
+    ///
+    /// *Negative test*
+    /// Collects round 1 of compiler WAKE using Bulletproofs, receiving a message of an unknown party and a second message of a member
+    /// Should fail, as only one message per member is collected
+    ///
+    #[test]
+    fn test_round_collector_rejects_unknown_and_duplicate_neg() {
+        let rng = &mut OsRng;
+        let clock = SimulatedClock::new();
+        let parties = compiler_bp_wake_protocol::setup_parties(rng, 2, vec![20, 30], &params()).unwrap();
+
+        let mut collector: RoundCollector<Scalar> = parties[0].collect_round(1, &clock);
+        let unknown = PartyId::from_commitment(&RistrettoPoint::random(rng));
+        assert!(collector.recieve(unknown, Scalar::random(rng)).is_err());
+
+        let (sender, nonce) = parties[1].round_1(rng);
+        collector.recieve(sender, nonce).unwrap();
+        assert!(collector.recieve(sender, nonce).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_signature::{CompMessage, CompSignature};
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
+    use wake_implementation::protocols::party_id::{PartyId, Ring};
+    use wake_implementation::protocols::session_id::{ReplayCache, SessionContext, SessionId, DEFAULT_APPLICATION};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::rngs::OsRng;
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+
+    fn params() -> Arc<WakeParams<BulletproofsKeys>> {
+        Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap())
+    }
+
+    //runs round 1 of compiler WAKE, where every party receives the given round 1 outputs
+    fn compiler_round_1(parties: &mut [compiler_bp_wake_protocol::Party], round1_outputs: &Vec<(PartyId, Scalar)>) {
+        for party in parties.iter_mut() {
+            party.round1_recieve(round1_outputs).unwrap();
+        }
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, with all parties in the same context
+    /// Asserts all parties derive the same session id, which differs from the session id in another context, and accept the round 2 messages
+    ///
+    #[test]
+    fn test_compiler_session_id() {
+        let rng = &mut OsRng;
+        let context = SessionContext::new("darkpool", "pool-1", 7);
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+        for party in parties.iter_mut() {
+            party.set_context(context.clone());
+        }
+        assert_eq!(parties[0].session_id(), None);
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        compiler_round_1(&mut parties, &round1_outputs);
+        let session_id = parties[0].session_id().unwrap();
+        assert!(parties.iter().all(|party| party.session_id() == Some(session_id)));
+
+        let round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+        for party in parties.iter() {
+            party.round_recieve(round2_outputs.clone(), 2, rng).unwrap();
+        }
+
+        //the same round 1 outputs in the next epoch of the pool form another session
+        parties[0].set_context(SessionContext::new("darkpool", "pool-1", 8));
+        parties[0].round1_recieve(&round1_outputs).unwrap();
+        assert_ne!(parties[0].session_id(), Some(session_id));
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs compiler WAKE using Bulletproofs, where one party runs the session for another pool
+    /// Should fail, as the parties derive different session ids, blaming the party in the other context
+    ///
+    #[test]
+    fn test_compiler_other_context_neg() {
+        let rng = &mut OsRng;
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+        for party in parties.iter_mut() {
+            party.set_context(SessionContext::new("darkpool", "pool-1", 0));
+        }
+        parties[2].set_context(SessionContext::new("darkpool", "pool-2", 0));
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        compiler_round_1(&mut parties, &round1_outputs);
+        let round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+
+        let misbehaviour = parties[0].round_recieve(round2_outputs, 2, rng).unwrap_err();
+        assert_eq!(misbehaviour.parties, vec![parties[2].id()]);
+        assert!(misbehaviour.reason.contains("Invalid session id"));
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs two sessions of compiler WAKE using Bulletproofs with the same parties, replaying a round 2 message of the first session into the second
+    /// Should fail, as the replayed message and its proof are bound to the session id of the first session
+    ///
+    #[test]
+    fn test_compiler_replay_into_next_session_neg() {
+        let rng = &mut OsRng;
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        compiler_round_1(&mut parties, &round1_outputs);
+        let old_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        compiler_round_1(&mut parties, &round1_outputs);
+        let mut round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+
+        //replay the message of party 1, with its nonces replaced by those of the new session
+        let mut replayed = old_outputs[1].clone();
+        replayed.0.nonces = round2_outputs[1].0.nonces.clone();
+        round2_outputs[1] = replayed;
+
+        let misbehaviour = parties[0].round_recieve(round2_outputs, 2, rng).unwrap_err();
+        assert_eq!(misbehaviour.parties, vec![parties[1].id()]);
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs sessions of compiler WAKE using Bulletproofs, where every party keeps a replay cache, replaying the round 1 outputs of an earlier session
+    /// Should fail, both for a replay of the complete session, and for a replay of a single nonce
+    ///
+    #[test]
+    fn test_compiler_replay_cache_neg() {
+        let rng = &mut OsRng;
+        let mut parties = compiler_bp_wake_protocol::setup_parties(rng, 3, vec![20, 30, 40], &params()).unwrap();
+        let caches: Vec<_> = parties.iter().map(|_| ReplayCache::shared()).collect();
+        for (party, cache) in parties.iter_mut().zip(caches.iter()) {
+            party.set_replay_cache(cache.clone());
+        }
+
+        let old_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        compiler_round_1(&mut parties, &old_outputs);
+        assert!(caches[0].lock().unwrap().contains(&parties[0].session_id().unwrap()));
+
+        //replay of the complete session
+        assert!(parties[0].round1_recieve(&old_outputs).is_err());
+
+        //replay of the nonce of a single party
+        let mut round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        round1_outputs[2].1 = old_outputs[2].1;
+        assert!(parties[1].round1_recieve(&round1_outputs).is_err());
+
+        //a fresh session is admitted
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        compiler_round_1(&mut parties, &round1_outputs);
+        assert!(caches.iter().all(|cache| cache.lock().unwrap().len() == 2));
+    }
+
+    ///
+    /// *Negative test*
+    /// Runs optimized WAKE using Bulletproofs, where a party keeps a replay cache, and the round 1 outputs of an earlier session are replayed
+    /// Should fail, and a party in another context rejects the session authentications of the other parties
+    ///
+    #[test]
+    fn test_optimized_replay_cache_and_context_neg() {
+        let rng = &mut OsRng;
+        let params = params();
+        let cache = ReplayCache::shared();
+        let blindings: Vec<Scalar> = (0..3).map(|_| Scalar::random(rng)).collect();
+        let commitments: Vec<RistrettoPoint> = (0..3).map(|i| (params.pc_gens().B * Scalar::from(UPPERBOUND - 20)) + (params.pc_gens().B_blinding * blindings[i])).collect();
+        let ring = Ring::from_commitments(&commitments).unwrap();
+        let mut parties: Vec<optimized_bp_wake_protocol::Party> = (0..3).map(|i| optimized_bp_wake_protocol::Party::new(i, params.clone(), commitments.clone(), ring.clone(), 20, blindings[i])).collect();
+        parties[0].set_replay_cache(cache.clone());
+
+        let round1_outputs: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| party.round_1(rng)).collect();
+        let round2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)> = parties.iter_mut().map(|party| party.round_2(&round1_outputs, rng).unwrap()).collect();
+        assert!(parties[0].round_2(&round1_outputs, rng).is_err());
+        assert_eq!(cache.lock().unwrap().len(), 1);
+
+        //a party in another context derives another session id
+        parties[1].set_context(SessionContext::new(DEFAULT_APPLICATION, "", 1));
+        let _ = parties[1].round_2(&round1_outputs, rng).unwrap();
+        let misbehaviour = parties[1].verify_round_2_outputs(&round1_outputs, round2_outputs, commitments, rng).unwrap_err();
+        assert_eq!(misbehaviour.parties.len(), 2);
+        assert!(!misbehaviour.parties.contains(&parties[1].id()));
+    }
+
+    ///
+    /// Derives session ids from the same round 1 contributions in different contexts
+    /// Asserts the session id depends on every field of the context, and contexts with the same concatenation differ
+    ///
+    #[test]
+    fn test_session_id_context_encoding() {
+        let ids: Vec<PartyId> = (0..2).map(|_| PartyId::from_commitment(&RistrettoPoint::random(&mut OsRng))).collect();
+        let contributions: Vec<[u8; 32]> = (0..2).map(|_| Scalar::random(&mut OsRng).to_bytes()).collect();
+        let session_id = |context: SessionContext| SessionId::derive(&context, &ids, &contributions);
+
+        let reference = session_id(SessionContext::new("ab", "c", 0));
+        assert_eq!(reference, session_id(SessionContext::new("ab", "c", 0)));
+        assert_ne!(reference, session_id(SessionContext::new("a", "bc", 0)));
+        assert_ne!(reference, session_id(SessionContext::new("ab", "c", 1)));
+        assert_ne!(reference, session_id(SessionContext::default()));
+        assert_ne!(reference, SessionId::derive(&SessionContext::new("ab", "c", 0), &ids, &contributions[..1]));
+    }
+}
@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_protocol;
+    use wake_implementation::protocols::compiler_bp_wake::compiler_bp_wake_signature::{CompMessage, CompSignature};
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_protocol;
+    use wake_implementation::protocols::optimized_bp_wake::optimized_bp_wake_signature_and_session_authentication::{OptimizedMessage, OptimizedSignatureAndSessionAuthentication};
+    use wake_implementation::protocols::party_id::{PartyId, Ring};
+    use wake_implementation::protocols::snapshot::{EncryptedSnapshot, SnapshotKey};
+    use wake_implementation::params::{WakeParams, BulletproofsKeys};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::RistrettoPoint};
+    use std::sync::Arc;
+    use rand::{rngs::OsRng, Rng};
+
+    const MIN_BAL: u64 = 10;
+    const UPPERBOUND: u64 = u8::MAX as u64;
+    const PARTY_AMOUNT: usize = 3;
+
+    //private inputs and public statements of the parties, from which a party can be re-created after a restart
+    struct Inputs {
+        params: Arc<WakeParams<BulletproofsKeys>>,
+        commitments: Vec<RistrettoPoint>,
+        ring: Ring,
+        balances: Vec<u64>,
+        blindings: Vec<Scalar>,
+    }
+
+    impl Inputs {
+        fn new(rng: &mut OsRng) -> Inputs {
+            let params: Arc<WakeParams<BulletproofsKeys>> = Arc::new(WakeParams::bulletproofs(MIN_BAL, UPPERBOUND).unwrap());
+            let balances: Vec<u64> = (0..PARTY_AMOUNT).map(|_| rng.gen_range(MIN_BAL..=UPPERBOUND)).collect();
+            let blindings: Vec<Scalar> = (0..PARTY_AMOUNT).map(|_| Scalar::random(rng)).collect();
+            let commitments: Vec<RistrettoPoint> = (0..PARTY_AMOUNT).map(|i| (params.pc_gens().B * Scalar::from(UPPERBOUND - balances[i])) + (params.pc_gens().B_blinding * blindings[i])).collect();
+            let ring = Ring::from_commitments(&commitments).unwrap();
+            Inputs { params, commitments, ring, balances, blindings }
+        }
+
+        fn compiler_party(&self, i: usize) -> compiler_bp_wake_protocol::Party {
+            compiler_bp_wake_protocol::Party::new(i, self.params.clone(), self.commitments.clone(), self.ring.clone(), self.balances[i], self.blindings[i])
+        }
+
+        fn optimized_party(&self, i: usize) -> optimized_bp_wake_protocol::Party {
+            optimized_bp_wake_protocol::Party::new(i, self.params.clone(), self.commitments.clone(), self.ring.clone(), self.balances[i], self.blindings[i])
+        }
+    }
+
+    //runs round 1 of compiler WAKE
+    fn compiler_round_1(parties: &mut [compiler_bp_wake_protocol::Party], rng: &mut OsRng) -> Vec<(PartyId, Scalar)> {
+        let round1_outputs: Vec<(PartyId, Scalar)> = parties.iter().map(|party| party.round_1(rng)).collect();
+        for party in parties.iter_mut() {
+            party.round1_recieve(&round1_outputs).unwrap();
+        }
+        round1_outputs
+    }
+
+    //runs rounds 3 of compiler WAKE and computes the keys, from the round 2 outputs
+    fn compiler_finish(parties: &mut [compiler_bp_wake_protocol::Party], round2_outputs: Vec<(CompMessage, CompSignature)>, rng: &mut OsRng) -> Vec<RistrettoPoint> {
+        let z_list: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+        for party in parties.iter() {
+            party.round_recieve(round2_outputs.clone(), 2, rng).unwrap();
+        }
+
+        let round3_outputs: Vec<(CompMessage, CompSignature)> = parties.iter().map(|party| party.round_3(&z_list, rng).unwrap()).collect();
+        let x_list: Vec<(PartyId, RistrettoPoint)> = round3_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+        for party in parties.iter() {
+            party.round_recieve(round3_outputs.clone(), 3, rng).unwrap();
+        }
+
+        parties.iter_mut().map(|party| party.compute_key(&z_list, &x_list).unwrap()).collect()
+    }
+
+    fn assert_same_keys(keys: &[RistrettoPoint]) {
+        for i in 1..keys.len() {
+            assert_eq!(keys[i-1], keys[i])
+        }
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, where a party is restarted after round 2 and restored from its snapshot
+    /// Asserts the restored party continues the session, and similar keys are produced
+    ///
+    #[test]
+    fn test_compiler_restore_after_round_2() {
+        let rng = &mut OsRng;
+        let inputs = Inputs::new(rng);
+        let key = SnapshotKey::random(rng);
+        let mut parties: Vec<compiler_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.compiler_party(i)).collect();
+
+        let round1_outputs = compiler_round_1(&mut parties, rng);
+        let round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+
+        //restart party 1 from its serialized snapshot
+        let snapshot = parties[1].snapshot(&key, rng).unwrap().to_bytes();
+        parties[1] = inputs.compiler_party(1);
+        parties[1].restore(&EncryptedSnapshot::from_bytes(&snapshot).unwrap(), &key, &round1_outputs).unwrap();
+
+        let keys = compiler_finish(&mut parties, round2_outputs, rng);
+        assert_same_keys(&keys);
+    }
+
+    ///
+    /// Runs compiler WAKE using Bulletproofs, where a party is restarted after round 1 and restored from its snapshot
+    /// Asserts the restored party runs the remaining rounds, and similar keys are produced
+    ///
+    #[test]
+    fn test_compiler_restore_after_round_1() {
+        let rng = &mut OsRng;
+        let inputs = Inputs::new(rng);
+        let key = SnapshotKey::random(rng);
+        let mut parties: Vec<compiler_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.compiler_party(i)).collect();
+
+        let round1_outputs = compiler_round_1(&mut parties, rng);
+        let snapshot = parties[0].snapshot(&key, rng).unwrap();
+        parties[0] = inputs.compiler_party(0);
+        parties[0].restore(&snapshot, &key, &round1_outputs).unwrap();
+
+        let round2_outputs: Vec<(CompMessage, CompSignature)> = parties.iter_mut().map(|party| party.round_2(rng)).collect();
+        let keys = compiler_finish(&mut parties, round2_outputs, rng);
+        assert_same_keys(&keys);
+    }
+
+    ///
+    /// *Negative test*
+    /// Takes a snapshot of a party in a compiler WAKE session, and restores it into a new session of the same parties
+    /// Should fail, as the snapshot is bound to the nonces of its session
+    ///
+    #[test]
+    fn test_compiler_replay_into_new_session_neg() {
+        let rng = &mut OsRng;
+        let inputs = Inputs::new(rng);
+        let key = SnapshotKey::random(rng);
+
+        let mut old_session: Vec<compiler_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.compiler_party(i)).collect();
+        compiler_round_1(&mut old_session, rng);
+        let _ = old_session[0].round_2(rng);
+        let old_snapshot = old_session[0].snapshot(&key, rng).unwrap();
+
+        let mut new_session: Vec<compiler_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.compiler_party(i)).collect();
+        let round1_outputs = compiler_round_1(&mut new_session, rng);
+        new_session[0] = inputs.compiler_party(0);
+        assert!(new_session[0].restore(&old_snapshot, &key, &round1_outputs).is_err());
+    }
+
+    ///
+    /// *Negative test*
+    /// Restores the snapshot of a compiler WAKE party with another key, into another party, and after altering it
+    /// Should fail, as the snapshot is authenticated and bound to its party
+    ///
+    #[test]
+    fn test_compiler_restore_wrong_key_party_or_altered_neg() {
+        let rng = &mut OsRng;
+        let inputs = Inputs::new(rng);
+        let key = SnapshotKey::random(rng);
+        let mut parties: Vec<compiler_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.compiler_party(i)).collect();
+
+        //no snapshot before the round 1 outputs are received
+        assert!(parties[0].snapshot(&key, rng).is_err());
+
+        let round1_outputs = compiler_round_1(&mut parties, rng);
+        let _ = parties[0].round_2(rng);
+        let snapshot = parties[0].snapshot(&key, rng).unwrap();
+
+        assert!(inputs.compiler_party(0).restore(&snapshot, &SnapshotKey::random(rng), &round1_outputs).is_err());
+        assert!(inputs.compiler_party(1).restore(&snapshot, &key, &round1_outputs).is_err());
+
+        let mut altered = snapshot.to_bytes();
+        let last = altered.len() - 1;
+        altered[last] ^= 1;
+        assert!(inputs.compiler_party(0).restore(&EncryptedSnapshot::from_bytes(&altered).unwrap(), &key, &round1_outputs).is_err());
+        assert!(EncryptedSnapshot::from_bytes(&[0u8; 4]).is_err());
+    }
+
+    ///
+    /// Runs optimized WAKE using Bulletproofs, where a party is restarted after round 1 and restored from its snapshot
+    /// Asserts the restored party runs round 2, and similar keys are produced
+    ///
+    #[test]
+    fn test_optimized_restore_after_round_1() {
+        let rng = &mut OsRng;
+        let inputs = Inputs::new(rng);
+        let key = SnapshotKey::random(rng);
+        let mut parties: Vec<optimized_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.optimized_party(i)).collect();
+
+        let round1_outputs: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| party.round_1(rng)).collect();
+        let snapshot = parties[2].snapshot(&key, rng).unwrap();
+        parties[2] = inputs.optimized_party(2);
+        parties[2].restore(&snapshot, &key, &round1_outputs[2..]).unwrap();
+
+        let round2_outputs: Vec<(OptimizedMessage, OptimizedSignatureAndSessionAuthentication)> = parties.iter_mut().map(|party| party.round_2(&round1_outputs, rng).unwrap()).collect();
+        let x_list: Vec<(PartyId, RistrettoPoint)> = round2_outputs.iter().map(|(message, _)| (message.sender, message.payload)).collect();
+
+        //restart party 0 after round 2
+        let snapshot = parties[0].snapshot(&key, rng).unwrap();
+        parties[0] = inputs.optimized_party(0);
+        parties[0].restore(&snapshot, &key, &round1_outputs).unwrap();
+
+        for party in parties.iter() {
+            party.verify_round_2_outputs(&round1_outputs, round2_outputs.clone(), inputs.commitments.clone(), rng).unwrap();
+        }
+        let keys: Vec<RistrettoPoint> = parties.iter_mut().map(|party| party.compute_key(&round1_outputs, &x_list).unwrap()).collect();
+        assert_same_keys(&keys);
+    }
+
+    ///
+    /// *Negative test*
+    /// Takes a snapshot of a party after round 1 of an optimized WAKE session, and restores it into a new session
+    /// Should fail, as the snapshot is bound to the round 1 output of the party in its session
+    ///
+    #[test]
+    fn test_optimized_replay_into_new_session_neg() {
+        let rng = &mut OsRng;
+        let inputs = Inputs::new(rng);
+        let key = SnapshotKey::random(rng);
+
+        let mut old_party = inputs.optimized_party(0);
+        let _ = old_party.round_1(rng);
+        let old_snapshot = old_party.snapshot(&key, rng).unwrap();
+
+        let mut parties: Vec<optimized_bp_wake_protocol::Party> = (0..PARTY_AMOUNT).map(|i| inputs.optimized_party(i)).collect();
+        let round1_outputs: Vec<(PartyId, RistrettoPoint)> = parties.iter_mut().map(|party| party.round_1(rng)).collect();
+        parties[0] = inputs.optimized_party(0);
+        assert!(parties[0].restore(&old_snapshot, &key, &round1_outputs).is_err());
+    }
+}
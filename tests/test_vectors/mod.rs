@@ -0,0 +1 @@
+pub mod test_known_answers;
\ No newline at end of file
@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use wake_implementation::test_vectors::{TestVector, VARIANTS, replay_test_vector};
+    use curve25519_dalek_ng::{scalar::Scalar, ristretto::{CompressedRistretto, RistrettoPoint}};
+    use std::{fs, path::Path};
+
+    fn load(variant: &str) -> TestVector {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test_vectors/data").join(TestVector::file_name(variant));
+        TestVector::from_json(&fs::read_to_string(&path).unwrap()).unwrap()
+    }
+
+    fn point(hex_string: &str) -> RistrettoPoint {
+        CompressedRistretto::from_slice(&hex::decode(hex_string).unwrap()).decompress().unwrap()
+    }
+
+    fn scalar(hex_string: &str) -> Scalar {
+        Scalar::from_canonical_bytes(hex::decode(hex_string).unwrap().try_into().unwrap()).unwrap()
+    }
+
+    //replays the stored test vector of a variant, and asserts the session is reproduced exactly
+    //on failure, regenerate the vectors with: cargo run --release --example generate_test_vectors, if the change to the protocol is intended
+    fn replay(variant: &str) {
+        let expected = load(variant);
+        assert_eq!(expected.variant, variant);
+
+        let replayed = replay_test_vector(&expected).unwrap();
+
+        //compare piece by piece, such that a failure points at the part of the protocol that changed
+        assert_eq!(replayed.balances, expected.balances, "balances drawn from the seed differ");
+        assert_eq!(replayed.public_parameters, expected.public_parameters, "public parameters differ");
+        assert_eq!(replayed.transcript.statements, expected.transcript.statements, "statements differ");
+        assert_eq!(replayed.transcript.messages.len(), expected.transcript.messages.len());
+        for (replayed_message, expected_message) in replayed.transcript.messages.iter().zip(expected.transcript.messages.iter()) {
+            assert_eq!(replayed_message, expected_message, "round {} message of party {} differs", expected_message.round, expected_message.sender);
+        }
+        assert_eq!(replayed.transcript.secret_inputs, expected.transcript.secret_inputs, "secret inputs differ");
+        assert_eq!(replayed.keys, expected.keys, "derived keys differ");
+        assert_eq!(replayed, expected);
+
+        //all parties derive the same key
+        for i in 1..expected.keys.len() {
+            assert_eq!(expected.keys[i-1], expected.keys[i])
+        }
+    }
+
+    #[test]
+    fn test_compiler_bp_vector() {
+        replay("compiler_bp");
+    }
+
+    #[test]
+    fn test_optimized_bp_vector() {
+        replay("optimized_bp");
+    }
+
+    #[test]
+    fn test_compiler_gm17_vector() {
+        replay("compiler_gm17");
+    }
+
+    #[test]
+    fn test_optimized_gm17_vector() {
+        replay("optimized_gm17");
+    }
+
+    #[test]
+    fn test_compiler_marlin_vector() {
+        replay("compiler_marlin");
+    }
+
+    #[test]
+    fn test_optimized_marlin_vector() {
+        replay("optimized_marlin");
+    }
+
+    ///
+    /// Recomputes the Burmester-Desmedt values and the key of every variant from the recorded secret inputs, as another implementation would:
+    /// the key is g^(x_1 x_2 + x_2 x_3 + ... + x_n x_1) over the exponents in the order of the party identifiers. For the Bulletproofs variants,
+    /// the commitments are recomputed from the balances and blindings, and for the optimized variants, the round 1 messages from the exponents
+    ///
+    #[test]
+    fn test_vectors_recomputed_from_secret_inputs() {
+        for variant in VARIANTS {
+            let vector = load(variant);
+            let g = point(&vector.public_parameters.generators[0]);
+            let h = point(&vector.public_parameters.generators[1]);
+            assert_eq!(vector.transcript.secret_inputs.len(), vector.party_amount);
+
+            if variant.ends_with("_bp") {
+                for (i, secret_inputs) in vector.transcript.secret_inputs.iter().enumerate() {
+                    let commitment = g * Scalar::from(vector.upperbound - vector.balances[i]) + h * scalar(&secret_inputs.blinding);
+                    assert_eq!(commitment, point(&vector.transcript.statements[i]), "{} commitment of party {} differs", variant, secret_inputs.party);
+                }
+            }
+
+            if variant.starts_with("optimized") {
+                for secret_inputs in vector.transcript.secret_inputs.iter() {
+                    let round_1_message = vector.transcript.messages.iter().find(|message| message.round == 1 && message.sender == secret_inputs.party).unwrap();
+                    assert_eq!(g * scalar(&secret_inputs.exponent), point(&round_1_message.message), "{} round 1 message of party {} differs", variant, secret_inputs.party);
+                }
+            }
+
+            let mut secret_inputs = vector.transcript.secret_inputs.clone();
+            secret_inputs.sort_by(|a, b| a.party.cmp(&b.party));
+            let exponents: Vec<Scalar> = secret_inputs.iter().map(|secret_inputs| scalar(&secret_inputs.exponent)).collect();
+            let exponent: Scalar = (0..exponents.len()).map(|i| exponents[i] * exponents[(i + 1) % exponents.len()]).sum();
+            for key in vector.keys.iter() {
+                assert_eq!(g * exponent, point(key), "{} key differs", variant);
+            }
+        }
+    }
+
+    ///
+    /// Asserts a vector is stored for every variant, and survives a JSON round trip unchanged
+    ///
+    #[test]
+    fn test_vectors_stored_for_all_variants() {
+        for variant in VARIANTS {
+            let vector = load(variant);
+            assert_eq!(TestVector::from_json(&vector.to_json().unwrap()).unwrap(), vector);
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod test_utility;
\ No newline at end of file
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use ark_bls12_377::Fr;
+    use ark_ff::{One, Zero};
+    use std::str::FromStr;
+    use wake_implementation::utility::{fr_to_bits, hash_string_to_fr, hash_to_field, GM17_AUX_DOMAIN, MARLIN_AUX_DOMAIN};
+
+    //converts a list of 0/1 values to the expected bit representation
+    fn to_field_bits(bits: &[u8]) -> Vec<Option<Fr>> {
+        bits.iter().map(|b| if *b == 1 { Some(Fr::one()) } else { Some(Fr::zero()) }).collect()
+    }
+
+    ///
+    /// Test that small values are decomposed most significant bit first, padded to the bit length
+    /// 
+    #[test]
+    fn fr_to_bits_small_values() {
+        assert_eq!(fr_to_bits(Fr::from(0u64), 8), to_field_bits(&[0, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(fr_to_bits(Fr::from(5u64), 8), to_field_bits(&[0, 0, 0, 0, 0, 1, 0, 1]));
+        assert_eq!(fr_to_bits(Fr::from(255u64), 8), to_field_bits(&[1, 1, 1, 1, 1, 1, 1, 1]));
+        assert_eq!(fr_to_bits(Fr::from(0xA5A5u64), 16), to_field_bits(&[1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1]));
+    }
+
+    ///
+    /// Test decomposition of the largest 64-bit value
+    /// 
+    #[test]
+    fn fr_to_bits_u64_max() {
+        assert_eq!(fr_to_bits(Fr::from(u64::MAX), 64), vec![Some(Fr::one()); 64]);
+    }
+
+    ///
+    /// Test that values beyond 64 bits are decomposed correctly
+    /// 
+    #[test]
+    fn fr_to_bits_beyond_64_bits() {
+        //2^70 + 1
+        let v = Fr::from_str("1180591620717411303425").unwrap();
+        let bits = fr_to_bits(v, 72);
+        let mut expected = vec![0u8; 72];
+        expected[72 - 71] = 1;
+        expected[71] = 1;
+        assert_eq!(bits, to_field_bits(&expected));
+
+        //p - 1 has all 253 bits significant, and its top bit set
+        let p_minus_one = -Fr::one();
+        let bits = fr_to_bits(p_minus_one, 253);
+        assert_eq!(bits[0], Some(Fr::one()));
+        assert_eq!(bits[252], Some(Fr::zero()));
+    }
+
+    ///
+    /// Test that only the lowest bits are kept when the bit length is smaller than the value
+    /// 
+    #[test]
+    fn fr_to_bits_truncates_to_bit_length() {
+        assert_eq!(fr_to_bits(Fr::from(0x1FFu64), 8), to_field_bits(&[1, 1, 1, 1, 1, 1, 1, 1]));
+        assert_eq!(fr_to_bits(Fr::from(0x100u64), 8), to_field_bits(&[0, 0, 0, 0, 0, 0, 0, 0]));
+    }
+
+    ///
+    /// Test hashing to the field against known values
+    /// the expected values are computed as: SHA256(len(tag) || tag || 0 || m) || SHA256(len(tag) || tag || 1 || m) mod p
+    /// 
+    #[test]
+    fn hash_string_to_fr_known_values() {
+        assert_eq!(hash_string_to_fr("".to_string()), Fr::from_str("4052030746187349639842665863435806926730757427035885404989536181390821283869").unwrap());
+        assert_eq!(hash_string_to_fr("test123".to_string()), Fr::from_str("2399935921144312475030819327067101793619263474708115916365707320081797812439").unwrap());
+        assert_eq!(hash_to_field::<Fr>(GM17_AUX_DOMAIN, b"test123"), hash_string_to_fr("test123".to_string()));
+    }
+
+    ///
+    /// Test that different domain separation tags yield different field elements
+    /// 
+    #[test]
+    fn hash_to_field_domain_separation() {
+        let other_domain: Fr = hash_to_field(b"OTHER", b"test123");
+        assert_eq!(other_domain, Fr::from_str("5685465664610632023792457299839418381532240931001559804846689840326398431724").unwrap());
+        assert_ne!(other_domain, hash_string_to_fr("test123".to_string()));
+
+        //aux inputs of Marlin proofs are hashed under their own tag
+        assert_ne!(hash_to_field::<Fr>(MARLIN_AUX_DOMAIN, b"test123"), hash_to_field::<Fr>(GM17_AUX_DOMAIN, b"test123"));
+    }
+}